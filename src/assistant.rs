@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -20,6 +21,54 @@ impl Assistant {
             Assistant::OpenCode => "opencode",
         }
     }
+
+    /// Where the assistant looks for project-local skills, relative to a project directory.
+    pub fn project_skills_dir(self) -> PathBuf {
+        match self {
+            Assistant::Codex => Path::new(".codex").join("skills"),
+            Assistant::ClaudeCode => Path::new(".claude").join("skills"),
+            Assistant::OpenCode => Path::new(".opencode").join("skill"),
+        }
+    }
+
+    /// Environment variable users set to relocate the assistant's home directory.
+    pub fn home_env(self) -> &'static str {
+        match self {
+            Assistant::Codex => "CODEX_HOME",
+            Assistant::ClaudeCode => "CLAUDE_CONFIG_DIR",
+            Assistant::OpenCode => "OPENCODE_CONFIG_DIR",
+        }
+    }
+
+    /// Where the assistant reads personal skills inside its home directory `home`.
+    pub fn home_skills_dir(self, home: &Path) -> PathBuf {
+        match self {
+            Assistant::OpenCode => home.join("skill"),
+            _ => home.join("skills"),
+        }
+    }
+
+    /// The skills directory under a relocated home, when [`Self::home_env`] is set.
+    pub fn env_skills_dir(self) -> Option<PathBuf> {
+        std::env::var_os(self.home_env())
+            .filter(|home| !home.is_empty())
+            .map(|home| self.home_skills_dir(Path::new(&home)))
+    }
+
+    /// The skills directory the assistant reads on its own: under its relocated home
+    /// when [`Self::home_env`] is set, else under `~/.codex`, `~/.claude` or
+    /// `~/.config/opencode`.
+    pub fn native_skills_dir(self) -> Option<PathBuf> {
+        self.env_skills_dir().or_else(|| {
+            let home = dirs::home_dir()?;
+            let assistant_home = match self {
+                Assistant::Codex => home.join(".codex"),
+                Assistant::ClaudeCode => home.join(".claude"),
+                Assistant::OpenCode => home.join(".config").join("opencode"),
+            };
+            Some(self.home_skills_dir(&assistant_home))
+        })
+    }
 }
 
 impl fmt::Display for Assistant {
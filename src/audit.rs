@@ -0,0 +1,217 @@
+use crate::assistant::Assistant;
+use crate::paths::{append_private, ensure_private_dir, AppPaths};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+const AUDIT_FILE_NAME: &str = "audit.jsonl";
+const TOP_USED_LIMIT: usize = 10;
+
+/// One line of the append-only audit log in the data dir.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub at: DateTime<Utc>,
+    pub action: AuditAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assistant: Option<Assistant>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+    /// Skill size after an install/update, or before a removal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    /// Rule ID for findings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    Install,
+    Remove,
+    Update,
+    Use,
+    Finding,
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction, assistant: Option<Assistant>, skill: &str) -> Self {
+        Self {
+            at: Utc::now(),
+            action,
+            assistant,
+            skill: Some(skill.to_string()),
+            bytes: None,
+            detail: None,
+        }
+    }
+
+    pub fn with_bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    pub fn with_detail(mut self, detail: &str) -> Self {
+        self.detail = Some(detail.to_string());
+        self
+    }
+}
+
+/// Appends `event` to the audit log. The log is informational, so failures are only
+/// reported as warnings and never fail the command that triggered them.
+pub fn record(paths: &AppPaths, event: AuditEvent) {
+    if let Err(err) = append(paths, &event) {
+        eprintln!("Warning: failed to write audit log: {err:#}");
+    }
+}
+
+/// Every readable event, oldest first. Lines that don't parse are skipped.
+pub fn load(paths: &AppPaths) -> Result<Vec<AuditEvent>> {
+    let path = audit_path(paths);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn append(paths: &AppPaths, event: &AuditEvent) -> Result<()> {
+    ensure_private_dir(&paths.data_dir)?;
+    let path = audit_path(paths);
+    let mut file = append_private(&path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn audit_path(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join(AUDIT_FILE_NAME)
+}
+
+/// Summary of one calendar month of the audit log.
+#[derive(Debug, Default, Serialize)]
+pub struct MonthlyReport {
+    pub month: String,
+    pub installs: Vec<String>,
+    pub removals: Vec<String>,
+    pub updates: Vec<String>,
+    /// `(skill, uses)`, most used first.
+    pub most_used: Vec<(String, u64)>,
+    /// Findings seen during installs and updates, by rule ID.
+    pub findings: BTreeMap<String, u64>,
+    pub bytes_added: u64,
+    pub bytes_removed: u64,
+    /// Installed size now, across all assistants.
+    pub bytes_installed: u64,
+}
+
+pub fn build_report(
+    events: &[AuditEvent],
+    year: i32,
+    month: u32,
+    bytes_installed: u64,
+) -> MonthlyReport {
+    let mut report = MonthlyReport {
+        month: format!("{year:04}-{month:02}"),
+        bytes_installed,
+        ..Default::default()
+    };
+    let mut uses: BTreeMap<String, u64> = BTreeMap::new();
+    for event in events
+        .iter()
+        .filter(|event| event.at.year() == year && event.at.month() == month)
+    {
+        let label = match (event.assistant, &event.skill) {
+            (Some(assistant), Some(skill)) => format!("{assistant}/{skill}"),
+            (None, Some(skill)) => skill.clone(),
+            _ => continue,
+        };
+        let bytes = event.bytes.unwrap_or(0);
+        match event.action {
+            AuditAction::Install => {
+                report.installs.push(label);
+                report.bytes_added += bytes;
+            }
+            AuditAction::Remove => {
+                report.removals.push(label);
+                report.bytes_removed += bytes;
+            }
+            AuditAction::Update => report.updates.push(label),
+            AuditAction::Use => *uses.entry(label).or_default() += 1,
+            AuditAction::Finding => {
+                let rule = event
+                    .detail
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string());
+                *report.findings.entry(rule).or_default() += 1;
+            }
+        }
+    }
+    let mut most_used: Vec<(String, u64)> = uses.into_iter().collect();
+    most_used.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    most_used.truncate(TOP_USED_LIMIT);
+    report.most_used = most_used;
+    report
+}
+
+pub fn render_markdown(report: &MonthlyReport) -> String {
+    let size = |bytes: u64| bytesize::ByteSize(bytes).to_string();
+    let mut output = format!("# Skills report for {}\n\n", report.month);
+    output.push_str(&format!(
+        "- Installs: {}\n- Removals: {}\n- Updates: {}\n\n",
+        report.installs.len(),
+        report.removals.len(),
+        report.updates.len()
+    ));
+
+    for (title, skills) in [
+        ("Installed", &report.installs),
+        ("Removed", &report.removals),
+        ("Updated", &report.updates),
+    ] {
+        if skills.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("## {title}\n\n"));
+        for skill in skills {
+            output.push_str(&format!("- {skill}\n"));
+        }
+        output.push('\n');
+    }
+
+    output.push_str("## Most used\n\n");
+    if report.most_used.is_empty() {
+        output.push_str("No recorded uses.\n\n");
+    } else {
+        for (skill, uses) in &report.most_used {
+            output.push_str(&format!("- {skill}: {uses}\n"));
+        }
+        output.push('\n');
+    }
+
+    output.push_str("## Scan findings\n\n");
+    if report.findings.is_empty() {
+        output.push_str("None.\n\n");
+    } else {
+        for (rule, count) in &report.findings {
+            output.push_str(&format!("- {rule}: {count}\n"));
+        }
+        output.push('\n');
+    }
+
+    output.push_str("## Space\n\n");
+    output.push_str(&format!(
+        "- Added: {}\n- Removed: {}\n- Installed now: {}\n",
+        size(report.bytes_added),
+        size(report.bytes_removed),
+        size(report.bytes_installed)
+    ));
+    output
+}
@@ -0,0 +1,201 @@
+use crate::assistant::Assistant;
+use crate::paths::create_private;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tar::{Archive, Builder};
+
+const BACKUP_PREFIX: &str = "skills-backup-";
+const BACKUP_SUFFIX: &str = ".tar.gz";
+/// Directory inside a backup that holds one subdirectory per assistant root.
+pub const SKILLS_DIR: &str = "skills";
+
+/// Something to put in a backup: a file or directory and the path it gets inside the
+/// archive. Missing sources are left out.
+#[derive(Debug, Clone)]
+pub struct BackupSource {
+    pub archive_path: PathBuf,
+    pub path: PathBuf,
+}
+
+/// Writes a timestamped `skills-backup-<UTC time>.tar.gz` into `target_dir`.
+pub fn create_backup(target_dir: &Path, sources: &[BackupSource]) -> Result<PathBuf> {
+    fs::create_dir_all(target_dir)
+        .with_context(|| format!("failed to create {}", target_dir.display()))?;
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut output = target_dir.join(format!("{BACKUP_PREFIX}{stamp}{BACKUP_SUFFIX}"));
+    let mut counter = 1;
+    while output.exists() {
+        output = target_dir.join(format!("{BACKUP_PREFIX}{stamp}-{counter}{BACKUP_SUFFIX}"));
+        counter += 1;
+    }
+    write_backup(&output, sources)?;
+    Ok(output)
+}
+
+/// Writes a backup of `sources` to `output`, replacing any file already there.
+pub fn write_backup(output: &Path, sources: &[BackupSource]) -> Result<()> {
+    if let Some(parent) = output
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    // Build next to the final name so a cron run that dies midway never leaves a
+    // truncated archive that rotation would count as a good backup.
+    let temp_path = output.with_extension("partial");
+    let result = write_archive(&temp_path, sources);
+    if let Err(err) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err);
+    }
+    fs::rename(&temp_path, output).with_context(|| format!("failed to write {}", output.display()))
+}
+
+/// Backups in `target_dir`, oldest first.
+pub fn list_backups(target_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !target_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(target_dir)
+        .with_context(|| format!("failed to read {}", target_dir.display()))?
+    {
+        let path = entry?.path();
+        let is_backup = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_SUFFIX));
+        if is_backup && path.is_file() {
+            backups.push(path);
+        }
+    }
+    backups.sort_by_key(|path| backup_order(path));
+    Ok(backups)
+}
+
+/// Deletes all but the newest `keep` backups and returns what was removed.
+pub fn rotate(target_dir: &Path, keep: usize) -> Result<Vec<PathBuf>> {
+    let backups = list_backups(target_dir)?;
+    let excess = backups.len().saturating_sub(keep);
+    let removed: Vec<PathBuf> = backups.into_iter().take(excess).collect();
+    for path in &removed {
+        fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+    Ok(removed)
+}
+
+/// Picks the backup to restore: `from` as a path, or as a name inside `target_dir`;
+/// the newest backup when `from` is not given.
+pub fn resolve_backup(target_dir: &Path, from: Option<&Path>) -> Result<PathBuf> {
+    match from {
+        Some(from) if from.is_file() => Ok(from.to_path_buf()),
+        Some(from) => {
+            let candidate = target_dir.join(from);
+            if candidate.is_file() {
+                Ok(candidate)
+            } else {
+                Err(anyhow!("backup not found: {}", from.display()))
+            }
+        }
+        None => list_backups(target_dir)?
+            .pop()
+            .ok_or_else(|| anyhow!("no backups found in {}", target_dir.display())),
+    }
+}
+
+/// Unpacks `archive` into `dest`. Entries that would land outside `dest` are rejected
+/// by `tar`.
+pub fn extract(archive: &Path, dest: &Path) -> Result<()> {
+    let file =
+        File::open(archive).with_context(|| format!("failed to open {}", archive.display()))?;
+    Archive::new(GzDecoder::new(file))
+        .unpack(dest)
+        .with_context(|| format!("failed to extract {}", archive.display()))
+}
+
+/// A skill found in an extracted backup.
+#[derive(Debug, Clone)]
+pub struct BackedUpSkill {
+    pub assistant: Assistant,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Skills under `skills/<assistant>/<name>` in an extracted backup, sorted.
+pub fn backed_up_skills(extracted: &Path) -> Result<Vec<BackedUpSkill>> {
+    let mut skills = Vec::new();
+    for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+        let root = extracted.join(SKILLS_DIR).join(assistant.as_str());
+        if !root.is_dir() {
+            continue;
+        }
+        for entry in
+            fs::read_dir(&root).with_context(|| format!("failed to read {}", root.display()))?
+        {
+            let path = entry?.path();
+            if path.join("SKILL.md").is_file()
+                && let Some(name) = path.file_name().and_then(|name| name.to_str())
+            {
+                skills.push(BackedUpSkill {
+                    assistant,
+                    name: name.to_string(),
+                    path: path.clone(),
+                });
+            }
+        }
+    }
+    skills.sort_by(|a, b| (a.assistant.as_str(), &a.name).cmp(&(b.assistant.as_str(), &b.name)));
+    Ok(skills)
+}
+
+/// Sort key for a backup name: its UTC timestamp, then the counter added when two
+/// backups were taken within the same second.
+fn backup_order(path: &Path) -> (String, u32) {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let stem = name
+        .strip_prefix(BACKUP_PREFIX)
+        .and_then(|name| name.strip_suffix(BACKUP_SUFFIX))
+        .unwrap_or(name);
+    match stem.split_once('-') {
+        Some((stamp, counter)) => (stamp.to_string(), counter.parse().unwrap_or(0)),
+        None => (stem.to_string(), 0),
+    }
+}
+
+fn write_archive(output: &Path, sources: &[BackupSource]) -> Result<()> {
+    // Backups hold the install records and usage counts, so they get the same
+    // owner-only mode as the store.
+    let file = create_private(output)?;
+    let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+    builder.follow_symlinks(false);
+    for source in sources {
+        if source.path.is_dir() {
+            builder
+                .append_dir_all(&source.archive_path, &source.path)
+                .with_context(|| format!("failed to archive {}", source.path.display()))?;
+        } else if source.path.is_file() {
+            builder
+                .append_path_with_name(&source.path, &source.archive_path)
+                .with_context(|| format!("failed to archive {}", source.path.display()))?;
+        } else if source.path.exists() {
+            return Err(anyhow!(
+                "cannot archive {}: not a file or directory",
+                source.path.display()
+            ));
+        }
+    }
+    builder
+        .into_inner()?
+        .finish()
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use crate::assistant::Assistant;
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+/// Exit status when a multi-target command failed for some targets but not all, so
+/// scripts can tell "needs attention" apart from a command that did nothing.
+pub const PARTIAL_FAILURE_EXIT_CODE: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    Skipped,
+    Failed,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Skipped => "skipped",
+            Outcome::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BatchRow {
+    pub assistant: Assistant,
+    pub name: String,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+/// Per-skill results of a command that works on several skills or assistants, printed
+/// as one table once every target has been tried.
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    rows: Vec<BatchRow>,
+}
+
+impl BatchSummary {
+    pub fn push(
+        &mut self,
+        assistant: Assistant,
+        name: &str,
+        outcome: Outcome,
+        detail: impl Into<String>,
+    ) {
+        self.rows.push(BatchRow {
+            assistant,
+            name: name.to_string(),
+            outcome,
+            detail: detail.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| row.outcome == Outcome::Failed)
+            .count()
+    }
+
+    pub fn print(&self) {
+        let name_width = self
+            .rows
+            .iter()
+            .map(|row| row.name.len())
+            .chain(["SKILL".len()])
+            .max()
+            .unwrap_or_default();
+        println!(
+            "{:<10}  {:<name_width$}  {:<7}  DETAIL",
+            "ASSISTANT", "SKILL", "RESULT"
+        );
+        for row in &self.rows {
+            println!(
+                "{:<10}  {:<name_width$}  {:<7}  {}",
+                row.assistant.as_str(),
+                row.name,
+                row.outcome.as_str(),
+                row.detail
+            );
+        }
+    }
+
+    /// `Ok` when nothing failed, [`PartialFailure`] when only some targets failed, and a
+    /// plain error when every one of them did.
+    pub fn result(&self, action: &str) -> Result<()> {
+        let failed = self.failed();
+        if failed == 0 {
+            return Ok(());
+        }
+        if failed == self.rows.len() {
+            return Err(anyhow!("{failed} skill(s) failed to {action}"));
+        }
+        Err(PartialFailure {
+            failed,
+            total: self.rows.len(),
+            action: action.to_string(),
+        }
+        .into())
+    }
+}
+
+/// Error for a batch where some targets failed; `main` exits with
+/// [`PARTIAL_FAILURE_EXIT_CODE`] for it.
+#[derive(Debug)]
+pub struct PartialFailure {
+    pub failed: usize,
+    pub total: usize,
+    pub action: String,
+}
+
+impl fmt::Display for PartialFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} skill(s) failed to {}",
+            self.failed, self.total, self.action
+        )
+    }
+}
+
+impl std::error::Error for PartialFailure {}
+
+/// The `items` at the `chosen` indices, in their original order.
+pub fn keep_chosen<T>(items: Vec<T>, chosen: &[usize]) -> Vec<T> {
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| chosen.contains(index))
+        .map(|(_, item)| item)
+        .collect()
+}
@@ -0,0 +1,379 @@
+use crate::manifest::{self, InstallRecord};
+use crate::validation;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use walkdir::WalkDir;
+
+static URL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://[^\s<>"'`)\]}]+"#).expect("url regex"));
+
+/// Output format for `skill bom`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BomFormat {
+    #[default]
+    CycloneDx,
+    Spdx,
+}
+
+impl fmt::Display for BomFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BomFormat::CycloneDx => write!(f, "cyclonedx"),
+            BomFormat::Spdx => write!(f, "spdx"),
+        }
+    }
+}
+
+impl FromStr for BomFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "cyclonedx" | "cdx" => Ok(BomFormat::CycloneDx),
+            "spdx" => Ok(BomFormat::Spdx),
+            _ => Err(format!(
+                "unknown bom format '{value}'. Use cyclonedx or spdx."
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    Markdown,
+    Script,
+    Text,
+    Binary,
+}
+
+impl FileKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FileKind::Markdown => "markdown",
+            FileKind::Script => "script",
+            FileKind::Text => "text",
+            FileKind::Binary => "binary",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BomFile {
+    /// Path relative to the skill directory, `/`-separated.
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+    pub kind: FileKind,
+    /// Interpreter a script runs under, from its shebang or extension.
+    pub interpreter: Option<String>,
+    /// `http(s)` URLs referenced in the file.
+    pub urls: Vec<String>,
+}
+
+/// Inventory of one skill's files, as approved at a point in time.
+#[derive(Debug, Serialize)]
+pub struct SkillBom {
+    pub name: String,
+    pub description: String,
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub author: Option<String>,
+    pub source: Option<String>,
+    pub commit: Option<String>,
+    pub source_digest: Option<String>,
+    /// `tree-sha256:<hex>` over every file, as recorded by `skill add`.
+    pub tree_digest: String,
+    pub files: Vec<BomFile>,
+}
+
+impl SkillBom {
+    /// Inventories `skill_dir`; `record` adds where the skill was installed from.
+    pub fn build(skill_dir: &Path, record: Option<&InstallRecord>) -> Result<Self> {
+        let frontmatter = validation::read_frontmatter(skill_dir)?;
+        let mut files = Vec::new();
+        for entry in WalkDir::new(skill_dir).follow_links(false) {
+            let entry = entry?;
+            let rel_path = entry.path().strip_prefix(skill_dir)?;
+            if crate::commands::should_skip(rel_path) || !entry.file_type().is_file() {
+                continue;
+            }
+            let key = rel_path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let bytes = fs::read(entry.path())
+                .with_context(|| format!("failed to read {}", entry.path().display()))?;
+            files.push(describe_file(&key, &bytes));
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let hashes: BTreeMap<String, String> = files
+            .iter()
+            .map(|file| (file.path.clone(), file.sha256.clone()))
+            .collect();
+
+        Ok(Self {
+            version: frontmatter.version(),
+            name: frontmatter.name,
+            description: frontmatter.description,
+            license: frontmatter.license,
+            author: frontmatter.author,
+            source: record.map(|record| record.source.clone()),
+            commit: record.and_then(|record| record.commit.clone()),
+            source_digest: record.and_then(|record| record.source_digest.clone()),
+            tree_digest: manifest::tree_digest(&hashes),
+            files,
+        })
+    }
+
+    pub fn render(&self, format: BomFormat) -> Result<String> {
+        let document = match format {
+            BomFormat::CycloneDx => self.to_cyclonedx(),
+            BomFormat::Spdx => self.to_spdx(),
+        };
+        Ok(serde_json::to_string_pretty(&document)? + "\n")
+    }
+
+    /// CycloneDX 1.5 JSON: the skill is the described component, each file a
+    /// `file` component with interpreter and URLs as `skill:` properties.
+    pub fn to_cyclonedx(&self) -> serde_json::Value {
+        let components: Vec<serde_json::Value> = self
+            .files
+            .iter()
+            .map(|file| {
+                let mut properties = vec![
+                    json!({ "name": "skill:kind", "value": file.kind.as_str() }),
+                    json!({ "name": "skill:size", "value": file.size.to_string() }),
+                ];
+                if let Some(interpreter) = &file.interpreter {
+                    properties.push(json!({ "name": "skill:interpreter", "value": interpreter }));
+                }
+                properties.extend(
+                    file.urls
+                        .iter()
+                        .map(|url| json!({ "name": "skill:url", "value": url })),
+                );
+                json!({
+                    "type": "file",
+                    "bom-ref": format!("file:{}", file.path),
+                    "name": file.path,
+                    "hashes": [{ "alg": "SHA-256", "content": file.sha256 }],
+                    "properties": properties,
+                })
+            })
+            .collect();
+
+        let mut skill = json!({
+            "type": "application",
+            "bom-ref": format!("skill:{}", self.name),
+            "name": self.name,
+            "description": self.description,
+        });
+        if let Some(version) = &self.version {
+            skill["version"] = json!(version);
+        }
+        if let Some(author) = &self.author {
+            skill["author"] = json!(author);
+        }
+        if let Some(license) = &self.license {
+            skill["licenses"] = json!([{ "license": { "name": license } }]);
+        }
+        let mut properties =
+            vec![json!({ "name": "skill:tree-digest", "value": self.tree_digest })];
+        if let Some(commit) = &self.commit {
+            properties.push(json!({ "name": "skill:commit", "value": commit }));
+        }
+        if let Some(digest) = &self.source_digest {
+            properties.push(json!({ "name": "skill:source-digest", "value": digest }));
+        }
+        skill["properties"] = json!(properties);
+        if let Some(source) = &self.source {
+            skill["externalReferences"] = json!([{ "type": "distribution", "url": source }]);
+        }
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": {
+                "timestamp": Utc::now().to_rfc3339(),
+                "tools": [{ "name": "skill", "version": env!("CARGO_PKG_VERSION") }],
+                "component": skill,
+            },
+            "components": components,
+        })
+    }
+
+    /// SPDX 2.3 JSON: one package for the skill containing every file.
+    pub fn to_spdx(&self) -> serde_json::Value {
+        let files: Vec<serde_json::Value> = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| {
+                let mut comment = vec![format!("kind: {}", file.kind.as_str())];
+                if let Some(interpreter) = &file.interpreter {
+                    comment.push(format!("interpreter: {interpreter}"));
+                }
+                if !file.urls.is_empty() {
+                    comment.push(format!("urls: {}", file.urls.join(" ")));
+                }
+                json!({
+                    "SPDXID": format!("SPDXRef-File-{index}"),
+                    "fileName": format!("./{}", file.path),
+                    "checksums": [{ "algorithm": "SHA256", "checksumValue": file.sha256 }],
+                    "fileTypes": [spdx_file_type(file.kind)],
+                    "comment": comment.join("; "),
+                })
+            })
+            .collect();
+        let relationships: Vec<serde_json::Value> = std::iter::once(json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": "SPDXRef-Package",
+        }))
+        .chain((0..self.files.len()).map(|index| {
+            json!({
+                "spdxElementId": "SPDXRef-Package",
+                "relationshipType": "CONTAINS",
+                "relatedSpdxElement": format!("SPDXRef-File-{index}"),
+            })
+        }))
+        .collect();
+
+        let supplier = self
+            .author
+            .as_ref()
+            .map_or("NOASSERTION".to_string(), |author| {
+                format!("Person: {author}")
+            });
+        let mut comment = self.tree_digest.clone();
+        if let Some(commit) = &self.commit {
+            comment.push_str(&format!("; commit {commit}"));
+        }
+
+        json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": self.name,
+            "documentNamespace": format!(
+                "https://spdx.org/spdxdocs/skill-{}-{}",
+                self.name,
+                self.tree_digest.trim_start_matches("tree-sha256:")
+            ),
+            "creationInfo": {
+                "created": Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                "creators": [format!("Tool: skill-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": [{
+                "SPDXID": "SPDXRef-Package",
+                "name": self.name,
+                "versionInfo": self.version.as_deref().unwrap_or("NOASSERTION"),
+                "supplier": supplier,
+                "downloadLocation": self.source.as_deref().unwrap_or("NOASSERTION"),
+                "filesAnalyzed": true,
+                "licenseDeclared": self.license.as_deref().unwrap_or("NOASSERTION"),
+                "description": self.description,
+                "comment": comment,
+            }],
+            "files": files,
+            "relationships": relationships,
+        })
+    }
+}
+
+fn describe_file(path: &str, bytes: &[u8]) -> BomFile {
+    let sha256 = format!("{:x}", Sha256::digest(bytes));
+    let text = (!bytes.contains(&0))
+        .then(|| std::str::from_utf8(bytes).ok())
+        .flatten();
+    let Some(text) = text else {
+        return BomFile {
+            path: path.to_string(),
+            sha256,
+            size: bytes.len() as u64,
+            kind: FileKind::Binary,
+            interpreter: None,
+            urls: Vec::new(),
+        };
+    };
+
+    let interpreter = shebang_interpreter(text).or_else(|| extension_interpreter(path));
+    let kind = if interpreter.is_some() {
+        FileKind::Script
+    } else if path.to_ascii_lowercase().ends_with(".md") {
+        FileKind::Markdown
+    } else {
+        FileKind::Text
+    };
+    let urls: BTreeSet<String> = URL_PATTERN
+        .find_iter(text)
+        .map(|found| {
+            found
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ':'])
+                .to_string()
+        })
+        .collect();
+    BomFile {
+        path: path.to_string(),
+        sha256,
+        size: bytes.len() as u64,
+        kind,
+        interpreter,
+        urls: urls.into_iter().collect(),
+    }
+}
+
+/// `python3` for `#!/usr/bin/env python3`, `bash` for `#!/bin/bash -e`.
+fn shebang_interpreter(text: &str) -> Option<String> {
+    let line = text.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let program = words.next()?;
+    let program = program.rsplit('/').next().unwrap_or(program);
+    let interpreter = if program == "env" {
+        words.find(|word| !word.starts_with('-'))?
+    } else {
+        program
+    };
+    Some(interpreter.to_string())
+}
+
+fn extension_interpreter(path: &str) -> Option<String> {
+    let ext = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    let interpreter = match ext.as_str() {
+        "sh" => "sh",
+        "bash" => "bash",
+        "zsh" => "zsh",
+        "py" => "python",
+        "js" | "mjs" | "cjs" => "node",
+        "ts" => "ts-node",
+        "rb" => "ruby",
+        "pl" => "perl",
+        "ps1" => "powershell",
+        "bat" | "cmd" => "cmd",
+        _ => return None,
+    };
+    Some(interpreter.to_string())
+}
+
+fn spdx_file_type(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::Markdown => "DOCUMENTATION",
+        FileKind::Script => "SOURCE",
+        FileKind::Text => "TEXT",
+        FileKind::Binary => "BINARY",
+    }
+}
@@ -0,0 +1,226 @@
+use crate::paths::ensure_dir;
+use anyhow::{anyhow, Context, Result};
+use bytesize::ByteSize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const ARCHIVES_DIR_NAME: &str = "archives";
+
+/// On-disk cache of downloaded skill archives, keyed by source URL.
+#[derive(Debug, Clone)]
+pub struct DownloadCache {
+    dir: PathBuf,
+    limits: CacheLimits,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheLimits {
+    pub max_size: Option<u64>,
+    pub max_age: Option<Duration>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheMeta {
+    pub url: String,
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// ETag of the download left unfinished in the archive's `.part` file, which the
+    /// next download of the URL resumes when the server still serves that version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial_etag: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PruneSummary {
+    pub removed: usize,
+    pub freed_bytes: u64,
+    pub remaining_bytes: u64,
+}
+
+struct CacheEntry {
+    files: Vec<PathBuf>,
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl DownloadCache {
+    pub fn new(cache_dir: &Path, limits: CacheLimits) -> Self {
+        Self {
+            dir: cache_dir.join(ARCHIVES_DIR_NAME),
+            limits,
+        }
+    }
+
+    pub fn archive_path(&self, url: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.{extension}", cache_key(url)))
+    }
+
+    fn meta_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", cache_key(url)))
+    }
+
+    pub fn ensure(&self) -> Result<()> {
+        ensure_dir(&self.dir)
+    }
+
+    pub fn load_meta(&self, url: &str) -> Option<CacheMeta> {
+        let contents = fs::read_to_string(self.meta_path(url)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save_meta(&self, meta: &CacheMeta) -> Result<()> {
+        let path = self.meta_path(&meta.url);
+        let contents = serde_json::to_string_pretty(meta)?;
+        fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Marks a cached archive as recently used so LRU eviction keeps it.
+    pub fn touch(&self, path: &Path) -> Result<()> {
+        let file = File::options()
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        file.set_modified(SystemTime::now())
+            .with_context(|| format!("failed to update {}", path.display()))
+    }
+
+    /// Applies the configured limits; called automatically after each download.
+    pub fn enforce_limits(&self) -> Result<PruneSummary> {
+        self.prune(self.limits)
+    }
+
+    /// Evicts entries older than `max_age`, then least-recently-used entries until
+    /// the cache fits in `max_size`.
+    pub fn prune(&self, limits: CacheLimits) -> Result<PruneSummary> {
+        let mut summary = PruneSummary::default();
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|entry| entry.last_used);
+        let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+        let now = SystemTime::now();
+
+        for entry in entries {
+            let expired = limits.max_age.is_some_and(|max_age| {
+                now.duration_since(entry.last_used)
+                    .is_ok_and(|age| age > max_age)
+            });
+            let over_size = limits.max_size.is_some_and(|max_size| total > max_size);
+            if !expired && !over_size {
+                continue;
+            }
+
+            for file in &entry.files {
+                // Concurrent downloads may prune the same entry; a vanished file is fine.
+                if let Err(err) = fs::remove_file(file)
+                    && err.kind() != io::ErrorKind::NotFound
+                {
+                    return Err(err)
+                        .with_context(|| format!("failed to remove {}", file.display()));
+                }
+            }
+            total -= entry.size;
+            summary.removed += 1;
+            summary.freed_bytes += entry.size;
+        }
+
+        summary.remaining_bytes = total;
+        Ok(summary)
+    }
+
+    fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries: Vec<(String, CacheEntry)> = Vec::new();
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to read {}", self.dir.display()))?
+        {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let Some(key) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('.').next())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            let metadata = entry.metadata()?;
+            let modified = metadata.modified()?;
+
+            match entries.iter_mut().find(|(existing, _)| existing == &key) {
+                Some((_, cache_entry)) => {
+                    cache_entry.files.push(path);
+                    cache_entry.size += metadata.len();
+                    cache_entry.last_used = cache_entry.last_used.max(modified);
+                }
+                None => entries.push((
+                    key,
+                    CacheEntry {
+                        files: vec![path],
+                        size: metadata.len(),
+                        last_used: modified,
+                    },
+                )),
+            }
+        }
+
+        Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+    }
+}
+
+impl CacheLimits {
+    pub fn parse(max_size: Option<&str>, max_age: Option<&str>) -> Result<Self> {
+        let max_size = max_size
+            .map(|value| {
+                value
+                    .parse::<ByteSize>()
+                    .map(|size| size.as_u64())
+                    .map_err(|err| anyhow!("invalid size '{value}': {err}"))
+            })
+            .transpose()?;
+        let max_age = max_age.map(parse_duration).transpose()?;
+        Ok(Self { max_size, max_age })
+    }
+}
+
+/// Parses durations such as `30d`, `12h`, `45m`, or `90s`.
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| anyhow!("invalid duration '{value}'"))?;
+    let seconds = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" | "" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(anyhow!(
+                "invalid duration unit in '{value}'. Use s, m, h, d, or w."
+            ))
+        }
+    };
+    let seconds = number
+        .checked_mul(seconds)
+        .ok_or_else(|| anyhow!("duration '{value}' is too large"))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+fn cache_key(url: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(url.as_bytes()));
+    digest[..32].to_string()
+}
@@ -0,0 +1,189 @@
+use crate::validation;
+use anyhow::Result;
+use bytesize::ByteSize;
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// First line of every generated catalog, used to avoid overwriting hand-written files.
+pub const GENERATED_MARKER: &str = "<!-- generated by `skill index`; do not edit -->";
+
+/// Whether `path` is a file `skill index` or `skill export-site` wrote.
+pub fn is_generated(path: &Path) -> bool {
+    let mut head = vec![0; GENERATED_MARKER.len()];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut head))
+        .is_ok_and(|_| head == GENERATED_MARKER.as_bytes())
+}
+
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub size: u64,
+    /// Skill directory relative to the catalog root.
+    pub path: PathBuf,
+}
+
+/// Finds every skill (a directory with SKILL.md) under `root`, sorted by name.
+/// Skills whose frontmatter doesn't parse are reported and left out.
+pub fn collect(root: &Path) -> Result<Vec<CatalogEntry>> {
+    let mut entries = Vec::new();
+    for entry in WalkDir::new(root).follow_links(false) {
+        let entry = entry?;
+        let rel_path = entry.path().strip_prefix(root)?;
+        if !entry.file_type().is_file()
+            || entry.file_name() != "SKILL.md"
+            || crate::commands::should_skip(rel_path)
+        {
+            continue;
+        }
+        let Some(skill_dir) = entry.path().parent() else {
+            continue;
+        };
+        let frontmatter = match validation::read_frontmatter(skill_dir) {
+            Ok(frontmatter) => frontmatter,
+            Err(err) => {
+                eprintln!("Warning: skipping {}: {err}", skill_dir.display());
+                continue;
+            }
+        };
+        entries.push(CatalogEntry {
+            tags: frontmatter.tags(),
+            name: frontmatter.name,
+            description: frontmatter.description,
+            size: crate::commands::skill_size(skill_dir)?,
+            path: skill_dir.strip_prefix(root)?.to_path_buf(),
+        });
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+pub fn render_markdown(title: &str, entries: &[CatalogEntry]) -> String {
+    let mut output = format!("{GENERATED_MARKER}\n# {title}\n\n");
+    output.push_str(&format!("{} skill(s).\n\n", entries.len()));
+    output.push_str("| Name | Description | Tags | Size |\n");
+    output.push_str("| --- | --- | --- | --- |\n");
+    for entry in entries {
+        output.push_str(&format!(
+            "| [{}]({}) | {} | {} | {} |\n",
+            entry.name,
+            link_path(&entry.path.join("SKILL.md")),
+            markdown_cell(&entry.description),
+            markdown_cell(&entry.tags.join(", ")),
+            ByteSize(entry.size)
+        ));
+    }
+    output
+}
+
+pub fn render_html(title: &str, entries: &[CatalogEntry]) -> String {
+    let body = format!(
+        "<h1>{}</h1>\n<p>{} skill(s).</p>\n{}",
+        html_escape(title),
+        entries.len(),
+        html_table(entries, |entry| link_path(&entry.path.join("SKILL.md")))
+    );
+    format!("{GENERATED_MARKER}\n{}", html_page(title, &body))
+}
+
+/// Renders Markdown to HTML. Skills are untrusted, so raw HTML is shown as text and
+/// `javascript:` links are neutralized.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS,
+    )
+    .map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) if is_script_url(&dest_url) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: "#".into(),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) if is_script_url(&dest_url) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: "#".into(),
+            title,
+            id,
+        }),
+        other => other,
+    });
+    let mut output = String::new();
+    html::push_html(&mut output, parser);
+    output
+}
+
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2rem auto;max-width:960px;padding:0 1rem}table{border-collapse:collapse;width:100%}th,td{border-bottom:1px solid #ddd;padding:.4rem;text-align:left;vertical-align:top}#search{font-size:1rem;padding:.4rem;width:100%}dt{font-weight:bold}pre{background:#f5f5f5;overflow:auto;padding:.6rem}";
+
+pub(crate) fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        html_escape(title)
+    )
+}
+
+pub(crate) fn html_table(
+    entries: &[CatalogEntry],
+    href: impl Fn(&CatalogEntry) -> String,
+) -> String {
+    let mut table = String::from(
+        "<table>\n<thead><tr><th>Name</th><th>Description</th><th>Tags</th><th>Size</th></tr></thead>\n<tbody>\n",
+    );
+    for entry in entries {
+        let tags = entry.tags.join(", ");
+        let search = format!("{} {} {tags}", entry.name, entry.description).to_lowercase();
+        table.push_str(&format!(
+            "<tr data-search=\"{}\"><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            html_escape(&search),
+            html_escape(&href(entry)),
+            html_escape(&entry.name),
+            html_escape(&entry.description),
+            html_escape(&tags),
+            ByteSize(entry.size)
+        ));
+    }
+    table.push_str("</tbody>\n</table>\n");
+    table
+}
+
+fn is_script_url(url: &str) -> bool {
+    let url = url.trim_start().to_ascii_lowercase();
+    url.starts_with("javascript:")
+        || url.starts_with("vbscript:")
+        || url.starts_with("data:text/html")
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn link_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
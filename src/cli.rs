@@ -1,48 +1,204 @@
 use crate::assistant::Assistant;
+use crate::bom::BomFormat;
+use crate::config::DefaultAssistant;
+use crate::progress::ProgressMode;
+use crate::report::{CheckReport, ReportFormat};
+use anyhow::{anyhow, Result};
 use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 #[derive(Parser, Debug)]
 #[command(name = "skill", version, about = "Manage Agent Skills", long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
+    #[arg(
+        long,
+        global = true,
+        help = "Ignore `defaults` from config for this run"
+    )]
+    pub no_defaults: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Stable, tab-separated output for scripts (list, search, scan, validate, verify)"
+    )]
+    pub porcelain: bool,
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "porcelain",
+        help = "Print results as one JSON document (paths, list, show, stats, search, scan, validate, verify, and more)"
+    )]
+    pub json: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "MODE",
+        default_value = "auto",
+        help = "Progress on stderr: auto (a status line on a terminal), json (NDJSON events), or none"
+    )]
+    pub progress: ProgressMode,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
     Paths(PathsCommand),
+    Env(EnvCommand),
     Add(AddCommand),
     Remove(RemoveCommand),
+    Prune(PruneCommand),
+    Gc(PruneCommand),
+    Update(UpdateCommand),
+    Upgrade(UpgradeCommand),
+    Outdated(OutdatedCommand),
+    Diff(DiffCommand),
+    Compare(CompareCommand),
+    Sync(SyncCommand),
+    Workspace(WorkspaceCommand),
+    Verify(VerifyCommand),
+    Protect(ProtectCommand),
+    Unprotect(ProtectCommand),
+    Pin(ProtectCommand),
+    Unpin(ProtectCommand),
     List(ListCommand),
     Show(ShowCommand),
+    Bom(BomCommand),
     Default(DefaultCommand),
     Stats(StatsCommand),
+    Rank(RankCommand),
+    Report(ReportCommand),
     Search(SearchCommand),
     Scan(ScanCommand),
     Validate(ValidateCommand),
+    Explain(ExplainCommand),
     MarkUsed(MarkUsedCommand),
+    Convert(ConvertCommand),
+    New(NewCommand),
+    Pack(PackCommand),
+    Export(ExportCommand),
+    Publish(PublishCommand),
+    VerifyPack(VerifyPackCommand),
+    #[command(visible_alias = "trust")]
+    Keys(KeysCommand),
+    Index(IndexCommand),
+    ExportSite(ExportSiteCommand),
+    Shadows(ShadowsCommand),
+    MigrateLayout(MigrateLayoutCommand),
+    Backup(BackupCommand),
+    Restore(BackupRestoreCommand),
+    Cache(CacheCommand),
+    Rules(RulesCommand),
+    CleanDownloads(CleanDownloadsCommand),
+    Trash(TrashCommand),
+    Journal(JournalCommand),
+    Doctor(DoctorCommand),
+}
+
+/// Commands that print a JSON document under `--json`, for the error other commands give.
+pub const JSON_COMMANDS: &str = "paths, env, add, outdated, compare, list, show, bom, stats, rank, report, search, scan, validate, verify, explain, keys list, workspace status, and rules list";
+
+impl Command {
+    pub fn supports_json(&self) -> bool {
+        match self {
+            Command::Paths(_)
+            | Command::Env(_)
+            | Command::Add(_)
+            | Command::List(_)
+            | Command::Show(_)
+            | Command::Bom(_)
+            | Command::Outdated(_)
+            | Command::Compare(_)
+            | Command::Stats(_)
+            | Command::Rank(_)
+            | Command::Report(_)
+            | Command::Search(_)
+            | Command::Scan(_)
+            | Command::Validate(_)
+            | Command::Verify(_)
+            | Command::Explain(_) => true,
+            Command::Keys(cmd) => matches!(cmd.action, KeysAction::List),
+            Command::Workspace(cmd) => matches!(cmd.action, WorkspaceAction::Status(_)),
+            Command::Rules(cmd) => matches!(cmd.action, RulesAction::List),
+            _ => false,
+        }
+    }
+
+    /// `--jobs` of the commands that take it.
+    pub fn jobs(&self) -> Option<usize> {
+        match self {
+            Command::Add(cmd) => cmd.jobs.jobs,
+            Command::Update(cmd) => cmd.jobs.jobs,
+            Command::Upgrade(cmd) => cmd.jobs.jobs,
+            Command::Sync(cmd) => cmd.jobs.jobs,
+            Command::Verify(cmd) => cmd.jobs.jobs,
+            _ => None,
+        }
+    }
+}
+
+/// `--jobs`, shared by the commands that fetch, copy or hash several skills at once.
+#[derive(Args, Debug, Clone, Default)]
+pub struct JobsArgs {
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Worker threads for fetching, copying and hashing (default: `jobs` from config, else one per CPU)"
+    )]
+    pub jobs: Option<usize>,
 }
 
 #[derive(Args, Debug, Clone, Default)]
 pub struct AssistantArgs {
-    #[arg(long, conflicts_with_all = ["claudecode", "opencode"]) ]
+    #[arg(
+        short = 'a',
+        long = "assistant",
+        value_name = "NAME",
+        conflicts_with_all = ["codex", "claudecode", "opencode"],
+        help = "Assistant to act on: codex, claudecode, or opencode (repeat for commands that read several)"
+    )]
+    pub assistants: Vec<Assistant>,
+    #[arg(long, conflicts_with_all = ["claudecode", "opencode"], help = "Same as --assistant codex")]
     pub codex: bool,
-    #[arg(long, conflicts_with_all = ["codex", "opencode"]) ]
+    #[arg(long, conflicts_with_all = ["codex", "opencode"], help = "Same as --assistant claudecode")]
     pub claudecode: bool,
-    #[arg(long, conflicts_with_all = ["codex", "claudecode"]) ]
+    #[arg(long, conflicts_with_all = ["codex", "claudecode"], help = "Same as --assistant opencode")]
     pub opencode: bool,
 }
 
 impl AssistantArgs {
-    pub fn selected(&self) -> Option<Assistant> {
-        if self.codex {
-            Some(Assistant::Codex)
-        } else if self.claudecode {
-            Some(Assistant::ClaudeCode)
-        } else if self.opencode {
-            Some(Assistant::OpenCode)
-        } else {
-            None
+    /// Every assistant named on the command line, in order and without repeats.
+    pub fn selected_all(&self) -> Vec<Assistant> {
+        let flags = [
+            (self.codex, Assistant::Codex),
+            (self.claudecode, Assistant::ClaudeCode),
+            (self.opencode, Assistant::OpenCode),
+        ];
+        let mut selected: Vec<Assistant> = Vec::new();
+        let named = self.assistants.iter().copied();
+        let flagged = flags.into_iter().filter(|(set, _)| *set).map(|(_, a)| a);
+        for assistant in named.chain(flagged) {
+            if !selected.contains(&assistant) {
+                selected.push(assistant);
+            }
+        }
+        selected
+    }
+
+    /// The assistant named on the command line, for commands that act on one.
+    pub fn selected(&self) -> Result<Option<Assistant>> {
+        match self.selected_all().as_slice() {
+            [] => Ok(None),
+            [assistant] => Ok(Some(*assistant)),
+            several => Err(anyhow!(
+                "this command works on one assistant, but {} were given",
+                several
+                    .iter()
+                    .map(|assistant| assistant.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
         }
     }
 }
@@ -53,19 +209,101 @@ pub struct PathsCommand {
     pub assistant: AssistantArgs,
 }
 
+#[derive(Args, Debug)]
+pub struct EnvCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+}
+
 #[derive(Args, Debug)]
 pub struct AddCommand {
     #[command(flatten)]
     pub assistant: AssistantArgs,
+    #[command(flatten)]
+    pub jobs: JobsArgs,
     pub source: String,
     #[arg(
         long,
         value_name = "NAME",
-        help = "Select a skill directory inside a repo or archive"
+        help = "Select a skill directory inside a repo or archive (repeat to install several)"
     )]
-    pub skill: Option<String>,
+    pub skill: Vec<String>,
+    #[arg(
+        long,
+        conflicts_with = "skill",
+        help = "Install every skill found in the repo or archive"
+    )]
+    pub all: bool,
+    #[arg(
+        long = "ref",
+        value_name = "REF",
+        help = "Branch, tag or full commit ID to fetch from a git source (like `owner/repo@REF`)"
+    )]
+    pub reference: Option<String>,
     #[arg(long, help = "Skip confirmation prompts")]
     pub yes: bool,
+    #[arg(
+        long,
+        conflicts_with = "list",
+        help = "Fetch, validate and scan, then report what would be installed without installing it"
+    )]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        visible_alias = "overwrite",
+        help = "Replace a skill that is already installed, restoring it if the install fails"
+    )]
+    pub force: bool,
+    #[arg(
+        long,
+        help = "Require the fetched git HEAD commit to be signed by a trusted key"
+    )]
+    pub require_signed_commits: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "SSH allowed-signers file used to verify commit signatures"
+    )]
+    pub allowed_signers: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Refuse unsigned content: archives need a <archive>.sig by a trusted key, git sources a signed HEAD commit"
+    )]
+    pub verify: bool,
+    #[arg(
+        long,
+        help = "Install into the machine-wide system root (needs admin permissions)"
+    )]
+    pub system: bool,
+    #[arg(
+        long,
+        help = "Install a skill name locked to another source from this one, and lock it here"
+    )]
+    pub retarget: bool,
+    #[arg(
+        long,
+        conflicts_with = "system",
+        help = "Keep the skill once in the shared store and link it into the assistant's root"
+    )]
+    pub link: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["skill", "all", "system", "link"],
+        help = "Download an archive source and list its entries without installing"
+    )]
+    pub list: bool,
+    #[arg(
+        long,
+        value_name = "PASSWORD",
+        help = "Password for an encrypted zip archive (prompted for on a terminal otherwise)"
+    )]
+    pub archive_password: Option<String>,
+    #[arg(
+        long,
+        value_name = "DIGEST",
+        help = "Expected SHA-256 of the archive source ([sha256:]<hex>); refuse to extract anything else"
+    )]
+    pub sha256: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -75,52 +313,1038 @@ pub struct RemoveCommand {
     pub name: String,
     #[arg(long, help = "Skip confirmation prompts")]
     pub yes: bool,
+    #[arg(long, help = "Report what would be removed without removing it")]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        help = "Remove from the machine-wide system root (needs admin permissions)"
+    )]
+    pub system: bool,
+    #[arg(long, help = "Allow changing a skill marked with `skill protect`")]
+    pub force_protected: bool,
 }
 
 #[derive(Args, Debug)]
-pub struct ListCommand {
+pub struct UpdateCommand {
     #[command(flatten)]
     pub assistant: AssistantArgs,
+    #[command(flatten)]
+    pub jobs: JobsArgs,
+    pub name: String,
+    #[arg(
+        long,
+        help = "Skip confirmation prompts (fails if the skill has local modifications)"
+    )]
+    pub yes: bool,
+    #[arg(
+        long,
+        help = "Fetch, validate and scan the source, then report what would change without updating"
+    )]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        help = "Update a skill installed with `add --system` (needs admin permissions)"
+    )]
+    pub system: bool,
+    #[arg(long, help = "Allow changing a skill marked with `skill protect`")]
+    pub force_protected: bool,
 }
 
 #[derive(Args, Debug)]
-pub struct ShowCommand {
+pub struct PruneCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(long, help = "List what would be removed without changing anything")]
+    pub dry_run: bool,
+    #[arg(long, help = "Skip the confirmation prompt")]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct UpgradeCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[command(flatten)]
+    pub jobs: JobsArgs,
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub names: Vec<String>,
+    #[arg(long, help = "Upgrade every skill with a recorded source")]
+    pub all: bool,
+    #[arg(long, help = "Report what would be upgraded without changing anything")]
+    pub dry_run: bool,
+    #[arg(long, help = "Overwrite skills that have local modifications")]
+    pub force: bool,
+    #[arg(long, help = "Skip confirmation prompts")]
+    pub yes: bool,
+    #[arg(long, help = "Allow changing a skill marked with `skill protect`")]
+    pub force_protected: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct OutdatedCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(help = "Skills to check (default: every skill with a recorded source)")]
+    pub names: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffCommand {
     #[command(flatten)]
     pub assistant: AssistantArgs,
     pub name: String,
+    #[arg(
+        value_name = "SOURCE",
+        conflicts_with = "against",
+        help = "Path, git URL, archive or shorthand to compare with (default: the recorded source)"
+    )]
+    pub source: Option<String>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "source",
+        help = "Skill subdirectory inside SOURCE"
+    )]
+    pub skill: Option<String>,
+    #[arg(
+        long,
+        value_name = "ASSISTANT",
+        help = "Compare with this assistant's installed copy instead of a source"
+    )]
+    pub against: Option<Assistant>,
 }
 
 #[derive(Args, Debug)]
-pub struct DefaultCommand {
-    pub assistant: Assistant,
+pub struct CompareCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    pub name: String,
+    #[arg(
+        value_name = "SOURCE",
+        help = "Path, git URL, archive or shorthand of the candidate replacement"
+    )]
+    pub source: String,
+    #[arg(long, value_name = "NAME", help = "Skill subdirectory inside SOURCE")]
+    pub skill: Option<String>,
+    #[arg(long, help = "Only print the summary, without the content diff")]
+    pub stat: bool,
 }
 
 #[derive(Args, Debug)]
-pub struct StatsCommand {
+pub struct SyncCommand {
+    #[arg(
+        long,
+        value_name = "ASSISTANT",
+        help = "Assistant whose skills are copied"
+    )]
+    pub from: Assistant,
+    #[arg(
+        long,
+        value_name = "ASSISTANT",
+        help = "Assistant that receives the copies"
+    )]
+    pub to: Assistant,
+    #[arg(help = "Skills to sync (default: every skill of --from)")]
+    pub names: Vec<String>,
+    #[arg(long, help = "Also replace skills whose content differs")]
+    pub update: bool,
+    #[arg(long, help = "Report what would be copied without changing anything")]
+    pub dry_run: bool,
+    #[arg(long, help = "Skip confirmation prompts")]
+    pub yes: bool,
+    #[arg(long, help = "Allow replacing a skill marked with `skill protect`")]
+    pub force_protected: bool,
+    #[command(flatten)]
+    pub jobs: JobsArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct WorkspaceCommand {
+    #[command(subcommand)]
+    pub action: WorkspaceAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceAction {
+    /// Use a local checkout of a skills repo as the source of truth.
+    Init(WorkspaceInitCommand),
+    /// Show whether each workspace skill is installed and current per assistant.
+    Status(WorkspaceStatusCommand),
+    /// Install missing workspace skills and update outdated ones from the working tree.
+    Sync(WorkspaceSyncCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct WorkspaceInitCommand {
+    #[arg(value_name = "REPO_DIR")]
+    pub dir: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct WorkspaceStatusCommand {
     #[command(flatten)]
     pub assistant: AssistantArgs,
+    #[arg(
+        value_name = "REPO_DIR",
+        help = "Workspace checkout (default: the one set with `skill workspace init`)"
+    )]
+    pub dir: Option<PathBuf>,
 }
 
 #[derive(Args, Debug)]
-pub struct SearchCommand {
+pub struct WorkspaceSyncCommand {
     #[command(flatten)]
     pub assistant: AssistantArgs,
-    pub query: String,
+    #[arg(
+        value_name = "REPO_DIR",
+        help = "Workspace checkout (default: the one set with `skill workspace init`)"
+    )]
+    pub dir: Option<PathBuf>,
+    #[arg(
+        long = "skill",
+        value_name = "NAME",
+        help = "Only sync this skill (repeat for several)"
+    )]
+    pub names: Vec<String>,
+    #[arg(long, help = "Report what would change without changing anything")]
+    pub dry_run: bool,
+    #[arg(long, help = "Skip confirmation prompts")]
+    pub yes: bool,
+    #[arg(long, help = "Overwrite installed copies that were edited in place")]
+    pub force: bool,
 }
 
 #[derive(Args, Debug)]
-pub struct ScanCommand {
-    pub path: String,
+pub struct VerifyCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[command(flatten)]
+    pub jobs: JobsArgs,
+    #[arg(help = "Skills to verify (default: every skill with an install record)")]
+    pub names: Vec<String>,
+    #[arg(
+        long,
+        help = "Also re-fetch each source (or use the cache) and check it still serves the installed content"
+    )]
+    pub source: bool,
+    #[arg(
+        long,
+        help = "Restore modified and deleted files from the download cache or the recorded source"
+    )]
+    pub repair: bool,
+    #[arg(long, requires = "repair", help = "Repair without asking")]
+    pub yes: bool,
 }
 
 #[derive(Args, Debug)]
-pub struct ValidateCommand {
-    pub path: String,
+pub struct ProtectCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    pub name: String,
 }
 
 #[derive(Args, Debug)]
-pub struct MarkUsedCommand {
+pub struct ListCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Only list skills whose author contains NAME"
+    )]
+    pub author: Option<String>,
+    #[arg(
+        long,
+        value_name = "CATEGORY",
+        num_args = 0..=1,
+        help = "Group skills by category, or only list skills in CATEGORY"
+    )]
+    pub category: Option<Option<String>>,
+    #[arg(
+        long,
+        value_name = "ACTION",
+        conflicts_with_all = ["author", "category", "diff"],
+        help = "`save`: record names, versions, and hashes of the listed skills"
+    )]
+    pub snapshot: Option<SnapshotAction>,
+    #[arg(
+        long,
+        value_name = "SNAPSHOT",
+        conflicts_with_all = ["author", "category"],
+        help = "Print a JSON report of skills added, removed, or changed since SNAPSHOT (a file, a saved snapshot name, or `latest`)"
+    )]
+    pub diff: Option<String>,
+    #[arg(
+        short,
+        long,
+        help = "Also show each skill's install date, source digest, and source"
+    )]
+    pub long: bool,
+    #[arg(
+        long,
+        value_name = "KEY",
+        conflicts_with_all = ["snapshot", "diff"],
+        help = "Group skills by namespace (name up to the first '-'), category, or tag, with counts and sizes"
+    )]
+    pub group_by: Option<GroupBy>,
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["json", "porcelain", "snapshot", "diff", "long", "group_by"],
+        help = "Print each skill with a template like '{name}\\t{version}\\t{path}' (fields of --json), or one named under `templates` in config"
+    )]
+    pub format: Option<String>,
+}
+
+/// How `list --group-by` sections skills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Namespace,
+    Category,
+    Tag,
+}
+
+impl GroupBy {
+    /// Section for skills the key doesn't apply to.
+    pub fn fallback(self) -> &'static str {
+        match self {
+            GroupBy::Namespace => "(no namespace)",
+            GroupBy::Category => "(uncategorized)",
+            GroupBy::Tag => "(untagged)",
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "namespace" => Ok(GroupBy::Namespace),
+            "category" => Ok(GroupBy::Category),
+            "tag" | "tags" => Ok(GroupBy::Tag),
+            _ => Err(format!(
+                "unknown grouping '{value}'. Use namespace, category, or tag."
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotAction {
+    Save,
+}
+
+impl FromStr for SnapshotAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "save" => Ok(SnapshotAction::Save),
+            _ => Err(format!("unknown snapshot action '{value}'. Use save.")),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ShowCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    pub name: String,
+    #[arg(
+        long,
+        value_name = "LANG",
+        help = "Show a localized variant (SKILL.<lang>.md)"
+    )]
+    pub lang: Option<String>,
+    #[arg(long, requires = "json", help = "Leave the body out of --json output")]
+    pub frontmatter_only: bool,
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["json", "porcelain"],
+        help = "Print the skill with a template like '{version}\\t{install.source}' (fields of --json), or one named under `templates` in config"
+    )]
+    pub format: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct BomCommand {
     #[command(flatten)]
     pub assistant: AssistantArgs,
     pub name: String,
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value_t = BomFormat::CycloneDx,
+        help = "Output format: cyclonedx or spdx"
+    )]
+    pub format: BomFormat,
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Write the inventory to FILE instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DefaultCommand {
+    #[arg(
+        value_name = "ASSISTANT",
+        help = "codex, claudecode, opencode, or all (with --command, for commands that read several)"
+    )]
+    pub assistant: Option<DefaultAssistant>,
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "Set or unset the default for one command only (e.g. list, add)"
+    )]
+    pub command: Option<String>,
+    #[arg(long, conflicts_with = "assistant", help = "Clear the default")]
+    pub unset: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct StatsCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(long, help = "Aggregate size, count, and usage by tag and category")]
+    pub by_tag: bool,
+    #[arg(
+        long,
+        conflicts_with = "by_tag",
+        help = "Break down size by file type (markdown, scripts, images, data, binary) per skill"
+    )]
+    pub files_by_type: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RankCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "N",
+        help = "Show only the N highest-ranked skills"
+    )]
+    pub limit: Option<usize>,
+    #[arg(
+        long,
+        help = "Write the full ranking to .skill-rank.json in each assistant's skills root"
+    )]
+    pub write: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SearchCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    pub query: String,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Only match skills whose author contains NAME"
+    )]
+    pub author: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["assistants", "codex", "claudecode", "opencode"],
+        help = "Search every assistant's roots, whatever the defaults say"
+    )]
+    pub all_assistants: bool,
+    #[arg(
+        long,
+        help = "Show a skill installed identically for several assistants as one result"
+    )]
+    pub dedupe: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ScanCommand {
+    #[arg(
+        default_value = ".",
+        help = "Directory to scan, or a URL, git source, shorthand or archive to fetch and scan without installing"
+    )]
+    pub path: String,
+    #[arg(long, value_name = "NAME", help = "Only scan this skill inside PATH")]
+    pub skill: Option<String>,
+    #[arg(long, help = "Also report findings suppressed by .skillcheck.yaml")]
+    pub verbose: bool,
+    #[arg(
+        long,
+        help = "Only scan files staged in git that belong to a skill directory (for pre-commit hooks)"
+    )]
+    pub staged: bool,
+    #[arg(
+        long,
+        value_name = "REF",
+        conflicts_with = "staged",
+        help = "Only scan files changed since REF that belong to a skill directory"
+    )]
+    pub since: Option<String>,
+    #[command(flatten)]
+    pub report: ReportFileArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ValidateCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(
+        help = "Skill directory, or a URL, git source, shorthand or archive to fetch and validate without installing"
+    )]
+    pub path: String,
+    #[arg(long, value_name = "NAME", help = "Validate this skill inside PATH")]
+    pub skill: Option<String>,
+    #[arg(long, help = "Also report findings suppressed by .skillcheck.yaml")]
+    pub verbose: bool,
+    #[arg(long, help = "Enforce house-style checks such as required sections")]
+    pub strict: bool,
+    #[command(flatten)]
+    pub report: ReportFileArgs,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct ReportFileArgs {
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Also write the findings to FILE (SARIF for *.sarif, JSON otherwise)"
+    )]
+    pub report_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        requires = "report_file",
+        help = "Format for --report-file: json or sarif"
+    )]
+    pub report_format: Option<ReportFormat>,
+}
+
+impl ReportFileArgs {
+    /// Writes `report` when `--report-file` was given.
+    pub fn write(&self, report: &CheckReport) -> anyhow::Result<()> {
+        let Some(path) = &self.report_file else {
+            return Ok(());
+        };
+        let format = self
+            .report_format
+            .unwrap_or_else(|| ReportFormat::for_path(path));
+        report.write(path, format)
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ExplainCommand {
+    #[arg(
+        value_name = "RULE",
+        help = "Rule ID from validate or scan output (omit to list all rules)"
+    )]
+    pub rule: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportCommand {
+    #[arg(
+        long,
+        value_name = "YYYY-MM",
+        help = "Month to summarize (default: the current month)"
+    )]
+    pub month: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct MarkUsedCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    pub name: String,
+    #[arg(
+        long,
+        value_name = "TIMESTAMP",
+        help = "Record the use at TIMESTAMP (RFC 3339, or YYYY-MM-DD for midnight UTC) instead of now, for backfilled imports"
+    )]
+    pub at: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ConvertCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    pub path: String,
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "DIR",
+        help = "Directory to write the converted skill into"
+    )]
+    pub output: String,
+}
+
+#[derive(Args, Debug)]
+pub struct PublishCommand {
+    pub path: String,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Registry from `registries` in config to publish into"
+    )]
+    pub registry: String,
+    #[arg(long, help = "Check the name policy without publishing")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PackCommand {
+    pub path: String,
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "FILE",
+        help = "Archive to write (default: <name>.tar.gz)"
+    )]
+    pub output: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Write a detached SSH signature next to the pack (<pack>.sig)"
+    )]
+    pub sign: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "sign",
+        help = "SSH private key used with --sign (default: signing_key from config)"
+    )]
+    pub key: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    pub name: String,
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "FILE",
+        help = "Archive to write (default: <name>.tar.gz)"
+    )]
+    pub output: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Write a detached SSH signature next to the archive (<archive>.sig)"
+    )]
+    pub sign: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "sign",
+        help = "SSH private key used with --sign (default: signing_key from config)"
+    )]
+    pub key: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyPackCommand {
+    pub pack: PathBuf,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Signature file (default: <pack>.sig)"
+    )]
+    pub signature: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "SSH allowed-signers file (default: allowed_signers_file from config)"
+    )]
+    pub allowed_signers: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct IndexCommand {
+    pub path: String,
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "FILE",
+        help = "File to write (default: README.md, or index.html with --html)"
+    )]
+    pub output: Option<PathBuf>,
+    #[arg(long, help = "Write a static HTML page instead of Markdown")]
+    pub html: bool,
+    #[arg(long, help = "Catalog heading (default: the directory name)")]
+    pub title: Option<String>,
+    #[arg(
+        long,
+        help = "Overwrite an output file that was not generated by skill index"
+    )]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportSiteCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "DIR",
+        default_value = "skills-site",
+        help = "Directory to write the site into"
+    )]
+    pub output: String,
+    #[arg(long, help = "Site heading (default: Installed skills)")]
+    pub title: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ShadowsCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+}
+
+#[derive(Args, Debug)]
+#[command(group(clap::ArgGroup::new("layout").required(true).multiple(true).args(["to", "from"])))]
+pub struct MigrateLayoutCommand {
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(
+        long,
+        value_name = "LAYOUT",
+        help = "Layout to move to and point config at: `managed` or `native` (default: the configured root)"
+    )]
+    pub to: Option<Layout>,
+    #[arg(
+        long,
+        value_name = "LAYOUT|DIR",
+        help = "Layout the skills are in now: `managed`, `native`, or a previous skills_base_dir (default: the configured root)"
+    )]
+    pub from: Option<Layout>,
+    #[arg(
+        long,
+        help = "Leave a symlink at each old location pointing to the moved skill"
+    )]
+    pub link: bool,
+    #[arg(long, help = "Show what would move without changing anything")]
+    pub dry_run: bool,
+    #[arg(long, help = "Skip the confirmation prompt")]
+    pub yes: bool,
+}
+
+/// Where an assistant's personal skills live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// `<skills base dir>/<assistant>`.
+    Managed,
+    /// The assistant's own skills directory, e.g. `~/.claude/skills`.
+    Native,
+    /// `<dir>/<assistant>`, for a skills base dir that is no longer configured.
+    BaseDir(PathBuf),
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "managed" => Ok(Layout::Managed),
+            "native" => Ok(Layout::Native),
+            "" => Err("expected managed, native, or a directory".to_string()),
+            dir => Ok(Layout::BaseDir(PathBuf::from(dir))),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct BackupCommand {
+    #[command(subcommand)]
+    pub action: Option<BackupAction>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Directory to write backups into (default: backup_dir in config, else <data dir>/backups)"
+    )]
+    pub target: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Keep only the newest N backups (default: backup_keep in config, else 7)"
+    )]
+    pub keep: Option<usize>,
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["target", "keep"],
+        help = "Write the backup to this file instead of a timestamped one in the backup dir (no rotation)"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupAction {
+    Restore(BackupRestoreCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct BackupRestoreCommand {
+    #[arg(
+        value_name = "ARCHIVE",
+        conflicts_with = "from",
+        help = "Backup archive to restore (same as --from)"
+    )]
+    pub archive: Option<PathBuf>,
+    #[command(flatten)]
+    pub assistant: AssistantArgs,
+    #[arg(
+        long,
+        help = "List backups, or the skills in the backup given with --from"
+    )]
+    pub list: bool,
+    #[arg(
+        long,
+        value_name = "BACKUP",
+        help = "Backup archive path or file name (default: the newest backup)"
+    )]
+    pub from: Option<PathBuf>,
+    #[arg(
+        long = "skill",
+        value_name = "NAME",
+        help = "Only restore this skill (repeatable; default: every skill in the backup)"
+    )]
+    pub skills: Vec<String>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Directory holding the backups (default: backup_dir in config, else <data dir>/backups)"
+    )]
+    pub target: Option<PathBuf>,
+    #[arg(long, help = "Replace skills that are already installed")]
+    pub force: bool,
+    #[arg(
+        long,
+        help = "Also replace the config file with the one in the backup, before placing skills"
+    )]
+    pub config: bool,
+    #[arg(long, help = "Skip confirmation prompts")]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CacheCommand {
+    #[command(subcommand)]
+    pub action: CacheAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    Prune(CachePruneCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct CachePruneCommand {
+    #[arg(
+        long,
+        value_name = "SIZE",
+        help = "Evict least-recently-used archives until the cache fits (e.g. 1GB)"
+    )]
+    pub max_size: Option<String>,
+    #[arg(
+        long,
+        value_name = "AGE",
+        help = "Evict archives not used within this period (e.g. 30d)"
+    )]
+    pub max_age: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct CleanDownloadsCommand {
+    #[arg(
+        long,
+        value_name = "AGE",
+        default_value = "1h",
+        help = "Only remove download dirs untouched for this long, so running installs keep theirs"
+    )]
+    pub older_than: String,
+    #[arg(long, help = "List what would be removed without changing anything")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct NewCommand {
+    pub name: String,
+    #[arg(
+        long,
+        value_name = "DIR",
+        default_value = ".",
+        help = "Directory to create the skill in"
+    )]
+    pub path: PathBuf,
+    #[arg(
+        long,
+        value_name = "TEXT",
+        help = "What the skill does and when to use it"
+    )]
+    pub description: Option<String>,
+    #[arg(long, value_name = "LICENSE")]
+    pub license: Option<String>,
+    #[arg(long, value_name = "TOOLS", help = "Value for the allowed-tools field")]
+    pub allowed_tools: Option<String>,
+    #[arg(long, help = "Create an empty scripts/ directory")]
+    pub scripts: bool,
+    #[arg(long, help = "Create an empty references/ directory")]
+    pub references: bool,
+    #[arg(
+        short,
+        long,
+        help = "Prompt for the description, license, and allowed-tools not given as flags"
+    )]
+    pub interactive: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct KeysCommand {
+    #[command(subcommand)]
+    pub action: KeysAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KeysAction {
+    /// Create an ed25519 signing identity for `skill pack --sign`.
+    Generate(KeysGenerateCommand),
+    /// List signing identities and trusted publisher keys.
+    List,
+    /// Print an identity's public key to share with people who verify your packs.
+    Export(KeysExportCommand),
+    /// Trust a publisher's public key for pack and commit verification.
+    #[command(visible_alias = "add")]
+    Trust(KeysTrustCommand),
+    /// Stop trusting every key of a publisher.
+    #[command(visible_alias = "remove")]
+    Untrust(KeysUntrustCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct KeysGenerateCommand {
+    pub name: String,
+    #[arg(
+        long,
+        value_name = "PRINCIPAL",
+        help = "Identity the key signs as, usually an email address (default: NAME)"
+    )]
+    pub principal: Option<String>,
+    #[arg(
+        long,
+        help = "Protect the private key with a passphrase (prompted for)"
+    )]
+    pub passphrase: bool,
+    #[arg(
+        long = "default",
+        help = "Use the key as signing_key even if one is already configured"
+    )]
+    pub make_default: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct KeysExportCommand {
+    pub name: String,
+    #[arg(
+        long,
+        short,
+        value_name = "FILE",
+        help = "Write the public key to FILE instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct KeysTrustCommand {
+    pub principal: String,
+    #[arg(
+        value_name = "PUBLIC_KEY_FILE",
+        help = "OpenSSH public key file, or - for stdin"
+    )]
+    pub key: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct KeysUntrustCommand {
+    pub principal: String,
+}
+
+#[derive(Args, Debug)]
+pub struct RulesCommand {
+    #[command(subcommand)]
+    pub action: RulesAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RulesAction {
+    /// Show the active detection rule pack and its rules.
+    List,
+    /// Fetch a newer detection rule pack without upgrading skill.
+    Update(RulesUpdateCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct RulesUpdateCommand {
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Fetch the pack from URL instead of `rules_url` or the project"
+    )]
+    pub url: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct TrashCommand {
+    #[command(subcommand)]
+    pub action: TrashAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashAction {
+    /// List removed skills still in the trash.
+    List,
+    /// Permanently delete trashed skills.
+    Empty(TrashEmptyCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct TrashEmptyCommand {
+    #[arg(
+        long,
+        value_name = "AGE",
+        help = "Only delete skills trashed longer ago than this (e.g. 30d)"
+    )]
+    pub older_than: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct JournalCommand {
+    #[command(subcommand)]
+    pub action: JournalAction,
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorCommand {
+    #[arg(
+        long,
+        help = "Create missing skills roots and drop usage counts of skills no longer installed"
+    )]
+    pub fix: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum JournalAction {
+    /// Show the steps of an interrupted operation
+    Status,
+    /// Finish the remaining steps of an interrupted operation
+    Resume,
+    /// Undo the completed steps of an interrupted operation
+    Rollback,
 }
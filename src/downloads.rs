@@ -0,0 +1,91 @@
+use crate::cache::PruneSummary;
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+/// Prefix of the temp dirs that hold downloaded and extracted sources, so
+/// `clean-downloads` can tell them apart from other programs' temp files.
+pub const DOWNLOAD_DIR_PREFIX: &str = "skill-download-";
+
+/// Temp dirs created by this process that may still exist.
+static IN_FLIGHT: Lazy<Mutex<Vec<PathBuf>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Creates a temp dir for a download. It is removed when dropped, or by the Ctrl-C
+/// handler if the run is interrupted first.
+pub fn temp_dir() -> Result<TempDir> {
+    let dir = tempfile::Builder::new()
+        .prefix(DOWNLOAD_DIR_PREFIX)
+        .tempdir()
+        .context("failed to create temp dir")?;
+    let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|err| err.into_inner());
+    in_flight.retain(|path| path.exists());
+    in_flight.push(dir.path().to_path_buf());
+    Ok(dir)
+}
+
+/// Removes in-flight temp dirs and exits on Ctrl-C, since an interrupted process never
+/// runs the `TempDir` destructors.
+pub fn install_interrupt_handler() {
+    let result = ctrlc::set_handler(|| {
+        let in_flight = IN_FLIGHT.lock().unwrap_or_else(|err| err.into_inner());
+        for path in in_flight.iter() {
+            let _ = fs::remove_dir_all(path);
+        }
+        eprintln!("Interrupted");
+        std::process::exit(130);
+    });
+    if let Err(err) = result {
+        eprintln!("Warning: failed to install the Ctrl-C handler: {err}");
+    }
+}
+
+#[derive(Debug)]
+pub struct StaleDownload {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Download temp dirs in `temp_root` untouched for longer than `min_age`, left behind
+/// by runs that were killed. The age keeps concurrent runs' dirs out of the sweep.
+pub fn find_stale(temp_root: &Path, min_age: Duration) -> Result<Vec<StaleDownload>> {
+    let now = SystemTime::now();
+    let mut stale = Vec::new();
+    for entry in fs::read_dir(temp_root)
+        .with_context(|| format!("failed to read {}", temp_root.display()))?
+    {
+        let entry = entry?;
+        let is_download = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(DOWNLOAD_DIR_PREFIX));
+        if !is_download || !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if now.duration_since(modified).unwrap_or_default() < min_age {
+            continue;
+        }
+        let path = entry.path();
+        stale.push(StaleDownload {
+            size: crate::commands::skill_size(&path)?,
+            path,
+        });
+    }
+    stale.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(stale)
+}
+
+pub fn remove_stale(stale: &[StaleDownload]) -> Result<PruneSummary> {
+    let mut summary = PruneSummary::default();
+    for download in stale {
+        fs::remove_dir_all(&download.path)
+            .with_context(|| format!("failed to remove {}", download.path.display()))?;
+        summary.removed += 1;
+        summary.freed_bytes += download.size;
+    }
+    Ok(summary)
+}
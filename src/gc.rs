@@ -0,0 +1,156 @@
+use crate::assistant::Assistant;
+use crate::batch::keep_chosen;
+use crate::config::Config;
+use crate::doctor;
+use crate::journal::Journal;
+use crate::manifest::InstallManifest;
+use crate::paths::AppPaths;
+use crate::trash::Trash;
+use crate::validation;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory in a skills root that `prune` and `gc` clean up.
+#[derive(Debug)]
+pub struct Prunable {
+    pub name: String,
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A prunable directory, with the assistant and skills root it was found in.
+pub type PruneTarget = (Assistant, PathBuf, Prunable);
+
+/// Directories in a skills root that `list` hides: empty leftovers, directories
+/// without SKILL.md, `.<name>.previous` copies left by an interrupted replace, and
+/// skills that fail validation.
+pub fn find_prunable(root: &Path) -> Result<Vec<Prunable>> {
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut prunable = Vec::new();
+    for entry in fs::read_dir(root).with_context(|| format!("failed to read {}", root.display()))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let reason = if name.starts_with('.') {
+            if !(name.ends_with(".previous") && name.len() > ".previous".len() + 1) {
+                continue;
+            }
+            "leftover from an interrupted replace".to_string()
+        } else if fs::read_dir(&path)?.next().is_none() {
+            "empty directory".to_string()
+        } else if !path.join("SKILL.md").is_file() {
+            "no SKILL.md".to_string()
+        } else {
+            match validation::validate_skill_dir(&path) {
+                Ok(report) => {
+                    let Some(error) = report
+                        .issues
+                        .iter()
+                        .find(|issue| issue.severity == validation::Severity::Error)
+                    else {
+                        continue;
+                    };
+                    format!("fails validation: {}", error.message)
+                }
+                Err(err) => format!("fails validation: {err}"),
+            }
+        };
+        prunable.push(Prunable { name, path, reason });
+    }
+    prunable.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(prunable)
+}
+
+/// [`find_prunable`] over each assistant's root, leaving out protected skills.
+pub fn find_prune_targets(
+    assistants: &[Assistant],
+    config: &Config,
+    paths: &AppPaths,
+    manifest: &InstallManifest,
+) -> Result<Vec<PruneTarget>> {
+    let mut found = Vec::new();
+    for &assistant in assistants {
+        let root = config.skills_root_for(paths, assistant);
+        for prunable in find_prunable(&root)? {
+            // A notice, not a row: stdout only lists what will be cleaned up.
+            if manifest.is_protected(assistant, &prunable.name) {
+                eprintln!(
+                    "{assistant}/{}: {} (protected, kept)",
+                    prunable.name, prunable.reason
+                );
+                continue;
+            }
+            found.push((assistant, root.clone(), prunable));
+        }
+    }
+    Ok(found)
+}
+
+pub fn prunable_paths(found: &[PruneTarget]) -> Vec<PathBuf> {
+    found
+        .iter()
+        .map(|(_, _, prunable)| prunable.path.clone())
+        .collect()
+}
+
+/// Narrows what `gc` cleans up to the confirmed rows: directories first, then usage
+/// counts. Counts are only dropped for skills still gone once just the chosen
+/// directories are trashed, so deselecting a skill's directory keeps its counts.
+pub fn selection(
+    found: Vec<PruneTarget>,
+    orphaned: Vec<String>,
+    chosen: &[usize],
+    config: &Config,
+    paths: &AppPaths,
+) -> Result<(Vec<PruneTarget>, Vec<String>)> {
+    let directories = found.len();
+    let found = keep_chosen(found, chosen);
+    let usage_rows: Vec<usize> = chosen
+        .iter()
+        .filter_map(|index| index.checked_sub(directories))
+        .collect();
+    let still_orphaned = doctor::orphaned_usage(config, paths, &prunable_paths(&found))?;
+    let orphaned = keep_chosen(orphaned, &usage_rows)
+        .into_iter()
+        .filter(|name| still_orphaned.contains(name))
+        .collect();
+    Ok((found, orphaned))
+}
+
+pub fn prune_row((assistant, _, prunable): &PruneTarget) -> String {
+    format!(
+        "{assistant}/{}: {} ({})",
+        prunable.name,
+        prunable.reason,
+        prunable.path.display()
+    )
+}
+
+/// Moves `found` to the trash in one journaled step and drops their install records.
+pub fn trash_prunable(found: &[PruneTarget], paths: &AppPaths) -> Result<()> {
+    let trash = Trash::new(paths);
+    let mut steps = Vec::new();
+    for (assistant, root, prunable) in found {
+        let slot = trash.slot_for(*assistant, &prunable.name);
+        steps.extend(trash.steps(root, &prunable.path, &slot)?);
+    }
+    Journal::begin(paths, "prune", steps)?.run()?;
+
+    InstallManifest::update(paths, |manifest| {
+        for (assistant, _, prunable) in found {
+            manifest.remove(*assistant, &prunable.name);
+        }
+    })?;
+    println!(
+        "Moved {} directory(ies) to the trash at {}",
+        found.len(),
+        trash.dir().display()
+    );
+    Ok(())
+}
@@ -0,0 +1,313 @@
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use std::fmt;
+
+pub const DEFAULT_GITLAB_HOST: &str = "gitlab.com";
+const GITHUB_HOST: &str = "github.com";
+const BITBUCKET_HOST: &str = "bitbucket.org";
+
+/// Git hosts that `skill add` accepts `<host>:<path>[@ref]` shorthand for. GitHub
+/// also takes a bare `<owner>/<repo>[@ref]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitHost {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+impl GitHost {
+    fn prefix(self) -> &'static str {
+        match self {
+            GitHost::GitHub => "github:",
+            GitHost::GitLab => "gitlab:",
+            GitHost::Bitbucket => "bitbucket:",
+        }
+    }
+
+    /// Environment variable holding an access token for private repositories.
+    pub fn token_env(self) -> &'static str {
+        match self {
+            GitHost::GitHub => "GITHUB_TOKEN",
+            GitHost::GitLab => "GITLAB_TOKEN",
+            GitHost::Bitbucket => "BITBUCKET_TOKEN",
+        }
+    }
+}
+
+impl fmt::Display for GitHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GitHost::GitHub => "GitHub",
+            GitHost::GitLab => "GitLab",
+            GitHost::Bitbucket => "Bitbucket",
+        })
+    }
+}
+
+/// A repository named by shorthand, e.g. `gitlab:group/sub/project@v1.2.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostedRepo {
+    pub host: GitHost,
+    /// Domain serving the repository; `gitlab_host` from config for GitLab.
+    pub domain: String,
+    /// `owner/repo` for GitHub, `group/…/project` for GitLab, `workspace/repo` for
+    /// Bitbucket.
+    pub path: String,
+    /// Branch, tag or commit after `@`.
+    pub reference: Option<String>,
+}
+
+/// Parses host shorthand. Returns `Ok(None)` when `source` has no known host prefix
+/// and is not a bare `<owner>/<repo>[@ref]`.
+pub fn parse_shorthand(source: &str, gitlab_host: &str) -> Result<Option<HostedRepo>> {
+    let Some((host, rest)) = [GitHost::GitHub, GitHost::GitLab, GitHost::Bitbucket]
+        .into_iter()
+        .find_map(|host| source.strip_prefix(host.prefix()).map(|rest| (host, rest)))
+    else {
+        return Ok(parse_bare_github(source));
+    };
+
+    let (path, reference) = match rest.rsplit_once('@') {
+        Some((path, reference)) if !reference.is_empty() => (path, Some(reference.to_string())),
+        Some(_) => return Err(anyhow!("missing ref after '@' in {source}")),
+        None => (rest, None),
+    };
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let segments: Vec<&str> = path.split('/').collect();
+    let valid_segment = |segment: &&str| {
+        !segment.is_empty()
+            && *segment != "."
+            && *segment != ".."
+            && segment
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+    };
+    let shape_ok = match host {
+        GitHost::GitLab => segments.len() >= 2,
+        GitHost::GitHub | GitHost::Bitbucket => segments.len() == 2,
+    };
+    if !shape_ok || !segments.iter().all(valid_segment) {
+        let expected = match host {
+            GitHost::GitHub => "github:<owner>/<repo>[@ref]",
+            GitHost::GitLab => "gitlab:<group>/<project>[@ref]",
+            GitHost::Bitbucket => "bitbucket:<workspace>/<repo>[@ref]",
+        };
+        return Err(anyhow!(
+            "invalid {host} source '{source}'; expected {expected}"
+        ));
+    }
+
+    let domain = match host {
+        GitHost::GitHub => GITHUB_HOST.to_string(),
+        GitHost::GitLab => gitlab_host.trim_end_matches('/').to_string(),
+        GitHost::Bitbucket => BITBUCKET_HOST.to_string(),
+    };
+    Ok(Some(HostedRepo {
+        host,
+        domain,
+        path: path.to_string(),
+        reference,
+    }))
+}
+
+/// `owner/repo[@ref]` with nothing else in it: no scheme, no leading `.`, `/` or `~`,
+/// and names GitHub allows. Anything else is left for the other source kinds.
+fn parse_bare_github(source: &str) -> Option<HostedRepo> {
+    let (path, reference) = match source.rsplit_once('@') {
+        Some((path, reference)) if !reference.is_empty() => (path, Some(reference.to_string())),
+        Some(_) => return None,
+        None => (source, None),
+    };
+    let (owner, repo) = path.split_once('/')?;
+    let owner_ok = !owner.is_empty()
+        && !owner.starts_with('-')
+        && owner
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-');
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    let repo_ok = !repo.is_empty()
+        && repo != "."
+        && repo != ".."
+        && repo
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'));
+    (owner_ok && repo_ok).then(|| HostedRepo {
+        host: GitHost::GitHub,
+        domain: GITHUB_HOST.to_string(),
+        path: format!("{owner}/{repo}"),
+        reference,
+    })
+}
+
+impl HostedRepo {
+    pub fn clone_url(&self) -> String {
+        format!("https://{}/{}.git", self.domain, self.path)
+    }
+
+    /// `.tar.gz` download of `reference`, or of the default branch when none was given.
+    pub fn archive_url(&self) -> String {
+        match self.host {
+            // Private repositories are only served through the API, with a token.
+            GitHost::GitHub if self.token().is_some() => {
+                let mut url = format!("https://api.github.com/repos/{}/tarball", self.path);
+                if let Some(reference) = &self.reference {
+                    url.push_str(&format!("/{}", encode_ref_path(reference)));
+                }
+                url
+            }
+            GitHost::GitHub => format!(
+                "https://{}/{}/archive/{}.tar.gz",
+                self.domain,
+                self.path,
+                encode_ref_path(self.reference.as_deref().unwrap_or("HEAD"))
+            ),
+            GitHost::GitLab => {
+                let mut url = format!(
+                    "https://{}/api/v4/projects/{}/repository/archive.tar.gz",
+                    self.domain,
+                    percent_encode(&self.path)
+                );
+                if let Some(reference) = &self.reference {
+                    url.push_str(&format!("?sha={}", percent_encode(reference)));
+                }
+                url
+            }
+            GitHost::Bitbucket => format!(
+                "https://{}/{}/get/{}.tar.gz",
+                self.domain,
+                self.path,
+                percent_encode(self.reference.as_deref().unwrap_or("HEAD"))
+            ),
+        }
+    }
+
+    fn token(&self) -> Option<String> {
+        std::env::var(self.host.token_env())
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+    }
+
+    /// Header to send with archive downloads when a token is set.
+    pub fn archive_auth(&self) -> Option<(&'static str, String)> {
+        let token = self.token()?;
+        Some(match self.host {
+            GitHost::GitLab => ("PRIVATE-TOKEN", token),
+            GitHost::GitHub | GitHost::Bitbucket => ("Authorization", format!("Bearer {token}")),
+        })
+    }
+
+    /// `Authorization` header for git over HTTPS when a token is set. It is passed to
+    /// git through the environment so the token never shows up in the process list.
+    pub fn git_auth_header(&self) -> Option<String> {
+        let token = self.token()?;
+        let user = match self.host {
+            GitHost::GitHub => "x-access-token",
+            GitHost::GitLab => "oauth2",
+            GitHost::Bitbucket => "x-token-auth",
+        };
+        let credentials =
+            base64::engine::general_purpose::STANDARD.encode(format!("{user}:{token}"));
+        Some(format!("Authorization: Basic {credentials}"))
+    }
+}
+
+/// A repository's pages on a known host, for linking to a skill upstream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebRepo {
+    pub host: GitHost,
+    /// `https://<domain>/<path>`.
+    pub url: String,
+    pub reference: Option<String>,
+}
+
+impl WebRepo {
+    /// Page listing `path` inside the repository at `commit`, falling back to the
+    /// reference the source named and then to the default branch.
+    pub fn tree_url(&self, commit: Option<&str>, path: &str) -> String {
+        let revision = encode_ref_path(commit.or(self.reference.as_deref()).unwrap_or("HEAD"));
+        let mut url = match self.host {
+            GitHost::GitHub => format!("{}/tree/{revision}", self.url),
+            GitHost::GitLab => format!("{}/-/tree/{revision}", self.url),
+            GitHost::Bitbucket => format!("{}/src/{revision}", self.url),
+        };
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            url.push('/');
+            url.push_str(&percent_encode(segment));
+        }
+        url
+    }
+}
+
+/// The browsable repository behind `source`: host shorthand, or an `https`, `ssh` or
+/// `git@host:path` URL on GitHub, Bitbucket or `gitlab_host`. `reference` overrides a
+/// ref given in the shorthand. Returns `None` for other hosts and for local sources.
+pub fn web_repo(source: &str, reference: Option<&str>, gitlab_host: &str) -> Option<WebRepo> {
+    let gitlab_host = gitlab_host.trim_end_matches('/');
+    let (host, domain, path, shorthand_ref) = match parse_shorthand(source, gitlab_host) {
+        Ok(Some(repo)) => (repo.host, repo.domain, repo.path, repo.reference),
+        Ok(None) => {
+            let (domain, path) = split_git_url(source)?;
+            let host = if domain.eq_ignore_ascii_case(GITHUB_HOST) {
+                GitHost::GitHub
+            } else if domain.eq_ignore_ascii_case(BITBUCKET_HOST) {
+                GitHost::Bitbucket
+            } else if domain.eq_ignore_ascii_case(gitlab_host) {
+                GitHost::GitLab
+            } else {
+                return None;
+            };
+            (host, domain, path, None)
+        }
+        Err(_) => return None,
+    };
+    Some(WebRepo {
+        host,
+        url: format!("https://{domain}/{path}"),
+        reference: reference.map(str::to_string).or(shorthand_ref),
+    })
+}
+
+/// Domain and repository path of a remote git URL, without credentials, port or `.git`.
+fn split_git_url(source: &str) -> Option<(String, String)> {
+    let (domain, path) = match url::Url::parse(source) {
+        Ok(url) if matches!(url.scheme(), "https" | "http" | "ssh" | "git") => {
+            (url.host_str()?.to_string(), url.path().to_string())
+        }
+        Ok(_) => return None,
+        Err(_) => {
+            let (user_host, path) = source.split_once(':')?;
+            let domain = user_host.rsplit('@').next()?;
+            if domain.is_empty() || domain.contains('/') || path.starts_with('/') {
+                return None;
+            }
+            (domain.to_string(), path.to_string())
+        }
+    };
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    path.contains('/').then(|| (domain, path.to_string()))
+}
+
+/// Percent-encodes a ref for a URL path, keeping the `/` of branches like `feature/x`.
+fn encode_ref_path(reference: &str) -> String {
+    reference
+        .split('/')
+        .map(percent_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+                (byte as char).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
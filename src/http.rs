@@ -0,0 +1,127 @@
+use anyhow::{anyhow, Context, Result};
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Redirects followed before a download is refused.
+pub const MAX_REDIRECTS: usize = 5;
+
+/// A response and the URL that finally served it.
+pub struct Fetched {
+    pub response: ureq::Response,
+    pub final_url: String,
+}
+
+/// GETs `url`, following redirects here rather than inside ureq so the policy is ours:
+/// at most [`MAX_REDIRECTS`] hops, never from https to http, and `auth` sent only to
+/// the requested host. Warns when the response comes from a different host. Error
+/// statuses come back as a `ureq::Error::Status` inside the error.
+pub fn get(url: &str, headers: &[(&str, &str)], auth: Option<(&str, &str)>) -> Result<Fetched> {
+    request("GET", url, headers, auth)
+}
+
+/// Like [`get`], but asks only for the headers.
+pub fn head(url: &str, headers: &[(&str, &str)], auth: Option<(&str, &str)>) -> Result<Fetched> {
+    request("HEAD", url, headers, auth)
+}
+
+fn request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    auth: Option<(&str, &str)>,
+) -> Result<Fetched> {
+    let agent = ureq::AgentBuilder::new().redirects(0).build();
+    let requested = Url::parse(url).with_context(|| format!("invalid URL {url}"))?;
+    let mut current = requested.clone();
+    for _ in 0..=MAX_REDIRECTS {
+        let same_host = current.host_str() == requested.host_str();
+        let mut request = agent.request(method, current.as_str());
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        if let Some((name, value)) = auth.filter(|_| same_host) {
+            request = request.set(name, value);
+        }
+        let response = request.call()?;
+        let location = match response.status() {
+            301 | 302 | 303 | 307 | 308 => response.header("Location"),
+            _ => None,
+        };
+        let Some(location) = location else {
+            if !same_host {
+                eprintln!(
+                    "Warning: {url} redirected to {current}, on a different host than requested"
+                );
+            }
+            return Ok(Fetched {
+                response,
+                final_url: current.to_string(),
+            });
+        };
+        current = redirect_target(&current, location)?;
+    }
+    Err(anyhow!("{url} redirected more than {MAX_REDIRECTS} times"))
+}
+
+/// Where a redirect from `current` to `location` leads; relative locations resolve
+/// against `current`. Errors for a redirect from https to anything else.
+pub fn redirect_target(current: &Url, location: &str) -> Result<Url> {
+    let next = current
+        .join(location)
+        .with_context(|| format!("{current} redirected to an invalid URL {location}"))?;
+    if current.scheme() == "https" && next.scheme() != "https" {
+        return Err(anyhow!(
+            "refusing the redirect from {current} to {next}, which drops https"
+        ));
+    }
+    Ok(next)
+}
+
+/// Whether `err` from [`get`] is the server answering `status`.
+pub fn is_status(err: &anyhow::Error, status: u16) -> bool {
+    matches!(err.downcast_ref::<ureq::Error>(), Some(ureq::Error::Status(code, _)) if *code == status)
+}
+
+/// Where a `206 Partial Content` response starts, from its `Content-Range` header
+/// (`bytes <start>-<end>/<total>`).
+pub fn content_range_start(response: &ureq::Response) -> Option<u64> {
+    let range = response.header("Content-Range")?.trim();
+    let (start, _) = range.strip_prefix("bytes ")?.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+/// Reader that sleeps as needed to keep the average rate at or under `bytes_per_second`.
+pub struct RateLimited<R> {
+    inner: R,
+    bytes_per_second: u64,
+    started: Instant,
+    read: u64,
+}
+
+impl<R: Read> RateLimited<R> {
+    pub fn new(inner: R, bytes_per_second: u64) -> Self {
+        Self {
+            inner,
+            bytes_per_second: bytes_per_second.max(1),
+            started: Instant::now(),
+            read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for RateLimited<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        // Reads no more than a tenth of a second's worth at a time, so a small cap
+        // doesn't arrive as one burst after a long pause.
+        let chunk = (self.bytes_per_second / 10).max(1) as usize;
+        let limit = buffer.len().min(chunk);
+        let read = self.inner.read(&mut buffer[..limit])?;
+        self.read += read as u64;
+        let due = Duration::from_secs_f64(self.read as f64 / self.bytes_per_second as f64);
+        if let Some(wait) = due.checked_sub(self.started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        Ok(read)
+    }
+}
@@ -0,0 +1,243 @@
+use crate::paths::{ensure_dir, AppPaths};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+
+const JOURNAL_FILE_NAME: &str = "journal.json";
+const LOCK_FILE_NAME: &str = "journal.lock";
+
+/// A filesystem step of a multi-step operation, recorded before it runs so an
+/// interrupted operation can be resumed or rolled back on the next invocation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+#[allow(clippy::enum_variant_names)]
+pub enum JournalStep {
+    /// Copy a skill directory into a destination that did not exist before.
+    CopyDir { from: PathBuf, to: PathBuf },
+    /// Rename a directory; rolled back by renaming it back.
+    MoveDir { from: PathBuf, to: PathBuf },
+    /// Delete a directory; cannot be rolled back once started.
+    RemoveDir { path: PathBuf },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Journal {
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+    pub steps: Vec<JournalStep>,
+    #[serde(default)]
+    pub completed: usize,
+    #[serde(skip)]
+    path: PathBuf,
+    /// Exclusive lock on `journal.lock`, held until the journal is finished or
+    /// dropped, so other invocations never mistake a running operation for an
+    /// interrupted one.
+    #[serde(skip)]
+    _lock: Option<File>,
+}
+
+impl Journal {
+    /// Records the planned steps of `command`, waiting for an operation another
+    /// invocation is running and refusing to start while a previous one is still
+    /// incomplete.
+    pub fn begin(paths: &AppPaths, command: &str, steps: Vec<JournalStep>) -> Result<Self> {
+        ensure_dir(&paths.data_dir)?;
+        let lock = lock_file(paths)?;
+        if let Err(TryLockError::WouldBlock) = lock.try_lock() {
+            eprintln!("Waiting for another skill operation to finish...");
+            lock.lock()
+                .with_context(|| format!("failed to lock {}", lock_path(paths).display()))?;
+        }
+        if let Some(pending) = Self::read(paths)? {
+            return Err(anyhow!(
+                "an interrupted `{}` operation from {} is pending. Run `skill journal resume` or `skill journal rollback` first.",
+                pending.command,
+                pending.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+            ));
+        }
+
+        let journal = Self {
+            command: command.to_string(),
+            started_at: Utc::now(),
+            steps,
+            completed: 0,
+            path: journal_path(paths),
+            _lock: Some(lock),
+        };
+        journal.save()?;
+        Ok(journal)
+    }
+
+    /// The journal of an interrupted operation, i.e. one whose owner no longer holds
+    /// its lock. An operation still running in another invocation is not pending.
+    pub fn pending(paths: &AppPaths) -> Result<Option<Self>> {
+        if !journal_path(paths).exists() {
+            return Ok(None);
+        }
+        let lock = lock_file(paths)?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(err)) => {
+                return Err(err)
+                    .with_context(|| format!("failed to lock {}", lock_path(paths).display()));
+            }
+        }
+        Ok(Self::read(paths)?.map(|journal| Self {
+            _lock: Some(lock),
+            ..journal
+        }))
+    }
+
+    fn read(paths: &AppPaths) -> Result<Option<Self>> {
+        let path = journal_path(paths);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut journal: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        journal.path = path;
+        Ok(Some(journal))
+    }
+
+    /// Executes the remaining steps in order, persisting progress after each one,
+    /// and removes the journal once everything has completed. A failing step rolls
+    /// the operation back; a crash leaves the journal behind for the next run.
+    pub fn run(mut self) -> Result<()> {
+        while self.completed < self.steps.len() {
+            if let Err(err) = apply_step(&self.steps[self.completed]) {
+                self.rollback()
+                    .context("rollback after failed step also failed")?;
+                return Err(err);
+            }
+            self.completed += 1;
+            self.save()?;
+        }
+        self.finish()
+    }
+
+    /// Undoes completed (and partially applied) steps in reverse order.
+    pub fn rollback(self) -> Result<Vec<String>> {
+        let mut notes = Vec::new();
+        let last = (self.completed + 1).min(self.steps.len());
+        for step in self.steps[..last].iter().rev() {
+            match step {
+                JournalStep::CopyDir { to, .. } => {
+                    if to.exists() {
+                        fs::remove_dir_all(to)
+                            .with_context(|| format!("failed to remove {}", to.display()))?;
+                        notes.push(format!("removed {}", to.display()));
+                    }
+                }
+                JournalStep::MoveDir { from, to } => {
+                    if to.exists() && !from.exists() {
+                        fs::rename(to, from).with_context(|| {
+                            format!("failed to move {} back to {}", to.display(), from.display())
+                        })?;
+                        notes.push(format!("restored {}", from.display()));
+                    }
+                }
+                JournalStep::RemoveDir { path } => {
+                    if path.exists() {
+                        notes.push(format!(
+                            "{} was partially deleted and cannot be restored",
+                            path.display()
+                        ));
+                    }
+                }
+            }
+        }
+        self.finish()?;
+        Ok(notes)
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)
+                .with_context(|| format!("failed to remove {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            ensure_dir(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self)?;
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, contents)
+            .with_context(|| format!("failed to write {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+}
+
+impl JournalStep {
+    pub fn describe(&self) -> String {
+        match self {
+            JournalStep::CopyDir { from, to } => {
+                format!("copy {} -> {}", from.display(), to.display())
+            }
+            JournalStep::MoveDir { from, to } => {
+                format!("move {} -> {}", from.display(), to.display())
+            }
+            JournalStep::RemoveDir { path } => format!("remove {}", path.display()),
+        }
+    }
+}
+
+fn apply_step(step: &JournalStep) -> Result<()> {
+    match step {
+        JournalStep::CopyDir { from, to } => {
+            if !from.exists() {
+                return Err(anyhow!(
+                    "cannot copy {}: source no longer exists",
+                    from.display()
+                ));
+            }
+            if to.exists() {
+                // A previous attempt was interrupted midway; start the copy over.
+                fs::remove_dir_all(to)
+                    .with_context(|| format!("failed to remove {}", to.display()))?;
+            }
+            crate::commands::copy_dir_filtered(from, to)
+        }
+        JournalStep::MoveDir { from, to } => {
+            if !from.exists() && to.exists() {
+                // Already moved before the interruption.
+                return Ok(());
+            }
+            fs::rename(from, to)
+                .with_context(|| format!("failed to move {} to {}", from.display(), to.display()))
+        }
+        JournalStep::RemoveDir { path } => {
+            if path.exists() {
+                fs::remove_dir_all(path)
+                    .with_context(|| format!("failed to remove {}", path.display()))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn journal_path(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join(JOURNAL_FILE_NAME)
+}
+
+fn lock_path(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join(LOCK_FILE_NAME)
+}
+
+fn lock_file(paths: &AppPaths) -> Result<File> {
+    let path = lock_path(paths);
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))
+}
@@ -0,0 +1,302 @@
+use crate::paths::{ensure_dir, write_private, AppPaths};
+use crate::store;
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const KEYS_DIR_NAME: &str = "keys";
+const ALLOWED_SIGNERS_FILE_NAME: &str = "allowed_signers";
+
+/// A signing identity created by `skill keys generate`.
+#[derive(Debug)]
+pub struct Identity {
+    pub name: String,
+    pub private_key: PathBuf,
+    pub public_key: String,
+}
+
+/// A publisher key trusted with `skill keys trust`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedKey {
+    pub principal: String,
+    pub public_key: String,
+}
+
+/// SSH signing identities under `<data dir>/keys` and the publisher keys trusted for
+/// verification. Trusted keys are kept in the metadata store and written out, under
+/// the store lock, as a regular SSH allowed-signers file in the keys directory, so
+/// pack verification and `git verify-commit` read it directly.
+#[derive(Debug)]
+pub struct KeyStore {
+    dir: PathBuf,
+    paths: AppPaths,
+}
+
+impl KeyStore {
+    pub fn new(paths: &AppPaths) -> Self {
+        Self {
+            dir: paths.data_dir.join(KEYS_DIR_NAME),
+            paths: paths.clone(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn allowed_signers_path(&self) -> PathBuf {
+        self.dir.join(ALLOWED_SIGNERS_FILE_NAME)
+    }
+
+    /// The allowed-signers file, once at least one key has been trusted.
+    pub fn allowed_signers(&self) -> Option<PathBuf> {
+        Some(self.allowed_signers_path()).filter(|path| path.is_file())
+    }
+
+    pub fn private_key_path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// Creates an ed25519 key pair named `name` and trusts its public key as
+    /// `principal`, so packs signed with it verify locally too. With `passphrase`,
+    /// ssh-keygen asks for one on the terminal; otherwise the key is unencrypted.
+    pub fn generate(&self, name: &str, principal: &str, passphrase: bool) -> Result<Identity> {
+        check_name(name)?;
+        let private_key = self.private_key_path(name);
+        if private_key.exists() {
+            return Err(anyhow!("a key named {name} already exists"));
+        }
+        self.ensure_dir()?;
+        let mut command = Command::new("ssh-keygen");
+        command.args(["-q", "-t", "ed25519", "-C", principal, "-f"]);
+        command.arg(&private_key);
+        if !passphrase {
+            command.args(["-N", ""]).stdin(Stdio::null());
+        }
+        let status = command
+            .status()
+            .context("failed to run ssh-keygen; is OpenSSH installed?")?;
+        if !status.success() {
+            return Err(anyhow!("ssh-keygen failed to generate {name}"));
+        }
+        let public_key = self.public_key(name)?;
+        self.trust(principal, &public_key)?;
+        Ok(Identity {
+            name: name.to_string(),
+            private_key,
+            public_key,
+        })
+    }
+
+    pub fn identities(&self) -> Result<Vec<Identity>> {
+        let mut identities = Vec::new();
+        if !self.dir.is_dir() {
+            return Ok(identities);
+        }
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to read {}", self.dir.display()))?
+        {
+            let path = entry?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".pub"))
+            else {
+                continue;
+            };
+            if !self.private_key_path(name).is_file() {
+                continue;
+            }
+            identities.push(Identity {
+                name: name.to_string(),
+                private_key: self.private_key_path(name),
+                public_key: self.public_key(name)?,
+            });
+        }
+        identities.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(identities)
+    }
+
+    /// The `<type> <base64> <comment>` public key line of identity `name`.
+    pub fn public_key(&self, name: &str) -> Result<String> {
+        check_name(name)?;
+        let path = self.dir.join(format!("{name}.pub"));
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("no key named {name} ({} is missing)", path.display()))?;
+        Ok(contents.trim().to_string())
+    }
+
+    pub fn trusted(&self) -> Result<Vec<TrustedKey>> {
+        Ok(store::read(&self.paths)?.trusted_signers)
+    }
+
+    /// Adds `public_key` for `principal`; returns false if that pair was already trusted.
+    pub fn trust(&self, principal: &str, public_key: &str) -> Result<bool> {
+        if principal.is_empty() || principal.chars().any(char::is_whitespace) {
+            return Err(anyhow!(
+                "principal must be a single word such as an email address"
+            ));
+        }
+        let (key_type, blob) = parse_public_key(public_key)?;
+        let key = format!("{key_type} {blob}");
+        store::update(&self.paths, |data| {
+            let trusted = &mut data.trusted_signers;
+            if trusted
+                .iter()
+                .any(|entry| entry.principal == principal && key_of(&entry.public_key) == key)
+            {
+                return Ok(false);
+            }
+            trusted.push(TrustedKey {
+                principal: principal.to_string(),
+                public_key: key,
+            });
+            self.write_allowed_signers(trusted)?;
+            Ok(true)
+        })?
+    }
+
+    /// Removes every key trusted for `principal`; returns how many were removed.
+    pub fn untrust(&self, principal: &str) -> Result<usize> {
+        store::update(&self.paths, |data| {
+            let trusted = &mut data.trusted_signers;
+            let before = trusted.len();
+            trusted.retain(|entry| entry.principal != principal);
+            let removed = before - trusted.len();
+            if removed > 0 {
+                self.write_allowed_signers(trusted)?;
+            }
+            Ok(removed)
+        })?
+    }
+
+    fn write_allowed_signers(&self, trusted: &[TrustedKey]) -> Result<()> {
+        self.ensure_dir()?;
+        let path = self.allowed_signers_path();
+        let temp_path = path.with_extension("tmp");
+        let contents: String = trusted
+            .iter()
+            .map(|entry| format!("{} {}\n", entry.principal, entry.public_key))
+            .collect();
+        write_private(&temp_path, contents)?;
+        fs::rename(&temp_path, &path).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn ensure_dir(&self) -> Result<()> {
+        ensure_dir(&self.dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.dir, fs::Permissions::from_mode(0o700))
+                .with_context(|| format!("failed to secure {}", self.dir.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// `SHA256:<base64>` fingerprint of a public key, as `ssh-keygen -l` prints it.
+pub fn fingerprint(public_key: &str) -> Result<String> {
+    let (_, blob) = parse_public_key(public_key)?;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let bytes = engine
+        .decode(blob)
+        .context("public key is not valid base64")?;
+    let digest = Sha256::digest(bytes);
+    Ok(format!(
+        "SHA256:{}",
+        base64::engine::general_purpose::STANDARD_NO_PAD.encode(digest)
+    ))
+}
+
+/// Splits an OpenSSH public key line into its type and base64 blob.
+fn parse_public_key(line: &str) -> Result<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(key_type), Some(blob))
+            if key_type.starts_with("ssh-")
+                || key_type.starts_with("ecdsa-")
+                || key_type.starts_with("sk-") =>
+        {
+            base64::engine::general_purpose::STANDARD
+                .decode(blob)
+                .context("public key is not valid base64")?;
+            Ok((key_type, blob))
+        }
+        _ => Err(anyhow!(
+            "not an OpenSSH public key; expected a line like `ssh-ed25519 AAAA... comment`"
+        )),
+    }
+}
+
+fn key_of(public_key: &str) -> String {
+    parse_public_key(public_key)
+        .map(|(key_type, blob)| format!("{key_type} {blob}"))
+        .unwrap_or_default()
+}
+
+/// The keys in the allowed-signers file at `path`, which held the trusted keys before
+/// they moved into the metadata store.
+pub fn read_allowed_signers(path: &Path) -> Result<Vec<TrustedKey>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents.lines().filter_map(parse_allowed_signer).collect())
+}
+
+/// Parses `principal [options] key-type base64 [comment]`, skipping comments and
+/// lines in forms this store does not write.
+fn parse_allowed_signer(line: &str) -> Option<TrustedKey> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let mut tokens = line.split_whitespace();
+    let principal = tokens.next()?;
+    let tokens: Vec<&str> = tokens.collect();
+    let key_start = tokens
+        .windows(2)
+        .position(|pair| parse_public_key(&pair.join(" ")).is_ok())?;
+    Some(TrustedKey {
+        principal: principal.to_string(),
+        public_key: tokens[key_start..].join(" "),
+    })
+}
+
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name != ALLOWED_SIGNERS_FILE_NAME
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "invalid key name '{name}'; use letters, digits, '-', '_' and '.'"
+        ))
+    }
+}
+
+/// Reads a public key from a `.pub` file, or stdin for `-`.
+pub fn read_public_key(source: &Path) -> Result<String> {
+    let contents = if source == Path::new("-") {
+        std::io::read_to_string(std::io::stdin()).context("failed to read stdin")?
+    } else {
+        fs::read_to_string(source)
+            .with_context(|| format!("failed to read {}", source.display()))?
+    };
+    let line = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or_else(|| anyhow!("{} has no public key", source.display()))?;
+    parse_public_key(line)?;
+    Ok(line.to_string())
+}
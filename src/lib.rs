@@ -1,8 +1,44 @@
 pub mod assistant;
+pub mod audit;
+pub mod backup;
+pub mod batch;
+pub mod bom;
+pub mod cache;
+pub mod catalog;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod defaults;
+pub mod doctor;
+pub mod downloads;
+pub mod gc;
+pub mod hosts;
+pub mod http;
+pub mod journal;
+pub mod keys;
+pub mod manifest;
+pub mod output;
+pub mod pack;
+pub mod parallel;
 pub mod paths;
+pub mod process;
+pub mod progress;
+pub mod rank;
+pub mod registry;
+pub mod reload;
+pub mod roots;
+pub mod report;
+pub mod rule_pack;
+pub mod rules;
 pub mod scan;
+pub mod scan_profile;
+pub mod shared;
+pub mod site;
+pub mod snapshot;
+pub mod store;
+pub mod suppression;
+pub mod template;
+pub mod tools;
+pub mod trash;
 pub mod usage;
 pub mod validation;
@@ -1,9 +1,45 @@
 mod assistant;
+mod audit;
+mod backup;
+mod batch;
+mod bom;
+mod cache;
+mod catalog;
 mod cli;
 mod commands;
 mod config;
+mod defaults;
+mod doctor;
+mod downloads;
+mod gc;
+mod hosts;
+mod http;
+mod journal;
+mod keys;
+mod manifest;
+mod output;
+mod pack;
+mod parallel;
 mod paths;
+mod process;
+mod progress;
+mod rank;
+mod registry;
+mod reload;
+mod roots;
+mod report;
+mod rule_pack;
+mod rules;
 mod scan;
+mod scan_profile;
+mod shared;
+mod site;
+mod snapshot;
+mod store;
+mod suppression;
+mod template;
+mod tools;
+mod trash;
 mod usage;
 mod validation;
 
@@ -12,69 +48,228 @@ use clap::Parser;
 use cli::{Cli, Command};
 use config::Config;
 use paths::AppPaths;
-use std::path::Path;
+use report::CheckReport;
+use std::path::{Path, PathBuf};
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
     let paths = AppPaths::new()?;
-    let mut config = Config::load(&paths)?;
+    let mut config = match Config::load(&paths) {
+        // `doctor` reports a config that doesn't load rather than failing on it.
+        Err(_) if matches!(cli.command, Command::Doctor(_)) => Config::default(),
+        loaded => loaded?,
+    };
+    if !cli.no_defaults && !config.defaults.is_empty() {
+        let args = defaults::with_config_defaults(std::env::args_os().collect(), &config.defaults);
+        cli = Cli::parse_from(args);
+    }
+    parallel::set_jobs(cli.command.jobs().or(config.jobs).unwrap_or(0));
+    output::set_porcelain(cli.porcelain);
+    output::set_json(cli.json);
+    progress::set_mode(cli.progress);
+    if cli.json && !cli.command.supports_json() {
+        return Err(anyhow!(
+            "--json is not supported by this command; it works with {}",
+            cli::JSON_COMMANDS
+        ));
+    }
+    rule_pack::init(&paths);
+    downloads::install_interrupt_handler();
+    if !matches!(cli.command, Command::Journal(_)) {
+        commands::check_pending_journal(&paths)?;
+    }
 
-    match cli.command {
+    let result = run(cli.command, &paths, &mut config);
+    commands::tidy_trash(&config, &paths);
+    if let Err(err) = &result
+        && err.downcast_ref::<batch::PartialFailure>().is_some()
+    {
+        eprintln!("Error: {err:#}");
+        std::process::exit(batch::PARTIAL_FAILURE_EXIT_CODE);
+    }
+    result
+}
+
+fn run(command: Command, paths: &AppPaths, config: &mut Config) -> Result<()> {
+    match command {
         Command::Paths(cmd) => {
             let base_dir = config
                 .skills_base_dir
                 .as_ref()
                 .unwrap_or(&paths.skills_base_dir);
+            let mut assistants = cmd.assistant.selected_all();
+            if assistants.is_empty() {
+                assistants = vec![
+                    assistant::Assistant::Codex,
+                    assistant::Assistant::ClaudeCode,
+                    assistant::Assistant::OpenCode,
+                ];
+            }
+            if output::json() {
+                let roots = |root: &dyn Fn(assistant::Assistant) -> std::path::PathBuf| {
+                    assistants
+                        .iter()
+                        .map(|assistant| (assistant.as_str(), root(*assistant)))
+                        .collect::<std::collections::BTreeMap<_, _>>()
+                };
+                return output::print_json(&serde_json::json!({
+                    "config_dir": paths.config_dir,
+                    "config_file": paths.config_file,
+                    "data_dir": paths.data_dir,
+                    "metadata_store": store::store_path(paths),
+                    "cache_dir": paths.cache_dir,
+                    "skills_base_dir": base_dir,
+                    "skills_roots": roots(&|assistant| config.skills_root_for(paths, assistant)),
+                    "system_skills_roots": roots(&|assistant| config.system_root_for(assistant)),
+                }));
+            }
             println!("Config dir: {}", paths.config_dir.display());
             println!("Config file: {}", paths.config_file.display());
             println!("Data dir: {}", paths.data_dir.display());
-            println!("Usage file: {}", paths.usage_file.display());
+            println!("Metadata store: {}", store::store_path(paths).display());
+            println!("Cache dir: {}", paths.cache_dir.display());
             println!("Skills base dir: {}", base_dir.display());
 
-            if let Some(assistant) = cmd.assistant.selected() {
+            for assistant in assistants {
                 println!(
                     "Skills root ({assistant}): {}",
-                    config.skills_root_for(&paths, assistant).display()
+                    config.skills_root_for(paths, assistant).display()
+                );
+                println!(
+                    "System skills root ({assistant}): {}",
+                    config.system_root_for(assistant).display()
                 );
-            } else {
-                for assistant in [
-                    assistant::Assistant::Codex,
-                    assistant::Assistant::ClaudeCode,
-                    assistant::Assistant::OpenCode,
-                ] {
-                    println!(
-                        "Skills root ({assistant}): {}",
-                        config.skills_root_for(&paths, assistant).display()
-                    );
-                }
             }
             Ok(())
         }
-        Command::Default(cmd) => {
-            config.default_assistant = Some(cmd.assistant);
-            config.save(&paths)?;
-            println!("Default assistant set to {}", cmd.assistant);
-            Ok(())
-        }
-        Command::Add(cmd) => commands::cmd_add(&cmd, &config, &paths),
-        Command::Remove(cmd) => commands::cmd_remove(&cmd, &config, &paths),
-        Command::List(cmd) => commands::cmd_list(&cmd, &config, &paths),
-        Command::Show(cmd) => commands::cmd_show(&cmd, &config, &paths),
-        Command::Stats(cmd) => commands::cmd_stats(&cmd, &config, &paths),
-        Command::Search(cmd) => commands::cmd_search(&cmd, &config, &paths),
+        Command::Env(cmd) => commands::cmd_env(&cmd, config, paths),
+        Command::Default(cmd) => commands::cmd_default(&cmd, config, paths),
+        Command::Add(cmd) => commands::cmd_add(&cmd, config, paths),
+        Command::Remove(cmd) => commands::cmd_remove(&cmd, config, paths),
+        Command::Prune(cmd) => commands::cmd_prune(&cmd, config, paths),
+        Command::Gc(cmd) => commands::cmd_gc(&cmd, config, paths),
+        Command::Update(cmd) => commands::cmd_update(&cmd, config, paths),
+        Command::Upgrade(cmd) => commands::cmd_upgrade(&cmd, config, paths),
+        Command::Outdated(cmd) => commands::cmd_outdated(&cmd, config, paths),
+        Command::Diff(cmd) => commands::cmd_diff(&cmd, config, paths),
+        Command::Compare(cmd) => commands::cmd_compare(&cmd, config, paths),
+        Command::Sync(cmd) => commands::cmd_sync(&cmd, config, paths),
+        Command::Workspace(cmd) => commands::cmd_workspace(&cmd, config, paths),
+        Command::Verify(cmd) => commands::cmd_verify(&cmd, config, paths),
+        Command::Protect(cmd) => commands::cmd_protect(&cmd, config, paths, true),
+        Command::Unprotect(cmd) => commands::cmd_protect(&cmd, config, paths, false),
+        Command::Pin(cmd) => commands::cmd_pin(&cmd, config, paths, true),
+        Command::Unpin(cmd) => commands::cmd_pin(&cmd, config, paths, false),
+        Command::List(cmd) => commands::cmd_list(&cmd, config, paths),
+        Command::Show(cmd) => commands::cmd_show(&cmd, config, paths),
+        Command::Bom(cmd) => commands::cmd_bom(&cmd, config, paths),
+        Command::Stats(cmd) => commands::cmd_stats(&cmd, config, paths),
+        Command::Rank(cmd) => commands::cmd_rank(&cmd, config, paths),
+        Command::Report(cmd) => commands::cmd_report(&cmd, config, paths),
+        Command::Search(cmd) => commands::cmd_search(&cmd, config, paths),
         Command::Scan(cmd) => {
-            let report = scan::scan_path(Path::new(&cmd.path))?;
-            if report.issues.is_empty() && report.external.is_empty() {
-                println!("Scan passed");
+            let target = commands::resolve_check_target(
+                &cmd.path,
+                cmd.skill.as_deref(),
+                false,
+                config,
+                paths,
+            )?;
+            let changes = match (&cmd.since, cmd.staged) {
+                (Some(reference), _) => Some(scan::GitChanges::Since(reference)),
+                (None, true) => Some(scan::GitChanges::Staged),
+                (None, false) => None,
+            };
+            if changes.is_some() && target.fetched {
+                return Err(anyhow!("--staged and --since need a local git checkout"));
+            }
+            let shown = if target.fetched {
+                Path::new(&cmd.path)
+            } else {
+                target.dir.as_path()
+            };
+            let staged = matches!(changes, Some(scan::GitChanges::Staged))
+                .then(|| scan::StagedTree::export(&target.dir))
+                .transpose()?;
+            let tree = staged
+                .as_ref()
+                .map_or(target.dir.as_path(), |staged| staged.dir());
+            let files = changes
+                .map(|changes| scan::changed_skill_files(&target.dir, tree, changes))
+                .transpose()?;
+            if files.as_ref().is_some_and(|files| files.is_empty()) {
+                if output::json() {
+                    return output::print_json(&CheckReport::from_scan(
+                        shown,
+                        &scan::ScanReport::default(),
+                    ));
+                }
+                if !output::porcelain() {
+                    println!("No changed files in skill directories");
+                }
                 return Ok(());
             }
-
-            for issue in &report.issues {
-                println!("{issue}");
+            let options = scan::ScanOptions {
+                timeouts: config.tool_timeouts()?,
+                files,
+                suppressions: !target.fetched,
+                profile: !target.fetched || config.trust_scan_profiles,
+            };
+            let mut report = scan::scan_path(tree, &options)?;
+            if target.fetched || staged.is_some() {
+                let issues = report
+                    .issues
+                    .iter_mut()
+                    .chain(report.suppressed.iter_mut().map(|(issue, _)| issue));
+                for issue in issues {
+                    issue.path = relative_to(issue.path.take(), tree);
+                    // Staged findings point at the working-tree file they will commit.
+                    if staged.is_some() {
+                        issue.path = issue.path.take().map(|path| target.dir.join(path));
+                    }
+                }
             }
-
-            for external in &report.external {
-                println!("[{}] {}", external.tool, external.output);
+            let check_report = CheckReport::from_scan(shown, &report);
+            cmd.report.write(&check_report)?;
+            if cmd.verbose && !output::json() {
+                for (issue, reason) in &report.suppressed {
+                    println!("[suppressed] {}: {} ({reason})", issue.rule, issue.message);
+                }
+            }
+            if output::json() {
+                output::print_json(&check_report)?;
+            } else if output::porcelain() {
+                // severity, rule, path, message
+                for issue in &report.issues {
+                    output::record(&[
+                        issue.severity.as_str(),
+                        issue.rule,
+                        &issue
+                            .path
+                            .as_deref()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_default(),
+                        &issue.message,
+                    ]);
+                }
+                for external in &report.external {
+                    output::record(&[
+                        external.severity.as_str(),
+                        &format!("external/{}", external.tool),
+                        "",
+                        &external.output,
+                    ]);
+                }
+            } else if report.issues.is_empty() && report.external.is_empty() {
+                println!("Scan passed");
+                return Ok(());
+            } else {
+                for issue in &report.issues {
+                    println!("{issue}");
+                }
+                for external in &report.external {
+                    println!("[{}] {}", external.tool, external.output);
+                }
             }
 
             if report.has_errors() {
@@ -84,14 +279,68 @@ fn main() -> Result<()> {
             }
         }
         Command::Validate(cmd) => {
-            let report = validation::validate_skill_dir(Path::new(&cmd.path))?;
-            if report.issues.is_empty() {
+            let assistant = cmd.assistant.selected()?;
+            let target = commands::resolve_check_target(
+                &cmd.path,
+                cmd.skill.as_deref(),
+                true,
+                config,
+                paths,
+            )?;
+            let options = validation::ValidationOptions {
+                required_sections: if cmd.strict {
+                    config.required_sections.clone()
+                } else {
+                    Vec::new()
+                },
+                categories: config.categories.clone(),
+                assistant,
+                suppressions: !target.fetched,
+            };
+            let mut report = validation::validate_skill_dir_with(&target.dir, &options)?;
+            let shown = if target.fetched {
+                Path::new(&cmd.path)
+            } else {
+                target.dir.as_path()
+            };
+            if target.fetched {
+                let issues = report
+                    .issues
+                    .iter_mut()
+                    .chain(report.suppressed.iter_mut().map(|(issue, _)| issue));
+                for issue in issues {
+                    issue.path = relative_to(issue.path.take(), &target.dir);
+                }
+            }
+            let check_report = CheckReport::from_validation(shown, &report);
+            cmd.report.write(&check_report)?;
+            if cmd.verbose && !output::json() {
+                for (issue, reason) in &report.suppressed {
+                    println!("[suppressed] {}: {} ({reason})", issue.rule, issue.message);
+                }
+            }
+            if output::json() {
+                output::print_json(&check_report)?;
+            } else if output::porcelain() {
+                for issue in &report.issues {
+                    output::record(&[
+                        issue.severity.as_str(),
+                        issue.rule,
+                        &issue
+                            .path
+                            .as_deref()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_default(),
+                        &issue.message,
+                    ]);
+                }
+            } else if report.issues.is_empty() {
                 println!("Validation passed");
                 return Ok(());
-            }
-
-            for issue in &report.issues {
-                println!("{issue}");
+            } else {
+                for issue in &report.issues {
+                    println!("{issue}");
+                }
             }
 
             if report.has_errors() {
@@ -100,6 +349,36 @@ fn main() -> Result<()> {
                 Ok(())
             }
         }
-        Command::MarkUsed(cmd) => commands::cmd_mark_used(&cmd, &config, &paths),
+        Command::Explain(cmd) => commands::cmd_explain(&cmd),
+        Command::MarkUsed(cmd) => commands::cmd_mark_used(&cmd, config, paths),
+        Command::Convert(cmd) => commands::cmd_convert(&cmd, config),
+        Command::New(cmd) => commands::cmd_new(&cmd, config),
+        Command::Pack(cmd) => commands::cmd_pack(&cmd, config),
+        Command::Export(cmd) => commands::cmd_export(&cmd, config, paths),
+        Command::Publish(cmd) => commands::cmd_publish(&cmd, config, paths),
+        Command::VerifyPack(cmd) => commands::cmd_verify_pack(&cmd, config, paths),
+        Command::Index(cmd) => commands::cmd_index(&cmd),
+        Command::ExportSite(cmd) => commands::cmd_export_site(&cmd, config, paths),
+        Command::Shadows(cmd) => commands::cmd_shadows(&cmd, config, paths),
+        Command::MigrateLayout(cmd) => commands::cmd_migrate_layout(&cmd, config, paths),
+        Command::Backup(cmd) => commands::cmd_backup(&cmd, config, paths),
+        Command::Restore(cmd) => commands::cmd_backup_restore(&cmd, config, paths),
+        Command::Cache(cmd) => commands::cmd_cache(&cmd, config, paths),
+        Command::Rules(cmd) => commands::cmd_rules(&cmd, config, paths),
+        Command::Keys(cmd) => commands::cmd_keys(&cmd, config, paths),
+        Command::CleanDownloads(cmd) => commands::cmd_clean_downloads(&cmd),
+        Command::Trash(cmd) => commands::cmd_trash(&cmd, paths),
+        Command::Journal(cmd) => commands::cmd_journal(&cmd, paths),
+        Command::Doctor(cmd) => commands::cmd_doctor(&cmd, config, paths),
     }
 }
+
+/// `path` relative to a fetched `dir`, whose temp location means nothing once the
+/// command exits.
+fn relative_to(path: Option<PathBuf>, dir: &Path) -> Option<PathBuf> {
+    path.map(|path| {
+        path.strip_prefix(dir)
+            .map(Path::to_path_buf)
+            .unwrap_or(path)
+    })
+}
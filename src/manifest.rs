@@ -0,0 +1,297 @@
+use crate::assistant::Assistant;
+use crate::parallel;
+use crate::paths::AppPaths;
+use crate::store;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const MANIFEST_FILE_NAME: &str = "installed.json";
+
+/// Where each installed skill came from and the content hashes it had when it was
+/// installed, keyed by `<assistant>/<name>`, or `system/<assistant>/<name>` for skills
+/// `add --system` put in the system root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallManifest {
+    #[serde(default)]
+    pub skills: BTreeMap<String, InstallRecord>,
+    /// `<assistant>/<name>` of skills that remove/update/upgrade leave alone unless
+    /// given `--force-protected`.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub protected: BTreeSet<String>,
+    /// `<assistant>/<name>` of skills that `upgrade`, `sync` and `workspace sync` skip;
+    /// only `update <name>` changes them.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub pinned: BTreeSet<String>,
+    /// Source each `<assistant>/<name>` was first installed from. Kept when the skill
+    /// is removed, so re-adding the name from elsewhere needs `add --retarget`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub source_locks: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub source: String,
+    #[serde(default)]
+    pub skill: Option<String>,
+    /// Branch, tag or commit given with `add --ref`; a ref in shorthand sources stays
+    /// part of `source`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Git commit the skill was fetched at, when the source is a git repository.
+    #[serde(default)]
+    pub commit: Option<String>,
+    pub installed_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// `sha256:<hex>` of the downloaded archive, or `tree-sha256:<hex>` of the fetched
+    /// file tree for git and local sources (see [`tree_digest`]).
+    #[serde(default)]
+    pub source_digest: Option<String>,
+    /// URL a downloaded archive was finally served from, after any redirects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_url: Option<String>,
+    /// Upstream web page of the skill's directory at the installed commit or ref, for
+    /// hosted git sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_url: Option<String>,
+    /// `version` from the skill's frontmatter when it was installed or last updated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// SHA-256 of every file, by path relative to the skill directory.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+/// Paths that differ between two sets of file hashes.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileChanges {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl InstallManifest {
+    pub fn load(paths: &AppPaths) -> Result<Self> {
+        Ok(store::read(paths)?.installed)
+    }
+
+    /// Loads a pre-store `installed.json` from anywhere, e.g. one inside an old backup.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Runs `change` on the records under the store lock, so records another
+    /// invocation wrote since this one loaded them are kept.
+    pub fn update<T>(paths: &AppPaths, change: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        store::update(paths, |data| change(&mut data.installed))
+    }
+
+    pub fn get(&self, assistant: Assistant, name: &str) -> Option<&InstallRecord> {
+        self.skills.get(&record_key(assistant, name))
+    }
+
+    pub fn insert(&mut self, assistant: Assistant, name: &str, record: InstallRecord) {
+        self.skills.insert(record_key(assistant, name), record);
+    }
+
+    /// Installed skill names and records for `assistant`.
+    pub fn records_for(
+        &self,
+        assistant: Assistant,
+    ) -> impl Iterator<Item = (&str, &InstallRecord)> {
+        let prefix = format!("{assistant}/");
+        self.skills
+            .iter()
+            .filter_map(move |(key, record)| key.strip_prefix(&prefix).map(|name| (name, record)))
+    }
+
+    /// The record of `name` in the system root of `assistant`.
+    pub fn get_system(&self, assistant: Assistant, name: &str) -> Option<&InstallRecord> {
+        self.skills.get(&system_record_key(assistant, name))
+    }
+
+    pub fn insert_system(&mut self, assistant: Assistant, name: &str, record: InstallRecord) {
+        self.skills
+            .insert(system_record_key(assistant, name), record);
+    }
+
+    /// Skill names and records installed into the system root of `assistant`.
+    pub fn system_records_for(
+        &self,
+        assistant: Assistant,
+    ) -> impl Iterator<Item = (&str, &InstallRecord)> {
+        let prefix = system_record_key(assistant, "");
+        self.skills
+            .iter()
+            .filter_map(move |(key, record)| key.strip_prefix(&prefix).map(|name| (name, record)))
+    }
+
+    pub fn remove_system(&mut self, assistant: Assistant, name: &str) -> Option<InstallRecord> {
+        self.skills.remove(&system_record_key(assistant, name))
+    }
+
+    pub fn remove(&mut self, assistant: Assistant, name: &str) -> Option<InstallRecord> {
+        self.protected.remove(&record_key(assistant, name));
+        self.pinned.remove(&record_key(assistant, name));
+        self.skills.remove(&record_key(assistant, name))
+    }
+
+    pub fn is_protected(&self, assistant: Assistant, name: &str) -> bool {
+        self.protected.contains(&record_key(assistant, name))
+    }
+
+    pub fn is_pinned(&self, assistant: Assistant, name: &str) -> bool {
+        self.pinned.contains(&record_key(assistant, name))
+    }
+
+    /// The source `name` is locked to: its lock, or the source of an install recorded
+    /// before locks existed.
+    pub fn source_lock(&self, assistant: Assistant, name: &str) -> Option<&str> {
+        let key = record_key(assistant, name);
+        self.source_locks
+            .get(&key)
+            .or_else(|| self.skills.get(&key).map(|record| &record.source))
+            .map(String::as_str)
+    }
+
+    pub fn lock_source(&mut self, assistant: Assistant, name: &str, source: &str) {
+        self.source_locks
+            .insert(record_key(assistant, name), source.to_string());
+    }
+
+    /// Returns whether the flag changed.
+    pub fn set_protected(&mut self, assistant: Assistant, name: &str, protected: bool) -> bool {
+        let key = record_key(assistant, name);
+        if protected {
+            self.protected.insert(key)
+        } else {
+            self.protected.remove(&key)
+        }
+    }
+
+    /// Returns whether the flag changed.
+    pub fn set_pinned(&mut self, assistant: Assistant, name: &str, pinned: bool) -> bool {
+        let key = record_key(assistant, name);
+        if pinned {
+            self.pinned.insert(key)
+        } else {
+            self.pinned.remove(&key)
+        }
+    }
+}
+
+impl FileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} modified, {} removed",
+            self.added.len(),
+            self.modified.len(),
+            self.removed.len()
+        )
+    }
+
+    pub fn paths(&self) -> BTreeSet<&str> {
+        self.added
+            .iter()
+            .chain(&self.modified)
+            .chain(&self.removed)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Hashes every file under `dir` (skipping `.git`, `target`, and `.DS_Store`).
+pub fn hash_dir(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for entry in WalkDir::new(dir).follow_links(false) {
+        let entry = entry?;
+        let rel_path = entry.path().strip_prefix(dir)?;
+        if crate::commands::should_skip(rel_path) || !entry.file_type().is_file() {
+            continue;
+        }
+        files.push((relative_key(rel_path), entry.into_path()));
+    }
+
+    let hashes = parallel::map(&files, |(_, path)| hash_file(path))?;
+    Ok(files.into_iter().map(|(key, _)| key).zip(hashes).collect())
+}
+
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut file =
+        fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Digest of a whole file tree, from its per-file hashes.
+pub fn tree_digest(files: &BTreeMap<String, String>) -> String {
+    let mut hasher = Sha256::new();
+    for (path, hash) in files {
+        hasher.update(path.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(hash.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("tree-sha256:{:x}", hasher.finalize())
+}
+
+/// Compares `current` against `base`, e.g. an installed tree against its manifest.
+pub fn changes(base: &BTreeMap<String, String>, current: &BTreeMap<String, String>) -> FileChanges {
+    let mut changes = FileChanges::default();
+    for (path, hash) in current {
+        match base.get(path) {
+            None => changes.added.push(path.clone()),
+            Some(base_hash) if base_hash != hash => changes.modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    changes.removed = base
+        .keys()
+        .filter(|path| !current.contains_key(*path))
+        .cloned()
+        .collect();
+    changes
+}
+
+fn relative_key(rel_path: &Path) -> String {
+    rel_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn record_key(assistant: Assistant, name: &str) -> String {
+    format!("{assistant}/{name}")
+}
+
+fn system_record_key(assistant: Assistant, name: &str) -> String {
+    format!("system/{assistant}/{name}")
+}
+
+/// Where install records lived before the metadata store; see [`store`].
+pub fn manifest_path(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join(MANIFEST_FILE_NAME)
+}
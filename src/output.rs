@@ -0,0 +1,54 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PORCELAIN: AtomicBool = AtomicBool::new(false);
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Switches result-printing commands to `--porcelain` output: one tab-separated record
+/// per line on stdout, no headers or summaries, and a format kept stable across releases.
+pub fn set_porcelain(porcelain: bool) {
+    PORCELAIN.store(porcelain, Ordering::Relaxed);
+}
+
+pub fn porcelain() -> bool {
+    PORCELAIN.load(Ordering::Relaxed)
+}
+
+/// Switches result-printing commands to `--json` output: a single JSON document on
+/// stdout in place of the human-readable text.
+pub fn set_json(json: bool) {
+    JSON.store(json, Ordering::Relaxed);
+}
+
+pub fn json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Prints `value` as the command's `--json` document.
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Prints one porcelain record.
+pub fn record(fields: &[&str]) {
+    println!("{}", format_record(fields));
+}
+
+/// Joins `fields` with tabs. Tabs and line breaks inside a field become spaces and
+/// empty fields become `-`, so every record has the same number of columns.
+pub fn format_record(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let field = field.replace(['\t', '\n', '\r'], " ");
+            if field.trim().is_empty() {
+                "-".to_string()
+            } else {
+                field
+            }
+        })
+        .collect();
+    fields.join("\t")
+}
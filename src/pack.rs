@@ -0,0 +1,242 @@
+use crate::assistant::Assistant;
+use crate::manifest;
+use anyhow::{anyhow, Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use tar::{Builder, Header};
+use walkdir::WalkDir;
+
+/// `ssh-keygen -Y` namespace, so a pack signature can't be replayed as e.g. a git
+/// commit signature.
+const SIGNATURE_NAMESPACE: &str = "skill-pack";
+
+/// File next to `<name>/` in a `skill export` archive, describing the skill inside.
+pub const EXPORT_MANIFEST_FILE: &str = "skill-export.json";
+
+/// What `skill export` records about the skill it packed, so `add` can check the
+/// archive arrived intact and tell where the skill came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Assistant the skill was exported from.
+    pub assistant: Assistant,
+    /// Source the skill was installed from, when `skill` installed it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// `tree-sha256:<hex>` of `files` (see [`manifest::tree_digest`]).
+    pub digest: String,
+    /// SHA-256 of every file, by path relative to the skill directory.
+    pub files: BTreeMap<String, String>,
+}
+
+impl ExportManifest {
+    /// Errors unless `skill_dir` holds exactly the files listed, with the same hashes.
+    pub fn verify(&self, skill_dir: &Path) -> Result<()> {
+        if manifest::tree_digest(&self.files) != self.digest {
+            return Err(anyhow!(
+                "{EXPORT_MANIFEST_FILE} of {} lists files that do not match its digest",
+                self.name
+            ));
+        }
+        let changes = manifest::changes(&self.files, &manifest::hash_dir(skill_dir)?);
+        if !changes.is_empty() {
+            return Err(anyhow!(
+                "{} does not match its {EXPORT_MANIFEST_FILE}: {}",
+                self.name,
+                changes.summary()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Writes `skill_dir` as a `.tar.gz` with every entry under `<name>/`. Entries are
+/// sorted and timestamps zeroed so packing the same tree twice gives the same bytes.
+pub fn create_pack(skill_dir: &Path, name: &str, output: &Path) -> Result<()> {
+    write_archive(skill_dir, name, output, None)
+}
+
+/// Writes `skill_dir` like [`create_pack`], with `export` beside the skill directory
+/// as [`EXPORT_MANIFEST_FILE`].
+pub fn create_export(skill_dir: &Path, export: &ExportManifest, output: &Path) -> Result<()> {
+    let contents = serde_json::to_vec_pretty(export)?;
+    write_archive(skill_dir, &export.name, output, Some(&contents))
+}
+
+/// Reads the export manifest at the top of an extracted archive, if there is one,
+/// and checks the skill directory next to it against it.
+pub fn check_export(extract_dir: &Path) -> Result<Option<ExportManifest>> {
+    let path = extract_dir.join(EXPORT_MANIFEST_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let export: ExportManifest = serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    if !matches!(
+        Path::new(&export.name)
+            .components()
+            .collect::<Vec<_>>()
+            .as_slice(),
+        [Component::Normal(_)]
+    ) {
+        return Err(anyhow!(
+            "{EXPORT_MANIFEST_FILE} names an invalid skill '{}'",
+            export.name
+        ));
+    }
+    export.verify(&extract_dir.join(&export.name))?;
+    Ok(Some(export))
+}
+
+fn write_archive(
+    skill_dir: &Path,
+    name: &str,
+    output: &Path,
+    export_manifest: Option<&[u8]>,
+) -> Result<()> {
+    let file =
+        File::create(output).with_context(|| format!("failed to create {}", output.display()))?;
+    let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+
+    for entry in WalkDir::new(skill_dir)
+        .follow_links(false)
+        .sort_by_file_name()
+    {
+        let entry = entry?;
+        let rel_path = entry.path().strip_prefix(skill_dir)?;
+        if crate::commands::should_skip(rel_path) {
+            continue;
+        }
+        let archive_path = Path::new(name).join(rel_path);
+        let metadata = entry.metadata()?;
+        let mut header = Header::new_gnu();
+        header.set_mtime(0);
+        header.set_mode(file_mode(&metadata));
+        if entry.file_type().is_dir() {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            builder.append_data(&mut header, &archive_path, std::io::empty())?;
+        } else if entry.file_type().is_file() {
+            header.set_size(metadata.len());
+            let file = File::open(entry.path())
+                .with_context(|| format!("failed to read {}", entry.path().display()))?;
+            builder.append_data(&mut header, &archive_path, file)?;
+        }
+    }
+    if let Some(contents) = export_manifest {
+        let mut header = Header::new_gnu();
+        header.set_mtime(0);
+        header.set_mode(0o644);
+        header.set_size(contents.len() as u64);
+        builder.append_data(&mut header, EXPORT_MANIFEST_FILE, contents)?;
+    }
+
+    builder
+        .into_inner()?
+        .finish()
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    Ok(())
+}
+
+/// Where the detached signature of `pack` lives: `<pack>.sig`.
+pub fn signature_path(pack: &Path) -> PathBuf {
+    let mut path = pack.as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// Signs `pack` with an SSH private key, writing `<pack>.sig`.
+pub fn sign_pack(pack: &Path, key: &Path) -> Result<PathBuf> {
+    let signature = signature_path(pack);
+    if signature.exists() {
+        fs::remove_file(&signature)
+            .with_context(|| format!("failed to replace {}", signature.display()))?;
+    }
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", SIGNATURE_NAMESPACE, "-f"])
+        .arg(key)
+        .arg(pack)
+        .stdin(Stdio::null())
+        .output()
+        .context("failed to run ssh-keygen; is OpenSSH installed?")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "failed to sign {}: {}",
+            pack.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(signature)
+}
+
+/// Verifies `pack` against its detached `signature` and returns the signer's
+/// principal from `allowed_signers`.
+pub fn verify_pack(pack: &Path, signature: &Path, allowed_signers: &Path) -> Result<String> {
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "find-principals", "-s"])
+        .arg(signature)
+        .arg("-f")
+        .arg(allowed_signers)
+        .stdin(Stdio::null())
+        .output()
+        .context("failed to run ssh-keygen; is OpenSSH installed?")?;
+    let principal = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(str::to_string)
+        .filter(|_| output.status.success())
+        .ok_or_else(|| {
+            anyhow!(
+                "{} is not signed by a key in {}",
+                pack.display(),
+                allowed_signers.display()
+            )
+        })?;
+
+    let pack_file =
+        File::open(pack).with_context(|| format!("failed to read {}", pack.display()))?;
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "verify", "-n", SIGNATURE_NAMESPACE, "-f"])
+        .arg(allowed_signers)
+        .arg("-I")
+        .arg(&principal)
+        .arg("-s")
+        .arg(signature)
+        .stdin(pack_file)
+        .output()
+        .context("failed to run ssh-keygen; is OpenSSH installed?")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "signature verification failed for {}: {}",
+            pack.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(principal)
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.is_dir() {
+        0o755
+    } else {
+        0o644
+    }
+}
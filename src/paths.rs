@@ -1,17 +1,22 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use std::fs::{self, File, OpenOptions};
 use std::path::{Path, PathBuf};
 
 const SKILLS_HOME_DIR_NAME: &str = ".skills";
 const SKILLS_DATA_DIR_NAME: &str = "data";
 const CONFIG_FILE_NAME: &str = "config.yaml";
 const USAGE_FILE_NAME: &str = "usage.json";
+const CACHE_DIR_NAME: &str = "cache";
+const SYSTEM_SKILLS_DIR_NAME: &str = "agent-skills";
 
 #[derive(Debug, Clone)]
 pub struct AppPaths {
     pub config_dir: PathBuf,
     pub config_file: PathBuf,
     pub data_dir: PathBuf,
+    /// Pre-store usage counts, imported into the metadata store on first use.
     pub usage_file: PathBuf,
+    pub cache_dir: PathBuf,
     pub skills_base_dir: PathBuf,
 }
 
@@ -23,12 +28,14 @@ impl AppPaths {
         let skills_base_dir = default_skills_base_dir()?;
         let config_file = config_dir.join(CONFIG_FILE_NAME);
         let usage_file = skills_home.join(USAGE_FILE_NAME);
+        let cache_dir = skills_home.join(CACHE_DIR_NAME);
 
         Ok(Self {
             config_dir,
             config_file,
             data_dir,
             usage_file,
+            cache_dir,
             skills_base_dir,
         })
     }
@@ -38,6 +45,18 @@ pub fn default_skills_base_dir() -> Result<PathBuf> {
     default_data_dir()
 }
 
+/// Machine-level root for admin-provisioned skills, shared by every user.
+pub fn default_system_skills_dir() -> PathBuf {
+    if cfg!(windows) {
+        std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+            .join(SYSTEM_SKILLS_DIR_NAME)
+    } else {
+        Path::new("/usr/local/share").join(SYSTEM_SKILLS_DIR_NAME)
+    }
+}
+
 pub fn default_data_dir() -> Result<PathBuf> {
     let home_dir = skills_home_dir()?;
     Ok(home_dir.join(SKILLS_DATA_DIR_NAME))
@@ -52,3 +71,79 @@ pub fn ensure_dir(path: &Path) -> Result<()> {
     std::fs::create_dir_all(path)
         .map_err(|err| anyhow!("failed to create directory {}: {err}", path.display()))
 }
+
+/// Whether `a` and `b` are on the same filesystem, so a rename can move one into the
+/// other.
+#[cfg(unix)]
+pub fn same_filesystem(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn same_filesystem(a: &Path, b: &Path) -> bool {
+    a.components().next() == b.components().next()
+}
+
+/// Mode for files holding config, install records, usage counts, the audit log or
+/// backups of them.
+pub const PRIVATE_FILE_MODE: u32 = 0o600;
+/// Mode for the directories holding them.
+pub const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// Creates `path` like [`ensure_dir`] and makes it private to the user on Unix.
+pub fn ensure_private_dir(path: &Path) -> Result<()> {
+    ensure_dir(path)?;
+    restrict(path, PRIVATE_DIR_MODE);
+    Ok(())
+}
+
+/// Writes `contents` to `path`, readable and writable only by the user on Unix. A
+/// file that already exists is tightened to that mode too.
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    use std::io::Write;
+    let mut file = create_private(path)?;
+    file.write_all(contents.as_ref())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Creates or truncates `path` for writing, private to the user on Unix.
+pub fn create_private(path: &Path) -> Result<File> {
+    open_private(
+        path,
+        OpenOptions::new().write(true).create(true).truncate(true),
+    )
+}
+
+/// Opens `path` for appending, creating it private to the user on Unix.
+pub fn append_private(path: &Path) -> Result<File> {
+    open_private(path, OpenOptions::new().create(true).append(true))
+}
+
+fn open_private(path: &Path, options: &mut OpenOptions) -> Result<File> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(PRIVATE_FILE_MODE);
+    }
+    let file = options
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    restrict(path, PRIVATE_FILE_MODE);
+    Ok(file)
+}
+
+/// Sets `mode` on `path`, which the create mode alone can't do for paths that already
+/// exist. Best effort: a path owned by someone else keeps its mode, and `doctor`
+/// reports it.
+#[cfg(unix)]
+fn restrict(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode));
+}
+
+#[cfg(not(unix))]
+fn restrict(_path: &Path, _mode: u32) {}
@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Time budgets for external tools; `None` means no limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToolTimeouts {
+    pub trivy: Option<Duration>,
+    pub clamscan: Option<Duration>,
+    pub yara: Option<Duration>,
+    pub git: Option<Duration>,
+    pub reload: Option<Duration>,
+}
+
+/// Runs `command` capturing its output, killing it once `timeout` elapses.
+/// Returns `Ok(None)` when the process was terminated for exceeding its budget.
+pub fn output_with_timeout(
+    command: &mut Command,
+    timeout: Option<Duration>,
+) -> Result<Option<Output>> {
+    let Some(timeout) = timeout else {
+        return command
+            .output()
+            .map(Some)
+            .context("failed to spawn process");
+    };
+
+    let mut child = own_process_group(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to spawn process")?;
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            kill_process_group(&mut child);
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let collect = |handle: Option<thread::JoinHandle<Vec<u8>>>| {
+        handle
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+    let stdout = collect(stdout);
+    let stderr = collect(stderr);
+    Ok(status.map(|status| Output {
+        status,
+        stdout,
+        stderr,
+    }))
+}
+
+/// Runs `command` with inherited stdio, killing it once `timeout` elapses.
+/// Returns `Ok(None)` when the process was terminated for exceeding its budget.
+/// With a timeout, the child runs outside the terminal's foreground process group,
+/// so it cannot prompt on the terminal.
+pub fn status_with_timeout(
+    command: &mut Command,
+    timeout: Option<Duration>,
+) -> Result<Option<ExitStatus>> {
+    if timeout.is_some() {
+        own_process_group(command);
+    }
+    let mut child = command.spawn().context("failed to spawn process")?;
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            kill_process_group(&mut child);
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// A timeout as it appears in messages, e.g. `30s` or `250ms`.
+pub fn describe_timeout(timeout: Duration) -> String {
+    let millis = timeout.as_millis();
+    if millis.is_multiple_of(1000) {
+        format!("{}s", millis / 1000)
+    } else {
+        format!("{millis}ms")
+    }
+}
+
+/// Starts the child in a process group of its own (on unix), so a timeout can kill
+/// whatever it spawned along with it.
+fn own_process_group(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    command
+}
+
+/// Kills `child` and the rest of its process group. Helpers it started, such as the
+/// commands of a `sh -c` wrapper, would otherwise keep running and hold its output
+/// pipes open past the deadline.
+fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: `kill` has no memory-safety requirements; the group is the one
+        // `own_process_group` created for this child, which has not been reaped yet.
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn read_in_background<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = reader.read_to_end(&mut buffer);
+        buffer
+    })
+}
@@ -0,0 +1,147 @@
+use serde_json::{Map, Value};
+use std::fmt;
+use std::io::{self, IsTerminal, Read, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Bytes between `download_progress` events when the server sends no length.
+const UNSIZED_REPORT_BYTES: u64 = 1024 * 1024;
+
+/// How long operations report progress on stderr: `auto` draws a one-line display on
+/// a terminal, `json` writes one NDJSON event per line, `none` stays quiet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+    #[default]
+    Auto,
+    Json,
+    None,
+}
+
+impl fmt::Display for ProgressMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressMode::Auto => write!(f, "auto"),
+            ProgressMode::Json => write!(f, "json"),
+            ProgressMode::None => write!(f, "none"),
+        }
+    }
+}
+
+impl FromStr for ProgressMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "auto" => Ok(ProgressMode::Auto),
+            "json" => Ok(ProgressMode::Json),
+            "none" | "off" => Ok(ProgressMode::None),
+            _ => Err(format!(
+                "unknown progress mode '{value}'. Use auto, json, or none."
+            )),
+        }
+    }
+}
+
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_mode(mode: ProgressMode) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn mode() -> ProgressMode {
+    match MODE.load(Ordering::Relaxed) {
+        1 => ProgressMode::Json,
+        2 => ProgressMode::None,
+        _ => ProgressMode::Auto,
+    }
+}
+
+/// Whether progress goes out as NDJSON events, in place of the human-readable lines
+/// they describe.
+pub fn json() -> bool {
+    mode() == ProgressMode::Json
+}
+
+/// Whether to draw the one-line terminal display.
+pub fn interactive() -> bool {
+    mode() == ProgressMode::Auto && io::stderr().is_terminal()
+}
+
+/// Writes `{"event": event, ...fields}` as one line on stderr under `--progress json`.
+pub fn emit(event: &str, fields: Value) {
+    if !json() {
+        return;
+    }
+    let mut object = Map::new();
+    object.insert("event".to_string(), event.into());
+    if let Value::Object(fields) = fields {
+        object.extend(fields);
+    }
+    let _ = writeln!(io::stderr().lock(), "{}", Value::Object(object));
+}
+
+/// Reader that emits `download_progress` events for `url` as bytes arrive: on every
+/// whole percent when `total` is known, otherwise every megabyte.
+pub struct DownloadProgress<R> {
+    inner: R,
+    url: String,
+    total: Option<u64>,
+    read: u64,
+    reported: u64,
+}
+
+impl<R: Read> DownloadProgress<R> {
+    pub fn new(inner: R, url: &str, total: Option<u64>) -> Self {
+        Self {
+            inner,
+            url: url.to_string(),
+            total: total.filter(|total| *total > 0),
+            read: 0,
+            reported: 0,
+        }
+    }
+
+    /// Counts `offset` bytes as already read, for a download resumed part-way.
+    pub fn resumed_at(mut self, offset: u64) -> Self {
+        self.read = offset;
+        self.reported = match self.total {
+            Some(total) => (offset.min(total) * 100 / total).min(100),
+            None => offset / UNSIZED_REPORT_BYTES,
+        };
+        self
+    }
+
+    fn report(&mut self) {
+        let (mark, percent) = match self.total {
+            Some(total) => {
+                let percent = (self.read.min(total) * 100 / total).min(100);
+                (percent, Some(percent))
+            }
+            None => (self.read / UNSIZED_REPORT_BYTES, None),
+        };
+        if mark == self.reported {
+            return;
+        }
+        self.reported = mark;
+        emit(
+            "download_progress",
+            serde_json::json!({
+                "url": self.url,
+                "bytes": self.read,
+                "total": self.total,
+                "percent": percent,
+            }),
+        );
+    }
+}
+
+impl<R: Read> Read for DownloadProgress<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buffer)?;
+        self.read += read as u64;
+        if read > 0 && json() {
+            self.report();
+        }
+        Ok(read)
+    }
+}
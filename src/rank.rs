@@ -0,0 +1,124 @@
+use crate::assistant::Assistant;
+use crate::paths::ensure_dir;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Ordering hint `skill rank --write` leaves in each assistant's skills root.
+pub const HINT_FILE_NAME: &str = ".skill-rank.json";
+const DEFAULT_HALF_LIFE_DAYS: f64 = 14.0;
+
+/// How `skill rank` weighs skills, from the `rank` section of the config.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RankConfig {
+    /// Days after which a use counts half as much (null = 14).
+    #[serde(default)]
+    pub half_life_days: Option<f64>,
+    /// Skills ranked ahead of every other one, in this order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<String>,
+    /// Score added to skills with a tag or category, by lowercase name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, f64>,
+}
+
+impl RankConfig {
+    fn half_life_days(&self) -> f64 {
+        self.half_life_days
+            .filter(|days| *days > 0.0)
+            .unwrap_or(DEFAULT_HALF_LIFE_DAYS)
+    }
+
+    fn pin_position(&self, name: &str) -> Option<usize> {
+        self.pinned.iter().position(|pinned| pinned == name)
+    }
+
+    fn tag_boost(&self, tags: &[String]) -> f64 {
+        tags.iter()
+            .filter_map(|tag| self.tags.get(&tag.to_lowercase()))
+            .sum()
+    }
+}
+
+/// An installed skill and what is known about its use.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub name: String,
+    /// Tags and category from the frontmatter.
+    pub tags: Vec<String>,
+    pub uses: u64,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Ranked {
+    pub name: String,
+    pub score: f64,
+    pub uses: u64,
+    pub last_used: Option<DateTime<Utc>>,
+    pub pinned: bool,
+}
+
+/// Orders `candidates` for context prioritization: pinned skills first in their
+/// configured order, then by score, where each use is worth half as much every
+/// half-life since the skill was last used, plus the boosts of its tags. Uses with no
+/// recorded time count as one half-life old.
+pub fn rank(candidates: Vec<Candidate>, config: &RankConfig, now: DateTime<Utc>) -> Vec<Ranked> {
+    let half_life = config.half_life_days();
+    let mut ranked: Vec<(Option<usize>, Ranked)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            let age_days = candidate.last_used.map_or(half_life, |last_used| {
+                ((now - last_used).num_seconds().max(0) as f64) / 86_400.0
+            });
+            let decayed = candidate.uses as f64 * 0.5f64.powf(age_days / half_life);
+            let position = config.pin_position(&candidate.name);
+            (
+                position,
+                Ranked {
+                    score: decayed + config.tag_boost(&candidate.tags),
+                    pinned: position.is_some(),
+                    name: candidate.name,
+                    uses: candidate.uses,
+                    last_used: candidate.last_used,
+                },
+            )
+        })
+        .collect();
+    ranked.sort_by(|(a_pin, a), (b_pin, b)| match (a_pin, b_pin) {
+        (Some(a_pin), Some(b_pin)) => a_pin.cmp(b_pin),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => b
+            .score
+            .total_cmp(&a.score)
+            .then_with(|| a.name.cmp(&b.name)),
+    });
+    ranked.into_iter().map(|(_, ranked)| ranked).collect()
+}
+
+/// Contents of [`HINT_FILE_NAME`]: skill names, most relevant first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankHints {
+    pub assistant: Assistant,
+    pub generated_at: DateTime<Utc>,
+    pub skills: Vec<String>,
+}
+
+/// Writes the ranking for `assistant` into `root` and returns the file written.
+pub fn write_hints(root: &Path, assistant: Assistant, ranked: &[Ranked]) -> Result<PathBuf> {
+    ensure_dir(root)?;
+    let path = root.join(HINT_FILE_NAME);
+    let hints = RankHints {
+        assistant,
+        generated_at: Utc::now(),
+        skills: ranked.iter().map(|ranked| ranked.name.clone()).collect(),
+    };
+    fs::write(&path, serde_json::to_string_pretty(&hints)? + "\n")
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
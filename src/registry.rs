@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Records who owns each name, in the registry root.
+pub const OWNERS_FILE_NAME: &str = ".skill-owners.yaml";
+
+/// Who may publish under a name that is already taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NamePolicy {
+    /// The first author to publish a name owns it; others are rejected.
+    #[default]
+    FirstPublish,
+    /// Names must start with a configured prefix; members of that prefix may publish.
+    OrgPrefix,
+    /// Anyone may publish any name; replacing another author's skill only warns.
+    Open,
+}
+
+impl fmt::Display for NamePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NamePolicy::FirstPublish => "first-publish",
+            NamePolicy::OrgPrefix => "org-prefix",
+            NamePolicy::Open => "open",
+        })
+    }
+}
+
+/// A shared directory (e.g. a git checkout or network share) skills are published into.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub policy: NamePolicy,
+    /// For `org-prefix`: name prefix to the authors (name or email) allowed to use it.
+    /// An empty list lets anyone publish under the prefix.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prefixes: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Owners {
+    /// Skill name to the `author` that first published it.
+    #[serde(default)]
+    pub skills: BTreeMap<String, String>,
+}
+
+impl Owners {
+    pub fn load(registry_root: &Path) -> Result<Self> {
+        let path = registry_root.join(OWNERS_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, registry_root: &Path) -> Result<()> {
+        let path = registry_root.join(OWNERS_FILE_NAME);
+        fs::write(&path, serde_yaml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// What publishing a name would do, once the policy allows it.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PublishCheck {
+    /// The name is free.
+    New,
+    /// The name exists and the publisher may replace it.
+    Replace,
+    /// `open` policy: the name belongs to someone else and is replaced anyway.
+    Takeover { owner: String },
+}
+
+/// Applies `config.policy` to publishing `name` as `author`, where `owner` is the
+/// author currently holding the name, if any.
+pub fn check_publish(
+    config: &RegistryConfig,
+    name: &str,
+    author: Option<&str>,
+    owner: Option<&str>,
+) -> Result<PublishCheck> {
+    if config.policy == NamePolicy::OrgPrefix {
+        let Some((prefix, members)) = config
+            .prefixes
+            .iter()
+            .filter(|(prefix, _)| name.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+        else {
+            let prefixes: Vec<&str> = config.prefixes.keys().map(String::as_str).collect();
+            return Err(anyhow!(
+                "'{name}' must start with an org prefix ({})",
+                if prefixes.is_empty() {
+                    "none configured".to_string()
+                } else {
+                    prefixes.join(", ")
+                }
+            ));
+        };
+        if !members.is_empty() {
+            let author = require_author(author)?;
+            if !members.iter().any(|member| same_author(member, author)) {
+                return Err(anyhow!(
+                    "{author} is not allowed to publish under the '{prefix}' prefix"
+                ));
+            }
+        }
+        return Ok(if owner.is_some() {
+            PublishCheck::Replace
+        } else {
+            PublishCheck::New
+        });
+    }
+
+    let Some(owner) = owner else {
+        if config.policy == NamePolicy::FirstPublish {
+            require_author(author)?;
+        }
+        return Ok(PublishCheck::New);
+    };
+    if author.is_some_and(|author| same_author(owner, author)) {
+        return Ok(PublishCheck::Replace);
+    }
+    match config.policy {
+        NamePolicy::Open => Ok(PublishCheck::Takeover {
+            owner: owner.to_string(),
+        }),
+        _ => Err(anyhow!("'{name}' is already published by {owner}")),
+    }
+}
+
+fn require_author(author: Option<&str>) -> Result<&str> {
+    author
+        .filter(|author| !author.trim().is_empty())
+        .ok_or_else(|| anyhow!("set `author` in SKILL.md; this registry tracks name ownership"))
+}
+
+/// Compares authors by email when they have one (`Name <email>`), by name otherwise.
+/// A bare email (as in `prefixes` member lists) matches an author with that email.
+pub fn same_author(a: &str, b: &str) -> bool {
+    let key = |author: &str| {
+        let author = author.trim();
+        match author
+            .rsplit_once('<')
+            .and_then(|(_, email)| email.strip_suffix('>'))
+        {
+            Some(email) => email.trim().to_lowercase(),
+            None => author.to_lowercase(),
+        }
+    };
+    key(a) == key(b)
+}
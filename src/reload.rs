@@ -0,0 +1,123 @@
+use crate::assistant::Assistant;
+use crate::config::Config;
+use crate::process::status_with_timeout;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+/// What changed in a skills root, passed to reload commands as `SKILL_EVENT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReloadEvent {
+    Install,
+    Update,
+    Remove,
+}
+
+impl fmt::Display for ReloadEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReloadEvent::Install => "install",
+            ReloadEvent::Update => "update",
+            ReloadEvent::Remove => "remove",
+        })
+    }
+}
+
+/// How to make one assistant pick up changed skills without a restart.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ReloadHook {
+    /// File whose modification time is bumped (created if missing), for assistants
+    /// that watch a reload file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub touch: Option<PathBuf>,
+    /// Program and arguments to run, e.g. `["pkill", "-HUP", "opencode"]` to signal
+    /// a running assistant or a CLI that asks it to rescan.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReloadHooks {
+    #[serde(default)]
+    pub codex: Option<ReloadHook>,
+    #[serde(default)]
+    pub claudecode: Option<ReloadHook>,
+    #[serde(default)]
+    pub opencode: Option<ReloadHook>,
+}
+
+impl ReloadHooks {
+    pub fn for_assistant(&self, assistant: Assistant) -> Option<&ReloadHook> {
+        match assistant {
+            Assistant::Codex => self.codex.as_ref(),
+            Assistant::ClaudeCode => self.claudecode.as_ref(),
+            Assistant::OpenCode => self.opencode.as_ref(),
+        }
+    }
+}
+
+/// Runs the configured reload hook for `assistant` after `names` were changed. Skills
+/// are already in place at this point, so failures are only reported as warnings.
+pub fn notify(config: &Config, assistant: Assistant, event: ReloadEvent, names: &[String]) {
+    let Some(hook) = config.reload.for_assistant(assistant) else {
+        return;
+    };
+    if names.is_empty() {
+        return;
+    }
+    if let Err(err) = run_hook(config, hook, assistant, event, names) {
+        eprintln!("Warning: failed to notify {assistant} of the change: {err:#}");
+    }
+}
+
+fn run_hook(
+    config: &Config,
+    hook: &ReloadHook,
+    assistant: Assistant,
+    event: ReloadEvent,
+    names: &[String],
+) -> Result<()> {
+    if let Some(path) = &hook.touch {
+        touch(path)?;
+    }
+    let Some((program, args)) = hook.command.split_first() else {
+        return Ok(());
+    };
+    let timeout = config.tool_timeouts()?.reload;
+    let status = status_with_timeout(
+        Command::new(program)
+            .args(args)
+            .env("SKILL_ASSISTANT", assistant.as_str())
+            .env("SKILL_EVENT", event.to_string())
+            .env("SKILL_NAMES", names.join(" "))
+            .stdin(Stdio::null()),
+        timeout,
+    )
+    .with_context(|| format!("failed to run {program}"))?;
+    match status {
+        Some(status) if status.success() => Ok(()),
+        Some(status) => Err(anyhow!("{program} exited with {status}")),
+        None => Err(anyhow!("{program} timed out")),
+    }
+}
+
+fn touch(path: &Path) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    file.set_modified(SystemTime::now())
+        .with_context(|| format!("failed to touch {}", path.display()))
+}
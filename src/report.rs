@@ -0,0 +1,251 @@
+use crate::rules;
+use crate::scan::{self, ScanReport};
+use crate::validation::{self, ValidationReport};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::json;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// File format for `--report-file`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Sarif,
+}
+
+impl ReportFormat {
+    /// `sarif` for `*.sarif` / `*.sarif.json` files, JSON otherwise.
+    pub fn for_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if name.ends_with(".sarif") || name.ends_with(".sarif.json") {
+            ReportFormat::Sarif
+        } else {
+            ReportFormat::Json
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportFormat::Json => write!(f, "json"),
+            ReportFormat::Sarif => write!(f, "sarif"),
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            _ => Err(format!(
+                "unknown report format '{value}'. Use json or sarif."
+            )),
+        }
+    }
+}
+
+/// The result of one `scan` or `validate` run, in a shape independent of the check.
+#[derive(Debug, Serialize)]
+pub struct CheckReport {
+    /// `scan` or `validate`.
+    pub check: &'static str,
+    pub target: PathBuf,
+    pub passed: bool,
+    pub findings: Vec<Finding>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suppressed: Vec<Finding>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub external: Vec<ExternalFinding>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub severity: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Reason given in `.skillcheck.yaml`, for suppressed findings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternalFinding {
+    pub tool: String,
+    pub severity: &'static str,
+    pub output: String,
+}
+
+impl CheckReport {
+    pub fn from_scan(target: &Path, report: &ScanReport) -> Self {
+        let finding = |issue: &scan::ScanIssue, reason: Option<&String>| Finding {
+            rule: issue.rule,
+            severity: issue.severity.as_str(),
+            message: issue.message.clone(),
+            path: issue.path.clone(),
+            reason: reason.cloned(),
+        };
+        Self {
+            check: "scan",
+            target: target.to_path_buf(),
+            passed: !report.has_errors(),
+            findings: report
+                .issues
+                .iter()
+                .map(|issue| finding(issue, None))
+                .collect(),
+            suppressed: report
+                .suppressed
+                .iter()
+                .map(|(issue, reason)| finding(issue, Some(reason)))
+                .collect(),
+            external: report
+                .external
+                .iter()
+                .map(|scan| ExternalFinding {
+                    tool: scan.tool.clone(),
+                    severity: scan.severity.as_str(),
+                    output: scan.output.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_validation(target: &Path, report: &ValidationReport) -> Self {
+        let finding = |issue: &validation::ValidationIssue, reason: Option<&String>| Finding {
+            rule: issue.rule,
+            severity: issue.severity.as_str(),
+            message: issue.message.clone(),
+            path: issue.path.clone(),
+            reason: reason.cloned(),
+        };
+        Self {
+            check: "validate",
+            target: target.to_path_buf(),
+            passed: !report.has_errors(),
+            findings: report
+                .issues
+                .iter()
+                .map(|issue| finding(issue, None))
+                .collect(),
+            suppressed: report
+                .suppressed
+                .iter()
+                .map(|(issue, reason)| finding(issue, Some(reason)))
+                .collect(),
+            external: Vec::new(),
+        }
+    }
+
+    /// Writes the report to `path`, creating parent directories as needed.
+    pub fn write(&self, path: &Path, format: ReportFormat) -> Result<()> {
+        let contents = match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)?,
+            ReportFormat::Sarif => serde_json::to_string_pretty(&self.to_sarif())?,
+        };
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(path, contents + "\n")
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// SARIF 2.1.0 log with one run. Suppressed findings are kept as results with an
+    /// external suppression so code-scanning UIs show them as dismissed.
+    pub fn to_sarif(&self) -> serde_json::Value {
+        let mut rule_ids: Vec<&str> = self
+            .findings
+            .iter()
+            .chain(&self.suppressed)
+            .map(|finding| finding.rule)
+            .collect();
+        rule_ids.sort_unstable();
+        rule_ids.dedup();
+        let sarif_rules: Vec<serde_json::Value> = rule_ids
+            .iter()
+            .map(|id| match rules::find_rule(id) {
+                Some(doc) => json!({
+                    "id": id,
+                    "shortDescription": { "text": doc.checks },
+                    "help": { "text": doc.rationale },
+                }),
+                None => json!({ "id": id }),
+            })
+            .collect();
+
+        let mut results: Vec<serde_json::Value> = self
+            .findings
+            .iter()
+            .chain(&self.suppressed)
+            .map(|finding| {
+                let mut result = json!({
+                    "ruleId": finding.rule,
+                    "level": sarif_level(finding.severity),
+                    "message": { "text": finding.message },
+                });
+                if let Some(path) = &finding.path {
+                    result["locations"] = json!([{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": path.to_string_lossy() }
+                        }
+                    }]);
+                }
+                if let Some(reason) = &finding.reason {
+                    result["suppressions"] = json!([{
+                        "kind": "external",
+                        "justification": reason,
+                    }]);
+                }
+                result
+            })
+            .collect();
+        results.extend(self.external.iter().map(|external| {
+            json!({
+                "ruleId": format!("external/{}", external.tool),
+                "level": sarif_level(external.severity),
+                "message": { "text": external.output },
+            })
+        }));
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "skill",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": sarif_rules,
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+}
+
+fn sarif_level(severity: &str) -> &'static str {
+    match severity {
+        "error" => "error",
+        "warning" => "warning",
+        _ => "note",
+    }
+}
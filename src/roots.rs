@@ -0,0 +1,45 @@
+use crate::assistant::Assistant;
+use crate::config::Config;
+use crate::paths::{ensure_dir, AppPaths};
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// The personal or system skills root, without creating or checking it (`--dry-run`).
+pub fn target_root(
+    config: &Config,
+    paths: &AppPaths,
+    assistant: Assistant,
+    system: bool,
+) -> PathBuf {
+    if system {
+        config.system_root_for(assistant)
+    } else {
+        config.skills_root_for(paths, assistant)
+    }
+}
+
+/// Root that `add`, `remove` and `update` modify, created if missing. The system root
+/// is only writable with `--system`, and then only if the user has the permissions to
+/// change it.
+pub fn writable_root(
+    config: &Config,
+    paths: &AppPaths,
+    assistant: Assistant,
+    system: bool,
+) -> Result<PathBuf> {
+    let root = target_root(config, paths, assistant, system);
+    if !system {
+        ensure_dir(&root)?;
+        return Ok(root);
+    }
+
+    ensure_dir(&root)
+        .and_then(|_| tempfile::tempfile_in(&root).map_err(anyhow::Error::from))
+        .with_context(|| {
+            format!(
+                "cannot modify the system skills root {}; re-run with administrator permissions",
+                root.display()
+            )
+        })?;
+    Ok(root)
+}
@@ -0,0 +1,346 @@
+use crate::http;
+use crate::pack;
+use crate::paths::{ensure_dir, AppPaths};
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The pack compiled into this binary.
+pub const BUNDLED_PACK: &str = include_str!("../rules/pack.yaml");
+
+/// Where `skill rules update` fetches packs from unless `rules_url` is configured. The
+/// detached signature is expected at the same URL with `.sig` appended.
+pub const DEFAULT_PACK_URL: &str =
+    "https://raw.githubusercontent.com/dmoliveira/skill/main/rules/pack.yaml";
+
+const PACK_FILE_NAME: &str = "rules.yaml";
+const MAX_PACK_BYTES: u64 = 1024 * 1024;
+
+/// Data dir set by `main`; the downloaded pack there is used when it is newer than the
+/// bundled one.
+static DATA_DIR: OnceCell<PathBuf> = OnceCell::new();
+static ACTIVE: OnceCell<CompiledPack> = OnceCell::new();
+
+/// Which `skill scan` rule a pack pattern reports under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackCheck {
+    Secret,
+    RiskyCommand,
+    PromptInjection,
+}
+
+impl PackCheck {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PackCheck::Secret => "secret",
+            PackCheck::RiskyCommand => "risky-command",
+            PackCheck::PromptInjection => "prompt-injection",
+        }
+    }
+}
+
+/// A versioned set of detection patterns, shipped in `rules/pack.yaml`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RulePack {
+    pub version: String,
+    pub rules: Vec<PackRule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackRule {
+    pub id: String,
+    pub check: PackCheck,
+    pub pattern: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackSource {
+    Bundled,
+    /// Fetched by `skill rules update`; always applied on top of the bundled rules.
+    Downloaded(PathBuf),
+}
+
+impl fmt::Display for PackSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackSource::Bundled => write!(f, "bundled"),
+            PackSource::Downloaded(path) => {
+                write!(f, "downloaded, {}, on top of the bundled rules", path.display())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CompiledPack {
+    pub pack: RulePack,
+    pub source: PackSource,
+    regexes: Vec<Regex>,
+}
+
+impl RulePack {
+    pub fn parse(contents: &str) -> Result<Self> {
+        let pack: RulePack = serde_yaml::from_str(contents).context("invalid rule pack")?;
+        if pack.version.trim().is_empty() {
+            return Err(anyhow!("rule pack has no version"));
+        }
+        let mut ids = BTreeSet::new();
+        for rule in &pack.rules {
+            if !ids.insert(rule.id.as_str()) {
+                return Err(anyhow!("rule pack lists {} twice", rule.id));
+            }
+        }
+        Ok(pack)
+    }
+
+    /// Fails unless the pack keeps every bundled rule ID, so an update can add and
+    /// refine patterns but never switch a bundled check off.
+    pub fn check_covers(&self, bundled: &RulePack) -> Result<()> {
+        let ids: BTreeSet<&str> = self.rules.iter().map(|rule| rule.id.as_str()).collect();
+        let dropped: Vec<&str> = bundled
+            .rules
+            .iter()
+            .map(|rule| rule.id.as_str())
+            .filter(|id| !ids.contains(id))
+            .collect();
+        if !dropped.is_empty() {
+            return Err(anyhow!(
+                "rule pack {} drops bundled rules: {}",
+                self.version,
+                dropped.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// The bundled rules followed by this pack's. A rule that shares a bundled ID is
+    /// checked in addition to the bundled pattern, never instead of it.
+    fn on_top_of(mut self, bundled: RulePack) -> RulePack {
+        let mut rules = bundled.rules;
+        rules.append(&mut self.rules);
+        RulePack {
+            version: self.version,
+            rules,
+        }
+    }
+
+    pub fn compile(self, source: PackSource) -> Result<CompiledPack> {
+        let regexes = self
+            .rules
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .with_context(|| format!("invalid pattern for rule {}", rule.id))
+            })
+            .collect::<Result<_>>()?;
+        Ok(CompiledPack {
+            pack: self,
+            source,
+            regexes,
+        })
+    }
+}
+
+impl CompiledPack {
+    /// The first rule for `check` whose pattern matches `content`.
+    pub fn find(&self, check: PackCheck, content: &str) -> Option<&PackRule> {
+        self.pack
+            .rules
+            .iter()
+            .zip(&self.regexes)
+            .find(|(rule, regex)| rule.check == check && regex.is_match(content))
+            .map(|(rule, _)| rule)
+    }
+
+    /// The `skill rules list --json` document: the pack's version, source and rules.
+    pub fn to_json(&self) -> serde_json::Value {
+        let rules: Vec<_> = self
+            .pack
+            .rules
+            .iter()
+            .map(|rule| {
+                serde_json::json!({
+                    "id": rule.id,
+                    "check": rule.check.as_str(),
+                    "pattern": rule.pattern,
+                    "description": rule.description,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "version": self.pack.version,
+            "source": self.source.to_string(),
+            "rules": rules,
+        })
+    }
+}
+
+/// Lets `active` pick up a pack installed by `skill rules update`.
+pub fn init(paths: &AppPaths) {
+    let _ = DATA_DIR.set(paths.data_dir.clone());
+}
+
+pub fn pack_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(PACK_FILE_NAME)
+}
+
+/// The pack `skill scan` uses: the downloaded one on top of the bundled rules when it
+/// is newer than the bundled one (a newer binary wins over an older download),
+/// otherwise the bundled one alone.
+pub fn active() -> &'static CompiledPack {
+    ACTIVE.get_or_init(|| {
+        let bundled = bundled();
+        let Some(path) = DATA_DIR.get().map(|dir| pack_path(dir)) else {
+            return bundled;
+        };
+        if !path.exists() {
+            return bundled;
+        }
+        match load(&path) {
+            Ok(downloaded)
+                if compare_versions(&downloaded.pack.version, &bundled.pack.version)
+                    == Ordering::Greater =>
+            {
+                downloaded
+            }
+            Ok(_) => bundled,
+            Err(err) => {
+                eprintln!("Warning: ignoring {}: {err:#}", path.display());
+                bundled
+            }
+        }
+    })
+}
+
+fn bundled_pack() -> RulePack {
+    RulePack::parse(BUNDLED_PACK).expect("bundled rule pack is valid")
+}
+
+fn bundled() -> CompiledPack {
+    bundled_pack()
+        .compile(PackSource::Bundled)
+        .expect("bundled rule pack is valid")
+}
+
+/// Compiles the downloaded pack at `path` on top of the bundled rules.
+pub fn load(path: &Path) -> Result<CompiledPack> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    RulePack::parse(&contents)?
+        .on_top_of(bundled_pack())
+        .compile(PackSource::Downloaded(path.to_path_buf()))
+}
+
+/// Compares dotted versions numerically per segment (`2026.10.2` < `2026.10.10`),
+/// falling back to text for segments that are not numbers.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut left = a.trim().split('.');
+    let mut right = b.trim().split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(x), Some(y)) => {
+                let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    _ => x.cmp(y),
+                };
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    UpToDate { version: String },
+    Updated { from: String, to: String },
+}
+
+/// Downloads the pack at `url` over https with its `<url>.sig` signature and installs
+/// it into the data dir when a key in `allowed_signers` signed it and it is newer
+/// than the active pack. The pack is fully compiled before anything is written.
+pub fn update(data_dir: &Path, url: &str, allowed_signers: &Path) -> Result<UpdateOutcome> {
+    if !url.starts_with("https://") {
+        return Err(anyhow!("rule packs are only fetched over https, not {url}"));
+    }
+    let contents = download(url)?;
+    let signature_url = format!("{url}.sig");
+    let signature = download(&signature_url)?;
+    install_signed(data_dir, &contents, &signature, allowed_signers)
+        .with_context(|| format!("rejected rule pack from {url}"))
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    let fetched = http::get(url, &[], None).with_context(|| format!("failed to download {url}"))?;
+    let mut contents = Vec::new();
+    fetched
+        .response
+        .into_reader()
+        .take(MAX_PACK_BYTES + 1)
+        .read_to_end(&mut contents)
+        .with_context(|| format!("failed to read {url}"))?;
+    if contents.len() as u64 > MAX_PACK_BYTES {
+        return Err(anyhow!("{url} is over {MAX_PACK_BYTES} bytes"));
+    }
+    Ok(contents)
+}
+
+/// Checks `signature` over `contents` against `allowed_signers`, then installs the
+/// pack like [`install`].
+pub fn install_signed(
+    data_dir: &Path,
+    contents: &[u8],
+    signature: &[u8],
+    allowed_signers: &Path,
+) -> Result<UpdateOutcome> {
+    let scratch = tempfile::tempdir().context("failed to create a temp dir")?;
+    let pack_file = scratch.path().join(PACK_FILE_NAME);
+    fs::write(&pack_file, contents)?;
+    let signature_file = pack::signature_path(&pack_file);
+    fs::write(&signature_file, signature)?;
+    pack::verify_pack(&pack_file, &signature_file, allowed_signers)?;
+    let contents = String::from_utf8(contents.to_vec()).context("rule pack is not UTF-8")?;
+    install(data_dir, &contents)
+}
+
+/// Installs `contents` as the downloaded pack if it is valid, keeps every bundled
+/// rule, and is newer than the active one.
+pub fn install(data_dir: &Path, contents: &str) -> Result<UpdateOutcome> {
+    let path = pack_path(data_dir);
+    let parsed = RulePack::parse(contents)?;
+    let bundled = bundled_pack();
+    parsed.check_covers(&bundled)?;
+    let fetched = parsed
+        .on_top_of(bundled)
+        .compile(PackSource::Downloaded(path.clone()))?;
+    let current = &active().pack.version;
+    if compare_versions(&fetched.pack.version, current) != Ordering::Greater {
+        return Ok(UpdateOutcome::UpToDate {
+            version: current.clone(),
+        });
+    }
+    ensure_dir(data_dir)?;
+    let temp_path = path.with_extension("yaml.tmp");
+    fs::write(&temp_path, contents)
+        .with_context(|| format!("failed to write {}", temp_path.display()))?;
+    fs::rename(&temp_path, &path).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(UpdateOutcome::Updated {
+        from: current.clone(),
+        to: fetched.pack.version,
+    })
+}
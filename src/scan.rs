@@ -1,11 +1,18 @@
+use crate::commands;
+use crate::process::{describe_timeout, output_with_timeout, ToolTimeouts};
+use crate::rule_pack::{self, PackCheck};
+use crate::scan_profile::{ScanProfile, SCAN_PROFILE_FILE_NAME};
+use crate::suppression::Suppressions;
 use anyhow::{anyhow, Context, Result};
-use once_cell::sync::Lazy;
-use regex::Regex;
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use serde::Deserialize;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use walkdir::WalkDir;
+use std::process::{Command, Output};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
@@ -14,8 +21,19 @@ pub enum Severity {
     Info,
 }
 
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ScanIssue {
+    pub rule: &'static str,
     pub severity: Severity,
     pub message: String,
     pub path: Option<PathBuf>,
@@ -23,15 +41,17 @@ pub struct ScanIssue {
 
 impl fmt::Display for ScanIssue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let level = match self.severity {
-            Severity::Error => "error",
-            Severity::Warning => "warning",
-            Severity::Info => "info",
-        };
+        let level = self.severity.as_str();
         if let Some(path) = &self.path {
-            write!(f, "[{level}] {} ({})", self.message, path.display())
+            write!(
+                f,
+                "[{level}] {}: {} ({})",
+                self.rule,
+                self.message,
+                path.display()
+            )
         } else {
-            write!(f, "[{level}] {}", self.message)
+            write!(f, "[{level}] {}: {}", self.rule, self.message)
         }
     }
 }
@@ -40,9 +60,49 @@ impl fmt::Display for ScanIssue {
 pub struct ScanReport {
     pub issues: Vec<ScanIssue>,
     pub external: Vec<ExternalScan>,
+    pub suppressed: Vec<(ScanIssue, String)>,
 }
 
 impl ScanReport {
+    pub fn apply_suppressions(&mut self, suppressions: &Suppressions) {
+        let (suppressed, kept): (Vec<_>, Vec<_>) = self
+            .issues
+            .drain(..)
+            .partition(|issue| suppressions.reason_for(issue.rule).is_some());
+        self.issues = kept;
+        for issue in suppressed {
+            let reason = suppressions.reason_for(issue.rule).unwrap_or_default();
+            self.suppressed.push((issue, reason.to_string()));
+        }
+    }
+
+    /// Downgrades findings that the `scan.yaml` of the skill directory holding them
+    /// declares as expected to info, so each skill in a monorepo scan keeps its own.
+    pub fn apply_profiles(&mut self, root: &Path) -> Result<()> {
+        let mut profiles = BTreeMap::new();
+        for issue in &mut self.issues {
+            let Some(path) = issue.path.as_deref() else {
+                continue;
+            };
+            let skill_dir = skill_dir_of(root, path).unwrap_or_else(|| root.to_path_buf());
+            let Ok(relative) = path.strip_prefix(&skill_dir) else {
+                continue;
+            };
+            let profile = match profiles.entry(skill_dir.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(ScanProfile::load_for(&skill_dir)?),
+            };
+            if let Some((pattern, reason)) = profile.expectation_for(issue.rule, relative) {
+                issue.severity = Severity::Info;
+                issue.message = format!(
+                    "{} (expected by {SCAN_PROFILE_FILE_NAME} '{pattern}': {reason})",
+                    issue.message
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub fn has_errors(&self) -> bool {
         self.issues
             .iter()
@@ -61,43 +121,162 @@ pub struct ExternalScan {
     pub output: String,
 }
 
-static SECRET_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
-    vec![
-        Regex::new(r"AKIA[0-9A-Z]{16}").expect("aws key regex"),
-        Regex::new(r"ASIA[0-9A-Z]{16}").expect("aws key regex"),
-        Regex::new(r"ghp_[A-Za-z0-9]{36,}").expect("github token regex"),
-        Regex::new(r"xox[baprs]-[A-Za-z0-9-]{10,}").expect("slack token regex"),
-        Regex::new(r"-----BEGIN (RSA|OPENSSH|EC|PGP) PRIVATE KEY-----").expect("private key regex"),
-    ]
-});
-
-static DANGEROUS_COMMANDS: Lazy<Vec<Regex>> = Lazy::new(|| {
-    vec![
-        Regex::new(r"rm\s+-rf\s+/").expect("rm -rf regex"),
-        Regex::new(r"curl\s+[^\n]+\|\s*sh").expect("curl|sh regex"),
-        Regex::new(r"wget\s+[^\n]+\|\s*sh").expect("wget|sh regex"),
-        Regex::new(r"chmod\s+777").expect("chmod 777 regex"),
-        Regex::new(r"sudo\s+").expect("sudo regex"),
-    ]
-});
-
 const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
 
-pub fn scan_path(path: &Path) -> Result<ScanReport> {
+#[derive(Debug, Default)]
+pub struct ScanOptions {
+    pub timeouts: ToolTimeouts,
+    /// When set, only these files get the built-in checks and external scanners only
+    /// run on the skill directories holding them (`scan --staged`/`--since`).
+    pub files: Option<BTreeSet<PathBuf>>,
+    /// Honour `.skillcheck.yaml` suppressions. Only local `skill scan` runs set this;
+    /// a downloaded skill must not be able to silence findings about itself.
+    pub suppressions: bool,
+    /// Honour the `scan.yaml` of each skill. Local scans set this; a downloaded skill's
+    /// own profile is only trusted with `trust_scan_profiles` in config.
+    pub profile: bool,
+}
+
+/// Which git changes `scan --staged`/`--since` limits the scan to.
+#[derive(Debug, Clone, Copy)]
+pub enum GitChanges<'a> {
+    Staged,
+    Since(&'a str),
+}
+
+/// The staged contents of a checkout, exported from the git index into a scratch dir
+/// so `scan --staged` checks what will be committed rather than the working tree.
+#[derive(Debug)]
+pub struct StagedTree {
+    _scratch: tempfile::TempDir,
+    dir: PathBuf,
+}
+
+impl StagedTree {
+    /// Exports the index of the repository holding `root`.
+    pub fn export(root: &Path) -> Result<Self> {
+        let git = |args: &[&str]| -> Result<String> {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(root)
+                .args(args)
+                .output()
+                .context("failed to run git")?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "git {} failed in {}: {}",
+                    args.join(" "),
+                    root.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        let top_level = git(&["rev-parse", "--show-toplevel"])?;
+        let prefix = git(&["rev-parse", "--show-prefix"])?;
+        let scratch = tempfile::tempdir().context("failed to create a temp dir")?;
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&top_level)
+            .args(["checkout-index", "--all", "--force"])
+            .arg(format!("--prefix={}/", scratch.path().display()))
+            .output()
+            .context("failed to run git")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git checkout-index failed in {top_level}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        // Marks the export's top as the repository root, where repository-wide
+        // `.skillcheck.yaml` suppressions are looked up.
+        fs::create_dir_all(scratch.path().join(".git"))?;
+        let dir = scratch.path().join(prefix);
+        Ok(Self {
+            _scratch: scratch,
+            dir,
+        })
+    }
+
+    /// Where `root` is in the export.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Files under `root` that are staged (or changed since a ref) and belong to a skill
+/// directory, joined onto `tree` so they match the paths `scan_path(tree)` walks.
+/// `tree` is `root` itself for `--since`, or the [`StagedTree`] export of `root`
+/// for `--staged`. Deleted files are left out.
+pub fn changed_skill_files(
+    root: &Path,
+    tree: &Path,
+    changes: GitChanges,
+) -> Result<BTreeSet<PathBuf>> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(root).args([
+        "diff",
+        "--name-only",
+        "-z",
+        "--diff-filter=ACMRT",
+        "--relative",
+    ]);
+    match changes {
+        GitChanges::Staged => command.arg("--cached"),
+        GitChanges::Since(reference) => command.arg(reference).arg("--"),
+    };
+    let output = command.output().context("failed to run git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git diff failed in {}: {}",
+            root.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output
+        .stdout
+        .split(|byte| *byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| tree.join(String::from_utf8_lossy(name).as_ref()))
+        .filter(|path| path.symlink_metadata().is_ok() && skill_dir_of(tree, path).is_some())
+        .collect())
+}
+
+/// The nearest directory above `file`, up to `root`, that holds a `SKILL.md`.
+fn skill_dir_of(root: &Path, file: &Path) -> Option<PathBuf> {
+    file.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .find(|dir| dir.join("SKILL.md").is_file())
+        .map(Path::to_path_buf)
+}
+
+pub fn scan_path(path: &Path, options: &ScanOptions) -> Result<ScanReport> {
     let mut report = ScanReport::default();
 
     if !path.exists() {
         return Err(anyhow!("path does not exist: {}", path.display()));
     }
 
-    for entry in WalkDir::new(path).follow_links(false) {
+    for entry in commands::walk_skill_tree(path) {
         let entry = entry?;
         let entry_path = entry.path();
+        if let Some(files) = &options.files
+            && !files.contains(entry_path)
+        {
+            continue;
+        }
 
         if entry.file_type().is_symlink() {
+            let message = if fs::metadata(entry_path).is_err() {
+                "dangling symlink detected; links are not installed"
+            } else {
+                "symlink detected; links are not installed"
+            };
             report.issues.push(ScanIssue {
+                rule: "symlink",
                 severity: Severity::Warning,
-                message: "symlink detected".to_string(),
+                message: message.to_string(),
                 path: Some(entry_path.to_path_buf()),
             });
             continue;
@@ -107,26 +286,38 @@ pub fn scan_path(path: &Path) -> Result<ScanReport> {
             continue;
         }
 
+        if let Some(kind) = commands::special_file_kind(entry.file_type()) {
+            report.issues.push(ScanIssue {
+                rule: "special-file",
+                severity: Severity::Error,
+                message: format!("{kind} detected; skills can only contain files and directories"),
+                path: Some(entry_path.to_path_buf()),
+            });
+            continue;
+        }
+
         let metadata = entry.metadata()?;
         if metadata.len() > MAX_FILE_BYTES {
             report.issues.push(ScanIssue {
+                rule: "large-file",
                 severity: Severity::Warning,
                 message: format!("large file ({} bytes)", metadata.len()),
                 path: Some(entry_path.to_path_buf()),
             });
         }
 
-        if let Some(ext) = entry_path.extension().and_then(|ext| ext.to_str()) {
-            if matches!(
+        if let Some(ext) = entry_path.extension().and_then(|ext| ext.to_str())
+            && matches!(
                 ext.to_ascii_lowercase().as_str(),
                 "exe" | "dll" | "dylib" | "so" | "bat" | "cmd" | "ps1"
-            ) {
-                report.issues.push(ScanIssue {
-                    severity: Severity::Warning,
-                    message: "executable or binary file detected".to_string(),
-                    path: Some(entry_path.to_path_buf()),
-                });
-            }
+            )
+        {
+            report.issues.push(ScanIssue {
+                rule: "executable-file",
+                severity: Severity::Warning,
+                message: "executable or binary file detected".to_string(),
+                path: Some(entry_path.to_path_buf()),
+            });
         }
 
         let bytes = fs::read(entry_path)
@@ -134,6 +325,7 @@ pub fn scan_path(path: &Path) -> Result<ScanReport> {
 
         if bytes.contains(&0) {
             report.issues.push(ScanIssue {
+                rule: "binary-content",
                 severity: Severity::Warning,
                 message: "binary content detected".to_string(),
                 path: Some(entry_path.to_path_buf()),
@@ -143,6 +335,7 @@ pub fn scan_path(path: &Path) -> Result<ScanReport> {
 
         let Ok(content) = std::str::from_utf8(&bytes) else {
             report.issues.push(ScanIssue {
+                rule: "non-utf8",
                 severity: Severity::Warning,
                 message: "non-utf8 file content detected".to_string(),
                 path: Some(entry_path.to_path_buf()),
@@ -150,33 +343,79 @@ pub fn scan_path(path: &Path) -> Result<ScanReport> {
             continue;
         };
 
-        for pattern in SECRET_PATTERNS.iter() {
-            if pattern.is_match(content) {
-                report.issues.push(ScanIssue {
-                    severity: Severity::Error,
-                    message: "potential secret detected".to_string(),
-                    path: Some(entry_path.to_path_buf()),
-                });
-                break;
-            }
+        let rules = rule_pack::active();
+        if let Some(rule) = rules.find(PackCheck::Secret, content) {
+            report.issues.push(ScanIssue {
+                rule: "secret",
+                severity: Severity::Error,
+                message: format!("potential secret detected ({})", rule.id),
+                path: Some(entry_path.to_path_buf()),
+            });
         }
 
-        if is_script(entry_path) {
-            for pattern in DANGEROUS_COMMANDS.iter() {
-                if pattern.is_match(content) {
-                    report.issues.push(ScanIssue {
-                        severity: Severity::Warning,
-                        message: "risky command detected in script".to_string(),
-                        path: Some(entry_path.to_path_buf()),
-                    });
-                    break;
-                }
-            }
+        if is_script(entry_path)
+            && let Some(rule) = rules.find(PackCheck::RiskyCommand, content)
+        {
+            report.issues.push(ScanIssue {
+                rule: "risky-command",
+                severity: Severity::Warning,
+                message: format!("risky command detected in script ({})", rule.id),
+                path: Some(entry_path.to_path_buf()),
+            });
+        }
+
+        if is_markdown(entry_path)
+            && let Some(rule) = fenced_code(content)
+                .iter()
+                .find_map(|code| rules.find(PackCheck::RiskyCommand, code))
+        {
+            report.issues.push(ScanIssue {
+                rule: "risky-command-example",
+                severity: Severity::Info,
+                message: format!(
+                    "risky command in a fenced code example ({}); review it before running it",
+                    rule.id
+                ),
+                path: Some(entry_path.to_path_buf()),
+            });
+        }
+
+        if !is_script(entry_path)
+            && let Some(rule) = rules.find(PackCheck::PromptInjection, content)
+        {
+            report.issues.push(ScanIssue {
+                rule: "prompt-injection",
+                severity: Severity::Warning,
+                message: format!(
+                    "possible prompt injection: {} ({})",
+                    rule.description, rule.id
+                ),
+                path: Some(entry_path.to_path_buf()),
+            });
         }
     }
 
     if std::env::var("SKILL_SKIP_EXTERNAL_SCANS").is_err() {
-        report.external.extend(run_external_scans(path)?);
+        match &options.files {
+            None => run_external_scans(path, &options.timeouts, &mut report)?,
+            Some(files) => {
+                let skill_dirs: BTreeSet<PathBuf> = files
+                    .iter()
+                    .filter_map(|file| skill_dir_of(path, file))
+                    .collect();
+                for dir in skill_dirs {
+                    run_external_scans(&dir, &options.timeouts, &mut report)?;
+                }
+            }
+        }
+    }
+    if path.is_dir() {
+        if options.profile {
+            report.apply_profiles(path)?;
+        }
+        if options.suppressions {
+            report.apply_suppressions(&Suppressions::load_for(path)?);
+        }
     }
     Ok(report)
 }
@@ -192,46 +431,152 @@ fn is_script(path: &Path) -> bool {
     )
 }
 
-fn run_external_scans(path: &Path) -> Result<Vec<ExternalScan>> {
-    let mut scans = Vec::new();
-
-    if which::which("trivy").is_ok() {
-        let output = Command::new("trivy")
-            .arg("fs")
-            .arg("--quiet")
-            .arg(path)
-            .output()
-            .with_context(|| "failed to run trivy fs")?;
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "md" | "markdown"))
+}
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        let combined = format!("{}{}", stdout, stderr).trim().to_string();
-        let severity = if output.status.success() {
-            Severity::Info
-        } else {
-            Severity::Warning
-        };
+/// Contents of the fenced code blocks in a markdown document, where instructions show
+/// example commands for the reader rather than running them.
+fn fenced_code(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<String> = None;
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(_))) => {
+                current = Some(String::new());
+            }
+            Event::Text(text) => {
+                if let Some(block) = &mut current {
+                    block.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => blocks.extend(current.take()),
+            _ => {}
+        }
+    }
+    blocks
+}
 
+/// Runs an external scanner within its time budget, recording a warning when it is
+/// terminated for running too long.
+fn run_tool(
+    tool: &str,
+    command: &mut Command,
+    timeout: Option<Duration>,
+    scans: &mut Vec<ExternalScan>,
+) -> Result<Option<Output>> {
+    let output =
+        output_with_timeout(command, timeout).with_context(|| format!("failed to run {tool}"))?;
+    if output.is_none() {
+        let timeout = timeout.map(describe_timeout).unwrap_or_default();
         scans.push(ExternalScan {
-            tool: "trivy".to_string(),
-            severity,
-            output: if combined.is_empty() {
-                "trivy produced no output".to_string()
-            } else {
-                combined
-            },
+            tool: tool.to_string(),
+            severity: Severity::Warning,
+            output: format!("{tool} timed out after {timeout} and was terminated"),
         });
     }
+    Ok(output)
+}
 
-    if which::which("clamscan").is_ok() {
-        let output = Command::new("clamscan")
-            .arg("-r")
-            .arg("--infected")
-            .arg("--no-summary")
-            .arg(path)
-            .output()
-            .with_context(|| "failed to run clamscan")?;
+fn run_external_scans(path: &Path, timeouts: &ToolTimeouts, report: &mut ScanReport) -> Result<()> {
+    let scans = &mut report.external;
+
+    if which::which("trivy").is_ok()
+        && let Some(output) = run_tool(
+            "trivy",
+            Command::new("trivy")
+                .arg("fs")
+                .arg("--quiet")
+                .arg("--format")
+                .arg("json")
+                .arg("--scanners")
+                .arg("vuln,secret,misconfig")
+                .arg(path),
+            timeouts.trivy,
+            scans,
+        )?
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        match parse_trivy_json(path, &stdout) {
+            Ok(issues) if output.status.success() => {
+                if issues.is_empty() {
+                    scans.push(ExternalScan {
+                        tool: "trivy".to_string(),
+                        severity: Severity::Info,
+                        output: "trivy found no issues".to_string(),
+                    });
+                }
+                report.issues.extend(issues);
+            }
+            _ => {
+                let combined = format!("{}{}", stdout, stderr).trim().to_string();
+                scans.push(ExternalScan {
+                    tool: "trivy".to_string(),
+                    severity: Severity::Warning,
+                    output: if combined.is_empty() {
+                        "trivy failed to scan".to_string()
+                    } else {
+                        combined
+                    },
+                });
+            }
+        }
+    }
 
+    if which::which("clamscan").is_ok()
+        && let Some(output) = run_tool(
+            "clamscan",
+            Command::new("clamscan")
+                .arg("-r")
+                .arg("--infected")
+                .arg("--no-summary")
+                .arg(path),
+            timeouts.clamscan,
+            scans,
+        )?
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let detections = parse_clamscan_output(&stdout);
+        match output.status.code() {
+            Some(0) => scans.push(ExternalScan {
+                tool: "clamscan".to_string(),
+                severity: Severity::Info,
+                output: "clamscan found no issues".to_string(),
+            }),
+            // Exit 1 means infected files were found; they are listed on stdout.
+            Some(1) if !detections.is_empty() => report.issues.extend(detections),
+            code => {
+                let combined = format!("{}{}", stdout, stderr).trim().to_string();
+                scans.push(ExternalScan {
+                    tool: "clamscan".to_string(),
+                    severity: if code == Some(1) {
+                        Severity::Error
+                    } else {
+                        Severity::Warning
+                    },
+                    output: if combined.is_empty() {
+                        "clamscan failed to scan".to_string()
+                    } else {
+                        combined
+                    },
+                });
+            }
+        }
+    }
+
+    if which::which("yara").is_ok()
+        && let Ok(rules) = std::env::var("SKILL_YARA_RULES")
+        && let Some(output) = run_tool(
+            "yara",
+            Command::new("yara").arg("-r").arg(&rules).arg(path),
+            timeouts.yara,
+            scans,
+        )?
+    {
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         let combined = format!("{}{}", stdout, stderr).trim().to_string();
@@ -243,56 +588,132 @@ fn run_external_scans(path: &Path) -> Result<Vec<ExternalScan>> {
         };
         let output_message = if combined.is_empty() {
             match exit_code {
-                0 => "clamscan found no issues".to_string(),
-                1 => "clamscan detected malware".to_string(),
-                _ => "clamscan failed to scan".to_string(),
+                0 => "yara found no matches".to_string(),
+                1 => "yara detected matches".to_string(),
+                _ => "yara failed to scan".to_string(),
             }
         } else {
             combined
         };
 
         scans.push(ExternalScan {
-            tool: "clamscan".to_string(),
+            tool: "yara".to_string(),
             severity,
             output: output_message,
         });
     }
 
-    if which::which("yara").is_ok() {
-        if let Ok(rules) = std::env::var("SKILL_YARA_RULES") {
-            let output = Command::new("yara")
-                .arg("-r")
-                .arg(&rules)
-                .arg(path)
-                .output()
-                .with_context(|| "failed to run yara")?;
-
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            let combined = format!("{}{}", stdout, stderr).trim().to_string();
-            let exit_code = output.status.code().unwrap_or(-1);
-            let severity = match exit_code {
-                0 => Severity::Info,
-                1 => Severity::Error,
-                _ => Severity::Warning,
-            };
-            let output_message = if combined.is_empty() {
-                match exit_code {
-                    0 => "yara found no matches".to_string(),
-                    1 => "yara detected matches".to_string(),
-                    _ => "yara failed to scan".to_string(),
-                }
-            } else {
-                combined
-            };
+    Ok(())
+}
 
-            scans.push(ExternalScan {
-                tool: "yara".to_string(),
-                severity,
-                output: output_message,
-            });
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyReport {
+    #[serde(default)]
+    results: Vec<TrivyResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyResult {
+    target: String,
+    #[serde(default)]
+    vulnerabilities: Vec<TrivyFinding>,
+    #[serde(default)]
+    secrets: Vec<TrivyFinding>,
+    #[serde(default)]
+    misconfigurations: Vec<TrivyFinding>,
+}
+
+/// The fields shared by trivy's vulnerability, secret and misconfiguration entries.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyFinding {
+    #[serde(default, rename = "VulnerabilityID")]
+    vulnerability_id: Option<String>,
+    #[serde(default, rename = "RuleID")]
+    rule_id: Option<String>,
+    #[serde(default, rename = "ID")]
+    id: Option<String>,
+    #[serde(default)]
+    pkg_name: Option<String>,
+    #[serde(default)]
+    installed_version: Option<String>,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+}
+
+/// Turns `trivy fs --format json` output into one issue per finding. Trivy's
+/// CRITICAL/HIGH become errors, MEDIUM warnings, and LOW/UNKNOWN info.
+pub fn parse_trivy_json(root: &Path, json: &str) -> Result<Vec<ScanIssue>> {
+    let report: TrivyReport =
+        serde_json::from_str(json).context("failed to parse trivy JSON output")?;
+    let mut issues = Vec::new();
+    for result in report.results {
+        let path = if root.is_dir() {
+            root.join(&result.target)
+        } else {
+            root.to_path_buf()
+        };
+        let findings = [
+            ("trivy-vulnerability", result.vulnerabilities),
+            ("trivy-secret", result.secrets),
+            ("trivy-misconfiguration", result.misconfigurations),
+        ];
+        for (rule, findings) in findings {
+            for finding in findings {
+                if finding.status.as_deref() == Some("PASS") {
+                    continue;
+                }
+                let id = finding
+                    .vulnerability_id
+                    .or(finding.rule_id)
+                    .or(finding.id)
+                    .unwrap_or_else(|| "unknown".to_string());
+                let mut message = id;
+                if let Some(package) = &finding.pkg_name {
+                    message.push_str(&format!(" in {package}"));
+                    if let Some(version) = &finding.installed_version {
+                        message.push_str(&format!(" {version}"));
+                    }
+                }
+                message.push_str(&format!(" ({})", finding.severity.to_ascii_uppercase()));
+                if let Some(title) = finding.title.filter(|title| !title.is_empty()) {
+                    message.push_str(&format!(": {title}"));
+                }
+                issues.push(ScanIssue {
+                    rule,
+                    severity: match finding.severity.to_ascii_uppercase().as_str() {
+                        "CRITICAL" | "HIGH" => Severity::Error,
+                        "MEDIUM" => Severity::Warning,
+                        _ => Severity::Info,
+                    },
+                    message,
+                    path: Some(path.clone()),
+                });
+            }
         }
     }
+    Ok(issues)
+}
 
-    Ok(scans)
+/// Turns clamscan's `<path>: <signature> FOUND` lines into one error per infected file.
+pub fn parse_clamscan_output(output: &str) -> Vec<ScanIssue> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().strip_suffix(" FOUND")?;
+            let (path, signature) = line.rsplit_once(": ")?;
+            Some(ScanIssue {
+                rule: "malware",
+                severity: Severity::Error,
+                message: format!("{signature} detected by clamscan"),
+                path: Some(PathBuf::from(path)),
+            })
+        })
+        .collect()
 }
@@ -0,0 +1,154 @@
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+pub const SCAN_PROFILE_FILE_NAME: &str = "scan.yaml";
+
+/// Scan rules a skill author may declare as expected. Content rules such as `secret`,
+/// `risky-command` or `malware` are never downgraded.
+pub const DECLARABLE_RULES: &[&str] = &[
+    "binary-content",
+    "non-utf8",
+    "executable-file",
+    "large-file",
+    "symlink",
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    #[serde(default)]
+    expected: Vec<ExpectedEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExpectedEntry {
+    path: String,
+    rules: Vec<String>,
+    reason: String,
+}
+
+#[derive(Debug)]
+struct Expectation {
+    pattern: String,
+    matcher: Regex,
+    rules: Vec<String>,
+    reason: String,
+}
+
+/// The `scan.yaml` shipped in a skill directory, listing files the author expects the
+/// scanner to flag (bundled fonts, images, prebuilt helpers).
+#[derive(Debug, Default)]
+pub struct ScanProfile {
+    expected: Vec<Expectation>,
+}
+
+impl ScanProfile {
+    /// Loads `scan.yaml` from `skill_dir`; an absent file yields an empty profile.
+    pub fn load_for(skill_dir: &Path) -> Result<Self> {
+        let path = skill_dir.join(SCAN_PROFILE_FILE_NAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let file: ProfileFile = serde_yaml::from_str(contents)?;
+        let mut expected = Vec::new();
+        for entry in file.expected {
+            if entry.reason.trim().is_empty() {
+                return Err(anyhow!(
+                    "expected entry for '{}' must include a reason",
+                    entry.path
+                ));
+            }
+            if entry.rules.is_empty() {
+                return Err(anyhow!(
+                    "expected entry for '{}' must list the rules it expects",
+                    entry.path
+                ));
+            }
+            if let Some(rule) = entry
+                .rules
+                .iter()
+                .find(|rule| !DECLARABLE_RULES.contains(&rule.as_str()))
+            {
+                return Err(anyhow!(
+                    "rule '{rule}' cannot be declared expected; use one of: {}",
+                    DECLARABLE_RULES.join(", ")
+                ));
+            }
+            expected.push(Expectation {
+                matcher: glob_regex(&entry.path)?,
+                pattern: entry.path,
+                rules: entry.rules,
+                reason: entry.reason,
+            });
+        }
+        Ok(Self { expected })
+    }
+
+    /// Returns the declared pattern and reason when `rule` is expected for `relative`,
+    /// a path relative to the skill directory.
+    pub fn expectation_for(&self, rule: &str, relative: &Path) -> Option<(&str, &str)> {
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.expected
+            .iter()
+            .find(|entry| {
+                entry.rules.iter().any(|expected| expected == rule)
+                    && entry.matcher.is_match(&relative)
+            })
+            .map(|entry| (entry.pattern.as_str(), entry.reason.as_str()))
+    }
+}
+
+/// Translates a glob (`*`, `?`, `**`) relative to the skill directory into an anchored
+/// regex. Patterns made only of wildcards (`**`, `*`, `**/*.*`) are refused: an entry
+/// has to name a path or an extension.
+fn glob_regex(pattern: &str) -> Result<Regex> {
+    let pattern = pattern.trim_start_matches("./");
+    if pattern.is_empty() || pattern.starts_with('/') || pattern.split('/').any(|part| part == "..")
+    {
+        return Err(anyhow!(
+            "expected path '{pattern}' must be relative to the skill directory"
+        ));
+    }
+    if pattern
+        .chars()
+        .all(|ch| matches!(ch, '*' | '?' | '/' | '.'))
+    {
+        return Err(anyhow!(
+            "expected path '{pattern}' matches every file; name a path or an extension"
+        ));
+    }
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            ch => regex.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).with_context(|| format!("invalid expected path '{pattern}'"))
+}
@@ -0,0 +1,77 @@
+use crate::manifest;
+use crate::paths::{ensure_dir, AppPaths};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SHARED_DIR_NAME: &str = "shared";
+const DIGEST_CHARS: usize = 12;
+
+/// Skill trees installed with `add --link`, kept once as `<name>-<digest>` and linked
+/// from each assistant's root. Assistants whose overrides make the tree differ get
+/// their own copy; identical trees are shared.
+pub struct SharedStore {
+    dir: PathBuf,
+}
+
+impl SharedStore {
+    pub fn new(paths: &AppPaths) -> Self {
+        Self {
+            dir: paths.data_dir.join(SHARED_DIR_NAME),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Builds a tree for skill `name` with `fill` and keeps it, reusing the stored copy
+    /// when an identical tree is already there. Returns the stored directory.
+    pub fn store(&self, name: &str, fill: impl FnOnce(&Path) -> Result<()>) -> Result<PathBuf> {
+        ensure_dir(&self.dir)?;
+        let staging = tempfile::Builder::new()
+            .prefix(".staging-")
+            .tempdir_in(&self.dir)
+            .with_context(|| format!("failed to create a staging dir in {}", self.dir.display()))?;
+        let staged = staging.path().join(name);
+        fill(&staged)?;
+        let digest = manifest::tree_digest(&manifest::hash_dir(&staged)?);
+        let hex = digest.rsplit(':').next().unwrap_or(&digest);
+        let target = self
+            .dir
+            .join(format!("{name}-{}", &hex[..hex.len().min(DIGEST_CHARS)]));
+        if !target.exists()
+            && let Err(err) = fs::rename(&staged, &target)
+            && !target.exists()
+        {
+            return Err(err)
+                .with_context(|| format!("failed to move {} into place", target.display()));
+        }
+        Ok(target)
+    }
+
+    /// Deletes the stored `target` unless one of `links` still points at it. Returns
+    /// whether it was deleted.
+    pub fn release(&self, target: &Path, links: &[PathBuf]) -> Result<bool> {
+        if !target.starts_with(&self.dir) || !target.is_dir() {
+            return Ok(false);
+        }
+        if links
+            .iter()
+            .any(|link| linked_target(link).is_some_and(|linked| linked == target))
+        {
+            return Ok(false);
+        }
+        fs::remove_dir_all(target)
+            .with_context(|| format!("failed to remove {}", target.display()))?;
+        Ok(true)
+    }
+}
+
+/// Where `dir` links to, when it is a link rather than a directory of its own.
+pub fn linked_target(dir: &Path) -> Option<PathBuf> {
+    fs::symlink_metadata(dir)
+        .ok()
+        .filter(|metadata| metadata.file_type().is_symlink())?;
+    fs::read_link(dir).ok()
+}
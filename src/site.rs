@@ -0,0 +1,151 @@
+use crate::assistant::Assistant;
+use crate::catalog::{
+    self, html_escape, html_page, html_table, is_generated, markdown_to_html, CatalogEntry,
+    GENERATED_MARKER,
+};
+use crate::paths::ensure_dir;
+use crate::validation::{self, SkillFrontmatter};
+use anyhow::{anyhow, Context, Result};
+use bytesize::ByteSize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const SEARCH_SCRIPT: &str = "document.getElementById('search').addEventListener('input',function(e){var q=e.target.value.toLowerCase();document.querySelectorAll('tr[data-search]').forEach(function(row){row.style.display=row.dataset.search.indexOf(q)>=0?'':'none';});});";
+
+/// Renders the skills of each assistant's root into `output`: an `index.html` listing
+/// them all and one page per skill under `<assistant>/`. Only replaces what an
+/// earlier export generated. Returns the number of skill pages written.
+pub fn export(output: &Path, title: &str, roots: &[(Assistant, PathBuf)]) -> Result<usize> {
+    if output.exists()
+        && fs::read_dir(output)?.next().is_some()
+        && !is_generated(&output.join("index.html"))
+    {
+        return Err(anyhow!(
+            "{} is not empty and was not exported by `skill export-site`",
+            output.display()
+        ));
+    }
+    ensure_dir(output)?;
+
+    let mut groups = Vec::new();
+    let mut pages = 0;
+    for (assistant, root) in roots {
+        let mut entries = if root.exists() {
+            catalog::collect(root)?
+        } else {
+            Vec::new()
+        };
+        entries.retain(|entry| {
+            let safe = !entry.name.starts_with('.')
+                && !entry.name.contains(['/', '\\'])
+                && !entry.name.is_empty();
+            if !safe {
+                eprintln!("Warning: skipping skill with unsafe name '{}'", entry.name);
+            }
+            safe
+        });
+        let assistant_dir = output.join(assistant.as_str());
+        if fs::symlink_metadata(&assistant_dir).is_ok() {
+            ensure_generated_dir(&assistant_dir)?;
+            fs::remove_dir_all(&assistant_dir)
+                .with_context(|| format!("failed to remove {}", assistant_dir.display()))?;
+        }
+        ensure_dir(&assistant_dir)?;
+        for entry in &entries {
+            let skill_dir = root.join(&entry.path);
+            let frontmatter = validation::read_frontmatter(&skill_dir)?;
+            let body = validation::read_body(&skill_dir)?;
+            let page = assistant_dir.join(format!("{}.html", entry.name));
+            fs::write(&page, render_skill_page(entry, &frontmatter, &body))
+                .with_context(|| format!("failed to write {}", page.display()))?;
+            pages += 1;
+        }
+        groups.push((*assistant, entries));
+    }
+
+    let index = output.join("index.html");
+    fs::write(&index, render_site_index(title, &groups))
+        .with_context(|| format!("failed to write {}", index.display()))?;
+    Ok(pages)
+}
+
+/// Refuses to let `export-site` replace `dir` unless it is a real directory holding
+/// only pages an earlier export generated.
+fn ensure_generated_dir(dir: &Path) -> Result<()> {
+    let refuse = |path: &Path| {
+        anyhow!(
+            "{} was not generated by `skill export-site`; move it away or pick another --output",
+            path.display()
+        )
+    };
+    if !fs::symlink_metadata(dir)?.is_dir() {
+        return Err(refuse(dir));
+    }
+    for entry in WalkDir::new(dir).min_depth(1) {
+        let entry = entry?;
+        if !entry.file_type().is_dir() && !is_generated(entry.path()) {
+            return Err(refuse(entry.path()));
+        }
+    }
+    Ok(())
+}
+
+/// Landing page of `skill export-site`: one searchable table per assistant.
+pub fn render_site_index(title: &str, groups: &[(Assistant, Vec<CatalogEntry>)]) -> String {
+    let mut body = format!(
+        "<h1>{}</h1>\n<input id=\"search\" type=\"search\" placeholder=\"Search skills\" autofocus>\n",
+        html_escape(title)
+    );
+    for (assistant, entries) in groups {
+        body.push_str(&format!(
+            "<h2>{assistant}</h2>\n<p>{} skill(s).</p>\n",
+            entries.len()
+        ));
+        body.push_str(&html_table(entries, |entry| {
+            format!("{assistant}/{}.html", entry.name)
+        }));
+    }
+    body.push_str(&format!("<script>{SEARCH_SCRIPT}</script>\n"));
+    format!("{GENERATED_MARKER}\n{}", html_page(title, &body))
+}
+
+/// Per-skill page of `skill export-site` with the rendered SKILL.md body.
+pub fn render_skill_page(
+    entry: &CatalogEntry,
+    frontmatter: &SkillFrontmatter,
+    body_markdown: &str,
+) -> String {
+    let mut details = vec![
+        ("Description", frontmatter.description.clone()),
+        ("Size", ByteSize(entry.size).to_string()),
+    ];
+    if !entry.tags.is_empty() {
+        details.push(("Tags", entry.tags.join(", ")));
+    }
+    for (label, value) in [
+        ("Author", &frontmatter.author),
+        ("Category", &frontmatter.category),
+        ("Compatibility", &frontmatter.compatibility),
+        ("License", &frontmatter.license),
+        ("Allowed tools", &frontmatter.allowed_tools),
+    ] {
+        if let Some(value) = value {
+            details.push((label, value.clone()));
+        }
+    }
+
+    let mut body = format!(
+        "<p><a href=\"../index.html\">&larr; All skills</a></p>\n<h1>{}</h1>\n<dl>\n",
+        html_escape(&entry.name)
+    );
+    for (label, value) in details {
+        body.push_str(&format!(
+            "<dt>{label}</dt><dd>{}</dd>\n",
+            html_escape(&value)
+        ));
+    }
+    body.push_str("</dl>\n<hr>\n");
+    body.push_str(&markdown_to_html(body_markdown));
+    format!("{GENERATED_MARKER}\n{}", html_page(&entry.name, &body))
+}
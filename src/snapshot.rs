@@ -0,0 +1,176 @@
+use crate::assistant::Assistant;
+use crate::config::{Config, RootScope};
+use crate::manifest;
+use crate::paths::{ensure_dir, AppPaths};
+use crate::validation;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+
+/// Installed skills at one point in time, keyed by `<assistant>/<name>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    pub taken_at: DateTime<Utc>,
+    pub skills: BTreeMap<String, InventoryEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// `tree-sha256:<hex>` of the skill's files (see [`manifest::tree_digest`]).
+    pub digest: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub system: bool,
+}
+
+/// Differences between two inventories, for change management.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct InventoryDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub changed: Vec<ChangedEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DiffEntry {
+    pub skill: String,
+    #[serde(flatten)]
+    pub entry: InventoryEntry,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct ChangedEntry {
+    pub skill: String,
+    pub before: InventoryEntry,
+    pub after: InventoryEntry,
+}
+
+impl Inventory {
+    /// Inventories every personal and system skill of `assistants`. A name installed in
+    /// both keeps the personal one, as `list` shows it.
+    pub fn take(config: &Config, paths: &AppPaths, assistants: &[Assistant]) -> Result<Self> {
+        let mut skills = BTreeMap::new();
+        for assistant in assistants {
+            for root in config.read_roots_for(paths, *assistant) {
+                if !root.path.exists() {
+                    continue;
+                }
+                for entry in fs::read_dir(&root.path)
+                    .with_context(|| format!("failed to read {}", root.path.display()))?
+                {
+                    let skill_dir = entry?.path();
+                    if !skill_dir.join("SKILL.md").exists() {
+                        continue;
+                    }
+                    let Some(name) = skill_dir.file_name().and_then(|n| n.to_str()) else {
+                        continue;
+                    };
+                    let key = format!("{assistant}/{name}");
+                    if skills.contains_key(&key) {
+                        continue;
+                    }
+                    let version = validation::read_frontmatter(&skill_dir)
+                        .ok()
+                        .and_then(|frontmatter| frontmatter.version());
+                    skills.insert(
+                        key,
+                        InventoryEntry {
+                            version,
+                            digest: manifest::tree_digest(&manifest::hash_dir(&skill_dir)?),
+                            system: root.scope == RootScope::System,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            taken_at: Utc::now(),
+            skills,
+        })
+    }
+
+    /// Writes the inventory to `<data dir>/snapshots/<timestamp>.json` and returns the path.
+    pub fn save(&self, paths: &AppPaths) -> Result<PathBuf> {
+        let dir = snapshots_dir(paths);
+        ensure_dir(&dir)?;
+        let path = dir.join(format!("{}.json", self.taken_at.format("%Y%m%dT%H%M%SZ")));
+        fs::write(&path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// What changed from `self` (the older inventory) to `current`.
+    pub fn diff(&self, current: &Inventory) -> InventoryDiff {
+        let keys: BTreeSet<&String> = self.skills.keys().chain(current.skills.keys()).collect();
+        let mut diff = InventoryDiff::default();
+        for key in keys {
+            match (self.skills.get(key), current.skills.get(key)) {
+                (None, Some(after)) => diff.added.push(DiffEntry {
+                    skill: key.clone(),
+                    entry: after.clone(),
+                }),
+                (Some(before), None) => diff.removed.push(DiffEntry {
+                    skill: key.clone(),
+                    entry: before.clone(),
+                }),
+                (Some(before), Some(after)) if before != after => diff.changed.push(ChangedEntry {
+                    skill: key.clone(),
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+                _ => {}
+            }
+        }
+        diff
+    }
+}
+
+/// Resolves `--diff` to a snapshot file: a path, a snapshot name from
+/// `<data dir>/snapshots` (with or without `.json`), or `latest`.
+pub fn resolve(paths: &AppPaths, snapshot: &str) -> Result<PathBuf> {
+    let as_path = PathBuf::from(snapshot);
+    if as_path.is_file() {
+        return Ok(as_path);
+    }
+    let dir = snapshots_dir(paths);
+    if snapshot == "latest" {
+        let mut saved: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        saved.sort();
+        return saved
+            .pop()
+            .ok_or_else(|| anyhow!("no saved snapshots; run `skill list --snapshot save` first"));
+    }
+    let name = snapshot.strip_suffix(".json").unwrap_or(snapshot);
+    let path = dir.join(format!("{name}.json"));
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(anyhow!(
+            "snapshot not found: {snapshot} (looked in {})",
+            dir.display()
+        ))
+    }
+}
+
+fn snapshots_dir(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join(SNAPSHOTS_DIR_NAME)
+}
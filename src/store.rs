@@ -0,0 +1,196 @@
+use crate::keys::{self, KeyStore, TrustedKey};
+use crate::manifest::{self, InstallManifest};
+use crate::paths::{self, ensure_private_dir, AppPaths};
+use crate::usage::UsageStore;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+const STORE_FILE_NAME: &str = "store.json";
+const LOCK_FILE_NAME: &str = "store.lock";
+const MIGRATED_SUFFIX: &str = "migrated";
+
+/// Schema version this binary reads and writes. Version 0 is the layout before the
+/// store existed: separate `installed.json` and `usage.json` files. Version 2 added
+/// the trusted signers, which version 1 kept only in `keys/allowed_signers`.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Install records, usage counts and trusted signers, kept in one versioned file
+/// under `<data dir>` so a single lock covers every read-modify-write.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreData {
+    pub schema_version: u64,
+    #[serde(default)]
+    pub installed: InstallManifest,
+    #[serde(default)]
+    pub usage: UsageStore,
+    #[serde(default)]
+    pub trusted_signers: Vec<TrustedKey>,
+}
+
+impl Default for StoreData {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            installed: InstallManifest::default(),
+            usage: UsageStore::default(),
+            trusted_signers: Vec::new(),
+        }
+    }
+}
+
+pub fn store_path(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join(STORE_FILE_NAME)
+}
+
+/// Reads the store under a shared lock, migrating it first if it predates
+/// [`SCHEMA_VERSION`].
+pub fn read(paths: &AppPaths) -> Result<StoreData> {
+    let path = store_path(paths);
+    if !path.exists() {
+        if legacy_files(paths).is_empty() && !KeyStore::new(paths).allowed_signers_path().is_file()
+        {
+            return Ok(StoreData::default());
+        }
+        return update(paths, |data| data.clone());
+    }
+    let lock = lock_file(paths)?;
+    lock.lock_shared()
+        .with_context(|| format!("failed to lock {}", path.display()))?;
+    let value = read_value(&path)?;
+    if schema_version(&value, &path)? == SCHEMA_VERSION {
+        return parse(value, &path);
+    }
+    drop(lock);
+    update(paths, |data| data.clone())
+}
+
+/// Runs `change` on the store under an exclusive lock and writes the result, so
+/// concurrent invocations (e.g. `mark-used` from several hooks) never lose updates
+/// or see a torn file.
+pub fn update<T>(paths: &AppPaths, change: impl FnOnce(&mut StoreData) -> T) -> Result<T> {
+    ensure_private_dir(&paths.data_dir)?;
+    let path = store_path(paths);
+    let lock = lock_file(paths)?;
+    lock.lock()
+        .with_context(|| format!("failed to lock {}", path.display()))?;
+    let value = read_value(&path)?;
+    let (value, imported) = migrate(paths, value, &path)?;
+    let mut data = parse(value, &path)?;
+    let result = change(&mut data);
+    write(&path, &data)?;
+    for legacy in imported {
+        let mut migrated = legacy.clone().into_os_string();
+        migrated.push(format!(".{MIGRATED_SUFFIX}"));
+        fs::rename(&legacy, &migrated)
+            .with_context(|| format!("failed to rename {}", legacy.display()))?;
+    }
+    Ok(result)
+}
+
+/// Loads a store file from anywhere, e.g. one inside a backup, without locking it.
+pub fn load_from(path: &Path) -> Result<StoreData> {
+    parse(migrate_value(read_value(path)?, path)?, path)
+}
+
+fn lock_file(paths: &AppPaths) -> Result<File> {
+    let path = paths.data_dir.join(LOCK_FILE_NAME);
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))
+}
+
+fn read_value(path: &Path) -> Result<Value> {
+    if !path.exists() {
+        return Ok(serde_json::json!({ "schema_version": 0 }));
+    }
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn schema_version(value: &Value, path: &Path) -> Result<u64> {
+    let version = value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| anyhow!("{} has no schema_version", path.display()))?;
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "{} uses schema version {version}, but this skill only knows up to {SCHEMA_VERSION}; upgrade skill",
+            path.display()
+        ));
+    }
+    Ok(version)
+}
+
+/// Brings `value` up to [`SCHEMA_VERSION`], returning the legacy files it imported
+/// so they can be set aside once the migrated store is written.
+fn migrate(paths: &AppPaths, mut value: Value, path: &Path) -> Result<(Value, Vec<PathBuf>)> {
+    let mut imported = Vec::new();
+    if schema_version(&value, path)? == 0 {
+        imported = legacy_files(paths);
+        let installed = InstallManifest::load_from(&manifest::manifest_path(paths))?;
+        let usage = load_legacy_usage(&paths.usage_file)?;
+        value = serde_json::json!({
+            "schema_version": 1,
+            "installed": installed,
+            "usage": usage,
+        });
+    }
+    if schema_version(&value, path)? == 1 {
+        let trusted = keys::read_allowed_signers(&KeyStore::new(paths).allowed_signers_path())?;
+        value["trusted_signers"] = serde_json::to_value(trusted)?;
+    }
+    Ok((migrate_value(value, path)?, imported))
+}
+
+/// Migrations between store versions that need nothing outside the file. Add a
+/// step here, upgrading one version at a time, when [`SCHEMA_VERSION`] is bumped.
+fn migrate_value(value: Value, path: &Path) -> Result<Value> {
+    match schema_version(&value, path)? {
+        SCHEMA_VERSION => Ok(value),
+        // A version 1 store outside the data dir, e.g. in a backup, has no
+        // allowed-signers file next to it to import.
+        1 => {
+            let mut value = value;
+            value["schema_version"] = SCHEMA_VERSION.into();
+            Ok(value)
+        }
+        version => Err(anyhow!(
+            "no migration from schema version {version} in {}",
+            path.display()
+        )),
+    }
+}
+
+fn parse(value: Value, path: &Path) -> Result<StoreData> {
+    serde_json::from_value(value).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn write(path: &Path, data: &StoreData) -> Result<()> {
+    let contents = serde_json::to_string_pretty(&data)?;
+    let temp_path = path.with_extension("json.tmp");
+    paths::write_private(&temp_path, contents)?;
+    fs::rename(&temp_path, path).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn legacy_files(paths: &AppPaths) -> Vec<PathBuf> {
+    [manifest::manifest_path(paths), paths.usage_file.clone()]
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn load_legacy_usage(path: &Path) -> Result<UsageStore> {
+    if !path.exists() {
+        return Ok(UsageStore::default());
+    }
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+}
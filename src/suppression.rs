@@ -0,0 +1,81 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SUPPRESSION_FILE_NAME: &str = ".skillcheck.yaml";
+
+#[derive(Debug, Default, Deserialize)]
+struct SuppressionFile {
+    #[serde(default)]
+    suppress: Vec<Suppression>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Suppression {
+    pub rule: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    entries: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// Loads `.skillcheck.yaml` from the skill directory and from the enclosing
+    /// repository root (the nearest ancestor containing `.git`), if present.
+    pub fn load_for(skill_dir: &Path) -> Result<Self> {
+        let mut suppressions = Self::default();
+        for file in suppression_files(skill_dir) {
+            suppressions.entries.extend(read_suppression_file(&file)?);
+        }
+        Ok(suppressions)
+    }
+
+    pub fn reason_for(&self, rule: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.rule == rule)
+            .map(|entry| entry.reason.as_str())
+    }
+}
+
+fn suppression_files(skill_dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let local = skill_dir.join(SUPPRESSION_FILE_NAME);
+    if local.is_file() {
+        files.push(local);
+    }
+
+    let Ok(skill_dir) = skill_dir.canonicalize() else {
+        return files;
+    };
+    for ancestor in skill_dir.ancestors().skip(1) {
+        if ancestor.join(".git").exists() {
+            let repo_file = ancestor.join(SUPPRESSION_FILE_NAME);
+            if repo_file.is_file() {
+                files.push(repo_file);
+            }
+            break;
+        }
+    }
+    files
+}
+
+fn read_suppression_file(path: &Path) -> Result<Vec<Suppression>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let file: SuppressionFile = serde_yaml::from_str(&contents)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    for entry in &file.suppress {
+        if entry.reason.trim().is_empty() {
+            return Err(anyhow!(
+                "suppression for '{}' in {} must include a reason",
+                entry.rule,
+                path.display()
+            ));
+        }
+    }
+    Ok(file.suppress)
+}
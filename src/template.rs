@@ -0,0 +1,150 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Output template for `list --format` and `show --format`: literal text with `{field}`
+/// placeholders, where `field` is a key of the command's `--json` object and dots reach
+/// into nested objects (`{frontmatter.author}`). `\t`, `\n` and `\\` are escapes, so a
+/// template can be written in single quotes on the command line; `{{` and `}}` are
+/// literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field(Vec<String>),
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some('\\') => text.push('\\'),
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => text.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => field.push(ch),
+                            None => {
+                                return Err(anyhow!("unclosed '{{' in template '{template}'"));
+                            }
+                        }
+                    }
+                    let path: Vec<String> = field.trim().split('.').map(str::to_string).collect();
+                    if path.iter().any(String::is_empty) {
+                        return Err(anyhow!("empty field name in template '{template}'"));
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Field(path));
+                }
+                '}' => {
+                    return Err(anyhow!(
+                        "unmatched '}}' in template '{template}'; write '}}}}' for a literal brace"
+                    ));
+                }
+                ch => text.push(ch),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Fills the placeholders from `value`. Null fields print as nothing, lists are
+    /// joined with commas, and a field `value` doesn't have is an error naming the ones
+    /// it does.
+    pub fn render(&self, value: &Value) -> Result<String> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Field(path) => rendered.push_str(&field_text(lookup(value, path)?)),
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+/// `spec` as a template: the one named `spec` under `templates` in config, or `spec`
+/// itself when it has a placeholder.
+pub fn resolve(spec: &str, named: &BTreeMap<String, String>) -> Result<Template> {
+    if let Some(template) = named.get(spec) {
+        return Template::parse(template);
+    }
+    if !spec.contains('{') {
+        let known: Vec<&str> = named.keys().map(String::as_str).collect();
+        return Err(anyhow!(
+            "no template named '{spec}'{}; pass a template with {{field}} placeholders or add one under `templates` in config",
+            if known.is_empty() {
+                String::new()
+            } else {
+                format!(" (known: {})", known.join(", "))
+            }
+        ));
+    }
+    Template::parse(spec)
+}
+
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Result<&'a Value> {
+    let mut current = value;
+    for (depth, key) in path.iter().enumerate() {
+        current = match current {
+            Value::Null => return Ok(&Value::Null),
+            Value::Object(object) => match object.get(key) {
+                Some(next) => next,
+                // Optional keys below the top level may be left out of the JSON.
+                None if depth > 0 => return Ok(&Value::Null),
+                None => {
+                    let known: Vec<&str> = object.keys().map(String::as_str).collect();
+                    return Err(anyhow!(
+                        "unknown field '{key}' in template; use one of {}",
+                        known.join(", ")
+                    ));
+                }
+            },
+            _ => {
+                return Err(anyhow!(
+                    "'{}' has no field '{key}'",
+                    path[..depth].join(".")
+                ));
+            }
+        };
+    }
+    Ok(current)
+}
+
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(field_text).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,229 @@
+use crate::assistant::Assistant;
+
+struct ToolMapping {
+    canonical: &'static str,
+    claudecode: Option<&'static str>,
+    codex: Option<&'static str>,
+    opencode: Option<&'static str>,
+}
+
+impl ToolMapping {
+    fn name_for(&self, assistant: Assistant) -> Option<&'static str> {
+        match assistant {
+            Assistant::ClaudeCode => self.claudecode,
+            Assistant::Codex => self.codex,
+            Assistant::OpenCode => self.opencode,
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.canonical.eq_ignore_ascii_case(name)
+            || [self.claudecode, self.codex, self.opencode]
+                .iter()
+                .flatten()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Tool names per assistant dialect. `None` means the assistant has no equivalent capability.
+const TOOL_TABLE: &[ToolMapping] = &[
+    ToolMapping {
+        canonical: "bash",
+        claudecode: Some("Bash"),
+        codex: Some("shell"),
+        opencode: Some("bash"),
+    },
+    ToolMapping {
+        canonical: "read",
+        claudecode: Some("Read"),
+        codex: None,
+        opencode: Some("read"),
+    },
+    ToolMapping {
+        canonical: "write",
+        claudecode: Some("Write"),
+        codex: Some("apply_patch"),
+        opencode: Some("write"),
+    },
+    ToolMapping {
+        canonical: "edit",
+        claudecode: Some("Edit"),
+        codex: Some("apply_patch"),
+        opencode: Some("edit"),
+    },
+    ToolMapping {
+        canonical: "multiedit",
+        claudecode: Some("MultiEdit"),
+        codex: Some("apply_patch"),
+        opencode: Some("edit"),
+    },
+    ToolMapping {
+        canonical: "glob",
+        claudecode: Some("Glob"),
+        codex: None,
+        opencode: Some("glob"),
+    },
+    ToolMapping {
+        canonical: "grep",
+        claudecode: Some("Grep"),
+        codex: None,
+        opencode: Some("grep"),
+    },
+    ToolMapping {
+        canonical: "list",
+        claudecode: Some("LS"),
+        codex: None,
+        opencode: Some("list"),
+    },
+    ToolMapping {
+        canonical: "webfetch",
+        claudecode: Some("WebFetch"),
+        codex: None,
+        opencode: Some("webfetch"),
+    },
+    ToolMapping {
+        canonical: "websearch",
+        claudecode: Some("WebSearch"),
+        codex: Some("web_search"),
+        opencode: None,
+    },
+    ToolMapping {
+        canonical: "todo",
+        claudecode: Some("TodoWrite"),
+        codex: Some("update_plan"),
+        opencode: Some("todowrite"),
+    },
+];
+
+#[derive(Debug, Default)]
+pub struct ToolTranslation {
+    pub tools: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ToolTranslation {
+    /// Records `warning` once, however often the tool it is about is listed.
+    fn warn(&mut self, warning: String) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+}
+
+/// Prefix of MCP server tools in Claude Code's dialect, as in `mcp__github__get_issue`.
+const CLAUDECODE_MCP_PREFIX: &str = "mcp__";
+
+/// Translates an `allowed-tools` value, with tools separated by spaces or commas, into
+/// the dialect of `target`.
+pub fn translate_allowed_tools(value: &str, target: Assistant) -> ToolTranslation {
+    let mut translation = ToolTranslation::default();
+    for entry in split_tools(value) {
+        let (base, args) = match entry.split_once('(') {
+            Some((base, rest)) => (base, Some(rest.strip_suffix(')').unwrap_or(rest))),
+            None => (entry, None),
+        };
+
+        let Some(mapping) = TOOL_TABLE.iter().find(|mapping| mapping.matches(base)) else {
+            if target == Assistant::ClaudeCode && base.starts_with(CLAUDECODE_MCP_PREFIX) {
+                // Already in the target's dialect.
+                if !translation.tools.iter().any(|tool| tool == entry) {
+                    translation.tools.push(entry.to_string());
+                }
+                continue;
+            }
+            translation.warn(format!("unknown tool '{entry}' dropped for {target}"));
+            continue;
+        };
+
+        let Some(name) = mapping.name_for(target) else {
+            translation.warn(format!(
+                "tool '{entry}' has no {target} equivalent; dropped"
+            ));
+            continue;
+        };
+
+        let translated = match args {
+            Some(args) if target == Assistant::ClaudeCode => format!("{name}({args})"),
+            Some(args) => {
+                // Granting the bare tool would widen what the skill may run.
+                translation.warn(format!(
+                    "tool '{entry}' dropped: {target} cannot restrict '{base}' to '({args})'"
+                ));
+                continue;
+            }
+            None => name.to_string(),
+        };
+        if !translation.tools.contains(&translated) {
+            translation.tools.push(translated);
+        }
+    }
+    translation
+}
+
+/// Splits on whitespace and commas outside parentheses, so argument restrictions
+/// such as `Bash(git add:*)` stay whole.
+fn split_tools(value: &str) -> Vec<&str> {
+    let mut tools = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    for (index, ch) in value.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ch if depth == 0 && (ch.is_whitespace() || ch == ',') => {
+                if let Some(begin) = start.take() {
+                    tools.push(&value[begin..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(index);
+    }
+    if let Some(begin) = start {
+        tools.push(&value[begin..]);
+    }
+    tools
+}
+
+/// Rewrites the `allowed-tools` line of a SKILL.md frontmatter for `target`,
+/// removing the line entirely when nothing translates.
+pub fn rewrite_allowed_tools(contents: &str, target: Assistant) -> (String, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut output = Vec::new();
+    let mut delimiters = 0;
+
+    for line in contents.split_inclusive('\n') {
+        let marker = line.trim_start_matches('\u{feff}').trim();
+        if marker == "---" || (delimiters == 1 && marker == "...") {
+            delimiters += 1;
+        }
+        let in_frontmatter = delimiters == 1;
+        let Some(value) = line
+            .strip_prefix("allowed-tools:")
+            .filter(|_| in_frontmatter)
+        else {
+            output.push(line.to_string());
+            continue;
+        };
+
+        let value = value.trim().trim_matches('"').trim_matches('\'');
+        let translation = translate_allowed_tools(value, target);
+        warnings.extend(translation.warnings);
+        if !translation.tools.is_empty() {
+            let newline = if line.ends_with("\r\n") { "\r\n" } else { "\n" };
+            // Spaces inside an argument restriction need commas between the tools.
+            let separator = if translation.tools.iter().any(|tool| tool.contains(' ')) {
+                ", "
+            } else {
+                " "
+            };
+            output.push(format!(
+                "allowed-tools: {}{newline}",
+                translation.tools.join(separator)
+            ));
+        }
+    }
+
+    (output.concat(), warnings)
+}
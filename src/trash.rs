@@ -0,0 +1,159 @@
+use crate::assistant::Assistant;
+use crate::cache::{CacheLimits, PruneSummary};
+use crate::journal::JournalStep;
+use crate::paths::{ensure_dir, same_filesystem, AppPaths};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const TRASH_DIR_NAME: &str = "trash";
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Removed skills, kept as `<UTC time>-<assistant>-<name>` directories until they
+/// expire or the trash grows past its size limit.
+pub struct Trash {
+    dir: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct TrashEntry {
+    pub path: PathBuf,
+    /// `<assistant>/<name>` of the removed skill.
+    pub label: String,
+    pub trashed_at: DateTime<Utc>,
+    pub size: u64,
+}
+
+impl Trash {
+    pub fn new(paths: &AppPaths) -> Self {
+        Self {
+            dir: paths.data_dir.join(TRASH_DIR_NAME),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// A fresh, not yet existing path to move a removed skill to.
+    pub fn slot_for(&self, assistant: Assistant, name: &str) -> PathBuf {
+        let stamp = Utc::now().format(STAMP_FORMAT);
+        let mut path = self.dir.join(format!("{stamp}-{assistant}-{name}"));
+        let mut counter = 1;
+        while path.exists() {
+            path = self
+                .dir
+                .join(format!("{stamp}-{assistant}-{name}.{counter}"));
+            counter += 1;
+        }
+        path
+    }
+
+    /// Steps moving `dir` (under `root`) into the trash at `slot`, copying when the
+    /// trash is on another filesystem.
+    pub fn steps(&self, root: &Path, dir: &Path, slot: &Path) -> Result<Vec<JournalStep>> {
+        ensure_dir(&self.dir)?;
+        Ok(if same_filesystem(root, &self.dir) {
+            vec![JournalStep::MoveDir {
+                from: dir.to_path_buf(),
+                to: slot.to_path_buf(),
+            }]
+        } else {
+            vec![
+                JournalStep::CopyDir {
+                    from: dir.to_path_buf(),
+                    to: slot.to_path_buf(),
+                },
+                JournalStep::RemoveDir {
+                    path: dir.to_path_buf(),
+                },
+            ]
+        })
+    }
+
+    /// Trashed skills, oldest first.
+    pub fn entries(&self) -> Result<Vec<TrashEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to read {}", self.dir.display()))?
+        {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some((stamp, rest)) = file_name.split_once('-') else {
+                continue;
+            };
+            let Ok(trashed_at) = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT) else {
+                continue;
+            };
+            if !path.is_dir() {
+                continue;
+            }
+            entries.push(TrashEntry {
+                label: rest.replacen('-', "/", 1),
+                trashed_at: trashed_at.and_utc(),
+                size: crate::commands::skill_size(&path)?,
+                path,
+            });
+        }
+        entries.sort_by_key(|entry| entry.trashed_at);
+        Ok(entries)
+    }
+
+    /// Deletes entries older than `max_age`, then the oldest ones until the trash
+    /// fits in `max_size`.
+    pub fn prune(&self, limits: CacheLimits) -> Result<PruneSummary> {
+        let mut summary = PruneSummary::default();
+        let entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+        let now = Utc::now();
+
+        for entry in entries {
+            let expired = limits
+                .max_age
+                .is_some_and(|max_age| age(now, entry.trashed_at) > max_age);
+            let over_size = limits.max_size.is_some_and(|max_size| total > max_size);
+            if !expired && !over_size {
+                continue;
+            }
+            fs::remove_dir_all(&entry.path)
+                .with_context(|| format!("failed to remove {}", entry.path.display()))?;
+            total -= entry.size;
+            summary.removed += 1;
+            summary.freed_bytes += entry.size;
+        }
+
+        summary.remaining_bytes = total;
+        Ok(summary)
+    }
+
+    /// Deletes every entry, or only those older than `older_than`.
+    pub fn empty(&self, older_than: Option<Duration>) -> Result<PruneSummary> {
+        match older_than {
+            Some(max_age) => self.prune(CacheLimits {
+                max_size: None,
+                max_age: Some(max_age),
+            }),
+            None => {
+                let mut summary = PruneSummary::default();
+                for entry in self.entries()? {
+                    fs::remove_dir_all(&entry.path)
+                        .with_context(|| format!("failed to remove {}", entry.path.display()))?;
+                    summary.removed += 1;
+                    summary.freed_bytes += entry.size;
+                }
+                Ok(summary)
+            }
+        }
+    }
+}
+
+fn age(now: DateTime<Utc>, then: DateTime<Utc>) -> Duration {
+    (now - then).to_std().unwrap_or_default()
+}
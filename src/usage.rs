@@ -1,52 +1,125 @@
 use crate::assistant::Assistant;
-use crate::paths::{ensure_dir, AppPaths};
-use anyhow::{Context, Result};
+use crate::paths::AppPaths;
+use crate::store;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::fs;
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Uses kept in [`UsageStore::events`]; older ones are dropped as new ones arrive.
+pub const MAX_USAGE_EVENTS: usize = 1000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UsageStore {
     #[serde(default)]
     pub skills: BTreeMap<String, UsageCounts>,
+    /// The most recent uses, oldest first, capped at [`MAX_USAGE_EVENTS`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<UsageEvent>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UsageCounts {
     pub total: u64,
     pub codex: u64,
     pub claudecode: u64,
     pub opencode: u64,
+    /// When any assistant first used the skill; unset for uses counted before this was
+    /// recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_used: Option<DateTime<Utc>>,
+    /// When any assistant last used the skill; unset for uses counted before this was
+    /// recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// One `mark-used` call in the usage history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    pub at: DateTime<Utc>,
+    pub assistant: Assistant,
+    pub skill: String,
 }
 
 impl UsageStore {
     pub fn load(paths: &AppPaths) -> Result<Self> {
-        if !paths.usage_file.exists() {
-            return Ok(Self::default());
-        }
-        let contents = fs::read_to_string(&paths.usage_file)
-            .with_context(|| format!("failed to read {}", paths.usage_file.display()))?;
-        let store = serde_json::from_str(&contents)
-            .with_context(|| format!("failed to parse {}", paths.usage_file.display()))?;
-        Ok(store)
+        Ok(store::read(paths)?.usage)
     }
 
-    pub fn save(&self, paths: &AppPaths) -> Result<()> {
-        ensure_dir(&paths.data_dir)?;
-        let contents = serde_json::to_string_pretty(self)?;
-        fs::write(&paths.usage_file, contents)
-            .with_context(|| format!("failed to write {}", paths.usage_file.display()))?;
-        Ok(())
+    /// Counts one use of `skill` under the store lock, so hooks firing at the same
+    /// time all get counted.
+    pub fn record_use(paths: &AppPaths, assistant: Assistant, skill: &str) -> Result<()> {
+        Self::record_use_at(paths, assistant, skill, Utc::now())
     }
 
-    pub fn increment(&mut self, assistant: Assistant, skill: &str) {
+    /// Like [`record_use`](Self::record_use), for a use at `at`, e.g. one imported
+    /// from another tool's logs.
+    pub fn record_use_at(
+        paths: &AppPaths,
+        assistant: Assistant,
+        skill: &str,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        store::update(paths, |data| data.usage.increment(assistant, skill, at))
+    }
+
+    pub fn increment(&mut self, assistant: Assistant, skill: &str, at: DateTime<Utc>) {
         let entry = self.skills.entry(skill.to_string()).or_default();
+        // Uses counted before times were recorded leave the first one unknown.
+        entry.first_used = match entry.first_used {
+            Some(first) => Some(first.min(at)),
+            None if entry.total == 0 => Some(at),
+            None => None,
+        };
         entry.total += 1;
+        entry.last_used = Some(entry.last_used.map_or(at, |last| last.max(at)));
         match assistant {
             Assistant::Codex => entry.codex += 1,
             Assistant::ClaudeCode => entry.claudecode += 1,
             Assistant::OpenCode => entry.opencode += 1,
         }
+        self.add_events([UsageEvent {
+            at,
+            assistant,
+            skill: skill.to_string(),
+        }]);
+    }
+
+    /// Drops the counts and history of `skill`.
+    pub fn forget(&mut self, skill: &str) {
+        self.skills.remove(skill);
+        self.events.retain(|event| event.skill != skill);
+    }
+
+    /// Takes the counts and history of `skill` from `other` unless this store already
+    /// has counts for it.
+    pub fn import(&mut self, skill: &str, other: &UsageStore) {
+        if self.skills.contains_key(skill) {
+            return;
+        }
+        let Some(counts) = other.skills.get(skill) else {
+            return;
+        };
+        self.skills.insert(skill.to_string(), counts.clone());
+        self.add_events(
+            other
+                .events
+                .iter()
+                .filter(|event| event.skill == skill)
+                .cloned(),
+        );
+    }
+
+    /// Adds `events` in time order, so backfilled uses land where they happened, and
+    /// keeps only the newest [`MAX_USAGE_EVENTS`].
+    fn add_events(&mut self, events: impl IntoIterator<Item = UsageEvent>) {
+        for event in events {
+            let index = self.events.partition_point(|kept| kept.at <= event.at);
+            self.events.insert(index, event);
+        }
+        let excess = self.events.len().saturating_sub(MAX_USAGE_EVENTS);
+        self.events.drain(..excess);
     }
 
     pub fn count_for(&self, assistant: Assistant, skill: &str) -> u64 {
@@ -59,4 +132,12 @@ impl UsageStore {
             })
             .unwrap_or(0)
     }
+
+    pub fn last_used(&self, skill: &str) -> Option<DateTime<Utc>> {
+        self.skills.get(skill)?.last_used
+    }
+
+    pub fn first_used(&self, skill: &str) -> Option<DateTime<Utc>> {
+        self.skills.get(skill)?.first_used
+    }
 }
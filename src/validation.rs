@@ -1,19 +1,93 @@
+use crate::assistant::Assistant;
+use crate::suppression::Suppressions;
 use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// `Name` or `Name <email>`; handles such as `@alice` count as names.
+static AUTHOR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[^<>\s][^<>]*?(?:\s+<[^<>\s@]+@[^<>\s@]+\.[^<>\s@]+>)?$")
+        .expect("valid author regex")
+});
+
+static LANG_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[a-z]{2,3}(?:-[A-Za-z0-9]{2,8})*$").expect("valid language tag regex")
+});
+
+const MAX_METADATA_DEPTH: usize = 4;
+const MAX_METADATA_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    Bool,
+    Text,
+}
+
+/// A frontmatter key outside the spec that some assistants act on.
+#[derive(Debug)]
+pub struct AssistantKey {
+    pub key: &'static str,
+    pub assistants: &'static [Assistant],
+    pub kind: KeyKind,
+}
+
+pub const ASSISTANT_KEYS: &[AssistantKey] = &[
+    AssistantKey {
+        key: "disable-model-invocation",
+        assistants: &[Assistant::ClaudeCode],
+        kind: KeyKind::Bool,
+    },
+    AssistantKey {
+        key: "user-invocable",
+        assistants: &[Assistant::ClaudeCode],
+        kind: KeyKind::Bool,
+    },
+    AssistantKey {
+        key: "argument-hint",
+        assistants: &[Assistant::ClaudeCode],
+        kind: KeyKind::Text,
+    },
+    AssistantKey {
+        key: "model",
+        assistants: &[Assistant::ClaudeCode],
+        kind: KeyKind::Text,
+    },
+    AssistantKey {
+        key: "context",
+        assistants: &[Assistant::ClaudeCode],
+        kind: KeyKind::Text,
+    },
+    AssistantKey {
+        key: "agent",
+        assistants: &[Assistant::ClaudeCode],
+        kind: KeyKind::Text,
+    },
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
     Error,
     Warning,
 }
 
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ValidationIssue {
+    pub rule: &'static str,
     pub severity: Severity,
     pub message: String,
     pub path: Option<PathBuf>,
@@ -21,14 +95,17 @@ pub struct ValidationIssue {
 
 impl fmt::Display for ValidationIssue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let level = match self.severity {
-            Severity::Error => "error",
-            Severity::Warning => "warning",
-        };
+        let level = self.severity.as_str();
         if let Some(path) = &self.path {
-            write!(f, "[{level}] {} ({})", self.message, path.display())
+            write!(
+                f,
+                "[{level}] {}: {} ({})",
+                self.rule,
+                self.message,
+                path.display()
+            )
         } else {
-            write!(f, "[{level}] {}", self.message)
+            write!(f, "[{level}] {}: {}", self.rule, self.message)
         }
     }
 }
@@ -36,9 +113,22 @@ impl fmt::Display for ValidationIssue {
 #[derive(Debug, Default)]
 pub struct ValidationReport {
     pub issues: Vec<ValidationIssue>,
+    pub suppressed: Vec<(ValidationIssue, String)>,
 }
 
 impl ValidationReport {
+    pub fn apply_suppressions(&mut self, suppressions: &Suppressions) {
+        let (suppressed, kept): (Vec<_>, Vec<_>) = self
+            .issues
+            .drain(..)
+            .partition(|issue| suppressions.reason_for(issue.rule).is_some());
+        self.issues = kept;
+        for issue in suppressed {
+            let reason = suppressions.reason_for(issue.rule).unwrap_or_default();
+            self.suppressed.push((issue, reason.to_string()));
+        }
+    }
+
     pub fn has_errors(&self) -> bool {
         self.issues
             .iter()
@@ -46,18 +136,134 @@ impl ValidationReport {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct SkillFrontmatter {
     pub name: String,
     pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub compatibility: Option<String>,
-    pub metadata: Option<BTreeMap<String, String>>,
-    #[serde(rename = "allowed-tools")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Semantic version of the skill, e.g. `1.2.0`.
+    #[serde(
+        default,
+        deserialize_with = "string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, serde_yaml::Value>>,
+    #[serde(rename = "allowed-tools", skip_serializing_if = "Option::is_none")]
     pub allowed_tools: Option<String>,
+    /// Keys outside the spec, such as assistant-specific ones.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl SkillFrontmatter {
+    /// Case-insensitive substring match on `author`, for `--author` filters.
+    pub fn author_matches(&self, query: &str) -> bool {
+        self.author
+            .as_deref()
+            .is_some_and(|author| author.to_lowercase().contains(&query.to_lowercase()))
+    }
+
+    /// `version`, falling back to `metadata.version`, as a string or number.
+    pub fn version(&self) -> Option<String> {
+        if let Some(version) = &self.version {
+            return Some(version.clone());
+        }
+        match self.metadata.as_ref()?.get("version")? {
+            serde_yaml::Value::String(value) => Some(value.clone()),
+            serde_yaml::Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// [`Self::version`] parsed as semver, when it is one.
+    pub fn semver(&self) -> Option<semver::Version> {
+        parse_version(&self.version()?)
+    }
+
+    /// Tags from `metadata.tags`, given either as a list or a comma-separated string.
+    pub fn tags(&self) -> Vec<String> {
+        let Some(tags) = self
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("tags"))
+        else {
+            return Vec::new();
+        };
+        let tags: Vec<String> = match tags {
+            serde_yaml::Value::Sequence(items) => items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect(),
+            serde_yaml::Value::String(value) => value.split(',').map(str::to_string).collect(),
+            _ => Vec::new(),
+        };
+        tags.into_iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+}
+
+/// `version` as semver, accepting a leading `v` as in git tags.
+pub fn parse_version(version: &str) -> Option<semver::Version> {
+    let version = version.trim();
+    semver::Version::parse(version.strip_prefix('v').unwrap_or(version)).ok()
+}
+
+/// Accepts `version: 1.2.0` as well as YAML numbers such as `version: 2`.
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+        None | Some(serde_yaml::Value::Null) => Ok(None),
+        Some(serde_yaml::Value::String(value)) => Ok(Some(value)),
+        Some(serde_yaml::Value::Number(value)) => Ok(Some(value.to_string())),
+        Some(_) => Err(serde::de::Error::custom(
+            "version must be a string such as 1.2.0",
+        )),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationOptions {
+    /// Body headings every skill must contain (checked in strict mode).
+    pub required_sections: Vec<String>,
+    /// Allowed `category` values; empty accepts any category.
+    pub categories: Vec<String>,
+    /// Assistant the skill is installed for; assistant-specific keys it ignores are
+    /// reported as warnings.
+    pub assistant: Option<Assistant>,
+    /// Honour `.skillcheck.yaml` suppressions. Only local `skill validate` runs set
+    /// this; a downloaded skill must not be able to silence findings about itself.
+    pub suppressions: bool,
 }
 
 pub fn validate_skill_dir(path: &Path) -> Result<ValidationReport> {
+    validate_skill_dir_with(path, &ValidationOptions::default())
+}
+
+pub fn validate_skill_dir_with(
+    path: &Path,
+    options: &ValidationOptions,
+) -> Result<ValidationReport> {
+    let mut report = check_skill_dir(path, options)?;
+    if options.suppressions {
+        report.apply_suppressions(&Suppressions::load_for(path)?);
+    }
+    Ok(report)
+}
+
+fn check_skill_dir(path: &Path, options: &ValidationOptions) -> Result<ValidationReport> {
     let mut report = ValidationReport::default();
 
     if !path.exists() {
@@ -66,6 +272,7 @@ pub fn validate_skill_dir(path: &Path) -> Result<ValidationReport> {
 
     if !path.is_dir() {
         report.issues.push(ValidationIssue {
+            rule: "skill-not-directory",
             severity: Severity::Error,
             message: "skill path must be a directory".to_string(),
             path: Some(path.to_path_buf()),
@@ -76,6 +283,7 @@ pub fn validate_skill_dir(path: &Path) -> Result<ValidationReport> {
     let skill_md_path = path.join("SKILL.md");
     if !skill_md_path.exists() {
         report.issues.push(ValidationIssue {
+            rule: "skill-md-missing",
             severity: Severity::Error,
             message: "SKILL.md is missing".to_string(),
             path: Some(skill_md_path),
@@ -87,6 +295,7 @@ pub fn validate_skill_dir(path: &Path) -> Result<ValidationReport> {
         Ok(frontmatter) => frontmatter,
         Err(err) => {
             report.issues.push(ValidationIssue {
+                rule: "frontmatter-invalid",
                 severity: Severity::Error,
                 message: err.to_string(),
                 path: Some(skill_md_path),
@@ -111,6 +320,59 @@ pub fn validate_skill_dir(path: &Path) -> Result<ValidationReport> {
         &mut report,
         &skill_md_path,
     );
+    validate_optional_field(
+        "author",
+        &frontmatter.author,
+        200,
+        &mut report,
+        &skill_md_path,
+    );
+    if let Some(author) = frontmatter.author.as_deref()
+        && !author.trim().is_empty()
+        && !AUTHOR.is_match(author.trim())
+    {
+        report.issues.push(ValidationIssue {
+            rule: "author-format",
+            severity: Severity::Error,
+            message: "author must be a name, optionally followed by <email>".to_string(),
+            path: Some(skill_md_path.clone()),
+        });
+    }
+    validate_optional_field(
+        "category",
+        &frontmatter.category,
+        64,
+        &mut report,
+        &skill_md_path,
+    );
+    if let Some(category) = frontmatter.category.as_deref()
+        && !category.trim().is_empty()
+        && !options.categories.is_empty()
+        && !options
+            .categories
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(category.trim()))
+    {
+        report.issues.push(ValidationIssue {
+            rule: "category-unknown",
+            severity: Severity::Warning,
+            message: format!(
+                "category '{category}' is not in the configured taxonomy ({})",
+                options.categories.join(", ")
+            ),
+            path: Some(skill_md_path.clone()),
+        });
+    }
+    if let Some(version) = frontmatter.version.as_deref()
+        && parse_version(version).is_none()
+    {
+        report.issues.push(ValidationIssue {
+            rule: "version-semver",
+            severity: Severity::Error,
+            message: format!("version '{version}' is not a semantic version such as 1.2.0"),
+            path: Some(skill_md_path.clone()),
+        });
+    }
     validate_optional_field(
         "allowed-tools",
         &frontmatter.allowed_tools,
@@ -120,55 +382,344 @@ pub fn validate_skill_dir(path: &Path) -> Result<ValidationReport> {
     );
 
     if let Some(metadata) = &frontmatter.metadata {
-        for (key, value) in metadata {
-            if key.trim().is_empty() || value.trim().is_empty() {
-                report.issues.push(ValidationIssue {
-                    severity: Severity::Warning,
-                    message: "metadata entries should not be empty".to_string(),
-                    path: Some(skill_md_path.clone()),
-                });
-                break;
-            }
-        }
+        validate_metadata(metadata, &mut report, &skill_md_path);
+    }
+
+    validate_extra_keys(
+        &frontmatter.extra,
+        options.assistant,
+        &mut report,
+        &skill_md_path,
+    );
+
+    let contents = fs::read_to_string(&skill_md_path)
+        .with_context(|| format!("failed to read {}", skill_md_path.display()))?;
+    if let Err(err) = parse_overrides(&contents) {
+        report.issues.push(ValidationIssue {
+            rule: "frontmatter-overrides-invalid",
+            severity: Severity::Error,
+            message: err,
+            path: Some(skill_md_path.clone()),
+        });
+    }
+
+    validate_localized_variants(path, &frontmatter.name, &mut report)?;
+
+    if !options.required_sections.is_empty() {
+        let body = read_body(path)?;
+        validate_required_sections(
+            &body,
+            &options.required_sections,
+            &mut report,
+            &skill_md_path,
+        );
     }
 
     Ok(report)
 }
 
 pub fn read_frontmatter(path: &Path) -> Result<SkillFrontmatter> {
-    let skill_md_path = path.join("SKILL.md");
-    let contents = fs::read_to_string(&skill_md_path)
+    read_frontmatter_file(&path.join("SKILL.md"))
+}
+
+pub fn read_frontmatter_file(skill_md_path: &Path) -> Result<SkillFrontmatter> {
+    let contents = fs::read_to_string(skill_md_path)
         .with_context(|| format!("failed to read {}", skill_md_path.display()))?;
     parse_frontmatter(&contents).map_err(|err| anyhow!("invalid frontmatter: {err}"))
 }
 
+/// Returns the `SKILL.<lang>.md` variants shipped next to SKILL.md, sorted by language tag.
+pub fn localized_variants(path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut variants = Vec::new();
+    for entry in fs::read_dir(path).with_context(|| format!("failed to read {}", path.display()))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name();
+        let Some(lang) = file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix("SKILL."))
+            .and_then(|name| name.strip_suffix(".md"))
+        else {
+            continue;
+        };
+        if LANG_TAG.is_match(lang) {
+            variants.push((lang.to_string(), entry.path()));
+        }
+    }
+    variants.sort();
+    Ok(variants)
+}
+
+fn validate_localized_variants(
+    path: &Path,
+    name: &str,
+    report: &mut ValidationReport,
+) -> Result<()> {
+    for (lang, variant_path) in localized_variants(path)? {
+        match read_frontmatter_file(&variant_path) {
+            Ok(variant) if variant.name.trim() != name.trim() => {
+                report.issues.push(ValidationIssue {
+                    rule: "localized-name-mismatch",
+                    severity: Severity::Error,
+                    message: format!("SKILL.{lang}.md name must match SKILL.md name '{name}'"),
+                    path: Some(variant_path),
+                });
+            }
+            Ok(variant) => {
+                validate_description(&variant.description, report, &variant_path);
+            }
+            Err(err) => {
+                report.issues.push(ValidationIssue {
+                    rule: "frontmatter-invalid",
+                    severity: Severity::Error,
+                    message: err.to_string(),
+                    path: Some(variant_path),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
 fn parse_frontmatter(contents: &str) -> Result<SkillFrontmatter, String> {
-    let mut lines = contents.lines();
-    let first = lines.next().unwrap_or("").trim();
-    if first != "---" {
-        return Err("SKILL.md must start with YAML frontmatter (---)".to_string());
+    let parts = split_frontmatter(contents)?;
+    // Padding with the opening `---` line keeps serde_yaml's line numbers pointing at
+    // the lines of SKILL.md itself.
+    serde_yaml::from_str(&format!("\n{}", parts.yaml)).map_err(|err| format!("{err}"))
+}
+
+/// Per-assistant frontmatter overrides, from an optional second YAML document that
+/// follows the frontmatter and starts with an assistant key:
+///
+/// ```text
+/// ---
+/// name: demo
+/// allowed-tools: Bash Read
+/// ---
+/// codex:
+///   allowed-tools: shell
+/// ...
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantOverrides {
+    #[serde(default)]
+    pub codex: Option<serde_yaml::Mapping>,
+    #[serde(default, alias = "claude-code", alias = "claude_code")]
+    pub claudecode: Option<serde_yaml::Mapping>,
+    #[serde(default, alias = "open-code", alias = "open_code")]
+    pub opencode: Option<serde_yaml::Mapping>,
+}
+
+impl AssistantOverrides {
+    pub fn for_assistant(&self, assistant: Assistant) -> Option<&serde_yaml::Mapping> {
+        match assistant {
+            Assistant::Codex => self.codex.as_ref(),
+            Assistant::ClaudeCode => self.claudecode.as_ref(),
+            Assistant::OpenCode => self.opencode.as_ref(),
+        }
     }
+}
 
-    let mut yaml_lines = Vec::new();
-    for line in lines.by_ref() {
-        if line.trim() == "---" {
-            break;
+/// Parses the overrides document of SKILL.md, if any, and checks that the frontmatter
+/// each assistant ends up with is still valid.
+pub fn parse_overrides(contents: &str) -> Result<Option<AssistantOverrides>, String> {
+    let parts = split_frontmatter(contents)?;
+    let Some((yaml, line)) = &parts.overrides else {
+        return Ok(None);
+    };
+    let overrides: AssistantOverrides =
+        serde_yaml::from_str(&format!("{}{yaml}", "\n".repeat(line - 1)))
+            .map_err(|err| format!("overrides: {err}"))?;
+    for assistant in [Assistant::Codex, Assistant::ClaudeCode, Assistant::OpenCode] {
+        let Some(mapping) = overrides.for_assistant(assistant) else {
+            continue;
+        };
+        if mapping.contains_key("name") {
+            return Err(format!(
+                "line {line}: {assistant} overrides cannot change `name`; the skill directory is named after it"
+            ));
         }
-        yaml_lines.push(line);
+        let merged = merge_overrides(&parts.yaml, mapping)?;
+        serde_yaml::from_value::<SkillFrontmatter>(serde_yaml::Value::Mapping(merged))
+            .map_err(|err| format!("{assistant} overrides: {err}"))?;
+    }
+    Ok(Some(overrides))
+}
+
+/// Rewrites SKILL.md for `assistant`: its overrides are merged into the frontmatter
+/// and the overrides document is dropped. Returns `None` for files without one.
+pub fn apply_overrides(contents: &str, assistant: Assistant) -> Result<Option<String>, String> {
+    let Some(overrides) = parse_overrides(contents)? else {
+        return Ok(None);
+    };
+    let parts = split_frontmatter(contents)?;
+    let body = &contents.strip_prefix('\u{feff}').unwrap_or(contents)[parts.body_offset..];
+    let yaml = match overrides.for_assistant(assistant) {
+        Some(mapping) => serde_yaml::to_string(&merge_overrides(&parts.yaml, mapping)?)
+            .map_err(|err| format!("{err}"))?,
+        None => format!("{}\n", parts.yaml),
+    };
+    Ok(Some(format!("---\n{yaml}---\n{body}")))
+}
+
+/// Frontmatter keys replaced by `overrides`, keeping the original key order.
+fn merge_overrides(
+    yaml: &str,
+    overrides: &serde_yaml::Mapping,
+) -> Result<serde_yaml::Mapping, String> {
+    let mut merged: serde_yaml::Mapping =
+        serde_yaml::from_str(yaml).map_err(|err| format!("{err}"))?;
+    for (key, value) in overrides {
+        merged.insert(key.clone(), value.clone());
+    }
+    Ok(merged)
+}
+
+/// SKILL.md split into frontmatter, optional overrides document and body.
+struct FrontmatterParts {
+    yaml: String,
+    /// The overrides document and the SKILL.md line it starts on.
+    overrides: Option<(String, usize)>,
+    body: String,
+    /// Byte offset of the body, after any BOM.
+    body_offset: usize,
+}
+
+/// Splits SKILL.md into its YAML frontmatter and the markdown body that follows it.
+/// Accepts a leading BOM, CRLF line endings, and `...` as the closing marker.
+fn split_frontmatter(contents: &str) -> Result<FrontmatterParts, String> {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let mut offset = 0;
+    let lines: Vec<(usize, &str)> = contents
+        .split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            (start, line.strip_suffix('\r').unwrap_or(line))
+        })
+        .collect();
+    let is_marker = |line: &str| matches!(line.trim_end(), "---" | "...");
+    let join = |lines: &[(usize, &str)]| {
+        lines
+            .iter()
+            .map(|(_, line)| *line)
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    if lines.first().map(|(_, line)| line.trim()) != Some("---") {
+        return Err("line 1: SKILL.md must start with YAML frontmatter (---)".to_string());
+    }
+    let Some(close) = (1..lines.len()).find(|&i| is_marker(lines[i].1)) else {
+        return Err(
+            "line 1: frontmatter is never closed; end it with a `---` or `...` line".to_string(),
+        );
+    };
+    let yaml = join(&lines[1..close]);
+    if yaml.trim().is_empty() {
+        return Err(format!("line {}: SKILL.md frontmatter is empty", close + 1));
     }
 
-    if yaml_lines.is_empty() {
-        return Err("SKILL.md frontmatter is empty".to_string());
+    let mut body_line = close + 1;
+    let mut overrides = None;
+    let starts_overrides = |line: &str| {
+        line.trim_end()
+            .strip_suffix(':')
+            .is_some_and(|key| key.parse::<Assistant>().is_ok())
+    };
+    if lines[close].1.trim_end() == "---"
+        && lines
+            .get(close + 1)
+            .is_some_and(|(_, line)| starts_overrides(line))
+    {
+        let Some(end) = (close + 1..lines.len()).find(|&i| is_marker(lines[i].1)) else {
+            return Err(format!(
+                "line {}: overrides document is never closed; end it with a `---` or `...` line",
+                close + 2
+            ));
+        };
+        overrides = Some((join(&lines[close + 1..end]), close + 2));
+        body_line = end + 1;
     }
 
-    let yaml = yaml_lines.join("\n");
-    serde_yaml::from_str(&yaml).map_err(|err| format!("{err}"))
+    Ok(FrontmatterParts {
+        yaml,
+        overrides,
+        body: join(lines.get(body_line..).unwrap_or_default()),
+        body_offset: lines
+            .get(body_line)
+            .map_or(contents.len(), |(start, _)| *start),
+    })
+}
+
+pub fn read_body(path: &Path) -> Result<String> {
+    read_body_file(&path.join("SKILL.md"))
+}
+
+pub fn read_body_file(skill_md_path: &Path) -> Result<String> {
+    let contents = fs::read_to_string(skill_md_path)
+        .with_context(|| format!("failed to read {}", skill_md_path.display()))?;
+    let body = split_frontmatter(&contents)
+        .map_err(|err| anyhow!("invalid frontmatter: {err}"))?
+        .body;
+    Ok(body)
+}
+
+fn validate_required_sections(
+    body: &str,
+    required: &[String],
+    report: &mut ValidationReport,
+    path: &Path,
+) {
+    // Walk the parsed markdown so `## Usage` inside a code block is not a heading.
+    let mut headings = Vec::new();
+    let mut current: Option<String> = None;
+    for event in Parser::new(body) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => current = Some(String::new()),
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(text) = current.take() {
+                    headings.push(text.trim().to_lowercase());
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(current) = &mut current {
+                    current.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for section in required {
+        let wanted = section.trim().to_lowercase();
+        if !headings.iter().any(|heading| heading == &wanted) {
+            report.issues.push(ValidationIssue {
+                rule: "missing-section",
+                severity: Severity::Error,
+                message: format!("missing required section '{}'", section.trim()),
+                path: Some(path.to_path_buf()),
+            });
+        }
+    }
+}
+
+/// What `validate` would report about `name` for a skill directory of the same name.
+pub fn name_issues(name: &str) -> Vec<ValidationIssue> {
+    let mut report = ValidationReport::default();
+    validate_name(name, Path::new(name), &mut report);
+    report.issues
 }
 
 fn validate_name(name: &str, path: &Path, report: &mut ValidationReport) {
     let trimmed = name.trim();
     if trimmed.is_empty() {
         report.issues.push(ValidationIssue {
+            rule: "name-required",
             severity: Severity::Error,
             message: "name is required".to_string(),
             path: Some(path.to_path_buf()),
@@ -178,6 +729,7 @@ fn validate_name(name: &str, path: &Path, report: &mut ValidationReport) {
 
     if trimmed.len() > 64 {
         report.issues.push(ValidationIssue {
+            rule: "name-length",
             severity: Severity::Error,
             message: "name must be <= 64 characters".to_string(),
             path: Some(path.to_path_buf()),
@@ -187,6 +739,7 @@ fn validate_name(name: &str, path: &Path, report: &mut ValidationReport) {
     let pattern = Regex::new(r"^[a-z0-9]+(?:-[a-z0-9]+)*$").expect("valid name regex");
     if !pattern.is_match(trimmed) {
         report.issues.push(ValidationIssue {
+            rule: "name-format",
             severity: Severity::Error,
             message: "name must be lowercase alphanumeric with hyphens".to_string(),
             path: Some(path.to_path_buf()),
@@ -195,20 +748,22 @@ fn validate_name(name: &str, path: &Path, report: &mut ValidationReport) {
 
     if trimmed.contains("--") {
         report.issues.push(ValidationIssue {
+            rule: "name-hyphens",
             severity: Severity::Error,
             message: "name must not contain consecutive hyphens".to_string(),
             path: Some(path.to_path_buf()),
         });
     }
 
-    if let Some(dir_name) = path.file_name().and_then(|name| name.to_str()) {
-        if dir_name != trimmed {
-            report.issues.push(ValidationIssue {
-                severity: Severity::Error,
-                message: "name must match the skill directory name".to_string(),
-                path: Some(path.to_path_buf()),
-            });
-        }
+    if let Some(dir_name) = path.file_name().and_then(|name| name.to_str())
+        && dir_name != trimmed
+    {
+        report.issues.push(ValidationIssue {
+            rule: "name-mismatch",
+            severity: Severity::Error,
+            message: "name must match the skill directory name".to_string(),
+            path: Some(path.to_path_buf()),
+        });
     }
 }
 
@@ -216,6 +771,7 @@ fn validate_description(description: &str, report: &mut ValidationReport, path:
     let trimmed = description.trim();
     if trimmed.is_empty() {
         report.issues.push(ValidationIssue {
+            rule: "description-required",
             severity: Severity::Error,
             message: "description is required".to_string(),
             path: Some(path.to_path_buf()),
@@ -225,6 +781,7 @@ fn validate_description(description: &str, report: &mut ValidationReport, path:
 
     if trimmed.len() > 1024 {
         report.issues.push(ValidationIssue {
+            rule: "description-length",
             severity: Severity::Error,
             message: "description must be <= 1024 characters".to_string(),
             path: Some(path.to_path_buf()),
@@ -242,12 +799,14 @@ fn validate_optional_field(
     if let Some(value) = value {
         if value.trim().is_empty() {
             report.issues.push(ValidationIssue {
+                rule: "field-empty",
                 severity: Severity::Warning,
                 message: format!("{field} should not be empty"),
                 path: Some(path.to_path_buf()),
             });
         } else if value.len() > max_len {
             report.issues.push(ValidationIssue {
+                rule: "field-length",
                 severity: Severity::Error,
                 message: format!("{field} must be <= {max_len} characters"),
                 path: Some(path.to_path_buf()),
@@ -255,3 +814,113 @@ fn validate_optional_field(
         }
     }
 }
+
+fn validate_metadata(
+    metadata: &BTreeMap<String, serde_yaml::Value>,
+    report: &mut ValidationReport,
+    path: &Path,
+) {
+    if metadata
+        .iter()
+        .any(|(key, value)| key.trim().is_empty() || is_empty_yaml(value))
+    {
+        report.issues.push(ValidationIssue {
+            rule: "metadata-empty",
+            severity: Severity::Warning,
+            message: "metadata entries should not be empty".to_string(),
+            path: Some(path.to_path_buf()),
+        });
+    }
+
+    let depth = metadata.values().map(yaml_depth).max().unwrap_or(0) + 1;
+    if depth > MAX_METADATA_DEPTH {
+        report.issues.push(ValidationIssue {
+            rule: "metadata-depth",
+            severity: Severity::Error,
+            message: format!("metadata must be nested at most {MAX_METADATA_DEPTH} levels deep"),
+            path: Some(path.to_path_buf()),
+        });
+    }
+
+    let size = serde_yaml::to_string(metadata)
+        .map(|yaml| yaml.len())
+        .unwrap_or(0);
+    if size > MAX_METADATA_BYTES {
+        report.issues.push(ValidationIssue {
+            rule: "metadata-size",
+            severity: Severity::Error,
+            message: format!("metadata must be <= {MAX_METADATA_BYTES} bytes when serialized"),
+            path: Some(path.to_path_buf()),
+        });
+    }
+}
+
+fn validate_extra_keys(
+    extra: &BTreeMap<String, serde_yaml::Value>,
+    assistant: Option<Assistant>,
+    report: &mut ValidationReport,
+    path: &Path,
+) {
+    for (key, value) in extra {
+        let Some(known) = ASSISTANT_KEYS.iter().find(|known| known.key == key) else {
+            report.issues.push(ValidationIssue {
+                rule: "frontmatter-unknown-key",
+                severity: Severity::Warning,
+                message: format!("unknown frontmatter key '{key}'; put custom data under metadata"),
+                path: Some(path.to_path_buf()),
+            });
+            continue;
+        };
+
+        let type_ok = match known.kind {
+            KeyKind::Bool => value.is_bool(),
+            KeyKind::Text => value.as_str().is_some_and(|text| !text.trim().is_empty()),
+        };
+        if !type_ok {
+            let expected = match known.kind {
+                KeyKind::Bool => "true or false",
+                KeyKind::Text => "a non-empty string",
+            };
+            report.issues.push(ValidationIssue {
+                rule: "assistant-key-type",
+                severity: Severity::Error,
+                message: format!("{key} must be {expected}"),
+                path: Some(path.to_path_buf()),
+            });
+        }
+
+        if let Some(assistant) = assistant
+            && !known.assistants.contains(&assistant)
+        {
+            let supported: Vec<&str> = known.assistants.iter().map(|a| a.as_str()).collect();
+            report.issues.push(ValidationIssue {
+                rule: "assistant-key-unsupported",
+                severity: Severity::Warning,
+                message: format!(
+                    "{key} is only understood by {}; {assistant} ignores it",
+                    supported.join(", ")
+                ),
+                path: Some(path.to_path_buf()),
+            });
+        }
+    }
+}
+
+fn is_empty_yaml(value: &serde_yaml::Value) -> bool {
+    match value {
+        serde_yaml::Value::Null => true,
+        serde_yaml::Value::String(text) => text.trim().is_empty(),
+        serde_yaml::Value::Sequence(items) => items.is_empty(),
+        serde_yaml::Value::Mapping(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+fn yaml_depth(value: &serde_yaml::Value) -> usize {
+    match value {
+        serde_yaml::Value::Sequence(items) => 1 + items.iter().map(yaml_depth).max().unwrap_or(0),
+        serde_yaml::Value::Mapping(map) => 1 + map.values().map(yaml_depth).max().unwrap_or(0),
+        serde_yaml::Value::Tagged(tagged) => yaml_depth(&tagged.value),
+        _ => 0,
+    }
+}